@@ -2,6 +2,7 @@ use crate::storage::traits::Entity;
 use gpui_component::Size::Large;
 use gpui_component::{Icon, IconName, Sizable};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ConnectionType {
@@ -18,7 +19,8 @@ pub enum DatabaseType {
     MySQL,
     PostgreSQL,
     MSSQL,
-    Oracle
+    Oracle,
+    SQLite,
 }
 
 impl DatabaseType {
@@ -28,6 +30,7 @@ impl DatabaseType {
             DatabaseType::PostgreSQL => "PostgreSQL",
             DatabaseType::MSSQL => "MSSQL",
             DatabaseType::Oracle => "Oracle",
+            DatabaseType::SQLite => "SQLite",
         }
     }
 
@@ -37,6 +40,7 @@ impl DatabaseType {
             "PostgreSQL" => Some(DatabaseType::PostgreSQL),
             "MSSQL" => Some(DatabaseType::MSSQL),
             "Oracle" => Some(DatabaseType::Oracle),
+            "SQLite" => Some(DatabaseType::SQLite),
             _ => None,
         }
     }
@@ -47,6 +51,7 @@ impl DatabaseType {
             DatabaseType::PostgreSQL => IconName::PostgreSQLColor.color().with_size(Large),
             DatabaseType::MSSQL => IconName::MSSQLColor.color().with_size(Large),
             DatabaseType::Oracle => IconName::OracleColor.color().with_size(Large),
+            DatabaseType::SQLite => IconName::SQLiteColor.color().with_size(Large),
         }
     }
 }
@@ -60,6 +65,9 @@ pub struct DatabaseParams {
     pub username: String,
     pub password: String,
     pub database: Option<String>,
+    /// Driver specific options keyed by connection form field name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra_params: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,6 +111,9 @@ pub struct DbConnectionConfig {
     pub database: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workspace_id: Option<i64>,
+    /// Driver specific options keyed by connection form field name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra_params: HashMap<String, String>,
 }
 
 impl ConnectionType {
@@ -277,6 +288,7 @@ impl StoredConnection {
             password: params.password,
             database: params.database,
             workspace_id: self.workspace_id,
+            extra_params: params.extra_params,
         })
     }
 
@@ -288,6 +300,7 @@ impl StoredConnection {
             username: connection.username,
             password: connection.password,
             database: connection.database,
+            extra_params: connection.extra_params,
         };
         Self::new_database(connection.name, params, connection.workspace_id)
     }
@@ -318,10 +331,185 @@ impl KeyValue {
     }
 }
 
-pub fn parse_db_type(s: &str) -> DatabaseType {
-    match s {
-        "MySQL" => DatabaseType::MySQL,
-        "PostgreSQL" => DatabaseType::PostgreSQL,
-        _ => DatabaseType::MySQL,
+impl Entity for KeyValue {
+    fn id(&self) -> Option<i64> {
+        self.id
+    }
+
+    fn created_at(&self) -> i64 {
+        self.created_at.unwrap()
+    }
+
+    fn updated_at(&self) -> i64 {
+        self.updated_at.unwrap()
+    }
+}
+
+/// One executed statement, recorded per connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryHistory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub connection_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+    pub sql: String,
+    /// Execution time in milliseconds
+    pub elapsed_ms: i64,
+    /// Rows returned by a query or affected by a statement
+    pub rows: i64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// When the statement ran, also used as the record's update time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+}
+
+impl Entity for QueryHistory {
+    fn id(&self) -> Option<i64> {
+        self.id
+    }
+
+    fn created_at(&self) -> i64 {
+        self.created_at.unwrap()
+    }
+
+    fn updated_at(&self) -> i64 {
+        self.created_at.unwrap()
+    }
+}
+
+impl QueryHistory {
+    pub fn new(connection_id: impl Into<String>, database: Option<String>, sql: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            connection_id: connection_id.into(),
+            database,
+            sql: sql.into(),
+            elapsed_ms: 0,
+            rows: 0,
+            success: true,
+            error: None,
+            created_at: None,
+        }
+    }
+}
+
+/// How much query history is kept for each connection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRetention {
+    /// Newest entries kept per connection, 0 keeps all
+    pub max_entries: i64,
+    /// Entries older than this many days are removed, 0 keeps them forever
+    pub max_days: i64,
+}
+
+impl HistoryRetention {
+    /// Key of the retention settings in the key-value store
+    pub const KEY: &'static str = "query_history.retention";
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            max_days: 90,
+        }
+    }
+}
+
+/// Case the SQL formatter writes keywords in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    /// Keep keywords as typed
+    Preserve,
+}
+
+/// Where the SQL formatter puts the commas of a list split over lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommaStyle {
+    /// `a,` at the end of each line
+    Trailing,
+    /// `, b` at the start of each following line
+    Leading,
+}
+
+/// Options of the SQL formatter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SqlFormatOptions {
+    pub keyword_case: KeywordCase,
+    pub comma_style: CommaStyle,
+    /// Spaces per indentation level
+    pub indent_width: usize,
+    /// Lines longer than this are split, a clause that fits stays on one line
+    pub max_line_width: usize,
+}
+
+impl SqlFormatOptions {
+    /// Key of the formatter options in the key-value store
+    pub const KEY: &'static str = "sql_format.options";
+}
+
+impl Default for SqlFormatOptions {
+    fn default() -> Self {
+        Self {
+            keyword_case: KeywordCase::Upper,
+            comma_style: CommaStyle::Trailing,
+            indent_width: 2,
+            max_line_width: 80,
+        }
+    }
+}
+
+/// Named SQL snippet, global or scoped to a workspace or connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlSnippet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Template text, placeholders such as `${name}` are asked for when it runs
+    pub sql: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+impl Entity for SqlSnippet {
+    fn id(&self) -> Option<i64> {
+        self.id
+    }
+
+    fn created_at(&self) -> i64 {
+        self.created_at.unwrap()
+    }
+
+    fn updated_at(&self) -> i64 {
+        self.updated_at.unwrap()
+    }
+}
+
+impl SqlSnippet {
+    pub fn new(name: impl Into<String>, sql: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            name: name.into(),
+            description: None,
+            sql: sql.into(),
+            workspace_id: None,
+            connection_id: None,
+            created_at: None,
+            updated_at: None,
+        }
     }
 }
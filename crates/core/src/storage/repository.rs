@@ -4,6 +4,7 @@ use gpui::{App, SharedString};
 use sqlx::{Row, SqlitePool};
 use crate::gpui_tokio::Tokio;
 use crate::storage::{traits::Repository, StoredConnection};
+use crate::tab_container::TabLayout;
 
 /// Repository for StoredConnection
 #[derive(Clone)]
@@ -189,7 +190,7 @@ impl ConnectionRepository {
 
 use crate::storage::ConnectionType;
 use crate::storage::manager::{now, GlobalStorageState};
-use crate::storage::Workspace;
+use crate::storage::{HistoryRetention, KeyValue, QueryHistory, SqlFormatOptions, SqlSnippet, Workspace};
 
 fn parse_connection_type(s: &str) -> ConnectionType {
     match s {
@@ -362,6 +363,596 @@ impl WorkspaceRepository {
     }
 }
 
+/// Repository for KeyValue, used for application settings
+#[derive(Clone, Default)]
+pub struct KeyValueRepository;
+
+impl KeyValueRepository {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Repository for KeyValueRepository {
+    type Entity = KeyValue;
+
+    fn entity_type(&self) -> SharedString {
+        SharedString::from("KeyValue")
+    }
+
+    async fn create_table(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS key_values (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL UNIQUE,
+                value TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert(&self, pool: &SqlitePool, item: &mut Self::Entity) -> Result<i64> {
+        let now = now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO key_values (key, value, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(&item.key)
+        .bind(&item.value)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        let id = result.last_insert_rowid();
+        item.id = Some(id);
+        item.created_at = Some(now);
+        item.updated_at = Some(now);
+
+        Ok(id)
+    }
+
+    async fn update(&self, pool: &SqlitePool, item: &Self::Entity) -> Result<()> {
+        let id = item.id.ok_or_else(|| anyhow::anyhow!("Cannot update without ID"))?;
+        sqlx::query("UPDATE key_values SET key = ?, value = ?, updated_at = ? WHERE id = ?")
+            .bind(&item.key)
+            .bind(&item.value)
+            .bind(now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, pool: &SqlitePool, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM key_values WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, pool: &SqlitePool, id: i64) -> Result<Option<Self::Entity>> {
+        let row = sqlx::query("SELECT id, key, value, created_at, updated_at FROM key_values WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.map(|r| Self::row_to_entity(&r)))
+    }
+
+    async fn list(&self, pool: &SqlitePool) -> Result<Vec<Self::Entity>> {
+        let rows = sqlx::query("SELECT id, key, value, created_at, updated_at FROM key_values ORDER BY key")
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.iter().map(Self::row_to_entity).collect())
+    }
+
+    async fn count(&self, pool: &SqlitePool) -> Result<i64> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM key_values")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(row.get("count"))
+    }
+
+    async fn exists(&self, pool: &SqlitePool, id: i64) -> Result<bool> {
+        let row = sqlx::query("SELECT 1 FROM key_values WHERE id = ? LIMIT 1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.is_some())
+    }
+}
+
+impl KeyValueRepository {
+    fn row_to_entity(row: &sqlx::sqlite::SqliteRow) -> KeyValue {
+        KeyValue {
+            id: Some(row.get("id")),
+            key: row.get("key"),
+            value: row.get("value"),
+            created_at: Some(row.get("created_at")),
+            updated_at: Some(row.get("updated_at")),
+        }
+    }
+
+    pub async fn get_value(&self, pool: &SqlitePool, key: &str) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT value FROM key_values WHERE key = ?")
+            .bind(key)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.map(|r| r.get("value")))
+    }
+
+    /// Insert the key or replace its value
+    pub async fn set_value(&self, pool: &SqlitePool, key: &str, value: &str) -> Result<()> {
+        let now = now();
+        sqlx::query(
+            r#"
+            INSERT INTO key_values (key, value, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Query history retention, the defaults when never saved
+    pub async fn history_retention(&self, pool: &SqlitePool) -> Result<HistoryRetention> {
+        let value = self.get_value(pool, HistoryRetention::KEY).await?;
+        Ok(value
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_history_retention(&self, pool: &SqlitePool, retention: &HistoryRetention) -> Result<()> {
+        self.set_value(pool, HistoryRetention::KEY, &serde_json::to_string(retention)?).await
+    }
+
+    /// SQL formatter options, the defaults when never saved
+    pub async fn sql_format_options(&self, pool: &SqlitePool) -> Result<SqlFormatOptions> {
+        let value = self.get_value(pool, SqlFormatOptions::KEY).await?;
+        Ok(value
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_sql_format_options(&self, pool: &SqlitePool, options: &SqlFormatOptions) -> Result<()> {
+        self.set_value(pool, SqlFormatOptions::KEY, &serde_json::to_string(options)?).await
+    }
+
+    /// Tabs of the main window saved on the last exit
+    pub async fn tab_layout(&self, pool: &SqlitePool) -> Result<Option<TabLayout>> {
+        let value = self.get_value(pool, TabLayout::KEY).await?;
+        Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+    }
+
+    pub async fn set_tab_layout(&self, pool: &SqlitePool, layout: &TabLayout) -> Result<()> {
+        self.set_value(pool, TabLayout::KEY, &serde_json::to_string(layout)?).await
+    }
+}
+
+/// Repository for QueryHistory
+#[derive(Clone, Default)]
+pub struct QueryHistoryRepository;
+
+impl QueryHistoryRepository {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+const QUERY_HISTORY_COLUMNS: &str = "id, connection_id, database, sql, elapsed_ms, rows, success, error, created_at";
+
+#[async_trait]
+impl Repository for QueryHistoryRepository {
+    type Entity = QueryHistory;
+
+    fn entity_type(&self) -> SharedString {
+        SharedString::from("QueryHistory")
+    }
+
+    async fn create_table(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS query_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id TEXT NOT NULL,
+                database TEXT,
+                sql TEXT NOT NULL,
+                elapsed_ms INTEGER NOT NULL,
+                rows INTEGER NOT NULL,
+                success INTEGER NOT NULL,
+                error TEXT,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_query_history_connection ON query_history(connection_id, created_at)")
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn insert(&self, pool: &SqlitePool, item: &mut Self::Entity) -> Result<i64> {
+        let created_at = item.created_at.unwrap_or_else(now);
+        let result = sqlx::query(
+            r#"
+            INSERT INTO query_history (connection_id, database, sql, elapsed_ms, rows, success, error, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&item.connection_id)
+        .bind(&item.database)
+        .bind(&item.sql)
+        .bind(item.elapsed_ms)
+        .bind(item.rows)
+        .bind(item.success)
+        .bind(&item.error)
+        .bind(created_at)
+        .execute(pool)
+        .await?;
+
+        let id = result.last_insert_rowid();
+        item.id = Some(id);
+        item.created_at = Some(created_at);
+
+        Ok(id)
+    }
+
+    async fn update(&self, pool: &SqlitePool, item: &Self::Entity) -> Result<()> {
+        let id = item.id.ok_or_else(|| anyhow::anyhow!("Cannot update without ID"))?;
+        sqlx::query(
+            r#"
+            UPDATE query_history
+            SET connection_id = ?, database = ?, sql = ?, elapsed_ms = ?, rows = ?, success = ?, error = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&item.connection_id)
+        .bind(&item.database)
+        .bind(&item.sql)
+        .bind(item.elapsed_ms)
+        .bind(item.rows)
+        .bind(item.success)
+        .bind(&item.error)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, pool: &SqlitePool, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM query_history WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, pool: &SqlitePool, id: i64) -> Result<Option<Self::Entity>> {
+        let row = sqlx::query(&format!("SELECT {} FROM query_history WHERE id = ?", QUERY_HISTORY_COLUMNS))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.map(|r| Self::row_to_entity(&r)))
+    }
+
+    async fn list(&self, pool: &SqlitePool) -> Result<Vec<Self::Entity>> {
+        let rows = sqlx::query(&format!("SELECT {} FROM query_history ORDER BY created_at DESC, id DESC", QUERY_HISTORY_COLUMNS))
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.iter().map(Self::row_to_entity).collect())
+    }
+
+    async fn count(&self, pool: &SqlitePool) -> Result<i64> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM query_history")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(row.get("count"))
+    }
+
+    async fn exists(&self, pool: &SqlitePool, id: i64) -> Result<bool> {
+        let row = sqlx::query("SELECT 1 FROM query_history WHERE id = ? LIMIT 1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.is_some())
+    }
+}
+
+impl QueryHistoryRepository {
+    fn row_to_entity(row: &sqlx::sqlite::SqliteRow) -> QueryHistory {
+        QueryHistory {
+            id: Some(row.get("id")),
+            connection_id: row.get("connection_id"),
+            database: row.get("database"),
+            sql: row.get("sql"),
+            elapsed_ms: row.get("elapsed_ms"),
+            rows: row.get("rows"),
+            success: row.get("success"),
+            error: row.get("error"),
+            created_at: Some(row.get("created_at")),
+        }
+    }
+
+    /// Newest entries of the given connections whose SQL contains `keyword`
+    pub async fn search(&self, pool: &SqlitePool, connection_ids: &[String], keyword: &str, limit: i64) -> Result<Vec<QueryHistory>> {
+        if connection_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; connection_ids.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT {}
+            FROM query_history
+            WHERE connection_id IN ({}) AND sql LIKE ? ESCAPE '\'
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+            QUERY_HISTORY_COLUMNS, placeholders
+        );
+        let pattern = format!(
+            "%{}%",
+            keyword.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+
+        let mut query = sqlx::query(&sql);
+        for id in connection_ids {
+            query = query.bind(id);
+        }
+        let rows = query.bind(pattern).bind(limit).fetch_all(pool).await?;
+
+        Ok(rows.iter().map(Self::row_to_entity).collect())
+    }
+
+    /// Remove the entries `retention` no longer keeps, returning how many were removed
+    pub async fn prune(&self, pool: &SqlitePool, retention: &HistoryRetention) -> Result<u64> {
+        let mut removed = 0;
+        if retention.max_days > 0 {
+            removed += sqlx::query("DELETE FROM query_history WHERE created_at < ?")
+                .bind(now() - retention.max_days * 24 * 60 * 60)
+                .execute(pool)
+                .await?
+                .rows_affected();
+        }
+        if retention.max_entries > 0 {
+            removed += sqlx::query(
+                r#"
+                DELETE FROM query_history WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, ROW_NUMBER() OVER (PARTITION BY connection_id ORDER BY created_at DESC, id DESC) AS position
+                        FROM query_history
+                    ) WHERE position > ?
+                )
+                "#,
+            )
+            .bind(retention.max_entries)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        }
+
+        Ok(removed)
+    }
+
+    /// Remove all entries of the given connections
+    pub async fn clear(&self, pool: &SqlitePool, connection_ids: &[String]) -> Result<()> {
+        for id in connection_ids {
+            sqlx::query("DELETE FROM query_history WHERE connection_id = ?")
+                .bind(id)
+                .execute(pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Repository for SqlSnippet
+#[derive(Clone, Default)]
+pub struct SnippetRepository;
+
+impl SnippetRepository {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+const SNIPPET_COLUMNS: &str = "id, name, description, sql, workspace_id, connection_id, created_at, updated_at";
+
+#[async_trait]
+impl Repository for SnippetRepository {
+    type Entity = SqlSnippet;
+
+    fn entity_type(&self) -> SharedString {
+        SharedString::from("Snippet")
+    }
+
+    async fn create_table(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS snippets (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                sql TEXT NOT NULL,
+                workspace_id INTEGER,
+                connection_id TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_snippets_name ON snippets(name)")
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn insert(&self, pool: &SqlitePool, item: &mut Self::Entity) -> Result<i64> {
+        let now = now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO snippets (name, description, sql, workspace_id, connection_id, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&item.name)
+        .bind(&item.description)
+        .bind(&item.sql)
+        .bind(item.workspace_id)
+        .bind(&item.connection_id)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        let id = result.last_insert_rowid();
+        item.id = Some(id);
+        item.created_at = Some(now);
+        item.updated_at = Some(now);
+
+        Ok(id)
+    }
+
+    async fn update(&self, pool: &SqlitePool, item: &Self::Entity) -> Result<()> {
+        let id = item.id.ok_or_else(|| anyhow::anyhow!("Cannot update without ID"))?;
+        sqlx::query(
+            r#"
+            UPDATE snippets
+            SET name = ?, description = ?, sql = ?, workspace_id = ?, connection_id = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&item.name)
+        .bind(&item.description)
+        .bind(&item.sql)
+        .bind(item.workspace_id)
+        .bind(&item.connection_id)
+        .bind(now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, pool: &SqlitePool, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM snippets WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, pool: &SqlitePool, id: i64) -> Result<Option<Self::Entity>> {
+        let row = sqlx::query(&format!("SELECT {} FROM snippets WHERE id = ?", SNIPPET_COLUMNS))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.map(|r| Self::row_to_entity(&r)))
+    }
+
+    async fn list(&self, pool: &SqlitePool) -> Result<Vec<Self::Entity>> {
+        let rows = sqlx::query(&format!("SELECT {} FROM snippets ORDER BY name", SNIPPET_COLUMNS))
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.iter().map(Self::row_to_entity).collect())
+    }
+
+    async fn count(&self, pool: &SqlitePool) -> Result<i64> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM snippets")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(row.get("count"))
+    }
+
+    async fn exists(&self, pool: &SqlitePool, id: i64) -> Result<bool> {
+        let row = sqlx::query("SELECT 1 FROM snippets WHERE id = ? LIMIT 1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.is_some())
+    }
+}
+
+impl SnippetRepository {
+    fn row_to_entity(row: &sqlx::sqlite::SqliteRow) -> SqlSnippet {
+        SqlSnippet {
+            id: Some(row.get("id")),
+            name: row.get("name"),
+            description: row.get("description"),
+            sql: row.get("sql"),
+            workspace_id: row.get("workspace_id"),
+            connection_id: row.get("connection_id"),
+            created_at: Some(row.get("created_at")),
+            updated_at: Some(row.get("updated_at")),
+        }
+    }
+
+    /// Snippets visible to a connection: global ones plus those scoped to its workspace or to it
+    pub async fn list_for_connection(&self, pool: &SqlitePool, workspace_id: Option<i64>, connection_id: &str) -> Result<Vec<SqlSnippet>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM snippets
+            WHERE (workspace_id IS NULL OR workspace_id = ?)
+              AND (connection_id IS NULL OR connection_id = ?)
+            ORDER BY name
+            "#,
+            SNIPPET_COLUMNS
+        ))
+        .bind(workspace_id)
+        .bind(connection_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(Self::row_to_entity).collect())
+    }
+}
+
 pub fn init(cx: &mut App) {
     let storage_state = cx.global::<GlobalStorageState>();
     let conn_repo = ConnectionRepository::new();
@@ -375,6 +966,18 @@ pub fn init(cx: &mut App) {
         
         conn_repo.create_table(&pool).await?;
         storage_state.storage.register(conn_repo).await?;
+
+        let key_value_repo = KeyValueRepository::new();
+        key_value_repo.create_table(&pool).await?;
+        storage_state.storage.register(key_value_repo).await?;
+
+        let history_repo = QueryHistoryRepository::new();
+        history_repo.create_table(&pool).await?;
+        storage_state.storage.register(history_repo).await?;
+
+        let snippet_repo = SnippetRepository::new();
+        snippet_repo.create_table(&pool).await?;
+        storage_state.storage.register(snippet_repo).await?;
         
         Ok(())
     });
@@ -1,9 +1,10 @@
-use std::{any::Any, sync::Arc};
+use std::{any::Any, rc::Rc, sync::Arc};
 use gpui::prelude::FluentBuilder;
 use gpui::StatefulInteractiveElement as _;
 use gpui::{div, px, AnyElement, App, AppContext, Context, InteractiveElement, IntoElement, MouseButton, ParentElement, Render, ScrollHandle, SharedString, Styled, Window};
-use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, Size};
+use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, Size, WindowExt};
 use gpui_component::menu::{ContextMenuExt, PopupMenuItem};
+use serde::{Deserialize, Serialize};
 // ============================================================================
 // TabContent Trait - Strategy Pattern Interface
 // ============================================================================
@@ -42,12 +43,31 @@ pub trait TabContent: Send + Sync {
         None  // Default: use container's default size
     }
 
+    /// Whether the tab holds unsaved changes, shown as a marker next to the title
+    fn is_dirty(&self, _cx: &App) -> bool {
+        false
+    }
+
+    /// Warning shown for confirmation before the tab is closed, e.g. work that would be lost
+    fn close_warning(&self, _cx: &App) -> Option<SharedString> {
+        None
+    }
+
+    /// Called after the tab has been removed from the container
+    fn on_close(&self, _cx: &mut App) {}
+
+    /// State saved on exit to rebuild the tab on the next launch.
+    /// Returns None for tabs that should not be restored
+    fn save_state(&self, _cx: &App) -> Option<serde_json::Value> {
+        None
+    }
+
     /// Enable downcasting to concrete types
     fn as_any(&self) -> &dyn Any;
 }
 
 /// Type-safe enum for different tab content types
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TabContentType {
     SqlEditor,
     TableData(String),    // Table name
@@ -56,6 +76,28 @@ pub enum TabContentType {
     Custom(String),       // Custom type identifier
 }
 
+/// Serialisable description of a tab, enough to rebuild it on the next launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabState {
+    pub id: String,
+    pub content_type: TabContentType,
+    /// Content specific state returned by `TabContent::save_state`
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// Restorable tabs of a container in order, and which one was active
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TabLayout {
+    pub tabs: Vec<TabState>,
+    pub active_id: Option<String>,
+}
+
+impl TabLayout {
+    /// Key of the main window layout in the key-value store
+    pub const KEY: &'static str = "tabs.layout";
+}
+
 // ============================================================================
 // TabItem - Represents a single tab with its content
 // ============================================================================
@@ -313,10 +355,98 @@ impl TabContainer {
         }
     }
 
+    /// Describe the tabs that can be restored, see `TabContent::save_state`
+    pub fn save_layout(&self, cx: &App) -> TabLayout {
+        let tabs = self
+            .tabs
+            .iter()
+            .filter_map(|tab| {
+                let data = tab.content().save_state(cx)?;
+                Some(TabState {
+                    id: tab.id().to_string(),
+                    content_type: tab.content().content_type(),
+                    data,
+                })
+            })
+            .collect();
+
+        TabLayout {
+            tabs,
+            active_id: self.active_tab().map(|tab| tab.id().to_string()),
+        }
+    }
+
+    /// Rebuild tabs from a saved layout, skipping ids that are already open.
+    /// `build` returns None for a tab that can no longer be restored
+    pub fn restore_layout<F>(&mut self, layout: TabLayout, mut build: F, window: &mut Window, cx: &mut Context<Self>)
+    where
+        F: FnMut(&TabState, &mut Window, &mut Context<Self>) -> Option<TabItem>,
+    {
+        for state in &layout.tabs {
+            if self.tabs.iter().any(|tab| tab.id() == state.id) {
+                continue;
+            }
+            if let Some(tab) = build(state, window, cx) {
+                self.tabs.push(tab);
+            }
+        }
+
+        if let Some(active_id) = layout.active_id {
+            self.set_active_by_id(&active_id, window, cx);
+        }
+        cx.notify();
+    }
+
+    /// Let removed tabs release what they hold
+    fn release_tabs(removed: Vec<TabItem>, cx: &mut App) {
+        for tab in removed {
+            tab.content().on_close(cx);
+        }
+    }
+
+    /// Run a close action, asking for confirmation first when one of the affected tabs has a close warning
+    fn close_with_confirm<F>(&mut self, indices: Vec<usize>, close: F, window: &mut Window, cx: &mut Context<Self>)
+    where
+        F: Fn(&mut Self, &mut Context<Self>) + 'static,
+    {
+        let warnings: Vec<SharedString> = indices
+            .iter()
+            .filter_map(|index| self.tabs.get(*index))
+            .filter(|tab| tab.content().closeable())
+            .filter_map(|tab| tab.content().close_warning(cx))
+            .collect();
+
+        if warnings.is_empty() {
+            close(self, cx);
+            return;
+        }
+
+        let view = cx.entity();
+        let close = Rc::new(close);
+        window.open_dialog(cx, move |dialog, _window, _cx| {
+            let view = view.clone();
+            let close = close.clone();
+            dialog
+                .title("Close Tab")
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .children(warnings.iter().map(|warning| div().child(warning.clone())))
+                        .child("Close anyway?"),
+                )
+                .on_ok(move |_, _, cx| {
+                    view.update(cx, |this, cx| close(this, cx));
+                    true
+                })
+        });
+    }
+
     /// Close a tab by index
     pub fn close_tab(&mut self, index: usize, cx: &mut Context<Self>) {
         if index < self.tabs.len() && self.tabs[index].content().closeable() {
-            self.tabs.remove(index);
+            let removed = self.tabs.remove(index);
+            Self::release_tabs(vec![removed], cx);
 
             // Adjust active index if needed
             if self.active_index >= self.tabs.len() && !self.tabs.is_empty() {
@@ -335,16 +465,24 @@ impl TabContainer {
 
         // Keep the tab at keep_index, remove all others
         let kept_tab = self.tabs.remove(keep_index);
-        self.tabs.retain(|tab| !tab.content().closeable());
+        let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tabs)
+            .into_iter()
+            .partition(|tab| tab.content().closeable());
+        self.tabs = kept;
         self.tabs.insert(0, kept_tab);
         self.active_index = 0;
+        Self::release_tabs(removed, cx);
 
         cx.notify();
     }
 
     /// Close all tabs
     pub fn close_all_tabs(&mut self, cx: &mut Context<Self>) {
-        self.tabs.retain(|tab| !tab.content().closeable());
+        let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tabs)
+            .into_iter()
+            .partition(|tab| tab.content().closeable());
+        self.tabs = kept;
+        Self::release_tabs(removed, cx);
 
         // Reset active index
         if self.active_index >= self.tabs.len() && !self.tabs.is_empty() {
@@ -364,14 +502,15 @@ impl TabContainer {
 
         // Remove closeable tabs from index-1 down to 0
         let mut i = 0;
-        let mut removed_count = 0;
+        let mut removed = Vec::new();
         while i < index {
             if self.tabs[i].content().closeable() {
-                self.tabs.remove(i);
-                removed_count += 1;
+                removed.push(self.tabs.remove(i));
             }
             i += 1;
         }
+        let removed_count = removed.len();
+        Self::release_tabs(removed, cx);
 
         // Adjust active index
         if self.active_index >= removed_count {
@@ -389,12 +528,14 @@ impl TabContainer {
 
         // Remove closeable tabs from index+1 to end
         let mut i = index + 1;
+        let mut removed = Vec::new();
         while i < self.tabs.len() {
             if self.tabs[i].content().closeable() {
-                self.tabs.remove(i);
+                removed.push(self.tabs.remove(i));
             }
             i += 1;
         }
+        Self::release_tabs(removed, cx);
 
         // Adjust active index if it was beyond the removed tabs
         if self.active_index > index && self.active_index >= self.tabs.len() {
@@ -564,6 +705,7 @@ impl TabContainer {
                         let title = tab.content().title();
                         let icon = tab.content().icon();
                         let closeable = tab.content().closeable();
+                        let dirty = tab.content().is_dirty(cx);
                         let is_active = idx == active_index;
                         let view_clone = view.clone();
                         let title_clone = title.clone();
@@ -627,7 +769,7 @@ impl TabContainer {
                                     .text_sm()
                                     .text_color(text_color)
                                     .text_ellipsis()
-                                    .child(title_clone.to_string())
+                                    .child(if dirty { format!("● {}", title_clone) } else { title_clone.to_string() })
                             )
                             // 关闭按钮 - 不被挤压
                             .when(closeable, |el| {
@@ -648,9 +790,9 @@ impl TabContainer {
                                                 .bg(gpui::rgb(0x5a5a5a))
                                                 .text_color(text_color)
                                         })
-                                        .on_mouse_down(MouseButton::Left, move |_event, _window, cx| {
+                                        .on_mouse_down(MouseButton::Left, move |_event, window, cx| {
                                             view_clone.update(cx, |this, cx| {
-                                                this.close_tab(idx, cx);
+                                                this.close_with_confirm(vec![idx], move |this, cx| this.close_tab(idx, cx), window, cx);
                                             });
                                         })
                                         .child("×")
@@ -668,31 +810,34 @@ impl TabContainer {
                                 // Close current tab
                                 .item(PopupMenuItem::new("Close")
                                     .disabled(!closeable)
-                                    .on_click(window.listener_for(&view_for_menu, move |this, _, _, cx| {
-                                        this.close_tab(idx, cx);
+                                    .on_click(window.listener_for(&view_for_menu, move |this, _, window, cx| {
+                                        this.close_with_confirm(vec![idx], move |this, cx| this.close_tab(idx, cx), window, cx);
                                     })))
                                 // Close all tabs
                                 .item(PopupMenuItem::new("Close All")
-                                    .on_click(window.listener_for(&view_for_menu, move |this, _, _, cx| {
-                                        this.close_all_tabs(cx);
+                                    .on_click(window.listener_for(&view_for_menu, move |this, _, window, cx| {
+                                        let indices = (0..this.tabs.len()).collect();
+                                        this.close_with_confirm(indices, |this, cx| this.close_all_tabs(cx), window, cx);
                                     })))
                                 // Close other tabs
                                 .item(PopupMenuItem::new("Close Others")
                                     .disabled(tab_count <= 1)
-                                    .on_click(window.listener_for(&view_for_menu, move |this, _, _, cx| {
-                                        this.close_other_tabs(idx, cx);
+                                    .on_click(window.listener_for(&view_for_menu, move |this, _, window, cx| {
+                                        let indices = (0..this.tabs.len()).filter(|i| *i != idx).collect();
+                                        this.close_with_confirm(indices, move |this, cx| this.close_other_tabs(idx, cx), window, cx);
                                     })))
                                 // Close tabs to the left
                                 .item(PopupMenuItem::new("Close Tabs To The Left")
                                     .disabled(!has_tabs_left)
-                                    .on_click(window.listener_for(&view_for_menu, move |this, _, _, cx| {
-                                        this.close_tabs_to_left(idx, cx);
+                                    .on_click(window.listener_for(&view_for_menu, move |this, _, window, cx| {
+                                        this.close_with_confirm((0..idx).collect(), move |this, cx| this.close_tabs_to_left(idx, cx), window, cx);
                                     })))
                                 // Close tabs to the right
                                 .item(PopupMenuItem::new("Close Tabs To The Right")
                                     .disabled(!has_tabs_right)
-                                    .on_click(window.listener_for(&view_for_menu, move |this, _, _, cx| {
-                                        this.close_tabs_to_right(idx, cx);
+                                    .on_click(window.listener_for(&view_for_menu, move |this, _, window, cx| {
+                                        let indices = (idx + 1..this.tabs.len()).collect();
+                                        this.close_with_confirm(indices, move |this, cx| this.close_tabs_to_right(idx, cx), window, cx);
                                     })))
 
                         })
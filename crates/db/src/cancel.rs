@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Server-side handles of the statements a connection is running, so another task can cancel them.
+/// A handle is whatever the backend needs to interrupt a statement: a MySQL connection id,
+/// a PostgreSQL backend pid or a SQLite database pointer.
+#[derive(Clone, Default)]
+pub(crate) struct RunningQueries {
+    handles: Arc<Mutex<Vec<u64>>>,
+    cancel_requested: Arc<AtomicBool>,
+}
+
+impl RunningQueries {
+    /// Clear a cancellation left over from a previous script
+    pub fn reset(&self) {
+        self.cancel_requested.store(false, Ordering::SeqCst);
+    }
+
+    /// Register a handle until the returned guard is dropped
+    pub fn track(&self, handle: u64) -> RunningQueryGuard {
+        self.handles.lock().unwrap().push(handle);
+        RunningQueryGuard {
+            queries: self.clone(),
+            handle,
+        }
+    }
+
+    pub fn handles(&self) -> Vec<u64> {
+        self.handles.lock().unwrap().clone()
+    }
+
+    /// Visit the handles without letting any of them be released meanwhile
+    pub fn with_handles<T>(&self, f: impl FnOnce(&[u64]) -> T) -> T {
+        f(&self.handles.lock().unwrap())
+    }
+
+    /// Ask the running script to stop before its next statement
+    pub fn request_cancel(&self) {
+        self.cancel_requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_requested.load(Ordering::SeqCst)
+    }
+}
+
+pub(crate) struct RunningQueryGuard {
+    queries: RunningQueries,
+    handle: u64,
+}
+
+impl Drop for RunningQueryGuard {
+    fn drop(&mut self) {
+        let mut handles = self.queries.handles.lock().unwrap();
+        if let Some(pos) = handles.iter().position(|h| *h == self.handle) {
+            handles.remove(pos);
+        }
+    }
+}
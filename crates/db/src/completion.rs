@@ -0,0 +1,602 @@
+use std::ops::Range;
+
+use one_core::storage::DatabaseType;
+
+use crate::types::ForeignKeyInfo;
+
+/// Kind of a token produced by `tokenize_sql`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlTokenKind {
+    /// Keyword or bare identifier
+    Word,
+    /// `"name"`, `` `name` `` or `[name]`, text holds the name without quotes
+    QuotedIdentifier,
+    /// String, number or dollar-quoted body
+    Literal,
+    Comment,
+    /// Any other single character
+    Punct,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlToken {
+    pub kind: SqlTokenKind,
+    pub text: String,
+    /// Byte range in the tokenized text, quotes included
+    pub range: Range<usize>,
+    /// The token has no closing delimiter before its end (line comments, unterminated
+    /// strings), so a cursor right at its end is still inside it
+    pub open: bool,
+}
+
+impl SqlToken {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == SqlTokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is_punct(&self, c: char) -> bool {
+        self.kind == SqlTokenKind::Punct && self.text.len() == c.len_utf8() && self.text.starts_with(c)
+    }
+
+    /// Whether the token can name a table, column or alias
+    pub fn is_name(&self) -> bool {
+        match self.kind {
+            SqlTokenKind::QuotedIdentifier => true,
+            SqlTokenKind::Word => !RESERVED_WORDS.iter().any(|word| self.text.eq_ignore_ascii_case(word)),
+            _ => false,
+        }
+    }
+}
+
+/// Words that end a table reference instead of being taken as its alias
+const RESERVED_WORDS: &[&str] = &[
+    "ALL", "AND", "APPLY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CONNECT", "CROSS", "DEFAULT",
+    "DESC", "DISTINCT", "ELSE", "END", "EXCEPT", "EXISTS", "FETCH", "FOR", "FORCE", "FROM", "FULL",
+    "GROUP", "HAVING", "IGNORE", "IN", "INNER", "INTERSECT", "INTO", "IS", "JOIN", "LATERAL", "LEFT",
+    "LIKE", "LIMIT", "MINUS", "NATURAL", "NOT", "NULL", "OFFSET", "ON", "ONLY", "OR", "ORDER", "OUTER",
+    "OUTPUT", "PARTITION", "PIVOT", "QUALIFY", "RETURNING", "RIGHT", "SELECT", "SET", "START",
+    "STRAIGHT_JOIN", "TABLESAMPLE", "THEN", "TOP", "UNION", "UNPIVOT", "USE", "USING", "VALUES", "WHEN",
+    "WHERE", "WINDOW", "WITH",
+];
+
+/// Keywords after which a table name is written
+const TABLE_KEYWORDS: &[&str] = &["FROM", "JOIN", "INTO", "UPDATE", "TABLE"];
+
+/// Keywords that start the part of a statement the cursor can be in
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "JOIN", "ON", "WHERE", "BY", "HAVING", "SET", "VALUES", "LIMIT", "OFFSET", "INTO",
+    "UPDATE", "TABLE", "USING", "RETURNING", "WITH", "UNION", "EXCEPT", "INTERSECT",
+];
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+/// Split SQL into tokens with the quoting and comment rules of `dialect`
+pub fn tokenize_sql(sql: &str, dialect: DatabaseType) -> Vec<SqlToken> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    let token = |kind, text: &str, range: Range<usize>, open| SqlToken {
+        kind,
+        text: text.to_string(),
+        range,
+        open,
+    };
+
+    while pos < bytes.len() {
+        let b = bytes[pos];
+        let start = pos;
+
+        if b.is_ascii_whitespace() {
+            pos += 1;
+        } else if sql[pos..].starts_with("--") || (b == b'#' && dialect == DatabaseType::MySQL) {
+            pos = sql[pos..].find('\n').map_or(bytes.len(), |i| pos + i);
+            tokens.push(token(SqlTokenKind::Comment, &sql[start..pos], start..pos, true));
+        } else if sql[pos..].starts_with("/*") {
+            let (end, open) = sql[pos + 2..].find("*/").map_or((bytes.len(), true), |i| (pos + 2 + i + 2, false));
+            pos = end;
+            tokens.push(token(SqlTokenKind::Comment, &sql[start..pos], start..pos, open));
+        } else if b == b'\'' || (b == b'"' && dialect == DatabaseType::MySQL) {
+            let (end, open) = scan_quoted(bytes, pos, b, dialect == DatabaseType::MySQL);
+            pos = end;
+            tokens.push(token(SqlTokenKind::Literal, &sql[start..pos], start..pos, open));
+        } else if b == b'"' || b == b'`' || (b == b'[' && matches!(dialect, DatabaseType::MSSQL | DatabaseType::SQLite)) {
+            let close = if b == b'[' { b']' } else { b };
+            let (end, open) = scan_quoted(bytes, pos, close, false);
+            pos = end;
+            let inner_end = if open { end } else { end - 1 };
+            let name = sql[start + 1..inner_end.max(start + 1)].replace(
+                &format!("{}{}", close as char, close as char),
+                &(close as char).to_string(),
+            );
+            tokens.push(token(SqlTokenKind::QuotedIdentifier, &name, start..pos, open));
+        } else if b == b'$' && dialect == DatabaseType::PostgreSQL && dollar_tag(&sql[pos..]).is_some() {
+            let tag = dollar_tag(&sql[pos..]).unwrap_or_default();
+            let body = pos + tag.len();
+            let (end, open) = sql[body..].find(tag).map_or((bytes.len(), true), |i| (body + i + tag.len(), false));
+            pos = end;
+            tokens.push(token(SqlTokenKind::Literal, &sql[start..pos], start..pos, open));
+        } else if b.is_ascii_digit() {
+            while pos < bytes.len() && (is_word_byte(bytes[pos]) || bytes[pos] == b'.') {
+                pos += 1;
+            }
+            tokens.push(token(SqlTokenKind::Literal, &sql[start..pos], start..pos, false));
+        } else if is_word_byte(b) {
+            while pos < bytes.len() && is_word_byte(bytes[pos]) {
+                pos += 1;
+            }
+            tokens.push(token(SqlTokenKind::Word, &sql[start..pos], start..pos, false));
+        } else {
+            let len = sql[pos..].chars().next().map_or(1, char::len_utf8);
+            pos += len;
+            tokens.push(token(SqlTokenKind::Punct, &sql[start..pos], start..pos, false));
+        }
+    }
+
+    tokens
+}
+
+/// End of a quoted span starting at `start`, and whether it is missing its closing quote.
+/// A doubled quote stands for the quote itself.
+fn scan_quoted(bytes: &[u8], start: usize, close: u8, backslash_escapes: bool) -> (usize, bool) {
+    let mut pos = start + 1;
+    while pos < bytes.len() {
+        if backslash_escapes && bytes[pos] == b'\\' {
+            pos += 2;
+        } else if bytes[pos] == close {
+            if bytes.get(pos + 1) == Some(&close) {
+                pos += 2;
+            } else {
+                return (pos + 1, false);
+            }
+        } else {
+            pos += 1;
+        }
+    }
+    (bytes.len(), true)
+}
+
+/// `$$` or `$tag$` at the start of `text`
+fn dollar_tag(text: &str) -> Option<&str> {
+    let end = text[1..].find('$')? + 2;
+    let tag = &text[..end];
+    tag[1..end - 1]
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        .then_some(tag)
+}
+
+/// A table named in a statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRef {
+    pub schema: Option<String>,
+    pub name: String,
+    pub alias: Option<String>,
+    /// Byte offset of the reference in the statement
+    pub offset: usize,
+}
+
+impl TableRef {
+    /// What the statement calls the table: its alias, or else its name
+    pub fn reference_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    /// `schema.name`, or the name alone when no schema is written
+    pub fn qualified_name(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", schema, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Whether `qualifier`, the text before a `.`, refers to this table
+    pub fn matches(&self, qualifier: &str) -> bool {
+        match &self.alias {
+            Some(alias) => alias.eq_ignore_ascii_case(qualifier),
+            None => {
+                self.name.eq_ignore_ascii_case(qualifier) || self.qualified_name().eq_ignore_ascii_case(qualifier)
+            }
+        }
+    }
+
+    /// Whether `foreign_key` points at this table
+    pub fn is_referenced_by(&self, foreign_key: &ForeignKeyInfo) -> bool {
+        let same_schema = match (&self.schema, &foreign_key.referenced_schema) {
+            (Some(schema), Some(referenced)) => schema.eq_ignore_ascii_case(referenced),
+            _ => true,
+        };
+        same_schema && self.name.eq_ignore_ascii_case(&foreign_key.referenced_table)
+    }
+}
+
+/// Tables a statement reads or writes: after FROM (comma lists included), JOIN, INTO,
+/// UPDATE and TABLE, with their schema and alias. Subqueries contribute their own tables.
+pub fn table_references(tokens: &[SqlToken]) -> Vec<TableRef> {
+    let tokens: Vec<&SqlToken> = tokens.iter().filter(|t| t.kind != SqlTokenKind::Comment).collect();
+    let mut tables = Vec::new();
+    // Whether a comma continues a FROM list, per parenthesis level
+    let mut in_from = vec![false];
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        if token.is_punct('(') {
+            in_from.push(false);
+        } else if token.is_punct(')') {
+            if in_from.len() > 1 {
+                in_from.pop();
+            }
+        } else {
+            let starts_list = token.is_keyword("FROM") || token.is_keyword("JOIN");
+            let continues_list = token.is_punct(',') && in_from.last() == Some(&true);
+            if starts_list || continues_list || TABLE_KEYWORDS.iter().any(|k| token.is_keyword(k)) {
+                if let Some(level) = in_from.last_mut() {
+                    *level = starts_list || continues_list;
+                }
+                if let Some((table, next)) = parse_table_ref(&tokens, i + 1) {
+                    tables.push(table);
+                    i = next;
+                    continue;
+                }
+            } else if CLAUSE_KEYWORDS.iter().any(|k| token.is_keyword(k)) {
+                if let Some(level) = in_from.last_mut() {
+                    *level = false;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    tables
+}
+
+/// Read `[schema.]name [[AS] alias]` at `start`, returning it and the index after it
+fn parse_table_ref(tokens: &[&SqlToken], start: usize) -> Option<(TableRef, usize)> {
+    let mut i = start;
+    while tokens.get(i).is_some_and(|t| t.is_keyword("ONLY") || t.is_keyword("LATERAL")) {
+        i += 1;
+    }
+
+    let first = tokens.get(i).filter(|t| t.is_name())?;
+    let offset = first.range.start;
+    let mut parts = vec![first.text.clone()];
+    i += 1;
+    while tokens.get(i).is_some_and(|t| t.is_punct('.')) && tokens.get(i + 1).is_some_and(|t| t.is_name()) {
+        parts.push(tokens[i + 1].text.clone());
+        i += 2;
+    }
+    let name = parts.pop()?;
+    let schema = parts.pop();
+
+    let mut alias = None;
+    if tokens.get(i).is_some_and(|t| t.is_keyword("AS")) {
+        i += 1;
+    }
+    if let Some(token) = tokens.get(i).filter(|t| t.is_name()) {
+        alias = Some(token.text.clone());
+        i += 1;
+    }
+
+    Some((TableRef { schema, name, alias, offset }, i))
+}
+
+/// What fits at the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// Inside a string literal or comment
+    None,
+    /// Start of a statement or of a clause
+    Keyword,
+    /// A table name after FROM, JOIN, INTO, UPDATE or TABLE, inside `schema` when written as `schema.`
+    Table { schema: Option<String> },
+    /// A column of whatever `qualifier.` refers to, an alias or a table name
+    QualifiedColumn { qualifier: String },
+    /// Columns of the statement's tables: select list, WHERE, GROUP/ORDER BY, SET ...
+    Column,
+    /// Right after `JOIN table ON`
+    JoinCondition { table: TableRef },
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletionContext {
+    pub kind: CompletionKind,
+    /// Tables referenced anywhere in the statement, including after the cursor
+    pub tables: Vec<TableRef>,
+    /// Part of the word typed before the cursor
+    pub prefix: String,
+}
+
+/// Work out what to complete at byte `offset` of a single statement
+pub fn completion_context(statement: &str, offset: usize, dialect: DatabaseType) -> CompletionContext {
+    let mut offset = offset.min(statement.len());
+    while !statement.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let tokens = tokenize_sql(statement, dialect);
+    let tables = table_references(&tokens);
+
+    let mut prefix_start = offset;
+    for token in &tokens {
+        let inside = token.range.start < offset && (offset < token.range.end || (token.open && offset == token.range.end));
+        if inside && matches!(token.kind, SqlTokenKind::Literal | SqlTokenKind::Comment) {
+            return CompletionContext {
+                kind: CompletionKind::None,
+                tables,
+                prefix: String::new(),
+            };
+        }
+        if token.kind == SqlTokenKind::Word && token.range.start < offset && offset <= token.range.end {
+            prefix_start = token.range.start;
+        }
+    }
+
+    let before: Vec<&SqlToken> = tokens
+        .iter()
+        .filter(|t| t.range.end <= prefix_start && t.kind != SqlTokenKind::Comment)
+        .collect();
+
+    CompletionContext {
+        kind: classify(&before, &tables),
+        prefix: statement[prefix_start..offset].to_string(),
+        tables,
+    }
+}
+
+fn classify(before: &[&SqlToken], tables: &[TableRef]) -> CompletionKind {
+    let Some(last) = before.last() else {
+        return CompletionKind::Keyword;
+    };
+
+    if last.is_punct('.') {
+        let rest = &before[..before.len() - 1];
+        return match rest.last() {
+            Some(qualifier) if qualifier.is_name() => {
+                if expects_table(&rest[..rest.len() - 1]) {
+                    CompletionKind::Table {
+                        schema: Some(qualifier.text.clone()),
+                    }
+                } else {
+                    CompletionKind::QualifiedColumn {
+                        qualifier: qualifier.text.clone(),
+                    }
+                }
+            }
+            _ => CompletionKind::None,
+        };
+    }
+
+    if last.is_punct(';') {
+        return CompletionKind::Keyword;
+    }
+    if expects_table(before) {
+        return CompletionKind::Table { schema: None };
+    }
+    if last.is_keyword("ON") {
+        return match tables.iter().rev().find(|table| table.offset < last.range.start) {
+            Some(table) => CompletionKind::JoinCondition { table: table.clone() },
+            None => CompletionKind::Column,
+        };
+    }
+
+    match current_clause(before) {
+        Clause::Keyword(keyword) => match keyword.as_str() {
+            "SELECT" | "WHERE" | "ON" | "BY" | "HAVING" | "SET" | "USING" | "RETURNING" => CompletionKind::Column,
+            _ => CompletionKind::Keyword,
+        },
+        Clause::Parenthesis { after_values } => {
+            if after_values {
+                CompletionKind::Keyword
+            } else {
+                CompletionKind::Column
+            }
+        }
+        Clause::Start => CompletionKind::Keyword,
+    }
+}
+
+enum Clause {
+    /// The cursor is at the top level of this clause
+    Keyword(String),
+    /// The cursor is inside parentheses with no clause keyword of their own yet
+    Parenthesis { after_values: bool },
+    Start,
+}
+
+/// Clause the cursor is in, looking back over balanced parentheses
+fn current_clause(before: &[&SqlToken]) -> Clause {
+    let mut depth = 0usize;
+    for (i, token) in before.iter().enumerate().rev() {
+        if token.is_punct(')') {
+            depth += 1;
+        } else if token.is_punct('(') {
+            if depth == 0 {
+                let after_values = i > 0 && before[i - 1].is_keyword("VALUES");
+                return Clause::Parenthesis { after_values };
+            }
+            depth -= 1;
+        } else if depth == 0 {
+            if let Some(keyword) = CLAUSE_KEYWORDS.iter().find(|k| token.is_keyword(k)) {
+                return Clause::Keyword(keyword.to_string());
+            }
+        }
+    }
+    Clause::Start
+}
+
+/// Whether the next word is a table name: right after a table keyword, or after a comma in a FROM list
+fn expects_table(before: &[&SqlToken]) -> bool {
+    let Some(last) = before.last() else {
+        return false;
+    };
+    if TABLE_KEYWORDS.iter().any(|k| last.is_keyword(k)) {
+        return true;
+    }
+    last.is_punct(',') && matches!(current_clause(&before[..before.len() - 1]), Clause::Keyword(k) if k == "FROM")
+}
+
+/// `ON` conditions joining `table` to the tables before it in the statement, built from
+/// foreign keys in either direction. `foreign_keys` gives the keys declared on a table.
+pub fn join_conditions<'a>(
+    table: &TableRef,
+    tables: &[TableRef],
+    foreign_keys: impl Fn(&TableRef) -> &'a [ForeignKeyInfo],
+) -> Vec<String> {
+    let mut conditions: Vec<String> = Vec::new();
+
+    for other in tables.iter().filter(|other| other.offset < table.offset) {
+        let outgoing = foreign_keys(table)
+            .iter()
+            .filter(|fk| other.is_referenced_by(fk))
+            .filter_map(|fk| equalities(table, &fk.columns, other, &fk.referenced_columns));
+        let incoming = foreign_keys(other)
+            .iter()
+            .filter(|fk| table.is_referenced_by(fk))
+            .filter_map(|fk| equalities(table, &fk.referenced_columns, other, &fk.columns));
+
+        for condition in outgoing.chain(incoming) {
+            if !conditions.contains(&condition) {
+                conditions.push(condition);
+            }
+        }
+    }
+
+    conditions
+}
+
+/// `l.a = r.b AND ...`, none when a column is unknown
+fn equalities(left: &TableRef, left_columns: &[String], right: &TableRef, right_columns: &[String]) -> Option<String> {
+    if left_columns.is_empty()
+        || left_columns.len() != right_columns.len()
+        || left_columns.iter().chain(right_columns).any(|c| c.is_empty())
+    {
+        return None;
+    }
+    let parts: Vec<String> = left_columns
+        .iter()
+        .zip(right_columns)
+        .map(|(l, r)| format!("{}.{} = {}.{}", left.reference_name(), l, right.reference_name(), r))
+        .collect();
+    Some(parts.join(" AND "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(sql_with_cursor: &str) -> CompletionContext {
+        let offset = sql_with_cursor.find('|').unwrap();
+        let sql = sql_with_cursor.replace('|', "");
+        completion_context(&sql, offset, DatabaseType::PostgreSQL)
+    }
+
+    fn table(schema: Option<&str>, name: &str, alias: Option<&str>) -> (Option<String>, String, Option<String>) {
+        (schema.map(String::from), name.to_string(), alias.map(String::from))
+    }
+
+    #[test]
+    fn test_tokenize_quotes_and_comments() {
+        let tokens = tokenize_sql("SELECT 'a''b', \"My Col\" -- note\nFROM t /* x */", DatabaseType::PostgreSQL);
+        let kinds: Vec<SqlTokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SqlTokenKind::Word,
+                SqlTokenKind::Literal,
+                SqlTokenKind::Punct,
+                SqlTokenKind::QuotedIdentifier,
+                SqlTokenKind::Comment,
+                SqlTokenKind::Word,
+                SqlTokenKind::Word,
+                SqlTokenKind::Comment,
+            ]
+        );
+        assert_eq!(tokens[3].text, "My Col");
+
+        // MySQL: double quotes are strings, backticks quote names, # starts a comment
+        let tokens = tokenize_sql("SELECT \"x\", `y` # c", DatabaseType::MySQL);
+        assert_eq!(tokens[1].kind, SqlTokenKind::Literal);
+        assert_eq!(tokens[3].kind, SqlTokenKind::QuotedIdentifier);
+        assert_eq!(tokens[4].kind, SqlTokenKind::Comment);
+    }
+
+    #[test]
+    fn test_table_references_with_aliases() {
+        let tokens = tokenize_sql(
+            "SELECT * FROM public.users u, orders AS o LEFT JOIN \"Line Items\" li ON li.order_id = o.id WHERE u.id = 1",
+            DatabaseType::PostgreSQL,
+        );
+        let tables: Vec<_> = table_references(&tokens)
+            .into_iter()
+            .map(|t| (t.schema, t.name, t.alias))
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                table(Some("public"), "users", Some("u")),
+                table(None, "orders", Some("o")),
+                table(None, "Line Items", Some("li")),
+            ]
+        );
+
+        let tokens = tokenize_sql("UPDATE accounts SET x = 1; INSERT INTO log (a) VALUES (1)", DatabaseType::MySQL);
+        let names: Vec<_> = table_references(&tokens).into_iter().map(|t| (t.name, t.alias)).collect();
+        assert_eq!(names, vec![("accounts".to_string(), None), ("log".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_context_tables_and_columns() {
+        assert_eq!(context("SELECT * FROM us|").kind, CompletionKind::Table { schema: None });
+        assert_eq!(context("SELECT * FROM us|").prefix, "us");
+        assert_eq!(context("SELECT * FROM a, |").kind, CompletionKind::Table { schema: None });
+        assert_eq!(
+            context("SELECT * FROM public.|").kind,
+            CompletionKind::Table { schema: Some("public".to_string()) }
+        );
+        assert_eq!(context("SELECT | FROM users").kind, CompletionKind::Column);
+        assert_eq!(context("SELECT * FROM users WHERE id = 1 AND na|").kind, CompletionKind::Column);
+        assert_eq!(context("SELECT * FROM users |").kind, CompletionKind::Keyword);
+        assert_eq!(context("SELECT count(|) FROM users").kind, CompletionKind::Column);
+        assert_eq!(context("|").kind, CompletionKind::Keyword);
+    }
+
+    #[test]
+    fn test_context_resolves_alias_after_cursor() {
+        let ctx = context("SELECT u.na| FROM users u");
+        assert_eq!(ctx.kind, CompletionKind::QualifiedColumn { qualifier: "u".to_string() });
+        assert_eq!(ctx.prefix, "na");
+        let table = ctx.tables.iter().find(|t| t.matches("u")).unwrap();
+        assert_eq!(table.name, "users");
+    }
+
+    #[test]
+    fn test_context_inside_literal_or_comment() {
+        assert_eq!(context("SELECT 'FROM |'").kind, CompletionKind::None);
+        assert_eq!(context("SELECT 1 -- FROM |").kind, CompletionKind::None);
+        assert_eq!(context("SELECT 'x' |").kind, CompletionKind::Column);
+    }
+
+    #[test]
+    fn test_join_conditions_from_foreign_keys() {
+        let ctx = context("SELECT * FROM orders o JOIN customers c ON |");
+        let CompletionKind::JoinCondition { table } = &ctx.kind else {
+            panic!("unexpected kind: {:?}", ctx.kind);
+        };
+        assert_eq!(table.name, "customers");
+
+        let order_keys = [ForeignKeyInfo {
+            name: "fk_orders_customer".to_string(),
+            columns: vec!["customer_id".to_string()],
+            referenced_schema: None,
+            referenced_table: "customers".to_string(),
+            referenced_columns: vec!["id".to_string()],
+        }];
+        let conditions = join_conditions(table, &ctx.tables, |t| {
+            if t.name == "orders" { &order_keys[..] } else { &[] }
+        });
+        assert_eq!(conditions, vec!["c.id = o.customer_id"]);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::executor::{ExecOptions, SqlResult};
+use crate::stream::QueryStream;
 use crate::types::{ SqlValue};
 use async_trait::async_trait;
 use one_core::storage::DbConnectionConfig;
@@ -36,7 +37,62 @@ pub trait DbConnection: Sync + Send {
     async fn execute(&self, script: &str, options: ExecOptions) -> Result<Vec<SqlResult>, DbError>;
     async fn query(&self, query: &str, params: Option<Vec<SqlValue>>, options: ExecOptions) -> Result<SqlResult, DbError>;
     
+    /// Run a single query and read its rows in batches of `batch_size` as they are requested,
+    /// instead of holding the whole result in memory.
+    /// Drivers without incremental reads fetch everything and hand it out in batches.
+    async fn query_stream(&self, query: &str, batch_size: usize) -> Result<QueryStream, DbError> {
+        match self.query(query, None, ExecOptions::default()).await? {
+            SqlResult::Query(result) => Ok(QueryStream::from_rows(result.columns, result.rows, batch_size)),
+            SqlResult::Exec(_) => Ok(QueryStream::from_rows(Vec::new(), Vec::new(), batch_size)),
+            SqlResult::Error(err) => Err(DbError::QueryError(err.message)),
+        }
+    }
+
+    /// Cheapest statement the server answers, used to check a pooled connection is still alive
+    fn ping_statement(&self) -> &'static str {
+        "SELECT 1"
+    }
+
+    /// Fails when the server can't be reached, drivers report a lost connection as a failed statement
     async fn ping(&self) -> Result<(), DbError> {
-        self.query("SELECT 1", None, ExecOptions::default()).await.map(|_| ())
+        match self.query(self.ping_statement(), None, ExecOptions::default()).await? {
+            SqlResult::Error(err) => Err(DbError::ConnectionError(err.message)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Open a transaction that stays open across `execute` calls until `commit` or `rollback`.
+    /// Only reliable on a pinned session, see `DatabasePlugin::create_session`.
+    async fn begin_transaction(&self) -> Result<(), DbError> {
+        self.run_transaction_statement("BEGIN").await
+    }
+
+    async fn commit(&self) -> Result<(), DbError> {
+        self.run_transaction_statement("COMMIT").await
+    }
+
+    async fn rollback(&self) -> Result<(), DbError> {
+        self.run_transaction_statement("ROLLBACK").await
+    }
+
+    /// Cancel whatever this connection is running, from another task.
+    /// The interrupted statement comes back as an error and the rest of the script is skipped,
+    /// so the caller still gets the results gathered before the cancellation.
+    async fn cancel_query(&self) -> Result<(), DbError> {
+        Err(DbError::QueryError("Cancelling queries is not supported for this database".to_string()))
+    }
+
+    /// Server session ids of the statements this connection is running, as listed by
+    /// `DatabasePlugin::list_server_sessions`. Empty while idle or when the driver doesn't track them.
+    fn running_server_ids(&self) -> Vec<u64> {
+        Vec::new()
+    }
+
+    /// Run a transaction control statement, turning a failed result into an error
+    async fn run_transaction_statement(&self, sql: &str) -> Result<(), DbError> {
+        match self.query(sql, None, ExecOptions::default()).await? {
+            SqlResult::Error(err) => Err(DbError::QueryError(err.message)),
+            _ => Ok(()),
+        }
     }
 }
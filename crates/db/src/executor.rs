@@ -1,5 +1,11 @@
+use std::ops::Range;
+
+use one_core::storage::DatabaseType;
 use serde::{Deserialize, Serialize};
 
+use crate::stream::QueryStream;
+use crate::types::{FieldType, SqlValue};
+
 /// Execution options for SQL script
 #[derive(Debug, Clone)]
 pub struct ExecOptions {
@@ -7,7 +13,8 @@ pub struct ExecOptions {
     pub stop_on_error: bool,
     /// Whether to wrap the entire script in a transaction
     pub transactional: bool,
-    /// Maximum number of rows to return for query results
+    /// Rows read up front for each query result. The statement itself is left untouched;
+    /// rows past this page are reported through `QueryResult::has_more` instead.
     pub max_rows: Option<usize>,
 }
 
@@ -38,12 +45,45 @@ pub enum SqlResult {
 pub struct QueryResult {
     /// Original SQL statement
     pub sql: String,
-    /// Column names
-    pub columns: Vec<String>,
-    /// Row data (each row is a vector of optional strings)
-    pub rows: Vec<Vec<Option<String>>>,
+    /// Result columns with their types
+    pub columns: Vec<ResultColumn>,
+    /// Row data as typed cells
+    pub rows: Vec<Vec<SqlValue>>,
     /// Execution time in milliseconds
     pub elapsed_ms: u128,
+    /// Whether the result has rows past `rows` that were not read up front
+    #[serde(default)]
+    pub has_more: bool,
+    /// The remaining rows, still readable when this was the last statement of the script.
+    /// Holds the connection until exhausted or closed.
+    #[serde(skip)]
+    pub more_rows: Option<QueryStream>,
+}
+
+impl QueryResult {
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|col| col.name.clone()).collect()
+    }
+}
+
+/// Column of a query result with the type reported by the driver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultColumn {
+    pub name: String,
+    /// Type name as reported by the driver, e.g. `VARCHAR` or `INT4`
+    pub db_type: String,
+    pub field_type: FieldType,
+}
+
+impl ResultColumn {
+    pub fn new(name: impl Into<String>, db_type: impl Into<String>) -> Self {
+        let db_type = db_type.into();
+        Self {
+            name: name.into(),
+            field_type: FieldType::from_db_type(&db_type),
+            db_type,
+        }
+    }
 }
 
 /// Execution result for non-query statements
@@ -59,6 +99,44 @@ pub struct ExecResult {
     pub message: Option<String>,
 }
 
+impl ExecResult {
+    /// Entry appended once a transactional script has been committed
+    pub fn transaction_committed(elapsed_ms: u128) -> Self {
+        Self {
+            sql: "COMMIT".to_string(),
+            rows_affected: 0,
+            elapsed_ms,
+            message: Some("Transaction committed".to_string()),
+        }
+    }
+
+    /// Entry appended when a transactional script is rolled back after a failed statement
+    pub fn transaction_rolled_back(elapsed_ms: u128) -> Self {
+        Self {
+            sql: "ROLLBACK".to_string(),
+            rows_affected: 0,
+            elapsed_ms,
+            message: Some("Transaction rolled back, no changes were applied".to_string()),
+        }
+    }
+
+    /// Whether this entry reports a rollback of the surrounding transaction
+    pub fn is_rollback(&self) -> bool {
+        self.sql.eq_ignore_ascii_case("ROLLBACK")
+    }
+}
+
+impl SqlResult {
+    /// The statement this result belongs to
+    pub fn sql(&self) -> &str {
+        match self {
+            SqlResult::Query(result) => &result.sql,
+            SqlResult::Exec(result) => &result.sql,
+            SqlResult::Error(error) => &error.sql,
+        }
+    }
+}
+
 /// Error information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SqlErrorInfo {
@@ -66,6 +144,125 @@ pub struct SqlErrorInfo {
     pub sql: String,
     /// Error message
     pub message: String,
+    /// Byte range of the statement in the executed script, see `SqlScriptSplitter::locate_errors`
+    #[serde(default)]
+    pub range: Option<Range<usize>>,
+    /// Line reported by the driver, 1-based within the statement
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Character position reported by the driver, 1-based within the statement
+    #[serde(default)]
+    pub position: Option<usize>,
+}
+
+impl SqlErrorInfo {
+    pub fn new(sql: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            message: message.into(),
+            range: None,
+            line: None,
+            position: None,
+        }
+    }
+
+    pub fn with_position(mut self, position: Option<usize>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Take the line number a driver wrote into the message after `marker`,
+    /// e.g. MySQL's `... near 'FORM t' at line 2`
+    pub fn with_line_from_message(mut self, marker: &str) -> Self {
+        self.line = self.message.rfind(marker).and_then(|i| {
+            let rest = &self.message[i + marker.len()..];
+            let digits = rest.bytes().take_while(|b| b.is_ascii_digit()).count();
+            rest[..digits].parse().ok().filter(|line| *line > 0)
+        });
+        self
+    }
+
+    /// Byte range of `script` to mark for this error: the reported token or line when the
+    /// driver gave one, otherwise the whole statement.
+    ///
+    /// The statement keeps the line breaks of the script, so lines map exactly; a comment
+    /// earlier on the same line shifts the column.
+    pub fn location(&self, script: &str) -> Option<Range<usize>> {
+        let range = self.range.clone()?;
+        let statement = script.get(range.clone())?;
+
+        let (line, column) = match (self.position, self.line) {
+            (Some(position), _) => {
+                let before: String = self.sql.chars().take(position.saturating_sub(1)).collect();
+                let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count());
+                (before.matches('\n').count(), Some(column))
+            }
+            (None, Some(line)) => (line.saturating_sub(1), None),
+            (None, None) => return Some(range),
+        };
+
+        let line_start = match line {
+            0 => Some(0),
+            _ => statement.match_indices('\n').nth(line - 1).map(|(i, _)| i + 1),
+        };
+        let Some(line_start) = line_start else {
+            return Some(range);
+        };
+        let line_end = statement[line_start..].find('\n').map_or(statement.len(), |i| line_start + i);
+        let text = statement[line_start..line_end].trim_end_matches('\r');
+
+        let token = column.and_then(|column| {
+            let (start, first) = text.char_indices().nth(column)?;
+            let len = if first.is_alphanumeric() || first == '_' {
+                text[start..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(text.len() - start)
+            } else {
+                first.len_utf8()
+            };
+            Some(start..start + len)
+        });
+        let marked = token.unwrap_or_else(|| {
+            let start = text.len() - text.trim_start().len();
+            start..text.trim_end().len().max(start)
+        });
+        if marked.is_empty() {
+            return Some(range);
+        }
+        Some(range.start + line_start + marked.start..range.start + line_start + marked.end)
+    }
+}
+
+/// Options for `SqlScriptSplitter::split_with`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplitOptions {
+    /// Dialect whose quoting, comment and block rules apply
+    pub dialect: DatabaseType,
+    /// Keep comments in the statement text. Comments before a statement belong to it.
+    pub keep_comments: bool,
+}
+
+impl SplitOptions {
+    pub fn new(dialect: DatabaseType) -> Self {
+        Self {
+            dialect,
+            keep_comments: false,
+        }
+    }
+
+    pub fn keep_comments(mut self, keep_comments: bool) -> Self {
+        self.keep_comments = keep_comments;
+        self
+    }
+}
+
+/// A statement cut out of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStatement {
+    /// Statement text, trimmed and without its delimiter
+    pub sql: String,
+    /// Byte range of the statement in the script, delimiter excluded
+    pub range: Range<usize>,
 }
 
 /// SQL script splitter
@@ -73,109 +270,512 @@ pub struct SqlScriptSplitter;
 
 impl SqlScriptSplitter {
     /// Split SQL script into individual statements
-    /// Handles string literals, comments, and multi-line statements
+    /// Handles string literals, comments, and multi-line statements.
+    /// Uses MySQL rules (`#` comments, backticks); see `split_for` for other databases.
     pub fn split(script: &str) -> Vec<String> {
-        let mut statements = Vec::new();
-        let mut current_statement = String::new();
-        let mut chars = script.chars().peekable();
-
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut in_backtick = false;
-        let mut in_line_comment = false;
-        let mut in_block_comment = false;
-
-        while let Some(ch) = chars.next() {
-            // Handle line comments (-- or #)
-            if !in_single_quote && !in_double_quote && !in_backtick && !in_block_comment {
-                if ch == '-' {
-                    if let Some(&next_ch) = chars.peek() {
-                        if next_ch == '-' {
-                            chars.next(); // consume the second '-'
-                            in_line_comment = true;
-                            continue;
-                        }
-                    }
-                } else if ch == '#' {
-                    in_line_comment = true;
-                    continue;
-                }
+        Self::split_for(script, DatabaseType::MySQL)
+    }
+
+    /// Split a script with the rules of `dialect`, dropping comments
+    pub fn split_for(script: &str, dialect: DatabaseType) -> Vec<String> {
+        Self::split_with(script, SplitOptions::new(dialect))
+            .into_iter()
+            .map(|statement| statement.sql)
+            .collect()
+    }
+
+    /// Split a script into statements that remember where they came from.
+    ///
+    /// Besides quotes and comments this understands MySQL `DELIMITER` commands and
+    /// conditional comments, PostgreSQL dollar quoting and `COPY ... FROM stdin` data,
+    /// `BEGIN ... END` blocks of routines, SQL Server `GO` batches and Oracle `/` lines.
+    pub fn split_with(script: &str, options: SplitOptions) -> Vec<ScriptStatement> {
+        let mut splitter = ScriptScanner::new(script, options);
+        splitter.run();
+        splitter.statements
+    }
+
+    /// Find the statement an editor cursor at byte `offset` refers to.
+    ///
+    /// A cursor inside a statement (or right after its text) picks that statement; a
+    /// cursor later on the same line picks the statement ending there, e.g. after `;`.
+    /// Between statements the next one is taken, after the last one the last one.
+    pub fn statement_at(script: &str, offset: usize, options: SplitOptions) -> Option<ScriptStatement> {
+        let offset = offset.min(script.len());
+        let statements = Self::split_with(script, options);
+
+        if let Some(statement) = statements
+            .iter()
+            .find(|statement| statement.range.start <= offset && offset <= statement.range.end)
+        {
+            return Some(statement.clone());
+        }
+
+        let previous = statements.iter().rev().find(|statement| statement.range.end < offset);
+        if let Some(previous) = previous {
+            if !script[previous.range.end..offset].contains('\n') {
+                return Some(previous.clone());
             }
+        }
 
-            // Handle end of line comment
-            if in_line_comment {
-                if ch == '\n' {
-                    in_line_comment = false;
-                    current_statement.push(ch);
-                }
+        statements
+            .iter()
+            .find(|statement| statement.range.start > offset)
+            .or(previous)
+            .cloned()
+    }
+
+    /// Record where each failed statement sits in `script`.
+    ///
+    /// Results come back in statement order and carry their statement's text, so each one is
+    /// matched to the next statement with the same text; entries added by the driver,
+    /// like the closing `COMMIT` of a transactional script, match nothing.
+    pub fn locate_errors(script: &str, dialect: DatabaseType, results: &mut [SqlResult]) {
+        let statements = Self::split_with(script, SplitOptions::new(dialect));
+        let mut next = 0;
+        for result in results.iter_mut() {
+            let Some(found) = statements[next..].iter().position(|statement| statement.sql == result.sql()) else {
+                continue;
+            };
+            next += found;
+            if let SqlResult::Error(error) = result {
+                error.range.get_or_insert_with(|| statements[next].range.clone());
+            }
+            next += 1;
+        }
+    }
+}
+
+/// How a statement is terminated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatementUnit {
+    /// Ends at a delimiter outside any `BEGIN ... END` block
+    Plain,
+    /// Routine body: `BEGIN ... END` blocks are tracked even where plain statements skip them
+    Routine,
+    /// PL/SQL unit: declarations end with `;`, so only the closing `END;` or a `/` line ends it
+    PlSql,
+    /// PL/SQL package or type: only a `/` line ends it
+    PlSqlSlash,
+    /// T-SQL module: the whole batch up to `GO`
+    Batch,
+}
+
+struct ScriptScanner<'a> {
+    script: &'a str,
+    bytes: &'a [u8],
+    dialect: DatabaseType,
+    keep_comments: bool,
+    pos: usize,
+    delimiter: String,
+    statements: Vec<ScriptStatement>,
+
+    // Statement being read
+    text: String,
+    start: Option<usize>,
+    code_start: Option<usize>,
+    end: usize,
+    words: Vec<String>,
+    unit: StatementUnit,
+    depth: usize,
+    seen_begin: bool,
+    // The previous word was END, so a CASE now closes rather than opens
+    after_end: bool,
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
+
+impl<'a> ScriptScanner<'a> {
+    fn new(script: &'a str, options: SplitOptions) -> Self {
+        Self {
+            script,
+            bytes: script.as_bytes(),
+            dialect: options.dialect,
+            keep_comments: options.keep_comments,
+            pos: 0,
+            delimiter: ";".to_string(),
+            statements: Vec::new(),
+            text: String::new(),
+            start: None,
+            code_start: None,
+            end: 0,
+            words: Vec::new(),
+            unit: StatementUnit::Plain,
+            depth: 0,
+            seen_begin: false,
+            after_end: false,
+        }
+    }
+
+    fn run(&mut self) {
+        while self.pos < self.bytes.len() {
+            if (self.pos == 0 || self.bytes[self.pos - 1] == b'\n') && self.line_command() {
+                continue;
+            }
+            if self.delimiter != ";" && self.script[self.pos..].starts_with(self.delimiter.as_str()) {
+                self.pos += self.delimiter.len();
+                self.finish();
                 continue;
             }
 
-            // Handle block comments (/* ... */)
-            if !in_single_quote && !in_double_quote && !in_backtick {
-                if ch == '/' {
-                    if let Some(&next_ch) = chars.peek() {
-                        if next_ch == '*' {
-                            chars.next(); // consume the '*'
-                            in_block_comment = true;
-                            continue;
-                        }
+            let b = self.bytes[self.pos];
+            let next = self.bytes.get(self.pos + 1).copied();
+            match b {
+                b'\'' => self.quoted(b'\'', self.string_escapes()),
+                b'"' => self.quoted(b'"', self.dialect == DatabaseType::MySQL),
+                b'`' if matches!(self.dialect, DatabaseType::MySQL | DatabaseType::SQLite) => self.quoted(b'`', false),
+                b'[' if matches!(self.dialect, DatabaseType::MSSQL | DatabaseType::SQLite) => self.quoted(b']', false),
+                b'$' if self.dialect == DatabaseType::PostgreSQL && self.dollar_quote() => {}
+                b'-' if next == Some(b'-') && self.is_dash_comment() => self.line_comment(),
+                b'#' if self.dialect == DatabaseType::MySQL => self.line_comment(),
+                b'/' if next == Some(b'*') => self.block_comment(),
+                b';' if self.delimiter == ";" && self.ends_at_semicolon() => {
+                    // PL/SQL blocks are only valid with the `;` after their END
+                    if self.unit == StatementUnit::PlSql {
+                        self.code(self.pos, self.pos + 1);
+                    } else {
+                        self.pos += 1;
+                    }
+                    if self.finish() && self.dialect == DatabaseType::PostgreSQL {
+                        self.copy_data();
                     }
                 }
+                b if b.is_ascii_whitespace() => {
+                    if self.start.is_some() && !self.keep_comments {
+                        self.text.push(b as char);
+                    }
+                    self.pos += 1;
+                }
+                b if is_word_byte(b) && b != b'$' => self.word(),
+                _ => self.code(self.pos, self.pos + 1),
             }
+        }
+        self.finish();
+    }
 
-            if in_block_comment {
-                if ch == '*' {
-                    if let Some(&next_ch) = chars.peek() {
-                        if next_ch == '/' {
-                            chars.next(); // consume the '/'
-                            in_block_comment = false;
-                            continue;
-                        }
+    /// Client commands that take a whole line: `DELIMITER`, `GO`, `/` and psql meta-commands
+    fn line_command(&mut self) -> bool {
+        let line_end = self.script[self.pos..].find('\n').map_or(self.bytes.len(), |i| self.pos + i);
+        let line = self.script[self.pos..line_end].trim();
+        let next_line = (line_end + 1).min(self.bytes.len());
+
+        match self.dialect {
+            DatabaseType::MySQL if self.code_start.is_none() => {
+                let mut parts = line.split_whitespace();
+                match (parts.next(), parts.next()) {
+                    (Some(cmd), Some(delimiter)) if cmd.eq_ignore_ascii_case("DELIMITER") => {
+                        self.delimiter = delimiter.to_string();
+                        self.pos = next_line;
+                        true
                     }
+                    _ => false,
                 }
-                continue;
             }
+            DatabaseType::MSSQL => {
+                let mut parts = line.split_whitespace();
+                let is_go = parts.next().is_some_and(|w| w.eq_ignore_ascii_case("GO"))
+                    && parts.all(|w| w.bytes().all(|b| b.is_ascii_digit()));
+                if is_go {
+                    self.pos = next_line;
+                    self.finish();
+                }
+                is_go
+            }
+            DatabaseType::Oracle if line == "/" => {
+                self.pos = next_line;
+                self.finish();
+                true
+            }
+            DatabaseType::PostgreSQL if self.code_start.is_none() && line.starts_with('\\') => {
+                let line_start = self.pos + (self.script[self.pos..line_end].len() - self.script[self.pos..line_end].trim_start().len());
+                self.code(line_start, line_start + line.len());
+                self.pos = next_line;
+                self.finish();
+                true
+            }
+            _ => false,
+        }
+    }
 
-            // Handle string literals
-            if ch == '\'' && !in_double_quote && !in_backtick {
-                in_single_quote = !in_single_quote;
-                current_statement.push(ch);
-                continue;
+    fn string_escapes(&self) -> bool {
+        match self.dialect {
+            DatabaseType::MySQL => true,
+            // E'...' strings
+            DatabaseType::PostgreSQL => {
+                self.pos > 0
+                    && matches!(self.bytes[self.pos - 1], b'E' | b'e')
+                    && (self.pos < 2 || !is_word_byte(self.bytes[self.pos - 2]))
             }
+            _ => false,
+        }
+    }
 
-            if ch == '"' && !in_single_quote && !in_backtick {
-                in_double_quote = !in_double_quote;
-                current_statement.push(ch);
+    /// Quoted string or identifier; a doubled quote stays inside it
+    fn quoted(&mut self, close: u8, backslash_escapes: bool) {
+        let from = self.pos;
+        let mut i = self.pos + 1;
+        while i < self.bytes.len() {
+            let b = self.bytes[i];
+            if backslash_escapes && b == b'\\' {
+                i += 2;
                 continue;
             }
+            if b == close {
+                if self.bytes.get(i + 1) == Some(&close) {
+                    i += 2;
+                    continue;
+                }
+                i += 1;
+                break;
+            }
+            i += 1;
+        }
+        let to = i.min(self.bytes.len());
+        self.code(from, to);
+    }
 
-            if ch == '`' && !in_single_quote && !in_double_quote {
-                in_backtick = !in_backtick;
-                current_statement.push(ch);
-                continue;
+    /// `$$ ... $$` or `$tag$ ... $tag$`; false when the `$` starts no quote
+    fn dollar_quote(&mut self) -> bool {
+        let from = self.pos;
+        let tag_len = self.bytes[from + 1..]
+            .iter()
+            .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+            .unwrap_or(self.bytes.len() - from - 1);
+        let tag_end = from + 1 + tag_len;
+        let valid_tag = self.bytes.get(tag_end) == Some(&b'$')
+            && !self.bytes.get(from + 1).is_some_and(|b| b.is_ascii_digit());
+        if !valid_tag {
+            return false;
+        }
+
+        let tag = &self.script[from..=tag_end];
+        let to = self.script[tag_end + 1..]
+            .find(tag)
+            .map_or(self.bytes.len(), |i| tag_end + 1 + i + tag.len());
+        self.code(from, to);
+        true
+    }
+
+    /// MySQL wants whitespace after `--`
+    fn is_dash_comment(&self) -> bool {
+        self.dialect != DatabaseType::MySQL
+            || self.bytes.get(self.pos + 2).is_none_or(|b| b.is_ascii_whitespace())
+    }
+
+    fn line_comment(&mut self) {
+        let from = self.pos;
+        let to = self.script[from..].find('\n').map_or(self.bytes.len(), |i| from + i);
+        self.comment(from, to);
+    }
+
+    fn block_comment(&mut self) {
+        let from = self.pos;
+        // MySQL runs /*! ... */ and reads optimizer hints from /*+ ... */
+        let executable = self.dialect == DatabaseType::MySQL
+            && matches!(self.bytes.get(from + 2), Some(b'!') | Some(b'+'));
+        let nested = self.dialect == DatabaseType::PostgreSQL;
+
+        let mut depth = 0;
+        let mut i = from;
+        let mut to = self.bytes.len();
+        while i + 1 < self.bytes.len() {
+            match (self.bytes[i], self.bytes[i + 1]) {
+                (b'/', b'*') if depth == 0 || nested => {
+                    depth += 1;
+                    i += 2;
+                }
+                (b'*', b'/') => {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        to = i;
+                        break;
+                    }
+                }
+                _ => i += 1,
             }
+        }
+
+        if executable {
+            self.code(from, to);
+        } else {
+            self.comment(from, to);
+        }
+    }
+
+    fn word(&mut self) {
+        let from = self.pos;
+        let to = self.bytes[from..]
+            .iter()
+            .position(|&b| !is_word_byte(b))
+            .map_or(self.bytes.len(), |i| from + i);
+        self.code(from, to);
+
+        let word = self.script[from..to].to_ascii_uppercase();
+        if self.words.len() < 8 {
+            self.words.push(word.clone());
+            self.detect_unit();
+        }
 
-            // Handle semicolon (statement separator)
-            if ch == ';' && !in_single_quote && !in_double_quote && !in_backtick {
-                let trimmed = current_statement.trim();
-                if !trimmed.is_empty() {
-                    statements.push(trimmed.to_string());
+        let tracks_blocks = matches!(self.dialect, DatabaseType::MSSQL | DatabaseType::Oracle)
+            || self.unit != StatementUnit::Plain;
+        let after_end = std::mem::take(&mut self.after_end);
+        if !tracks_blocks || (after_end && word == "CASE") {
+            return;
+        }
+        match word.as_str() {
+            "BEGIN" if self.opens_block() => {
+                self.depth += 1;
+                self.seen_begin = true;
+            }
+            "CASE" if self.depth > 0 => self.depth += 1,
+            "END" => {
+                let closes_other = matches!(self.next_word().as_str(), "IF" | "LOOP" | "WHILE" | "REPEAT" | "FOR");
+                if !closes_other {
+                    self.depth = self.depth.saturating_sub(1);
                 }
-                current_statement.clear();
-                continue;
+                self.after_end = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Decide how the statement ends from its leading keywords
+    fn detect_unit(&mut self) {
+        let first = self.words[0].as_str();
+        let has = |names: &[&str]| self.words.iter().any(|w| names.contains(&w.as_str()));
+        self.unit = match self.dialect {
+            DatabaseType::Oracle if first == "DECLARE" || first == "BEGIN" => StatementUnit::PlSql,
+            DatabaseType::Oracle if first == "CREATE" && has(&["PACKAGE", "TYPE"]) => StatementUnit::PlSqlSlash,
+            DatabaseType::Oracle if first == "CREATE" && has(&["PROCEDURE", "FUNCTION", "TRIGGER"]) => {
+                StatementUnit::PlSql
+            }
+            DatabaseType::MSSQL
+                if matches!(first, "CREATE" | "ALTER") && has(&["PROCEDURE", "PROC", "FUNCTION", "TRIGGER", "VIEW"]) =>
+            {
+                StatementUnit::Batch
+            }
+            DatabaseType::MySQL | DatabaseType::PostgreSQL | DatabaseType::SQLite
+                if first == "CREATE" && has(&["PROCEDURE", "FUNCTION", "TRIGGER", "EVENT"]) =>
+            {
+                StatementUnit::Routine
+            }
+            _ => StatementUnit::Plain,
+        };
+    }
+
+    /// `BEGIN` opens a block unless it starts a transaction
+    fn opens_block(&self) -> bool {
+        if self.dialect == DatabaseType::Oracle {
+            return true;
+        }
+        let rest = self.script[self.pos..].trim_start();
+        if rest.is_empty() || rest.starts_with(';') {
+            return false;
+        }
+        !matches!(
+            self.next_word().as_str(),
+            "TRAN" | "TRANSACTION" | "WORK" | "DISTRIBUTED" | "ISOLATION" | "DEFERRED" | "IMMEDIATE" | "EXCLUSIVE"
+        )
+    }
+
+    fn next_word(&self) -> String {
+        let rest = self.script[self.pos..].trim_start();
+        let len = rest.bytes().position(|b| !is_word_byte(b)).unwrap_or(rest.len());
+        rest[..len].to_ascii_uppercase()
+    }
+
+    fn ends_at_semicolon(&self) -> bool {
+        match self.unit {
+            StatementUnit::Batch | StatementUnit::PlSqlSlash => false,
+            StatementUnit::PlSql => self.seen_begin && self.depth == 0,
+            StatementUnit::Plain | StatementUnit::Routine => self.depth == 0,
+        }
+    }
+
+    /// Rows following `COPY ... FROM stdin;` up to the `\.` line belong to the statement
+    fn copy_data(&mut self) {
+        let Some(last) = self.statements.last_mut() else {
+            return;
+        };
+        let upper = last.sql.to_ascii_uppercase();
+        if !(upper.starts_with("COPY ") && upper.contains("FROM STDIN")) {
+            return;
+        }
+
+        let data_start = self.script[self.pos..].find('\n').map_or(self.bytes.len(), |i| self.pos + i + 1);
+        let mut line_start = data_start;
+        let mut data_end = self.bytes.len();
+        while line_start < self.bytes.len() {
+            let line_end = self.script[line_start..].find('\n').map_or(self.bytes.len(), |i| line_start + i);
+            if self.script[line_start..line_end].trim_end_matches('\r') == "\\." {
+                data_end = line_end;
+                break;
             }
+            line_start = line_end + 1;
+        }
+
+        last.sql.push_str(";\n");
+        last.sql.push_str(&self.script[data_start..data_end]);
+        last.range.end = data_end;
+        self.pos = data_end;
+    }
 
-            current_statement.push(ch);
+    fn code(&mut self, from: usize, to: usize) {
+        self.start.get_or_insert(from);
+        self.code_start.get_or_insert(from);
+        self.end = to;
+        if !self.keep_comments {
+            self.text.push_str(&self.script[from..to]);
         }
+        self.pos = to;
+    }
 
-        // Add the last statement if it's not empty
-        let trimmed = current_statement.trim();
-        if !trimmed.is_empty() {
-            statements.push(trimmed.to_string());
+    fn comment(&mut self, from: usize, to: usize) {
+        if self.keep_comments {
+            self.start.get_or_insert(from);
+            self.end = to;
+        } else if self.start.is_some() {
+            self.text.push(' ');
         }
+        self.pos = to;
+    }
 
-        statements
+    /// Close the statement being read; false when it held no code
+    fn finish(&mut self) -> bool {
+        let Some(code_start) = self.code_start else {
+            self.reset();
+            return false;
+        };
+
+        let statement = if self.keep_comments {
+            let start = self.start.unwrap_or(code_start);
+            ScriptStatement {
+                sql: self.script[start..self.end].to_string(),
+                range: start..self.end,
+            }
+        } else {
+            ScriptStatement {
+                sql: self.text.trim().to_string(),
+                range: code_start..self.end,
+            }
+        };
+        self.statements.push(statement);
+        self.reset();
+        true
+    }
+
+    fn reset(&mut self) {
+        self.text.clear();
+        self.start = None;
+        self.code_start = None;
+        self.words.clear();
+        self.unit = StatementUnit::Plain;
+        self.depth = 0;
+        self.seen_begin = false;
+        self.after_end = false;
     }
 }
 
@@ -195,6 +795,7 @@ impl SqlStatementClassifier {
             || trimmed.starts_with("EXPLAIN")
             || trimmed.starts_with("WITH") // CTE
             || trimmed.starts_with("TABLE") // PostgreSQL TABLE command
+            || trimmed.starts_with("PRAGMA") // SQLite PRAGMA returns rows
     }
 
     /// Determine the statement category
@@ -242,6 +843,25 @@ impl SqlStatementClassifier {
         StatementType::Exec
     }
 
+    /// Detect statements that open or close a transaction on the current session.
+    /// `BEGIN ... END` blocks and savepoint statements are not transaction boundaries.
+    pub fn transaction_control(sql: &str) -> Option<TransactionControl> {
+        let upper = sql.trim().trim_end_matches(';').to_uppercase();
+        let words: Vec<&str> = upper.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["BEGIN"] | ["START", "TRANSACTION", ..] => Some(TransactionControl::Begin),
+            ["BEGIN", next, ..]
+                if matches!(*next, "WORK" | "TRANSACTION" | "TRAN" | "DEFERRED" | "IMMEDIATE" | "EXCLUSIVE" | "ISOLATION") =>
+            {
+                Some(TransactionControl::Begin)
+            }
+            ["COMMIT", ..] | ["END"] | ["END", "WORK" | "TRANSACTION"] => Some(TransactionControl::Commit),
+            ["ROLLBACK", rest @ ..] if !rest.contains(&"TO") => Some(TransactionControl::Rollback),
+            _ => None,
+        }
+    }
+
     /// Format execution message based on query type
     pub fn format_message(sql: &str, rows_affected: u64) -> String {
         let trimmed = sql.trim().to_uppercase();
@@ -280,6 +900,14 @@ impl SqlStatementClassifier {
     }
 }
 
+/// Effect of a transaction control statement on the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionControl {
+    Begin,
+    Commit,
+    Rollback,
+}
+
 /// Statement type classification
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StatementType {
@@ -367,6 +995,124 @@ mod tests {
         assert_eq!(statements[0], "SELECT * FROM users");
     }
 
+    #[test]
+    fn test_split_mysql_delimiter_blocks() {
+        let script = "DROP PROCEDURE IF EXISTS p;\n\
+                      DELIMITER //\n\
+                      CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND //\n\
+                      DELIMITER ;\n\
+                      CALL p();";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::MySQL);
+
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "DROP PROCEDURE IF EXISTS p");
+        assert_eq!(statements[1], "CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND");
+        assert_eq!(statements[2], "CALL p()");
+    }
+
+    #[test]
+    fn test_split_routine_without_delimiter() {
+        let script = "CREATE TRIGGER t AFTER INSERT ON a BEGIN UPDATE b SET n = n + 1; DELETE FROM c; END; SELECT 1;";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::SQLite);
+
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("DELETE FROM c; END"));
+        assert_eq!(statements[1], "SELECT 1");
+
+        let script = "CREATE PROCEDURE p() BEGIN IF x THEN SELECT 1; END IF; CASE y WHEN 1 THEN SELECT 2; END CASE; END; BEGIN; COMMIT;";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::MySQL);
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[1], "BEGIN");
+    }
+
+    #[test]
+    fn test_split_mysql_dump() {
+        let script = "/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */;\n\
+                      -- Dumping data for table `t`\n\
+                      INSERT INTO `t` VALUES (1,'it\\'s; fine'),(2,'#not a comment');\n\
+                      DELIMITER ;;\n\
+                      /*!50003 CREATE*/ /*!50003 TRIGGER trg BEFORE INSERT ON t FOR EACH ROW BEGIN SET NEW.a = 1; END */;;\n\
+                      DELIMITER ;\n";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::MySQL);
+
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */");
+        assert_eq!(statements[1], "INSERT INTO `t` VALUES (1,'it\\'s; fine'),(2,'#not a comment')");
+        assert!(statements[2].ends_with("SET NEW.a = 1; END */"));
+    }
+
+    #[test]
+    fn test_split_postgres_dollar_quotes_and_operators() {
+        let script = "CREATE FUNCTION f() RETURNS int AS $body$\nBEGIN\n  RETURN 1;\nEND;\n$body$ LANGUAGE plpgsql;\n\
+                      SELECT '{1}'::int[] # 2, E'a\\';b';\n\
+                      DO $$ BEGIN PERFORM 1; END $$;";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::PostgreSQL);
+
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].ends_with("$body$ LANGUAGE plpgsql"));
+        assert_eq!(statements[1], "SELECT '{1}'::int[] # 2, E'a\\';b'");
+        assert_eq!(statements[2], "DO $$ BEGIN PERFORM 1; END $$");
+    }
+
+    #[test]
+    fn test_split_pg_dump() {
+        let script = "SET client_encoding = 'UTF8';\n\
+                      COPY public.t (id, name) FROM stdin;\n\
+                      1\tsemi;colon\n\
+                      2\t'quote\n\
+                      \\.\n\
+                      \n\
+                      SELECT pg_catalog.setval('public.t_id_seq', 2, true);\n";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::PostgreSQL);
+
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[1], "COPY public.t (id, name) FROM stdin;\n1\tsemi;colon\n2\t'quote\n\\.");
+        assert!(statements[2].starts_with("SELECT pg_catalog.setval"));
+    }
+
+    #[test]
+    fn test_split_tsql_batches() {
+        let script = "CREATE PROCEDURE p AS\nSELECT 1;\nSELECT 2;\nGO\n\
+                      IF 1 = 1\nBEGIN\n  SELECT [a;b] FROM #tmp;\n  SELECT 3;\nEND;\n\
+                      BEGIN TRAN;\n\
+                      BEGIN TRY SELECT 1; END TRY BEGIN CATCH SELECT 2; END CATCH;";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::MSSQL);
+
+        assert_eq!(statements.len(), 4);
+        assert_eq!(statements[0], "CREATE PROCEDURE p AS\nSELECT 1;\nSELECT 2;");
+        assert!(statements[1].starts_with("IF 1 = 1") && statements[1].ends_with("END"));
+        assert_eq!(statements[2], "BEGIN TRAN");
+        assert!(statements[3].ends_with("END CATCH"));
+    }
+
+    #[test]
+    fn test_split_plsql_units() {
+        let script = "DECLARE\n  n NUMBER;\nBEGIN\n  SELECT 1 INTO n FROM dual;\nEND;\n/\n\
+                      CREATE OR REPLACE PACKAGE BODY pkg AS\n  PROCEDURE a IS BEGIN NULL; END a;\nEND pkg;\n/\n\
+                      SELECT 1 / 2 FROM dual;";
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::Oracle);
+
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].starts_with("DECLARE") && statements[0].ends_with("END;"));
+        assert!(statements[1].ends_with("END pkg;"));
+        assert_eq!(statements[2], "SELECT 1 / 2 FROM dual");
+    }
+
+    #[test]
+    fn test_split_keeps_offsets_and_comments() {
+        let script = "-- first\nSELECT 1; /* second */ SELECT\n  2;";
+        let statements = SqlScriptSplitter::split_with(script, SplitOptions::new(DatabaseType::PostgreSQL));
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].sql, "SELECT 1");
+        assert_eq!(&script[statements[0].range.clone()], "SELECT 1");
+        assert_eq!(&script[statements[1].range.clone()], "SELECT\n  2");
+
+        let statements = SqlScriptSplitter::split_with(script, SplitOptions::new(DatabaseType::PostgreSQL).keep_comments(true));
+        assert_eq!(statements[0].sql, "-- first\nSELECT 1");
+        assert_eq!(statements[1].sql, "/* second */ SELECT\n  2");
+        assert_eq!(statements[1].range.start, script.find("/*").unwrap());
+    }
+
     #[test]
     fn test_classify_query_statements() {
         assert!(SqlStatementClassifier::is_query_statement("SELECT * FROM users"));
@@ -375,6 +1121,7 @@ mod tests {
         assert!(SqlStatementClassifier::is_query_statement("DESCRIBE users"));
         assert!(SqlStatementClassifier::is_query_statement("EXPLAIN SELECT * FROM users"));
         assert!(SqlStatementClassifier::is_query_statement("WITH cte AS (SELECT 1) SELECT * FROM cte"));
+        assert!(SqlStatementClassifier::is_query_statement("PRAGMA table_info(users)"));
     }
 
     #[test]
@@ -394,4 +1141,96 @@ mod tests {
         assert_eq!(SqlStatementClassifier::classify("BEGIN"), StatementType::Transaction);
         assert_eq!(SqlStatementClassifier::classify("USE mydb"), StatementType::Command);
     }
+
+    #[test]
+    fn test_transaction_control() {
+        use TransactionControl::*;
+
+        assert_eq!(SqlStatementClassifier::transaction_control("BEGIN"), Some(Begin));
+        assert_eq!(SqlStatementClassifier::transaction_control("begin transaction;"), Some(Begin));
+        assert_eq!(SqlStatementClassifier::transaction_control("START TRANSACTION READ ONLY"), Some(Begin));
+        assert_eq!(SqlStatementClassifier::transaction_control("COMMIT WORK"), Some(Commit));
+        assert_eq!(SqlStatementClassifier::transaction_control("END"), Some(Commit));
+        assert_eq!(SqlStatementClassifier::transaction_control("ROLLBACK"), Some(Rollback));
+        assert_eq!(SqlStatementClassifier::transaction_control("ROLLBACK TO SAVEPOINT a"), None);
+        assert_eq!(SqlStatementClassifier::transaction_control("BEGIN NULL; END"), None);
+        assert_eq!(SqlStatementClassifier::transaction_control("SAVEPOINT a"), None);
+        assert_eq!(SqlStatementClassifier::transaction_control("SELECT 1"), None);
+    }
+
+    #[test]
+    fn test_statement_at_cursor() {
+        let script = "SELECT 1;\nSELECT 2; -- two\n\nSELECT 3";
+        let options = SplitOptions::new(DatabaseType::MySQL);
+        let at = |offset: usize| SqlScriptSplitter::statement_at(script, offset, options).map(|s| s.sql);
+
+        assert_eq!(at(0).as_deref(), Some("SELECT 1"));
+        assert_eq!(at(8).as_deref(), Some("SELECT 1"));
+        // after the delimiter on the same line
+        assert_eq!(at(9).as_deref(), Some("SELECT 1"));
+        assert_eq!(at(script.find("-- two").unwrap()).as_deref(), Some("SELECT 2"));
+        // blank line between statements
+        assert_eq!(at(script.find("\n\n").unwrap() + 1).as_deref(), Some("SELECT 3"));
+        assert_eq!(at(script.len()).as_deref(), Some("SELECT 3"));
+        assert_eq!(at(script.len() + 10).as_deref(), Some("SELECT 3"));
+
+        let trailing = "SELECT 1;\n\n";
+        assert_eq!(
+            SqlScriptSplitter::statement_at(trailing, trailing.len(), options).map(|s| s.sql).as_deref(),
+            Some("SELECT 1")
+        );
+        assert_eq!(SqlScriptSplitter::statement_at("  -- nothing", 3, options), None);
+    }
+
+    #[test]
+    fn test_locate_errors() {
+        let script = "INSERT INTO t VALUES ('a;b');\nSELECT * FORM t;\nSELECT 1";
+        let mut results = vec![
+            SqlResult::Exec(ExecResult {
+                sql: "INSERT INTO t VALUES ('a;b')".to_string(),
+                rows_affected: 1,
+                elapsed_ms: 0,
+                message: None,
+            }),
+            SqlResult::Error(SqlErrorInfo::new("SELECT * FORM t", "syntax error")),
+            SqlResult::Error(SqlErrorInfo::new("ROLLBACK", "not in a transaction")),
+        ];
+        SqlScriptSplitter::locate_errors(script, DatabaseType::MySQL, &mut results);
+
+        let SqlResult::Error(error) = &results[1] else { unreachable!() };
+        assert_eq!(&script[error.range.clone().unwrap()], "SELECT * FORM t");
+        let SqlResult::Error(error) = &results[2] else { unreachable!() };
+        assert_eq!(error.range, None);
+    }
+
+    #[test]
+    fn test_error_location() {
+        let script = "SELECT 1;\nSELECT *\n  FORM t;";
+        let start = script.find("SELECT *").unwrap();
+        let end = script.len() - 1;
+        let error = |sql: &str, message: &str| SqlErrorInfo {
+            range: Some(start..end),
+            ..SqlErrorInfo::new(sql, message)
+        };
+        let sql = "SELECT *\n  FORM t";
+
+        // Nothing reported: the whole statement
+        assert_eq!(error(sql, "failed").location(script), Some(start..end));
+
+        // MySQL reports the line
+        let by_line = error(sql, "1064 (42000): ... near 'FORM t' at line 2").with_line_from_message(" at line ");
+        assert_eq!(by_line.line, Some(2));
+        assert_eq!(by_line.location(script).map(|r| &script[r]), Some("FORM t"));
+
+        // PostgreSQL reports the character position
+        let by_position = error(sql, "syntax error at or near \"FORM\"").with_position(Some(12));
+        assert_eq!(by_position.location(script).map(|r| &script[r]), Some("FORM"));
+
+        // A line past the statement falls back to the statement
+        let past_end = SqlErrorInfo { line: Some(9), ..error(sql, "failed") };
+        assert_eq!(past_end.location(script), Some(start..end));
+
+        assert_eq!(SqlErrorInfo::new(sql, "failed").location(script), None);
+        assert_eq!(SqlErrorInfo::new(sql, "at line x").with_line_from_message(" at line ").line, None);
+    }
 }
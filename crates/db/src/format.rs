@@ -0,0 +1,665 @@
+use one_core::storage::{CommaStyle, DatabaseType, KeywordCase, SqlFormatOptions};
+
+use crate::completion::{tokenize_sql, SqlToken, SqlTokenKind};
+use crate::executor::{SplitOptions, SqlScriptSplitter};
+
+/// Words written in the configured keyword case. Names that are common column names
+/// (`name`, `type`, `date`, ...) are left out so identifiers keep their case.
+const FORMAT_KEYWORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "AND", "ANY", "APPLY", "AS", "ASC", "BETWEEN", "BY", "CASCADE", "CASE",
+    "CAST", "CHECK", "COLUMN", "CONFLICT", "CONSTRAINT", "CREATE", "CROSS", "DATABASE", "DEFAULT",
+    "DELETE", "DESC", "DISTINCT", "DO", "DROP", "DUPLICATE", "ELSE", "END", "EXCEPT", "EXISTS",
+    "FETCH", "FIRST", "FOR", "FOREIGN", "FROM", "FULL", "GROUP", "HAVING", "IF", "ILIKE", "IN",
+    "INDEX", "INNER", "INSERT", "INTERSECT", "INTO", "IS", "JOIN", "KEY", "LATERAL", "LEFT", "LIKE",
+    "LIMIT", "MINUS", "NATURAL", "NEXT", "NOT", "NOTHING", "NULL", "OFFSET", "ON", "ONLY", "OR",
+    "ORDER", "OUTER", "OVER", "PARTITION", "PRIMARY", "QUALIFY", "RECURSIVE", "REFERENCES",
+    "REPLACE", "RETURNING", "RIGHT", "ROWS", "SCHEMA", "SELECT", "SET", "TABLE", "THEN", "TIES",
+    "TOP", "TRUNCATE", "UNION", "UNIQUE", "UPDATE", "USING", "VALUES", "VIEW", "WHEN", "WHERE",
+    "WINDOW", "WITH",
+];
+
+/// Keywords that start a clause on its own line, longest first
+const CLAUSES: &[&[&str]] = &[
+    &["ON", "DUPLICATE", "KEY", "UPDATE"],
+    &["LEFT", "OUTER", "JOIN"],
+    &["RIGHT", "OUTER", "JOIN"],
+    &["FULL", "OUTER", "JOIN"],
+    &["NATURAL", "LEFT", "JOIN"],
+    &["GROUP", "BY"],
+    &["ORDER", "BY"],
+    &["UNION", "ALL"],
+    &["UNION", "DISTINCT"],
+    &["INSERT", "INTO"],
+    &["REPLACE", "INTO"],
+    &["DELETE", "FROM"],
+    &["LEFT", "JOIN"],
+    &["RIGHT", "JOIN"],
+    &["FULL", "JOIN"],
+    &["INNER", "JOIN"],
+    &["CROSS", "JOIN"],
+    &["NATURAL", "JOIN"],
+    &["CROSS", "APPLY"],
+    &["OUTER", "APPLY"],
+    &["ON", "CONFLICT"],
+    &["FOR", "UPDATE"],
+    &["SELECT"],
+    &["FROM"],
+    &["WHERE"],
+    &["HAVING"],
+    &["WINDOW"],
+    &["QUALIFY"],
+    &["LIMIT"],
+    &["OFFSET"],
+    &["FETCH"],
+    &["UNION"],
+    &["EXCEPT"],
+    &["INTERSECT"],
+    &["MINUS"],
+    &["VALUES"],
+    &["SET"],
+    &["RETURNING"],
+    &["JOIN"],
+    &["STRAIGHT_JOIN"],
+    &["WITH"],
+    &["INSERT"],
+    &["UPDATE"],
+    &["DELETE"],
+];
+
+/// Clauses that begin a statement; only the first one after an optional WITH counts,
+/// so `FOR UPDATE` or `ON DELETE` further on are not taken for a new statement
+const VERBS: &[&str] = &["WITH", "INSERT", "REPLACE", "UPDATE", "DELETE"];
+
+/// Pretty-print a script statement by statement. Text between statements (comments,
+/// `DELIMITER` lines, `GO`) is kept; routine bodies are left as written.
+pub fn format_sql(script: &str, dialect: DatabaseType, options: &SqlFormatOptions) -> String {
+    let statements = SqlScriptSplitter::split_with(script, SplitOptions::new(dialect));
+    if statements.is_empty() {
+        return script.to_string();
+    }
+
+    let mut out = String::new();
+    let mut last = 0;
+    for (i, statement) in statements.iter().enumerate() {
+        let between = &script[last..statement.range.start];
+        if i == 0 {
+            out.push_str(between);
+        } else {
+            match between.trim() {
+                "" => out.push_str("\n\n"),
+                ";" => out.push_str(";\n\n"),
+                _ => out.push_str(between),
+            }
+        }
+        out.push_str(&format_statement(&script[statement.range.clone()], dialect, options));
+        last = statement.range.end;
+    }
+
+    let rest = &script[last..];
+    match rest.trim() {
+        "" | ";" => {
+            out.push_str(rest.trim());
+            if script.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+        _ => out.push_str(rest),
+    }
+    out
+}
+
+fn format_statement(sql: &str, dialect: DatabaseType, options: &SqlFormatOptions) -> String {
+    let tokens = tokenize_sql(sql, dialect);
+    if tokens.iter().any(|t| t.is_keyword("BEGIN") || t.is_keyword("DECLARE")) {
+        return sql.trim().to_string();
+    }
+
+    let mut parser = Parser {
+        sql,
+        tokens: &tokens,
+        pos: 0,
+        keyword_case: options.keyword_case,
+    };
+    let (nodes, _) = parser.nodes(Stop::Eof);
+
+    let mut writer = Writer {
+        options,
+        lines: Vec::new(),
+        line: String::new(),
+    };
+    write_statement(&mut writer, &nodes, 0);
+    writer.finish()
+}
+
+#[derive(Debug)]
+enum Node {
+    Token {
+        text: String,
+        line_comment: bool,
+        space_before: bool,
+    },
+    /// Parenthesized nodes
+    Group {
+        nodes: Vec<Node>,
+        closed: bool,
+        space_before: bool,
+    },
+    /// `CASE ... END`, `nodes` are the ones in between
+    Case {
+        case: String,
+        nodes: Vec<Node>,
+        end: Option<String>,
+        space_before: bool,
+    },
+}
+
+impl Node {
+    fn space_before(&self) -> bool {
+        match self {
+            Node::Token { space_before, .. } | Node::Group { space_before, .. } | Node::Case { space_before, .. } => {
+                *space_before
+            }
+        }
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self, Node::Token { text, .. } if text.eq_ignore_ascii_case(word))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stop {
+    Paren,
+    End,
+    Eof,
+}
+
+struct Parser<'a> {
+    sql: &'a str,
+    tokens: &'a [SqlToken],
+    pos: usize,
+    keyword_case: KeywordCase,
+}
+
+impl Parser<'_> {
+    /// Nodes up to `stop`, and the text of the token that closed them
+    fn nodes(&mut self, stop: Stop) -> (Vec<Node>, Option<String>) {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = self.tokens.get(self.pos) {
+            let space_before = self.pos > 0 && self.tokens[self.pos - 1].range.end < token.range.start;
+            self.pos += 1;
+
+            if stop == Stop::Paren && token.is_punct(')') {
+                return (nodes, Some(")".to_string()));
+            }
+            if stop == Stop::End && token.is_keyword("END") {
+                return (nodes, Some(self.text(token)));
+            }
+
+            if token.is_punct('(') {
+                let (inner, close) = self.nodes(Stop::Paren);
+                nodes.push(Node::Group {
+                    nodes: inner,
+                    closed: close.is_some(),
+                    space_before,
+                });
+            } else if token.is_keyword("CASE") {
+                let case = self.text(token);
+                let (inner, end) = self.nodes(Stop::End);
+                nodes.push(Node::Case {
+                    case,
+                    nodes: inner,
+                    end,
+                    space_before,
+                });
+            } else {
+                let line_comment = token.kind == SqlTokenKind::Comment && !token.text.starts_with("/*");
+                nodes.push(Node::Token {
+                    text: self.text(token),
+                    line_comment,
+                    space_before,
+                });
+            }
+        }
+
+        (nodes, None)
+    }
+
+    /// Source text of a token, keywords in the configured case
+    fn text(&self, token: &SqlToken) -> String {
+        let text = &self.sql[token.range.clone()];
+        let is_keyword = token.kind == SqlTokenKind::Word && FORMAT_KEYWORDS.iter().any(|k| text.eq_ignore_ascii_case(k));
+        match self.keyword_case {
+            KeywordCase::Upper if is_keyword => text.to_uppercase(),
+            KeywordCase::Lower if is_keyword => text.to_lowercase(),
+            _ => text.to_string(),
+        }
+    }
+}
+
+struct Writer<'a> {
+    options: &'a SqlFormatOptions,
+    lines: Vec<String>,
+    line: String,
+}
+
+impl Writer<'_> {
+    fn is_blank(&self) -> bool {
+        self.line.trim().is_empty()
+    }
+
+    /// Start a new line at `indent` levels, an empty current line is reused
+    fn newline(&mut self, indent: usize) {
+        if !self.is_blank() {
+            self.lines.push(std::mem::take(&mut self.line));
+        }
+        self.line = " ".repeat(indent * self.options.indent_width);
+    }
+
+    fn push(&mut self, text: &str, space: bool) {
+        if space && !self.is_blank() && !self.line.ends_with(' ') {
+            self.line.push(' ');
+        }
+        self.line.push_str(text);
+    }
+
+    /// Whether `text` still fits on the current line
+    fn fits(&self, text: &str) -> bool {
+        let space = usize::from(!self.is_blank());
+        self.line.chars().count() + space + text.chars().count() <= self.options.max_line_width
+    }
+
+    fn finish(mut self) -> String {
+        if !self.is_blank() {
+            self.lines.push(self.line);
+        }
+        self.lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Whether `node` is separated from the node before it by a space
+fn spaced(prev: Option<&Node>, node: &Node) -> bool {
+    if matches!(node, Node::Token { text, .. } if text == "," || text == ";") {
+        return false;
+    }
+    match prev {
+        None => false,
+        Some(prev) => prev.is_word(",") || node.space_before(),
+    }
+}
+
+/// The nodes on one line, none when a line comment forces a break
+fn inline(nodes: &[Node]) -> Option<String> {
+    let mut out = String::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if spaced(i.checked_sub(1).map(|p| &nodes[p]), node) {
+            out.push(' ');
+        }
+        out.push_str(&inline_node(node)?);
+    }
+    Some(out)
+}
+
+fn inline_node(node: &Node) -> Option<String> {
+    match node {
+        Node::Token { line_comment: true, .. } => None,
+        Node::Token { text, .. } => Some(text.clone()),
+        Node::Group { nodes, closed, .. } => {
+            Some(format!("({}{}", inline(nodes)?, if *closed { ")" } else { "" }))
+        }
+        Node::Case { case, nodes, end, .. } => {
+            let mut out = case.clone();
+            let inner = inline(nodes)?;
+            if !inner.is_empty() {
+                out.push(' ');
+                out.push_str(&inner);
+            }
+            if let Some(end) = end {
+                out.push(' ');
+                out.push_str(end);
+            }
+            Some(out)
+        }
+    }
+}
+
+fn is_query(nodes: &[Node]) -> bool {
+    nodes.first().is_some_and(|node| {
+        ["SELECT", "WITH", "INSERT", "UPDATE", "DELETE", "REPLACE", "VALUES"]
+            .iter()
+            .any(|word| node.is_word(word))
+    })
+}
+
+/// Length of the clause keyword starting at `nodes[0]`, if one does
+fn clause_at(nodes: &[Node], verb_allowed: bool) -> Option<usize> {
+    CLAUSES
+        .iter()
+        .find(|words| {
+            words.len() <= nodes.len()
+                && words.iter().zip(nodes).all(|(word, node)| node.is_word(word))
+                && (verb_allowed || words.len() > 1 || !VERBS.contains(&words[0]))
+        })
+        .map(|words| words.len())
+}
+
+/// Split a query into (keyword length, nodes) clauses
+fn split_clauses(nodes: &[Node]) -> Vec<(usize, &[Node])> {
+    let mut clauses: Vec<(usize, usize)> = Vec::new(); // (start, keyword length)
+    let mut verb_allowed = true;
+
+    let mut i = 0;
+    while i < nodes.len() {
+        match clause_at(&nodes[i..], verb_allowed) {
+            Some(len) => {
+                let is_with = nodes[i].is_word("WITH");
+                if !is_with {
+                    verb_allowed = false;
+                }
+                clauses.push((i, len));
+                i += len;
+            }
+            None => {
+                if clauses.is_empty() {
+                    clauses.push((0, 0));
+                }
+                i += 1;
+            }
+        }
+    }
+
+    clauses
+        .iter()
+        .enumerate()
+        .map(|(n, (start, len))| {
+            let end = clauses.get(n + 1).map_or(nodes.len(), |next| next.0);
+            (*len, &nodes[*start..end])
+        })
+        .collect()
+}
+
+fn split_commas(nodes: &[Node]) -> Vec<&[Node]> {
+    nodes.split(|node| node.is_word(",")).collect()
+}
+
+/// Split a condition before each top-level AND / OR, keeping `BETWEEN x AND y` whole
+fn split_conditions(nodes: &[Node]) -> Vec<&[Node]> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut between = false;
+    for (i, node) in nodes.iter().enumerate() {
+        if node.is_word("BETWEEN") {
+            between = true;
+        } else if node.is_word("AND") && between {
+            between = false;
+        } else if (node.is_word("AND") || node.is_word("OR")) && i > start {
+            parts.push(&nodes[start..i]);
+            start = i;
+        }
+    }
+    parts.push(&nodes[start..]);
+    parts
+}
+
+fn write_statement(w: &mut Writer, nodes: &[Node], indent: usize) {
+    if !is_query(nodes) {
+        // DDL: `CREATE VIEW v AS SELECT ...` puts the query on its own lines
+        let query_start = (1..nodes.len()).find(|&i| nodes[i - 1].is_word("AS") && is_query(&nodes[i..]));
+        match query_start {
+            Some(start) => {
+                write_item(w, &nodes[..start], indent);
+                w.newline(indent);
+                write_statement(w, &nodes[start..], indent);
+            }
+            None => write_item(w, nodes, indent),
+        }
+        return;
+    }
+
+    for (i, (keyword_len, clause)) in split_clauses(nodes).into_iter().enumerate() {
+        if i > 0 {
+            w.newline(indent);
+        }
+        write_clause(w, keyword_len, clause, indent);
+    }
+}
+
+fn write_clause(w: &mut Writer, keyword_len: usize, clause: &[Node], indent: usize) {
+    let keyword = clause[..keyword_len]
+        .iter()
+        .filter_map(inline_node)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let body = &clause[keyword_len..];
+
+    if let Some(text) = inline(body) {
+        let full = if body.is_empty() || keyword.is_empty() {
+            format!("{}{}", keyword, text)
+        } else {
+            format!("{} {}", keyword, text)
+        };
+        if w.fits(&full) {
+            w.push(&full, true);
+            return;
+        }
+    }
+
+    if keyword.is_empty() {
+        write_item(w, body, indent);
+        return;
+    }
+    w.push(&keyword, true);
+    if body.is_empty() {
+        return;
+    }
+
+    let last = &clause[keyword_len - 1];
+    if last.is_word("WHERE") || last.is_word("HAVING") {
+        write_conditions(w, None, body, indent + 1);
+    } else if last.is_word("JOIN") || last.is_word("APPLY") {
+        let on = body.iter().position(|node| node.is_word("ON") || node.is_word("USING"));
+        let (table, condition) = body.split_at(on.unwrap_or(body.len()));
+        write_item(w, table, indent);
+        if let Some((lead, condition)) = condition.split_first() {
+            write_conditions(w, Some(lead), condition, indent + 1);
+        }
+    } else {
+        write_list(w, body, indent + 1);
+    }
+}
+
+/// One condition per line, each after the first starting with its AND / OR
+fn write_conditions(w: &mut Writer, lead: Option<&Node>, nodes: &[Node], indent: usize) {
+    for (i, part) in split_conditions(nodes).into_iter().enumerate() {
+        w.newline(indent);
+        if i == 0 {
+            if let Some(lead) = lead.and_then(inline_node) {
+                w.push(&lead, true);
+            }
+        }
+        write_item(w, part, indent);
+    }
+}
+
+/// One comma separated item per line
+fn write_list(w: &mut Writer, nodes: &[Node], indent: usize) {
+    let items = split_commas(nodes);
+    let count = items.len();
+    for (i, item) in items.into_iter().enumerate() {
+        w.newline(indent);
+        if w.options.comma_style == CommaStyle::Leading && i > 0 {
+            w.push(", ", false);
+        }
+        write_item(w, item, indent);
+        if w.options.comma_style == CommaStyle::Trailing && i + 1 < count {
+            w.push(",", false);
+        }
+    }
+}
+
+/// Nodes continuing the current line, parentheses and CASE broken over lines when too long
+fn write_item(w: &mut Writer, nodes: &[Node], indent: usize) {
+    if nodes.is_empty() {
+        return;
+    }
+    if let Some(text) = inline(nodes) {
+        if w.fits(&text) {
+            w.push(&text, true);
+            return;
+        }
+    }
+
+    for (i, node) in nodes.iter().enumerate() {
+        let space = i == 0 || spaced(Some(&nodes[i - 1]), node);
+        match node {
+            Node::Token { text, line_comment, .. } => {
+                w.push(text, space);
+                if *line_comment {
+                    w.newline(indent);
+                }
+            }
+            Node::Group { nodes: inner, closed, .. } => {
+                if let Some(text) = inline_node(node).filter(|text| w.fits(text)) {
+                    w.push(&text, space);
+                    continue;
+                }
+                w.push("(", space);
+                if is_query(inner) {
+                    w.newline(indent + 1);
+                    write_statement(w, inner, indent + 1);
+                } else {
+                    write_list(w, inner, indent + 1);
+                }
+                if *closed {
+                    w.newline(indent);
+                    w.push(")", false);
+                }
+            }
+            Node::Case { case, nodes: inner, end, .. } => {
+                if let Some(text) = inline_node(node).filter(|text| w.fits(text)) {
+                    w.push(&text, space);
+                    continue;
+                }
+                w.push(case, space);
+                let first_branch = inner
+                    .iter()
+                    .position(|n| n.is_word("WHEN") || n.is_word("ELSE"))
+                    .unwrap_or(inner.len());
+                write_item(w, &inner[..first_branch], indent);
+
+                let mut start = first_branch;
+                while start < inner.len() {
+                    let next = inner[start + 1..]
+                        .iter()
+                        .position(|n| n.is_word("WHEN") || n.is_word("ELSE"))
+                        .map_or(inner.len(), |p| start + 1 + p);
+                    w.newline(indent + 1);
+                    write_item(w, &inner[start..next], indent + 1);
+                    start = next;
+                }
+                if let Some(end) = end {
+                    w.newline(indent);
+                    w.push(end, false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(sql: &str) -> String {
+        format_sql(sql, DatabaseType::MySQL, &SqlFormatOptions::default())
+    }
+
+    fn narrow(width: usize) -> SqlFormatOptions {
+        SqlFormatOptions {
+            max_line_width: width,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clauses_on_their_own_lines() {
+        assert_eq!(
+            format("select id, name from users u left join orders o on o.user_id = u.id where u.id = 1 order by name"),
+            "SELECT id, name\nFROM users u\nLEFT JOIN orders o ON o.user_id = u.id\nWHERE u.id = 1\nORDER BY name"
+        );
+    }
+
+    #[test]
+    fn test_long_lists_and_conditions_break() {
+        let sql = "SELECT id, customer_name, shipping_address FROM orders WHERE status = 'open' AND placed_at BETWEEN '2024-01-01' AND '2024-02-01'";
+        assert_eq!(
+            format_sql(sql, DatabaseType::MySQL, &narrow(40)),
+            "SELECT\n  id,\n  customer_name,\n  shipping_address\nFROM orders\nWHERE\n  status = 'open'\n  AND placed_at BETWEEN '2024-01-01' AND '2024-02-01'"
+        );
+
+        let options = SqlFormatOptions {
+            comma_style: CommaStyle::Leading,
+            indent_width: 4,
+            ..narrow(40)
+        };
+        assert_eq!(
+            format_sql("SELECT id, customer_name, shipping_address FROM orders", DatabaseType::MySQL, &options),
+            "SELECT\n    id\n    , customer_name\n    , shipping_address\nFROM orders"
+        );
+    }
+
+    #[test]
+    fn test_subquery_and_case_indentation() {
+        let sql = "SELECT CASE WHEN total > 100 THEN 'large' WHEN total > 10 THEN 'medium' ELSE 'small' END AS size FROM (SELECT order_id, SUM(amount) AS total FROM order_items GROUP BY order_id) t";
+        assert_eq!(
+            format_sql(sql, DatabaseType::PostgreSQL, &narrow(50)),
+            "SELECT\n  CASE\n    WHEN total > 100 THEN 'large'\n    WHEN total > 10 THEN 'medium'\n    ELSE 'small'\n  END AS size\nFROM\n  (\n    SELECT order_id, SUM(amount) AS total\n    FROM order_items\n    GROUP BY order_id\n  ) t"
+        );
+    }
+
+    #[test]
+    fn test_keyword_case_leaves_names_and_strings() {
+        let options = SqlFormatOptions {
+            keyword_case: KeywordCase::Lower,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_sql("SELECT \"Select\", 'FROM' FROM [Order] WHERE Name IS NOT NULL", DatabaseType::MSSQL, &options),
+            "select \"Select\", 'FROM'\nfrom [Order]\nwhere Name is not null"
+        );
+    }
+
+    #[test]
+    fn test_script_keeps_comments_and_is_stable() {
+        let script = "-- daily report\nselect a from t;  insert into log (a, b) values (1, 2), (3, 4);\n";
+        let formatted = format(script);
+        assert_eq!(
+            formatted,
+            "-- daily report\nSELECT a\nFROM t;\n\nINSERT INTO log (a, b)\nVALUES (1, 2), (3, 4);\n"
+        );
+        assert_eq!(format(&formatted), formatted);
+    }
+
+    #[test]
+    fn test_ddl_and_routines() {
+        assert_eq!(
+            format_sql(
+                "create table t (id int primary key, name varchar(20) not null)",
+                DatabaseType::MySQL,
+                &narrow(30)
+            ),
+            "CREATE TABLE t (\n  id int PRIMARY KEY,\n  name varchar(20) NOT NULL\n)"
+        );
+        assert_eq!(
+            format("create view v as select a from t"),
+            "CREATE VIEW v AS\nSELECT a\nFROM t"
+        );
+        let routine = "CREATE PROCEDURE p() BEGIN SELECT 1; END";
+        assert_eq!(format(routine), routine);
+    }
+}
@@ -5,7 +5,7 @@ use async_trait::async_trait;
 
 use crate::connection::DbConnection;
 use crate::executor::{ExecOptions, SqlResult};
-use crate::import_export::{ExportConfig, ExportResult, FormatHandler, ImportConfig, ImportResult};
+use crate::import_export::{limit_clause, quote_identifier, table_reference, ExportConfig, EXPORT_BATCH_SIZE, ExportResult, FormatHandler, ImportConfig, ImportResult};
 
 pub struct CsvFormatHandler;
 
@@ -83,7 +83,7 @@ impl FormatHandler for CsvFormatHandler {
 
         // TRUNCATE表
         if config.truncate_before_import {
-            let truncate_sql = format!("TRUNCATE TABLE `{}`", table);
+            let truncate_sql = format!("TRUNCATE TABLE {}", table_reference(connection, config.schema.as_deref(), table));
             let results = connection.execute(&truncate_sql, ExecOptions::default()).await
                 .map_err(|e| anyhow!("Truncate failed: {}", e))?;
             
@@ -117,14 +117,12 @@ impl FormatHandler for CsvFormatHandler {
                 continue;
             }
 
-            let mut insert_sql = format!("INSERT INTO `{}` (", table);
+            let mut insert_sql = format!("INSERT INTO {} (", table_reference(connection, config.schema.as_deref(), table));
             for (i, col) in columns.iter().enumerate() {
                 if i > 0 {
                     insert_sql.push_str(", ");
                 }
-                insert_sql.push('`');
-                insert_sql.push_str(col);
-                insert_sql.push('`');
+                insert_sql.push_str(&quote_identifier(connection, col));
             }
             insert_sql.push_str(") VALUES (");
 
@@ -186,40 +184,42 @@ impl FormatHandler for CsvFormatHandler {
         let mut total_rows = 0u64;
 
         for (table_idx, table) in config.tables.iter().enumerate() {
-            let mut select_sql = format!("SELECT * FROM `{}`", table);
+            let mut select_sql = format!("SELECT * FROM {}", table_reference(connection, config.schema.as_deref(), table));
             if let Some(where_clause) = &config.where_clause {
                 select_sql.push_str(" WHERE ");
                 select_sql.push_str(where_clause);
             }
             if let Some(limit) = config.limit {
-                select_sql.push_str(&format!(" LIMIT {}", limit));
+                select_sql.push_str(&limit_clause(connection, limit));
             }
 
-            let result = connection.query(&select_sql, None, ExecOptions::default()).await
+            let stream = connection.query_stream(&select_sql, EXPORT_BATCH_SIZE).await
                 .map_err(|e| anyhow!("Query failed: {}", e))?;
 
-            if let SqlResult::Query(query_result) = result {
-                if table_idx > 0 {
-                    output.push_str("\n\n");
-                }
+            if table_idx > 0 {
+                output.push_str("\n\n");
+            }
 
-                // 写入表头
-                for (i, col) in query_result.columns.iter().enumerate() {
-                    if i > 0 {
-                        output.push(',');
-                    }
-                    output.push_str(&Self::escape_csv_field(col));
+            // 写入表头
+            for (i, col) in stream.columns().iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
                 }
-                output.push('\n');
+                output.push_str(&Self::escape_csv_field(&col.name));
+            }
+            output.push('\n');
 
-                // 写入数据
-                for row in &query_result.rows {
+            // 按批写入数据
+            while let Some(batch) = stream.next_batch().await {
+                let batch = batch.map_err(|e| anyhow!("Query failed: {}", e))?;
+                for row in &batch {
                     for (i, val) in row.iter().enumerate() {
                         if i > 0 {
                             output.push(',');
                         }
-                        if let Some(v) = val {
-                            output.push_str(&Self::escape_csv_field(v));
+                        // NULL 导出为空字段
+                        if !val.is_null() {
+                            output.push_str(&Self::escape_csv_field(&val.to_string()));
                         }
                     }
                     output.push('\n');
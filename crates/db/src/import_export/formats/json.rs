@@ -6,7 +6,7 @@ use serde_json::Value;
 
 use crate::connection::DbConnection;
 use crate::executor::{ExecOptions, SqlResult};
-use crate::import_export::{ExportConfig, ExportResult, FormatHandler, ImportConfig, ImportResult};
+use crate::import_export::{limit_clause, quote_identifier, table_reference, ExportConfig, EXPORT_BATCH_SIZE, ExportResult, FormatHandler, ImportConfig, ImportResult};
 
 pub struct JsonFormatHandler;
 
@@ -44,7 +44,7 @@ impl FormatHandler for JsonFormatHandler {
 
         // TRUNCATE表
         if config.truncate_before_import {
-            let truncate_sql = format!("TRUNCATE TABLE `{}`", table);
+            let truncate_sql = format!("TRUNCATE TABLE {}", table_reference(connection, config.schema.as_deref(), table));
             let results = connection.execute(&truncate_sql, ExecOptions::default()).await
                 .map_err(|e| anyhow!("Truncate failed: {}", e))?;
             
@@ -81,14 +81,12 @@ impl FormatHandler for JsonFormatHandler {
                 }
             };
 
-            let mut insert_sql = format!("INSERT INTO `{}` (", table);
+            let mut insert_sql = format!("INSERT INTO {} (", table_reference(connection, config.schema.as_deref(), table));
             for (i, col) in columns.iter().enumerate() {
                 if i > 0 {
                     insert_sql.push_str(", ");
                 }
-                insert_sql.push('`');
-                insert_sql.push_str(col);
-                insert_sql.push('`');
+                insert_sql.push_str(&quote_identifier(connection, col));
             }
             insert_sql.push_str(") VALUES (");
 
@@ -154,39 +152,38 @@ impl FormatHandler for JsonFormatHandler {
         config: &ExportConfig,
     ) -> Result<ExportResult> {
         let start = Instant::now();
-        let mut all_data = Vec::new();
+        // 逐行写出数组元素，与 to_string_pretty 的输出格式一致
+        let mut output = String::from("[");
         let mut total_rows = 0u64;
 
         for table in &config.tables {
-            let mut select_sql = format!("SELECT * FROM `{}`", table);
+            let mut select_sql = format!("SELECT * FROM {}", table_reference(connection, config.schema.as_deref(), table));
             if let Some(where_clause) = &config.where_clause {
                 select_sql.push_str(" WHERE ");
                 select_sql.push_str(where_clause);
             }
             if let Some(limit) = config.limit {
-                select_sql.push_str(&format!(" LIMIT {}", limit));
+                select_sql.push_str(&limit_clause(connection, limit));
             }
 
-            let result = connection.query(&select_sql, None, ExecOptions::default()).await
+            let stream = connection.query_stream(&select_sql, EXPORT_BATCH_SIZE).await
                 .map_err(|e| anyhow!("Query failed: {}", e))?;
 
-            if let SqlResult::Query(query_result) = result {
-                for row in &query_result.rows {
+            while let Some(batch) = stream.next_batch().await {
+                let batch = batch.map_err(|e| anyhow!("Query failed: {}", e))?;
+                for row in &batch {
                     let mut obj = serde_json::Map::new();
-                    for (i, col_name) in query_result.columns.iter().enumerate() {
-                        let value = match &row[i] {
-                            Some(v) => Value::String(v.clone()),
-                            None => Value::Null,
-                        };
-                        obj.insert(col_name.clone(), value);
+                    for (i, column) in stream.columns().iter().enumerate() {
+                        obj.insert(column.name.clone(), row[i].to_json());
                     }
-                    all_data.push(Value::Object(obj));
+                    output.push_str(if total_rows == 0 { "\n  " } else { ",\n  " });
+                    output.push_str(&serde_json::to_string_pretty(&Value::Object(obj))?.replace('\n', "\n  "));
                     total_rows += 1;
                 }
             }
         }
 
-        let output = serde_json::to_string_pretty(&all_data)?;
+        output.push_str(if total_rows == 0 { "]" } else { "\n]" });
 
         Ok(ExportResult {
             success: true,
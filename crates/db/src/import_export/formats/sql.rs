@@ -2,10 +2,12 @@ use std::time::Instant;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use one_core::storage::DatabaseType;
 
 use crate::connection::DbConnection;
 use crate::executor::{ExecOptions, SqlResult};
-use crate::import_export::{ExportConfig, ExportResult, FormatHandler, ImportConfig, ImportResult};
+use crate::import_export::{limit_clause, table_reference, ExportConfig, EXPORT_BATCH_SIZE, ExportResult, FormatHandler, ImportConfig, ImportResult};
+use crate::types::SqlValue;
 
 pub struct SqlFormatHandler;
 
@@ -24,7 +26,7 @@ impl FormatHandler for SqlFormatHandler {
         // TRUNCATE表（如果需要）
         if config.truncate_before_import {
             if let Some(table) = &config.table {
-                let truncate_sql = format!("TRUNCATE TABLE `{}`", table);
+                let truncate_sql = format!("TRUNCATE TABLE {}", table_reference(connection, config.schema.as_deref(), table));
                 let results = connection.execute(&truncate_sql, ExecOptions::default()).await
                     .map_err(|e| anyhow::anyhow!("Truncate failed: {}", e))?;
                 
@@ -56,6 +58,10 @@ impl FormatHandler for SqlFormatHandler {
 
         for result in results {
             match result {
+                // 事务回滚后之前插入的行都已撤销
+                SqlResult::Exec(exec_result) if exec_result.is_rollback() => {
+                    total_rows = 0;
+                }
                 SqlResult::Exec(exec_result) => {
                     total_rows += exec_result.rows_affected;
                 }
@@ -82,21 +88,35 @@ impl FormatHandler for SqlFormatHandler {
         let start = Instant::now();
         let mut output = String::new();
         let mut total_rows = 0u64;
+        let is_sqlite = connection.config()
+            .map(|c| c.database_type == DatabaseType::SQLite)
+            .unwrap_or(false);
 
         for table in &config.tables {
             // 导出表结构
             if config.include_schema {
-                let show_create = format!("SHOW CREATE TABLE `{}`", table);
+                // SQLite 没有 SHOW CREATE TABLE，建表语句保存在 sqlite_master 中
+                let (show_create, create_index) = if is_sqlite {
+                    (
+                        format!(
+                            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = '{}'",
+                            table.replace('\'', "''")
+                        ),
+                        0,
+                    )
+                } else {
+                    (format!("SHOW CREATE TABLE {}", table_reference(connection, config.schema.as_deref(), table)), 1)
+                };
                 let result = connection.query(&show_create, None, ExecOptions::default()).await
                     .map_err(|e| anyhow::anyhow!("Query failed: {}", e))?;
 
                 if let SqlResult::Query(query_result) = result {
                     if let Some(row) = query_result.rows.first() {
-                        if let Some(Some(create_sql)) = row.get(1) {
+                        if let Some(create_sql) = row.get(create_index).and_then(|v| v.as_text()) {
                             output.push_str("-- Table structure for ");
                             output.push_str(table);
                             output.push_str("\n");
-                            output.push_str(create_sql);
+                            output.push_str(&create_sql);
                             output.push_str(";\n\n");
                         }
                     }
@@ -105,49 +125,47 @@ impl FormatHandler for SqlFormatHandler {
 
             // 导出数据
             if config.include_data {
-                let mut select_sql = format!("SELECT * FROM `{}`", table);
+                let mut select_sql = format!("SELECT * FROM {}", table_reference(connection, config.schema.as_deref(), table));
                 if let Some(where_clause) = &config.where_clause {
                     select_sql.push_str(" WHERE ");
                     select_sql.push_str(where_clause);
                 }
                 if let Some(limit) = config.limit {
-                    select_sql.push_str(&format!(" LIMIT {}", limit));
+                    select_sql.push_str(&limit_clause(connection, limit));
                 }
 
-                let result = connection.query(&select_sql, None, ExecOptions::default()).await
+                let stream = connection.query_stream(&select_sql, EXPORT_BATCH_SIZE).await
                     .map_err(|e| anyhow::anyhow!("Query failed: {}", e))?;
 
-                if let SqlResult::Query(query_result) = result {
-                    if !query_result.rows.is_empty() {
-                        output.push_str("-- Data for table ");
-                        output.push_str(table);
-                        output.push_str("\n");
-
-                        for row in &query_result.rows {
-                            output.push_str("INSERT INTO `");
+                let mut table_rows = 0u64;
+                while let Some(batch) = stream.next_batch().await {
+                    let batch = batch.map_err(|e| anyhow::anyhow!("Query failed: {}", e))?;
+                    for row in &batch {
+                        if table_rows == 0 {
+                            output.push_str("-- Data for table ");
                             output.push_str(table);
-                            output.push_str("` VALUES (");
-
-                            for (i, value) in row.iter().enumerate() {
-                                if i > 0 {
-                                    output.push_str(", ");
-                                }
-                                match value {
-                                    Some(v) => {
-                                        output.push('\'');
-                                        output.push_str(&v.replace('\'', "''"));
-                                        output.push('\'');
-                                    }
-                                    None => output.push_str("NULL"),
-                                }
-                            }
+                            output.push_str("\n");
+                        }
+
+                        output.push_str("INSERT INTO ");
+                        output.push_str(&table_reference(connection, config.schema.as_deref(), table));
+                        output.push_str(" VALUES (");
 
-                            output.push_str(");\n");
-                            total_rows += 1;
+                        for (i, value) in row.iter().enumerate() {
+                            if i > 0 {
+                                output.push_str(", ");
+                            }
+                            output.push_str(&sql_literal(value));
                         }
-                        output.push('\n');
+
+                        output.push_str(");\n");
+                        table_rows += 1;
                     }
                 }
+                if table_rows > 0 {
+                    output.push('\n');
+                }
+                total_rows += table_rows;
             }
         }
 
@@ -159,3 +177,14 @@ impl FormatHandler for SqlFormatHandler {
         })
     }
 }
+
+/// Render a result cell as a literal for an INSERT statement
+fn sql_literal(value: &SqlValue) -> String {
+    match value {
+        SqlValue::Null => "NULL".to_string(),
+        SqlValue::Bool(v) => if *v { "TRUE" } else { "FALSE" }.to_string(),
+        SqlValue::Int(_) | SqlValue::Float(_) | SqlValue::Decimal(_) => value.to_string(),
+        SqlValue::Bytes(v) => format!("X'{}'", hex::encode(v)),
+        other => format!("'{}'", other.to_string().replace('\'', "''")),
+    }
+}
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use crate::connection::DbConnection;
+use crate::manager::DbManager;
 
 pub mod formats;
 pub mod importer;
@@ -44,6 +45,8 @@ impl DataFormat {
 pub struct ImportConfig {
     pub format: DataFormat,
     pub database: String,
+    /// 目标表所在的 schema（仅支持 schema 的数据库）
+    pub schema: Option<String>,
     pub table: Option<String>,
     pub stop_on_error: bool,
     pub use_transaction: bool,
@@ -55,6 +58,7 @@ impl Default for ImportConfig {
         Self {
             format: DataFormat::Sql,
             database: String::new(),
+            schema: None,
             table: None,
             stop_on_error: true,
             use_transaction: true,
@@ -68,6 +72,8 @@ impl Default for ImportConfig {
 pub struct ExportConfig {
     pub format: DataFormat,
     pub database: String,
+    /// 导出表所在的 schema（仅支持 schema 的数据库）
+    pub schema: Option<String>,
     pub tables: Vec<String>,
     pub include_schema: bool,
     pub include_data: bool,
@@ -80,6 +86,7 @@ impl Default for ExportConfig {
         Self {
             format: DataFormat::Sql,
             database: String::new(),
+            schema: None,
             tables: Vec::new(),
             include_schema: true,
             include_data: true,
@@ -89,6 +96,37 @@ impl Default for ExportConfig {
     }
 }
 
+/// Rows fetched per round trip while exporting, so a table never has to fit in memory at once
+pub(crate) const EXPORT_BATCH_SIZE: usize = 1000;
+
+/// 按连接的数据库方言引用标识符，无法识别连接类型时沿用反引号
+pub(crate) fn quote_identifier(connection: &dyn DbConnection, identifier: &str) -> String {
+    match connection.config().and_then(|c| DbManager::new().get_plugin(&c.database_type).ok()) {
+        Some(plugin) => plugin.quote_identifier(identifier),
+        None => format!("`{}`", identifier),
+    }
+}
+
+/// 导出行数限制，SQL Server 和 Oracle 没有 LIMIT，按连接的数据库方言生成
+pub(crate) fn limit_clause(connection: &dyn DbConnection, limit: usize) -> String {
+    match connection.config().and_then(|c| DbManager::new().get_plugin(&c.database_type).ok()) {
+        Some(plugin) => plugin.pagination_clause(false, limit, 0),
+        None => format!(" LIMIT {}", limit),
+    }
+}
+
+/// 表引用，指定 schema 时写成 schema.table
+pub(crate) fn table_reference(connection: &dyn DbConnection, schema: Option<&str>, table: &str) -> String {
+    match schema {
+        Some(schema) => format!(
+            "{}.{}",
+            quote_identifier(connection, schema),
+            quote_identifier(connection, table)
+        ),
+        None => quote_identifier(connection, table),
+    }
+}
+
 /// 导入结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
@@ -125,3 +163,18 @@ pub trait FormatHandler: Send + Sync {
         config: &ExportConfig,
     ) -> Result<ExportResult>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_config, FakeConnection};
+    use one_core::storage::DatabaseType;
+
+    #[test]
+    fn test_limit_clause_follows_dialect() {
+        let limit = |db_type| limit_clause(&FakeConnection::new(test_config(db_type, &[])), 10);
+        assert_eq!(limit(DatabaseType::MySQL), " LIMIT 10 OFFSET 0");
+        assert_eq!(limit(DatabaseType::MSSQL), " ORDER BY (SELECT NULL) OFFSET 0 ROWS FETCH NEXT 10 ROWS ONLY");
+        assert_eq!(limit(DatabaseType::Oracle), " FETCH FIRST 10 ROWS ONLY");
+    }
+}
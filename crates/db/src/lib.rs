@@ -5,6 +5,17 @@ pub mod connection;
 pub mod executor;
 pub mod runtime;
 pub mod import_export;
+pub mod session;
+pub mod stream;
+pub mod params;
+pub mod plan;
+pub mod completion;
+pub mod format;
+pub mod ssh_tunnel;
+pub mod ssl;
+mod cancel;
+#[cfg(test)]
+mod test_support;
 
 // Database implementations
 pub mod mysql;
@@ -21,3 +32,11 @@ pub use connection::*;
 pub use executor::*;
 pub use runtime::*;
 pub use import_export::*;
+pub use session::*;
+pub use stream::*;
+pub use params::*;
+pub use plan::*;
+pub use completion::*;
+pub use format::*;
+pub use ssh_tunnel::*;
+pub use ssl::*;
@@ -2,10 +2,17 @@ use crate::connection::{DbConnection, DbError};
 use crate::plugin::DatabasePlugin;
 use crate::mysql::MySqlPlugin;
 use crate::postgresql::PostgresPlugin;
+use crate::sqlite::SqlitePlugin;
+use crate::mssql::MssqlPlugin;
+use crate::oracle::OraclePlugin;
+use crate::runtime::TOKIO_HANDLE;
+use crate::session::DbSession;
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time;
 use gpui::Global;
 use one_core::storage::{DatabaseType, DbConnectionConfig};
@@ -21,7 +28,9 @@ impl DbManager {
         match db_type {
             DatabaseType::MySQL => Ok(Box::new(MySqlPlugin::new())),
             DatabaseType::PostgreSQL => Ok(Box::new(PostgresPlugin::new())),
-            _ => Err(DbError::new("Unsupported database type")),
+            DatabaseType::SQLite => Ok(Box::new(SqlitePlugin::new())),
+            DatabaseType::MSSQL => Ok(Box::new(MssqlPlugin::new())),
+            DatabaseType::Oracle => Ok(Box::new(OraclePlugin::new())),
         }
     }
 }
@@ -38,26 +47,162 @@ impl Clone for DbManager {
     }
 }
 
+/// Extra param of a stored connection: seconds a pooled connection may sit unused before it is closed
+pub const IDLE_TIMEOUT_PARAM: &str = "pool_idle_timeout";
+/// Extra param of a stored connection: pooled connections and exclusive sessions it may hold at once
+pub const MAX_CONNECTIONS_PARAM: &str = "pool_max_connections";
+/// Extra param of a stored connection: seconds after which a pooled connection is pinged before reuse
+pub const PING_INTERVAL_PARAM: &str = "pool_ping_interval";
+
+/// How the pool treats the connections of a stored connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolOptions {
+    pub idle_timeout: Duration,
+    pub max_connections: usize,
+    /// Zero pings on every reuse
+    pub ping_interval: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(300),
+            max_connections: 8,
+            ping_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PoolOptions {
+    /// Options set in the extra params of `config`, `self` for those missing or invalid
+    pub fn for_config(&self, config: &DbConnectionConfig) -> Self {
+        let param = |name: &str| {
+            config
+                .extra_params
+                .get(name)
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        Self {
+            idle_timeout: param(IDLE_TIMEOUT_PARAM)
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(self.idle_timeout),
+            max_connections: param(MAX_CONNECTIONS_PARAM)
+                .filter(|max| *max > 0)
+                .map(|max| max as usize)
+                .unwrap_or(self.max_connections),
+            ping_interval: param(PING_INTERVAL_PARAM)
+                .map(Duration::from_secs)
+                .unwrap_or(self.ping_interval),
+        }
+    }
+}
+
+/// What a pooled connection is used for.
+/// Each purpose gets a connection of its own, so a slow query of one doesn't hold up the others.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SessionPurpose {
+    /// Tree expansion, object lists, completion and other short catalog queries, shared by all views
+    Metadata,
+    /// Queries of one tab, keyed by an id the tab keeps for its lifetime
+    Editor(String),
+    /// Export, import and other long running jobs, keyed by job id
+    Background(String),
+}
+
+impl std::fmt::Display for SessionPurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionPurpose::Metadata => write!(f, "metadata"),
+            SessionPurpose::Editor(id) => write!(f, "editor {}", id),
+            SessionPurpose::Background(id) => write!(f, "background {}", id),
+        }
+    }
+}
+
+/// State of a pooled connection, or of one that failed to open, for display
+#[derive(Debug, Clone)]
+pub struct PooledConnectionState {
+    pub config_id: String,
+    pub database: Option<String>,
+    pub purpose: SessionPurpose,
+    /// False when the last attempt to open it failed and nothing is pooled
+    pub connected: bool,
+    /// Held outside the pool, e.g. by a running query
+    pub in_use: bool,
+    /// Time since it was last handed out, or since it failed to open
+    pub idle_for: Duration,
+    /// Error of the last failed ping or connect, kept after a successful reconnect
+    pub last_error: Option<String>,
+    /// Server session ids of the statements running on it
+    pub server_ids: Vec<u64>,
+}
+
+/// Identifies a pooled connection
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    config_id: String,
+    database: Option<String>,
+    purpose: SessionPurpose,
+}
+
+impl PoolKey {
+    fn new(config: &DbConnectionConfig, purpose: SessionPurpose) -> Self {
+        Self {
+            config_id: config.id.clone(),
+            database: config.database.clone(),
+            purpose,
+        }
+    }
+}
+
+impl std::fmt::Display for PoolKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.database {
+            Some(database) => write!(f, "{}:{} ({})", self.config_id, database, self.purpose),
+            None => write!(f, "{} ({})", self.config_id, self.purpose),
+        }
+    }
+}
+
 /// Connection pool manager
 pub struct ConnectionPool {
-    connections: Arc<RwLock<HashMap<String, ConnectionEntry>>>,
-    /// Connection idle timeout in seconds (default: 300 seconds = 5 minutes)
-    idle_timeout: Duration,
+    connections: Arc<RwLock<HashMap<PoolKey, ConnectionEntry>>>,
+    /// Keys whose last connect failed, with the error
+    failures: Arc<RwLock<HashMap<PoolKey, FailedConnection>>>,
+    /// Exclusive sessions open outside the pool per config, counted against its connection limit
+    reserved: Arc<RwLock<HashMap<String, usize>>>,
+    /// Slots taken per config by connections still being opened, until they are stored
+    opening: Arc<RwLock<HashMap<String, usize>>>,
+    /// One lock per key being opened, so concurrent callers for a key share a single connect
+    open_locks: Arc<Mutex<HashMap<PoolKey, Arc<Mutex<()>>>>>,
+    /// Used for stored connections that don't set their own
+    defaults: PoolOptions,
 }
 
 struct ConnectionEntry {
     connection: Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>,
-    config: DbConnectionConfig,
+    options: PoolOptions,
     /// Last active time for this connection
     last_active: Instant,
+    /// Last time the connection was opened or pinged
+    last_checked: Instant,
+    last_error: Option<String>,
+}
+
+struct FailedConnection {
+    error: String,
+    at: Instant,
 }
 
 impl ConnectionEntry {
-    fn new(connection: Box<dyn DbConnection + Send + Sync>, config: DbConnectionConfig) -> Self {
+    fn new(connection: Box<dyn DbConnection + Send + Sync>, options: PoolOptions) -> Self {
         Self {
             connection: Arc::new(RwLock::new(connection)),
-            config,
+            options,
             last_active: Instant::now(),
+            last_checked: Instant::now(),
+            last_error: None,
         }
     }
 
@@ -65,94 +210,362 @@ impl ConnectionEntry {
         self.last_active = Instant::now();
     }
 
-    fn is_expired(&self, timeout: Duration) -> bool {
-        self.last_active.elapsed() > timeout
+    fn is_expired(&self) -> bool {
+        self.last_active.elapsed() > self.options.idle_timeout
+    }
+
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.connection) > 1
     }
 }
 
 impl ConnectionPool {
     pub fn new() -> Self {
-        Self::with_timeout(Duration::from_secs(300))
+        Self::with_options(PoolOptions::default())
     }
 
     pub fn with_timeout(idle_timeout: Duration) -> Self {
-        Self {
-            connections: Arc::new(RwLock::new(HashMap::new())),
+        Self::with_options(PoolOptions {
             idle_timeout,
-        }
+            ..PoolOptions::default()
+        })
     }
 
-    /// Generate connection key: config_id + database
-    fn make_connection_key(config_id: &str, database: Option<&str>) -> String {
-        match database {
-            Some(db) => format!("{}:{}", config_id, db),
-            None => config_id.to_string(),
+    pub fn with_options(defaults: PoolOptions) -> Self {
+        Self {
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            failures: Arc::new(RwLock::new(HashMap::new())),
+            reserved: Arc::new(RwLock::new(HashMap::new())),
+            opening: Arc::new(RwLock::new(HashMap::new())),
+            open_locks: Arc::new(Mutex::new(HashMap::new())),
+            defaults,
         }
     }
 
-    /// Get or create a connection
+    /// Get or create the connection for `purpose` on the database of `config`.
+    /// A connection unused for longer than the ping interval is pinged first and reopened once if that fails.
     pub async fn get_connection(
         &self,
         config: DbConnectionConfig,
+        purpose: SessionPurpose,
         db_manager: &DbManager
     ) -> Result<Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>, DbError> {
-        let key = Self::make_connection_key(&config.id, config.database.as_deref());
+        let plugin = db_manager.get_plugin(&config.database_type)?;
+        let plugin = &plugin;
+        self.get_connection_with(config, purpose, move |config| async move {
+            let mut connection = plugin.create_connection(config).await?;
+            connection.connect().await?;
+            Ok(connection)
+        })
+        .await
+    }
+
+    async fn get_connection_with<F, Fut>(
+        &self,
+        config: DbConnectionConfig,
+        purpose: SessionPurpose,
+        connect: F,
+    ) -> Result<Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>, DbError>
+    where
+        F: Fn(DbConnectionConfig) -> Fut,
+        Fut: Future<Output = Result<Box<dyn DbConnection + Send + Sync>, DbError>>,
+    {
+        let key = PoolKey::new(&config, purpose);
+        let options = self.defaults.for_config(&config);
 
         // First, try to get existing connection
-        {
+        let existing = {
             let mut connections = self.connections.write().await;
-            if let Some(entry) = connections.get_mut(&key) {
-                // Update last active time
+            connections.get_mut(&key).map(|entry| {
                 entry.update_last_active();
-                return Ok(entry.connection.clone());
+                entry.options = options;
+                // Checked now so that concurrent callers don't ping it too
+                let ping = entry.last_checked.elapsed() >= options.ping_interval;
+                if ping {
+                    entry.last_checked = Instant::now();
+                }
+                (entry.connection.clone(), ping)
+            })
+        };
+
+        if let Some((connection, ping)) = existing {
+            if !ping {
+                return Ok(connection);
             }
+            let ping_result = connection.read().await.ping().await;
+            let Err(ping_error) = ping_result else {
+                return Ok(connection);
+            };
+
+            tracing::warn!("Pooled connection {} is broken, reconnecting: {}", key, ping_error);
+            return match connect(config.clone()).await {
+                Ok(fresh) => {
+                    // Swapped in place, so handles given out earlier use the new connection too
+                    let mut broken = std::mem::replace(&mut *connection.write().await, fresh);
+                    let _ = broken.disconnect().await;
+                    if let Some(entry) = self.connections.write().await.get_mut(&key) {
+                        entry.last_checked = Instant::now();
+                        entry.last_error = Some(ping_error.to_string());
+                    }
+                    Ok(connection)
+                }
+                Err(e) => {
+                    self.connections.write().await.remove(&key);
+                    self.record_failure(key, &e).await;
+                    Err(e)
+                }
+            };
         }
 
-        // If connection doesn't exist, create a new one
-        let plugin = db_manager.get_plugin(&config.database_type)?;
-        let mut connection = plugin.create_connection(config.clone()).await?;
+        // Callers that miss at the same time queue here, the first one opens the connection
+        let open_lock = self.open_locks.lock().await.entry(key.clone()).or_default().clone();
+        let result = {
+            let _opening = open_lock.lock().await;
+            self.open_connection(key.clone(), config, options, connect).await
+        };
+        let mut open_locks = self.open_locks.lock().await;
+        // Held only by the map and this call once nobody else waits for the key
+        if Arc::strong_count(&open_lock) == 2 {
+            open_locks.remove(&key);
+        }
+        result
+    }
 
-        // Connect to the database
-        connection.connect().await?;
+    async fn open_connection<F, Fut>(
+        &self,
+        key: PoolKey,
+        config: DbConnectionConfig,
+        options: PoolOptions,
+        connect: F,
+    ) -> Result<Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>, DbError>
+    where
+        F: Fn(DbConnectionConfig) -> Fut,
+        Fut: Future<Output = Result<Box<dyn DbConnection + Send + Sync>, DbError>>,
+    {
+        // Opened by the caller this one waited for
+        if let Some(entry) = self.connections.write().await.get_mut(&key) {
+            entry.update_last_active();
+            return Ok(entry.connection.clone());
+        }
+
+        self.make_room(&config.id, options.max_connections, &self.opening).await?;
+        let connection = match connect(config.clone()).await {
+            Ok(connection) => connection,
+            Err(e) => {
+                Self::release_slot(&self.opening, &config.id).await;
+                self.record_failure(key, &e).await;
+                return Err(e);
+            }
+        };
 
         // Store the connection
-        let entry = ConnectionEntry::new(connection, config.clone());
+        let entry = ConnectionEntry::new(connection, options);
         let connection_arc = entry.connection.clone();
 
+        self.failures.write().await.remove(&key);
         {
             let mut connections = self.connections.write().await;
             connections.insert(key, entry);
+            // Released with the map locked, so the stored connection takes over the slot
+            Self::release_slot(&self.opening, &config.id).await;
         }
 
         Ok(connection_arc)
     }
 
+    /// Count an exclusive session of `config` against its connection limit,
+    /// closing idle pooled connections to make room for it
+    pub async fn reserve_session(&self, config: &DbConnectionConfig) -> Result<(), DbError> {
+        let options = self.defaults.for_config(config);
+        self.make_room(&config.id, options.max_connections, &self.reserved).await
+    }
+
+    /// Give back a slot taken by `reserve_session`
+    pub async fn release_session(&self, config_id: &str) {
+        Self::release_slot(&self.reserved, config_id).await;
+    }
+
+    async fn release_slot(slots: &RwLock<HashMap<String, usize>>, config_id: &str) {
+        let mut slots = slots.write().await;
+        if let Some(count) = slots.get_mut(config_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                slots.remove(config_id);
+            }
+        }
+    }
+
+    /// Exclusive sessions of `config_id` counted by `reserve_session`
+    pub async fn reserved_sessions(&self, config_id: &str) -> usize {
+        self.reserved.read().await.get(config_id).copied().unwrap_or(0)
+    }
+
+    /// Close least recently used idle connections of `config_id` until another one fits under `max`,
+    /// counting the exclusive sessions it holds and the connections being opened.
+    /// The new slot is taken in `slots` before the pool is unlocked, so concurrent callers can't both get it
+    async fn make_room(
+        &self,
+        config_id: &str,
+        max: usize,
+        slots: &RwLock<HashMap<String, usize>>,
+    ) -> Result<(), DbError> {
+        let evicted = {
+            let mut connections = self.connections.write().await;
+            let reserved = self.reserved_sessions(config_id).await
+                + self.opening.read().await.get(config_id).copied().unwrap_or(0);
+            let mut evicted = Vec::new();
+            loop {
+                let pooled: Vec<(&PoolKey, &ConnectionEntry)> = connections
+                    .iter()
+                    .filter(|(key, _)| key.config_id == config_id)
+                    .collect();
+                if pooled.len() + reserved < max {
+                    break;
+                }
+                let idle = pooled
+                    .into_iter()
+                    .filter(|(_, entry)| !entry.in_use())
+                    .min_by_key(|(_, entry)| entry.last_active)
+                    .map(|(key, _)| key.clone());
+                let Some(key) = idle else {
+                    return Err(DbError::ConnectionError(format!(
+                        "All {} connections are in use, try again when a query finishes",
+                        max
+                    )));
+                };
+                evicted.extend(connections.remove(&key).map(|entry| (key, entry)));
+            }
+            *slots.write().await.entry(config_id.to_string()).or_default() += 1;
+            evicted
+        };
+
+        for (key, entry) in evicted {
+            let _ = entry.connection.write().await.disconnect().await;
+            tracing::info!("Closed connection {} to stay under the connection limit", key);
+        }
+        Ok(())
+    }
+
+    async fn record_failure(&self, key: PoolKey, error: &DbError) {
+        self.failures.write().await.insert(
+            key,
+            FailedConnection {
+                error: error.to_string(),
+                at: Instant::now(),
+            },
+        );
+    }
+
+    /// State of the pooled and failed connections of `config_id`, or of every config when `None`
+    pub async fn connection_states(&self, config_id: Option<&str>) -> Vec<PooledConnectionState> {
+        let wanted = |key: &PoolKey| config_id.is_none_or(|config_id| config_id == key.config_id);
+        let mut states: Vec<PooledConnectionState> = {
+            let connections = self.connections.read().await;
+            connections
+                .iter()
+                .filter(|(key, _)| wanted(key))
+                .map(|(key, entry)| PooledConnectionState {
+                    config_id: key.config_id.clone(),
+                    database: key.database.clone(),
+                    purpose: key.purpose.clone(),
+                    connected: true,
+                    in_use: entry.in_use(),
+                    idle_for: entry.last_active.elapsed(),
+                    last_error: entry.last_error.clone(),
+                    server_ids: entry
+                        .connection
+                        .try_read()
+                        .map(|conn| conn.running_server_ids())
+                        .unwrap_or_default(),
+                })
+                .collect()
+        };
+
+        let failures = self.failures.read().await;
+        states.extend(failures.iter().filter(|(key, _)| wanted(key)).map(|(key, failure)| {
+            PooledConnectionState {
+                config_id: key.config_id.clone(),
+                database: key.database.clone(),
+                purpose: key.purpose.clone(),
+                connected: false,
+                in_use: false,
+                idle_for: failure.at.elapsed(),
+                last_error: Some(failure.error.clone()),
+                server_ids: Vec::new(),
+            }
+        }));
+        states.sort_by(|a, b| {
+            (&a.config_id, &a.database, &a.purpose).cmp(&(&b.config_id, &b.database, &b.purpose))
+        });
+        states
+    }
+
     /// Update last active time for a connection
-    pub async fn update_last_active(&self, config_id: &str, database: Option<&str>) {
-        let key = Self::make_connection_key(config_id, database);
+    pub async fn update_last_active(&self, config: &DbConnectionConfig, purpose: SessionPurpose) {
+        let key = PoolKey::new(config, purpose);
         let mut connections = self.connections.write().await;
         if let Some(entry) = connections.get_mut(&key) {
             entry.update_last_active();
         }
     }
 
-    pub async fn remove_connection(&self, config_id: &str, database: Option<&str>) -> Option<(Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>, DbConnectionConfig)> {
-        let key = Self::make_connection_key(config_id, database);
+    /// Remove the connections of `config_id` opened for `purpose`, whatever database they point at
+    pub async fn remove_connections_for_purpose(
+        &self,
+        config_id: &str,
+        purpose: &SessionPurpose,
+    ) -> Vec<Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>> {
+        let matches = |key: &PoolKey| key.config_id == config_id && &key.purpose == purpose;
+        self.failures.write().await.retain(|key, _| !matches(key));
+        let mut connections = self.connections.write().await;
+        let keys: Vec<PoolKey> = connections.keys().filter(|key| matches(key)).cloned().collect();
+
+        keys.iter()
+            .filter_map(|key| connections.remove(key))
+            .map(|entry| entry.connection)
+            .collect()
+    }
+
+    /// Remove a single pooled connection, e.g. when closed from the connection status view
+    pub async fn remove_connection(
+        &self,
+        config_id: &str,
+        database: Option<&str>,
+        purpose: &SessionPurpose,
+    ) -> Option<Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>> {
+        let key = PoolKey {
+            config_id: config_id.to_string(),
+            database: database.map(|db| db.to_string()),
+            purpose: purpose.clone(),
+        };
+        self.failures.write().await.remove(&key);
+        self.connections.write().await.remove(&key).map(|entry| entry.connection)
+    }
+
+    /// Remove every connection opened for a config, whatever database or purpose
+    pub async fn remove_connections_for(&self, config_id: &str) -> Vec<Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>> {
+        self.failures.write().await.retain(|key, _| key.config_id != config_id);
         let mut connections = self.connections.write().await;
-        let removed = connections.remove(&key);
+        let keys: Vec<PoolKey> = connections
+            .keys()
+            .filter(|key| key.config_id == config_id)
+            .cloned()
+            .collect();
 
-        removed.map(|entry| (entry.connection, entry.config))
+        keys.iter()
+            .filter_map(|key| connections.remove(key))
+            .map(|entry| entry.connection)
+            .collect()
     }
 
     /// Clean up expired connections
     async fn cleanup_expired_connections(&self) {
         let mut connections = self.connections.write().await;
-        let timeout = self.idle_timeout;
 
         // Find expired connection keys
-        let expired_keys: Vec<String> = connections
+        let expired_keys: Vec<PoolKey> = connections
             .iter()
-            .filter(|(_, entry)| entry.is_expired(timeout))
+            .filter(|(_, entry)| entry.is_expired())
             .map(|(key, _)| key.clone())
             .collect();
 
@@ -170,7 +583,7 @@ impl ConnectionPool {
 
     /// Start a background task to periodically clean up expired connections
     pub fn start_cleanup_task(self: Arc<Self>) {
-        tokio::spawn(async move {
+        TOKIO_HANDLE.spawn(async move {
             let mut interval = time::interval(Duration::from_secs(60)); // Check every minute
 
             loop {
@@ -191,7 +604,11 @@ impl Clone for ConnectionPool {
     fn clone(&self) -> Self {
         Self {
             connections: Arc::clone(&self.connections),
-            idle_timeout: self.idle_timeout,
+            failures: Arc::clone(&self.failures),
+            reserved: Arc::clone(&self.reserved),
+            opening: Arc::clone(&self.opening),
+            open_locks: Arc::clone(&self.open_locks),
+            defaults: self.defaults,
         }
     }
 }
@@ -204,6 +621,9 @@ pub struct GlobalDbState {
     pub connection_pool: ConnectionPool,
     /// connection_id -> config 映射
     connections: Arc<RwLock<HashMap<String, DbConnectionConfig>>>,
+    /// 独占会话，session_id -> session
+    sessions: Arc<RwLock<HashMap<String, DbSession>>>,
+    next_session_id: Arc<AtomicU64>,
 }
 
 impl GlobalDbState {
@@ -212,6 +632,8 @@ impl GlobalDbState {
             db_manager: DbManager::new(),
             connection_pool: ConnectionPool::new(),
             connections: Arc::new(RwLock::new(HashMap::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            next_session_id: Arc::new(AtomicU64::new(1)),
         }
     }
 
@@ -227,19 +649,153 @@ impl GlobalDbState {
         connections.get(connection_id).cloned()
     }
 
-    /// 获取 plugin 和 connection（封装重复逻辑）
+    /// 获取 plugin 和指定用途的 connection（封装重复逻辑）
     pub async fn get_plugin_and_connection(
         &self,
         connection_id: &str,
+        purpose: SessionPurpose,
     ) -> Result<(Box<dyn DatabasePlugin>, Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>), DbError> {
         let config = self.get_config(connection_id).await
             .ok_or_else(|| DbError::ConnectionError(format!("Connection not found: {}", connection_id)))?;
 
         let plugin = self.db_manager.get_plugin(&config.database_type)?;
-        let conn = self.connection_pool.get_connection(config, &self.db_manager).await?;
+        let conn = self.connection_pool.get_connection(config, purpose, &self.db_manager).await?;
+
+        Ok((plugin, conn))
+    }
+
+    /// 获取 plugin 和连接到指定数据库的 connection：支持 schema 的数据库只能看到连接所在库的 schema，
+    /// 其他数据库在查询里带库名，沿用连接配置里的数据库
+    pub async fn get_plugin_and_database_connection(
+        &self,
+        connection_id: &str,
+        database: Option<&str>,
+        purpose: SessionPurpose,
+    ) -> Result<(Box<dyn DatabasePlugin>, Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>), DbError> {
+        let mut config = self.get_config(connection_id).await
+            .ok_or_else(|| DbError::ConnectionError(format!("Connection not found: {}", connection_id)))?;
+
+        let plugin = self.db_manager.get_plugin(&config.database_type)?;
+        if let Some(database) = database.filter(|_| plugin.supports_schema()) {
+            config.database = Some(database.to_string());
+        }
+        let conn = self.connection_pool.get_connection(config, purpose, &self.db_manager).await?;
 
         Ok((plugin, conn))
     }
+
+    /// 断开某一用途的池连接，用于标签页关闭或后台任务结束
+    pub async fn release_connections(&self, connection_id: &str, purpose: &SessionPurpose) {
+        for connection in self.connection_pool.remove_connections_for_purpose(connection_id, purpose).await {
+            let result = TOKIO_HANDLE
+                .spawn(async move { connection.write().await.disconnect().await })
+                .await;
+            if let Ok(Err(e)) = result {
+                tracing::warn!("Failed to disconnect {} ({}): {}", connection_id, purpose, e);
+            }
+        }
+    }
+
+    /// 打开独占会话：不进入共享连接池，事务等会话状态在多次执行之间保持；
+    /// 占用连接数上限中的一个名额
+    pub async fn open_session(
+        &self,
+        connection_id: &str,
+        database: Option<String>,
+    ) -> Result<DbSession, DbError> {
+        let mut config = self.get_config(connection_id).await
+            .ok_or_else(|| DbError::ConnectionError(format!("Connection not found: {}", connection_id)))?;
+
+        // SQLite 的 database 字段保存的是文件路径
+        if config.database_type != DatabaseType::SQLite {
+            config.database = database.clone();
+        }
+
+        let plugin = self.db_manager.get_plugin(&config.database_type)?;
+        self.connection_pool.reserve_session(&config).await?;
+        let connection = match plugin.create_session(config).await {
+            Ok(connection) => connection,
+            Err(e) => {
+                self.connection_pool.release_session(connection_id).await;
+                return Err(e);
+            }
+        };
+
+        let id = format!("{}#{}", connection_id, self.next_session_id.fetch_add(1, Ordering::SeqCst));
+        let session = DbSession::new(id.clone(), connection_id.to_string(), database, connection);
+        self.sessions.write().await.insert(id, session.clone());
+
+        Ok(session)
+    }
+
+    /// 关闭会话，未提交的事务随连接断开而回滚
+    pub async fn close_session(&self, session: &DbSession) {
+        if self.sessions.write().await.remove(session.id()).is_some() {
+            self.connection_pool.release_session(session.connection_id()).await;
+        }
+        session.close().await;
+    }
+
+    /// 指定连接下打开的全部独占会话
+    pub async fn sessions_for(&self, connection_id: &str) -> Vec<DbSession> {
+        let sessions = self.sessions.read().await;
+        let mut sessions: Vec<DbSession> = sessions
+            .values()
+            .filter(|session| session.connection_id() == connection_id)
+            .cloned()
+            .collect();
+        sessions.sort_by(|a, b| a.id().cmp(b.id()));
+        sessions
+    }
+
+    /// 断开单个池连接，下次使用时重新打开
+    pub async fn close_pooled_connection(&self, connection_id: &str, database: Option<&str>, purpose: &SessionPurpose) {
+        let Some(connection) = self.connection_pool.remove_connection(connection_id, database, purpose).await else {
+            return;
+        };
+        let result = TOKIO_HANDLE
+            .spawn(async move { connection.write().await.disconnect().await })
+            .await;
+        if let Ok(Err(e)) = result {
+            tracing::warn!("Failed to disconnect {} ({}): {}", connection_id, purpose, e);
+        }
+    }
+
+    /// 指定连接下仍有未提交事务的会话
+    pub async fn sessions_with_open_transaction(&self, connection_id: &str) -> Vec<DbSession> {
+        let sessions = self.sessions.read().await;
+        sessions
+            .values()
+            .filter(|session| session.connection_id() == connection_id && session.transaction().open)
+            .cloned()
+            .collect()
+    }
+
+    /// 关闭连接：断开连接池中的连接以及该连接下的所有会话
+    pub async fn close_connection(&self, connection_id: &str) {
+        for connection in self.connection_pool.remove_connections_for(connection_id).await {
+            let result = TOKIO_HANDLE
+                .spawn(async move { connection.write().await.disconnect().await })
+                .await;
+            if let Ok(Err(e)) = result {
+                tracing::warn!("Failed to disconnect {}: {}", connection_id, e);
+            }
+        }
+
+        let sessions: Vec<DbSession> = {
+            let mut sessions = self.sessions.write().await;
+            let ids: Vec<String> = sessions
+                .values()
+                .filter(|session| session.connection_id() == connection_id)
+                .map(|session| session.id().to_string())
+                .collect();
+            ids.iter().filter_map(|id| sessions.remove(id)).collect()
+        };
+        for session in sessions {
+            self.connection_pool.release_session(connection_id).await;
+            session.close().await;
+        }
+    }
 }
 
 impl Default for GlobalDbState {
@@ -249,3 +805,242 @@ impl Default for GlobalDbState {
 }
 
 impl Global for GlobalDbState {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecOptions, SqlErrorInfo, SqlResult};
+    use crate::test_support::{rows_result, test_config, TEST_CONFIG_ID};
+    use crate::types::SqlValue;
+    use async_trait::async_trait;
+    use std::sync::atomic::AtomicBool;
+
+    /// Connection that fails every query once `alive` is cleared
+    struct FlakyConnection {
+        generation: u64,
+        alive: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl DbConnection for FlakyConnection {
+        fn config(&self) -> Option<DbConnectionConfig> {
+            None
+        }
+
+        async fn connect(&mut self) -> Result<(), DbError> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<(), DbError> {
+            Ok(())
+        }
+
+        async fn execute(&self, script: &str, options: ExecOptions) -> Result<Vec<SqlResult>, DbError> {
+            Ok(vec![self.query(script, None, options).await?])
+        }
+
+        async fn query(&self, query: &str, _params: Option<Vec<SqlValue>>, _options: ExecOptions) -> Result<SqlResult, DbError> {
+            // Like the real drivers, a lost connection comes back as a failed statement
+            if !self.alive.load(Ordering::SeqCst) {
+                return Ok(SqlResult::Error(SqlErrorInfo::new(query, "server has gone away".to_string())));
+            }
+            Ok(rows_result(vec![vec![SqlValue::Int(self.generation as i64)]]))
+        }
+    }
+
+    /// Opens a new live `FlakyConnection` on each call, numbered from 1
+    struct Connector {
+        opened: AtomicU64,
+        alive: Arc<AtomicBool>,
+    }
+
+    impl Connector {
+        fn new() -> Self {
+            Self {
+                opened: AtomicU64::new(0),
+                alive: Arc::new(AtomicBool::new(true)),
+            }
+        }
+
+        async fn connect(&self, _config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+            // Like a network round trip, lets concurrent callers run in between
+            tokio::task::yield_now().await;
+            self.alive.store(true, Ordering::SeqCst);
+            let generation = self.opened.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Box::new(FlakyConnection { generation, alive: self.alive.clone() }))
+        }
+    }
+
+    fn config_for(database: &str, params: &[(&str, &str)]) -> DbConnectionConfig {
+        let mut config = test_config(DatabaseType::MySQL, params);
+        config.database = Some(database.to_string());
+        config
+    }
+
+    async fn generation(connection: &Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>) -> Option<SqlValue> {
+        match connection.read().await.query("SELECT 1", None, ExecOptions::default()).await {
+            Ok(SqlResult::Query(result)) => result.rows[0].first().cloned(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_pool_options_from_extra_params() {
+        let defaults = PoolOptions::default();
+        let config = test_config(
+            DatabaseType::MySQL,
+            &[(IDLE_TIMEOUT_PARAM, "60"), (MAX_CONNECTIONS_PARAM, "0"), (PING_INTERVAL_PARAM, " 0 ")],
+        );
+
+        let options = defaults.for_config(&config);
+        assert_eq!(options.idle_timeout, Duration::from_secs(60));
+        assert_eq!(options.max_connections, defaults.max_connections);
+        assert_eq!(options.ping_interval, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_broken_connection_is_reopened_in_place() {
+        TOKIO_HANDLE.block_on(async {
+            let pool = ConnectionPool::new();
+            let connector = Connector::new();
+            let config = config_for("app", &[(PING_INTERVAL_PARAM, "0")]);
+
+            let first = pool.get_connection_with(config.clone(), SessionPurpose::Metadata, |c| connector.connect(c)).await.unwrap();
+            assert_eq!(generation(&first).await, Some(SqlValue::Int(1)));
+
+            connector.alive.store(false, Ordering::SeqCst);
+            let second = pool.get_connection_with(config.clone(), SessionPurpose::Metadata, |c| connector.connect(c)).await.unwrap();
+            assert!(Arc::ptr_eq(&first, &second));
+            assert_eq!(generation(&first).await, Some(SqlValue::Int(2)));
+
+            drop((first, second));
+            let states = pool.connection_states(Some(TEST_CONFIG_ID)).await;
+            assert_eq!(states.len(), 1);
+            assert!(states[0].connected && !states[0].in_use);
+            assert!(states[0].last_error.as_deref().unwrap().contains("server has gone away"));
+        });
+    }
+
+    #[test]
+    fn test_connection_limit_closes_idle_connections() {
+        TOKIO_HANDLE.block_on(async {
+            let pool = ConnectionPool::new();
+            let connector = Connector::new();
+            let limit = [(MAX_CONNECTIONS_PARAM, "2")];
+
+            let app = pool.get_connection_with(config_for("app", &limit), SessionPurpose::Metadata, |c| connector.connect(c)).await.unwrap();
+            drop(pool.get_connection_with(config_for("logs", &limit), SessionPurpose::Metadata, |c| connector.connect(c)).await.unwrap());
+            let _audit = pool.get_connection_with(config_for("audit", &limit), SessionPurpose::Metadata, |c| connector.connect(c)).await.unwrap();
+
+            let databases: Vec<Option<String>> = pool
+                .connection_states(Some(TEST_CONFIG_ID))
+                .await
+                .into_iter()
+                .map(|state| state.database)
+                .collect();
+            assert_eq!(databases, vec![Some("app".to_string()), Some("audit".to_string())]);
+
+            let error = pool
+                .get_connection_with(config_for("logs", &limit), SessionPurpose::Metadata, |c| connector.connect(c))
+                .await
+                .err()
+                .unwrap();
+            assert!(error.to_string().contains("in use"));
+            drop(app);
+        });
+    }
+
+    #[test]
+    fn test_each_purpose_gets_its_own_connection() {
+        TOKIO_HANDLE.block_on(async {
+            let pool = ConnectionPool::new();
+            let connector = Connector::new();
+            let config = config_for("app", &[]);
+            let get = |purpose| pool.get_connection_with(config.clone(), purpose, |c| connector.connect(c));
+
+            let metadata = get(SessionPurpose::Metadata).await.unwrap();
+            let editor = get(SessionPurpose::Editor("tab-1".to_string())).await.unwrap();
+            let other_editor = get(SessionPurpose::Editor("tab-2".to_string())).await.unwrap();
+            let metadata_again = get(SessionPurpose::Metadata).await.unwrap();
+
+            assert!(Arc::ptr_eq(&metadata, &metadata_again));
+            assert!(!Arc::ptr_eq(&metadata, &editor));
+            assert!(!Arc::ptr_eq(&editor, &other_editor));
+            assert_eq!(generation(&other_editor).await, Some(SqlValue::Int(3)));
+
+            drop((editor, other_editor));
+            let removed = pool
+                .remove_connections_for_purpose(TEST_CONFIG_ID, &SessionPurpose::Editor("tab-1".to_string()))
+                .await;
+            assert_eq!(removed.len(), 1);
+            assert_eq!(pool.connection_states(Some(TEST_CONFIG_ID)).await.len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_concurrent_callers_open_one_connection_per_key() {
+        TOKIO_HANDLE.block_on(async {
+            let pool = ConnectionPool::new();
+            let connector = Connector::new();
+            let limit = [(MAX_CONNECTIONS_PARAM, "2")];
+            let get = |database| pool.get_connection_with(config_for(database, &limit), SessionPurpose::Metadata, |c| connector.connect(c));
+
+            let (first, second) = futures::join!(get("app"), get("app"));
+            assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+            assert_eq!(connector.opened.load(Ordering::SeqCst), 1);
+
+            // Connects running side by side still stay under the limit
+            let (logs, audit) = futures::join!(get("logs"), get("audit"));
+            assert!(logs.is_ok() && audit.is_ok());
+            assert_eq!(pool.connection_states(Some(TEST_CONFIG_ID)).await.len(), 2);
+            assert!(pool.open_locks.lock().await.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_remove_single_pooled_connection() {
+        TOKIO_HANDLE.block_on(async {
+            let pool = ConnectionPool::new();
+            let connector = Connector::new();
+            for database in ["app", "logs"] {
+                pool.get_connection_with(config_for(database, &[]), SessionPurpose::Metadata, |c| connector.connect(c))
+                    .await
+                    .unwrap();
+            }
+
+            assert!(pool.remove_connection(TEST_CONFIG_ID, Some("app"), &SessionPurpose::Background("job".to_string())).await.is_none());
+            assert!(pool.remove_connection(TEST_CONFIG_ID, Some("app"), &SessionPurpose::Metadata).await.is_some());
+
+            let states = pool.connection_states(Some(TEST_CONFIG_ID)).await;
+            assert_eq!(states.len(), 1);
+            assert_eq!(states[0].database.as_deref(), Some("logs"));
+        });
+    }
+
+    #[test]
+    fn test_reserved_sessions_count_against_the_limit() {
+        TOKIO_HANDLE.block_on(async {
+            let pool = ConnectionPool::new();
+            let connector = Connector::new();
+            let config = config_for("app", &[(MAX_CONNECTIONS_PARAM, "2")]);
+
+            let metadata = pool
+                .get_connection_with(config.clone(), SessionPurpose::Metadata, |c| connector.connect(c))
+                .await
+                .unwrap();
+            pool.reserve_session(&config).await.unwrap();
+            assert!(pool.reserve_session(&config).await.is_err());
+
+            // An idle pooled connection makes way for the session
+            drop(metadata);
+            pool.reserve_session(&config).await.unwrap();
+            assert_eq!(pool.reserved_sessions(TEST_CONFIG_ID).await, 2);
+            assert!(pool.connection_states(Some(TEST_CONFIG_ID)).await.is_empty());
+
+            pool.release_session(TEST_CONFIG_ID).await;
+            pool.get_connection_with(config, SessionPurpose::Metadata, |c| connector.connect(c))
+                .await
+                .unwrap();
+        });
+    }
+}
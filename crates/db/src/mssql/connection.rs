@@ -1,313 +1,626 @@
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
-use tiberius::{Client, Config, AuthMethod, Row};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+use tiberius::time::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use tiberius::{AuthMethod, Client, ColumnData, ColumnType, Config, FromSql, Query, QueryItem, Row};
 use tokio::net::TcpStream;
-use tokio_util::compat::{TokioAsyncWriteCompatExt, Compat};
-
-use crate::{
-    ExecResult, QueryResult, SqlErrorInfo, SqlResult, SqlScriptSplitter,
-    SqlStatementClassifier,
+use tokio::sync::Mutex;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::cancel::RunningQueries;
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{
+    ExecOptions, ExecResult, QueryResult, ResultColumn, SqlErrorInfo, SqlResult, SqlScriptSplitter,
+    SqlStatementClassifier, StatementType,
 };
+use crate::runtime::TOKIO_HANDLE;
+use crate::ssl::SSL_TRUST_SERVER_CERT_PARAM;
+use crate::types::SqlValue;
 
-pub struct MssqlConnection {
-    config: Option<MssqlConfig>,
-    client: RwLock<Option<Client<Compat<TcpStream>>>>,
-    current_database: RwLock<Option<String>>,
-}
+type MssqlClient = Client<Compat<TcpStream>>;
 
-#[derive(Debug, Clone)]
-pub struct MssqlConfig {
-    pub id: String,
-    pub name: String,
-    pub host: String,
-    pub port: u16,
-    pub username: String,
-    pub password: String,
-    pub database: Option<String>,
+/// Rows fetched from a single result set: column metadata, typed cells and whether rows were left out
+type FetchedRows = (Vec<ResultColumn>, Vec<Vec<SqlValue>>, bool);
+
+pub struct MssqlDbConnection {
+    config: Option<DbConnectionConfig>,
+    // A TDS client is a single session; the async mutex serializes statements on it
+    client: Arc<Mutex<Option<MssqlClient>>>,
+    // Track current database selected on this connection
+    current_database: RwLock<Option<String>>,
+    // Tracks the session id while a statement runs, which cancel_query KILLs
+    running: RunningQueries,
+    // Server process id (@@SPID) of the open session
+    session_id: AtomicU64,
+    // Set when cancel_query KILLed the session, the next statement finds it gone
+    killed: Arc<AtomicBool>,
+    // Bumped each time a KILLed session is replaced, so a script notices its transaction is gone
+    session_generation: AtomicU64,
 }
 
-impl MssqlConnection {
-    pub fn new(config: MssqlConfig) -> Self {
+impl MssqlDbConnection {
+    pub fn new(config: DbConnectionConfig) -> Self {
         Self {
             config: Some(config),
-            client: RwLock::new(None),
+            client: Arc::new(Mutex::new(None)),
             current_database: RwLock::new(None),
+            running: RunningQueries::default(),
+            session_id: AtomicU64::new(0),
+            killed: Arc::new(AtomicBool::new(false)),
+            session_generation: AtomicU64::new(0),
         }
     }
 
-    fn ensure_connected(&self) -> Result<(), String> {
-        let guard = self.client.read().unwrap();
-        if guard.is_none() {
-            return Err("Not connected to database".to_string());
-        }
-        Ok(())
-    }
-
-    pub async fn connect(&mut self) -> Result<(), String> {
-        let config = self.config.as_ref().ok_or("No configuration provided")?;
-        
+    fn tiberius_config(config: &DbConnectionConfig) -> Config {
         let mut tiberius_config = Config::new();
         tiberius_config.host(&config.host);
         tiberius_config.port(config.port);
         tiberius_config.authentication(AuthMethod::sql_server(&config.username, &config.password));
-        
+        if Self::trusts_server_certificate(config) {
+            tiberius_config.trust_cert();
+        }
         if let Some(db) = &config.database {
             tiberius_config.database(db);
         }
+        tiberius_config
+    }
+
+    /// Skipping certificate validation is opt-in, for development servers with a self-signed certificate
+    fn trusts_server_certificate(config: &DbConnectionConfig) -> bool {
+        config
+            .extra_params
+            .get(SSL_TRUST_SERVER_CERT_PARAM)
+            .is_some_and(|value| value.trim() == "true")
+    }
 
+    async fn open_client(config: &DbConnectionConfig) -> Result<MssqlClient, String> {
+        let tiberius_config = Self::tiberius_config(config);
         let tcp = TcpStream::connect(tiberius_config.get_addr())
             .await
             .map_err(|e| format!("Failed to connect to TCP: {}", e))?;
+        tcp.set_nodelay(true)
+            .map_err(|e| format!("Failed to configure TCP: {}", e))?;
 
-        let client = Client::connect(tiberius_config, tcp.compat_write())
+        Client::connect(tiberius_config, tcp.compat_write())
             .await
-            .map_err(|e| format!("Failed to connect to MSSQL: {}", e))?;
+            .map_err(|e| format!("Failed to connect to MSSQL: {}", e))
+    }
 
-        {
-            let mut guard = self.client.write().unwrap();
-            *guard = Some(client);
+    /// Open a client and look up its server process id
+    async fn open_session(config: DbConnectionConfig) -> Result<(MssqlClient, u64), String> {
+        let mut client = Self::open_client(&config).await?;
+        let session_id = client
+            .simple_query("SELECT @@SPID")
+            .await
+            .map_err(|e| e.to_string())?
+            .into_row()
+            .await
+            .map_err(|e| e.to_string())?
+            .and_then(|row| row.get::<i16, _>(0))
+            .ok_or("Failed to read the session id")?;
+        Ok((client, session_id as u64))
+    }
+
+    /// Replace a session ended by KILL, staying on the database the old one had switched to
+    async fn reopen_session(&self) -> Result<(), String> {
+        let mut config = self.config.clone().ok_or("No database configuration provided")?;
+        config.database = self.current_database.read().unwrap().clone();
+
+        let (client, session_id) = TOKIO_HANDLE
+            .spawn(Self::open_session(config))
+            .await
+            .map_err(|e| e.to_string())??;
+        *self.client.lock().await = Some(client);
+        self.session_id.store(session_id, Ordering::SeqCst);
+        self.session_generation.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Database a `USE` statement switches to
+    fn used_database(sql: &str) -> Option<String> {
+        if !sql.to_uppercase().starts_with("USE ") {
+            return None;
+        }
+        let name = sql[4..]
+            .trim()
+            .trim_end_matches(';')
+            .trim_matches(|c| c == '[' || c == ']');
+        Some(name.to_string())
+    }
+
+    fn build_query(sql: &str, params: Vec<SqlValue>) -> Query<'static> {
+        let mut query = Query::new(sql.to_string());
+        for param in params {
+            match param {
+                SqlValue::Null => query.bind(Option::<String>::None),
+                SqlValue::Bool(v) => query.bind(v),
+                SqlValue::Int(v) => query.bind(v),
+                SqlValue::Float(v) => query.bind(v),
+                SqlValue::String(v) => query.bind(v),
+                SqlValue::Bytes(v) => query.bind(v),
+                SqlValue::Json(v) => query.bind(v.to_string()),
+                SqlValue::Decimal(v) => query.bind(v),
+                SqlValue::Date(v) => query.bind(v),
+                SqlValue::Time(v) => query.bind(v),
+                SqlValue::DateTime(v) => query.bind(v),
+                SqlValue::DateTimeTz(v) => query.bind(v),
+                SqlValue::Uuid(v) => query.bind(v),
+            }
+        }
+        query
+    }
+
+    /// Run a row-returning statement and collect the first result set.
+    /// Rows are read as they arrive and reading stops after `max_rows`; the client discards
+    /// the rest of the result before it sends the next statement.
+    async fn fetch(
+        &self,
+        sql: &str,
+        params: Option<Vec<SqlValue>>,
+        max_rows: Option<usize>,
+    ) -> Result<FetchedRows, String> {
+        let client = self.client.clone();
+        let sql = sql.to_string();
+
+        TOKIO_HANDLE
+            .spawn(async move {
+                let mut guard = client.lock().await;
+                let client = guard.as_mut().ok_or("Not connected to database")?;
+
+                let mut stream = match params {
+                    Some(params) => Self::build_query(&sql, params).query(client).await,
+                    // simple_query keeps session state (temp tables, SET options) on the connection
+                    None => client.simple_query(sql).await,
+                }
+                .map_err(|e| e.to_string())?;
+
+                let columns: Vec<ResultColumn> = stream
+                    .columns()
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map(|cols| {
+                        cols.iter()
+                            .map(|c| ResultColumn::new(c.name(), Self::type_name(c.column_type())))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let limit = max_rows.unwrap_or(usize::MAX);
+                let mut data_rows = Vec::new();
+                let mut has_more = false;
+                while let Some(item) = stream.try_next().await.map_err(|e| e.to_string())? {
+                    match item {
+                        QueryItem::Row(row) if row.result_index() == 0 => {
+                            if data_rows.len() == limit {
+                                has_more = true;
+                                break;
+                            }
+                            data_rows.push(Self::extract_row(row));
+                        }
+                        QueryItem::Metadata(meta) if meta.result_index() == 0 => {}
+                        // Only the first result set is shown
+                        _ => break,
+                    }
+                }
+
+                Ok((columns, data_rows, has_more))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    /// Run a statement that does not return rows
+    async fn exec(&self, sql: &str, params: Option<Vec<SqlValue>>) -> Result<u64, String> {
+        let client = self.client.clone();
+        let sql = sql.to_string();
+        let is_dml = SqlStatementClassifier::classify(&sql) == StatementType::Dml;
+
+        TOKIO_HANDLE
+            .spawn(async move {
+                let mut guard = client.lock().await;
+                let client = guard.as_mut().ok_or("Not connected to database")?;
+
+                match params {
+                    Some(params) => Self::build_query(&sql, params)
+                        .execute(client)
+                        .await
+                        .map(|r| r.total())
+                        .map_err(|e| e.to_string()),
+                    None if is_dml => client
+                        .execute(sql, &[])
+                        .await
+                        .map(|r| r.total())
+                        .map_err(|e| e.to_string()),
+                    // DDL, USE and SET must run as a plain batch, sp_executesql would scope them
+                    None => {
+                        client
+                            .simple_query(sql)
+                            .await
+                            .map_err(|e| e.to_string())?
+                            .into_results()
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        Ok(0)
+                    }
+                }
+            })
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    fn extract_row(row: Row) -> Vec<SqlValue> {
+        row.into_iter().map(|data| Self::extract_value(&data)).collect()
+    }
+
+    fn extract_value(data: &ColumnData<'static>) -> SqlValue {
+        let value = match data {
+            ColumnData::U8(v) => v.map(|v| SqlValue::Int(v as i64)),
+            ColumnData::I16(v) => v.map(|v| SqlValue::Int(v as i64)),
+            ColumnData::I32(v) => v.map(|v| SqlValue::Int(v as i64)),
+            ColumnData::I64(v) => v.map(SqlValue::Int),
+            ColumnData::F32(v) => v.map(|v| SqlValue::Float(v as f64)),
+            ColumnData::F64(v) => v.map(SqlValue::Float),
+            ColumnData::Bit(v) => v.map(SqlValue::Bool),
+            ColumnData::String(v) => v.as_ref().map(|s| SqlValue::String(s.to_string())),
+            ColumnData::Guid(v) => v.map(SqlValue::Uuid),
+            ColumnData::Numeric(v) => v.map(|v| SqlValue::Decimal(v.to_string())),
+            ColumnData::Xml(v) => v.as_ref().map(|x| SqlValue::String(x.to_string())),
+            ColumnData::Binary(v) => v.as_ref().map(|b| SqlValue::Bytes(b.to_vec())),
+            ColumnData::DateTime(_) | ColumnData::SmallDateTime(_) | ColumnData::DateTime2(_) => {
+                NaiveDateTime::from_sql(data).ok().flatten().map(SqlValue::DateTime)
+            }
+            ColumnData::Date(_) => NaiveDate::from_sql(data).ok().flatten().map(SqlValue::Date),
+            ColumnData::Time(_) => NaiveTime::from_sql(data).ok().flatten().map(SqlValue::Time),
+            ColumnData::DateTimeOffset(_) => DateTime::<FixedOffset>::from_sql(data)
+                .ok()
+                .flatten()
+                .map(SqlValue::DateTimeTz),
+        };
+        value.unwrap_or(SqlValue::Null)
+    }
+
+    /// SQL Server type name for the column metadata TDS reports
+    fn type_name(column_type: ColumnType) -> &'static str {
+        match column_type {
+            ColumnType::Null => "NULL",
+            ColumnType::Bit | ColumnType::Bitn => "BIT",
+            ColumnType::Int1 => "TINYINT",
+            ColumnType::Int2 => "SMALLINT",
+            ColumnType::Int4 | ColumnType::Intn => "INT",
+            ColumnType::Int8 => "BIGINT",
+            ColumnType::Float4 => "REAL",
+            ColumnType::Float8 | ColumnType::Floatn => "FLOAT",
+            ColumnType::Money | ColumnType::Money4 => "MONEY",
+            ColumnType::Decimaln => "DECIMAL",
+            ColumnType::Numericn => "NUMERIC",
+            ColumnType::Datetime4 => "SMALLDATETIME",
+            ColumnType::Datetime | ColumnType::Datetimen => "DATETIME",
+            ColumnType::Datetime2 => "DATETIME2",
+            ColumnType::DatetimeOffsetn => "DATETIMEOFFSET",
+            ColumnType::Daten => "DATE",
+            ColumnType::Timen => "TIME",
+            ColumnType::Guid => "UNIQUEIDENTIFIER",
+            ColumnType::BigVarBin => "VARBINARY",
+            ColumnType::BigBinary => "BINARY",
+            ColumnType::Image => "IMAGE",
+            ColumnType::BigVarChar => "VARCHAR",
+            ColumnType::BigChar => "CHAR",
+            ColumnType::NVarchar => "NVARCHAR",
+            ColumnType::NChar => "NCHAR",
+            ColumnType::Text => "TEXT",
+            ColumnType::NText => "NTEXT",
+            ColumnType::Xml => "XML",
+            ColumnType::Udt => "UDT",
+            ColumnType::SSVariant => "SQL_VARIANT",
+        }
+    }
+
+    /// Commit or roll back the transaction opened by a transactional script
+    async fn end_transaction(&self, commit: bool) -> SqlResult {
+        let start = Instant::now();
+        let sql = if commit { "COMMIT TRANSACTION" } else { "ROLLBACK TRANSACTION" };
+
+        match self.exec(sql, None).await {
+            Ok(_) if commit => SqlResult::Exec(ExecResult::transaction_committed(start.elapsed().as_millis())),
+            Ok(_) => SqlResult::Exec(ExecResult::transaction_rolled_back(start.elapsed().as_millis())),
+            Err(message) => SqlResult::Error(SqlErrorInfo::new(sql, message)),
+        }
+    }
+
+    /// Execute a single statement and wrap the outcome as a SqlResult
+    async fn run_statement(
+        &self,
+        sql: &str,
+        params: Option<Vec<SqlValue>>,
+        max_rows: Option<usize>,
+    ) -> SqlResult {
+        let start = Instant::now();
+        let running = self.running.track(self.session_id.load(Ordering::SeqCst));
+
+        let result = if SqlStatementClassifier::is_query_statement(sql) {
+            match self.fetch(sql, params, max_rows).await {
+                Ok((columns, rows, has_more)) => SqlResult::Query(QueryResult {
+                    sql: sql.to_string(),
+                    columns,
+                    rows,
+                    elapsed_ms: start.elapsed().as_millis(),
+                    has_more,
+                    more_rows: None,
+                }),
+                Err(message) => SqlResult::Error(SqlErrorInfo::new(sql, message).with_line_from_message(" on line ")),
+            }
+        } else {
+            match self.exec(sql, params).await {
+                Ok(rows_affected) => SqlResult::Exec(ExecResult {
+                    sql: sql.to_string(),
+                    rows_affected,
+                    elapsed_ms: start.elapsed().as_millis(),
+                    message: Some(SqlStatementClassifier::format_message(sql, rows_affected)),
+                }),
+                Err(message) => SqlResult::Error(SqlErrorInfo::new(sql, message).with_line_from_message(" on line ")),
+            }
+        };
+        drop(running);
+
+        if !self.killed.swap(false, Ordering::SeqCst) {
+            return result;
+        }
+        // KILL ended the whole session, rolling back its transaction and dropping its temp tables
+        let message = match self.reopen_session().await {
+            Ok(()) => "Query cancelled, SQL Server ended the session and rolled back its open transaction".to_string(),
+            Err(e) => format!("Query cancelled, failed to reconnect: {}", e),
+        };
+        match result {
+            SqlResult::Error(_) => SqlResult::Error(SqlErrorInfo::new(sql, message)),
+            finished => finished,
         }
+    }
+}
+
+#[async_trait]
+impl DbConnection for MssqlDbConnection {
+    fn config(&self) -> Option<DbConnectionConfig> {
+        self.config.clone()
+    }
+
+    async fn connect(&mut self) -> Result<(), DbError> {
+        let config = self
+            .config
+            .clone()
+            .ok_or_else(|| DbError::ConnectionError("No database configuration provided".to_string()))?;
+
+        let (client, session_id) = TOKIO_HANDLE
+            .spawn(Self::open_session(config.clone()))
+            .await
+            .map_err(|e| DbError::ConnectionError(format!("Failed to spawn connection task: {}", e)))?
+            .map_err(DbError::ConnectionError)?;
+
+        *self.client.lock().await = Some(client);
+        self.session_id.store(session_id, Ordering::SeqCst);
         {
             let mut db_guard = self.current_database.write().unwrap();
-            *db_guard = config.database.clone();
+            db_guard.clone_from(&config.database);
         }
 
         Ok(())
     }
 
-    pub fn disconnect(&mut self) -> Result<(), String> {
-        let client_opt = {
-            let mut guard = self.client.write().unwrap();
-            guard.take()
-        };
-        
-        if let Some(_client) = client_opt {
-            // Client will be dropped automatically
+    async fn disconnect(&mut self) -> Result<(), DbError> {
+        let client_opt = self.client.lock().await.take();
+        if let Some(client) = client_opt {
+            TOKIO_HANDLE
+                .spawn(async move { client.close().await })
+                .await
+                .map_err(|e| DbError::ConnectionError(e.to_string()))?
+                .map_err(|e| DbError::ConnectionError(e.to_string()))?;
         }
-        
         Ok(())
     }
 
-    pub async fn execute(&self, sql: &str) -> Result<Vec<SqlResult>, String> {
-        self.ensure_connected()?;
-        
-        let statements = SqlScriptSplitter::split(sql);
+    async fn execute(&self, script: &str, options: ExecOptions) -> Result<Vec<SqlResult>, DbError> {
+        if self.client.lock().await.is_none() {
+            return Err(DbError::ConnectionError("Not connected to database".to_string()));
+        }
+
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::MSSQL);
         let mut results = Vec::new();
 
-        for stmt in statements {
-            let stmt = stmt.trim();
-            if stmt.is_empty() {
+        // The single client keeps the session, so the script shares one transaction
+        let mut in_transaction = options.transactional;
+        let generation = self.session_generation.load(Ordering::SeqCst);
+        if in_transaction {
+            self.exec("BEGIN TRANSACTION", None)
+                .await
+                .map_err(|e| DbError::QueryError(format!("Failed to begin transaction: {}", e)))?;
+        }
+
+        self.running.reset();
+        for sql in statements {
+            let sql = sql.trim();
+            if sql.is_empty() {
                 continue;
             }
+            if self.running.is_cancelled() {
+                break;
+            }
 
-            let start = Instant::now();
-            let stmt_upper = stmt.to_uppercase();
-
-            if stmt_upper.starts_with("USE ") {
-                let db_name = stmt.trim_start_matches("USE ")
-                    .trim_start_matches("use ")
-                    .trim()
-                    .trim_matches('[')
-                    .trim_matches(']')
-                    .trim_matches(';')
-                    .to_string();
-
-                let mut guard = self.client.write().unwrap();
-                let client = guard.as_mut().ok_or("Not connected")?;
-
-                match client.execute(stmt, &[]).await {
-                    Ok(_) => {
-                        let mut db_guard = self.current_database.write().unwrap();
-                        *db_guard = Some(db_name.clone());
-                        
-                        let elapsed_ms = start.elapsed().as_millis();
-                        results.push(SqlResult::Exec(ExecResult {
-                            sql: stmt.to_string(),
-                            rows_affected: 0,
-                            elapsed_ms,
-                            message: Some(format!("Database changed to '{}'", db_name)),
-                        }));
-                    }
-                    Err(e) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: stmt.to_string(),
-                            message: e.to_string(),
-                        }));
+            let result = self.run_statement(sql, None, options.max_rows).await;
+
+            if let SqlResult::Error(_) = &result {
+                results.push(result);
+                if options.stop_on_error {
+                    if in_transaction {
+                        results.push(self.end_transaction(false).await);
+                        in_transaction = false;
                     }
+                    break;
                 }
                 continue;
             }
 
-            let is_query = SqlStatementClassifier::is_query_statement(stmt);
-
-            let mut guard = self.client.write().unwrap();
-            let client = guard.as_mut().ok_or("Not connected")?;
+            if let Some(db_name) = Self::used_database(sql) {
+                {
+                    let mut db_guard = self.current_database.write().unwrap();
+                    *db_guard = Some(db_name.clone());
+                }
+                results.push(SqlResult::Exec(ExecResult {
+                    sql: sql.to_string(),
+                    rows_affected: 0,
+                    elapsed_ms: 0,
+                    message: Some(format!("Database changed to '{}'", db_name)),
+                }));
+                continue;
+            }
 
-            if is_query {
-                match client.query(stmt, &[]).await {
-                    Ok(stream) => {
-                        let rows = stream.into_first_result()
-                            .await
-                            .map_err(|e| e.to_string())?;
+            results.push(result);
+        }
 
-                        let elapsed_ms = start.elapsed().as_millis();
-
-                        let columns: Vec<String> = if let Some(first_row) = rows.first() {
-                            first_row.columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect()
-                        } else {
-                            Vec::new()
-                        };
-
-                        let data_rows: Vec<Vec<Option<String>>> = rows.into_iter()
-                            .map(|row| {
-                                (0..columns.len()).map(|i| {
-                                    Self::extract_value(&row, i)
-                                }).collect()
-                            })
-                            .collect();
-
-                        results.push(SqlResult::Query(QueryResult {
-                            sql: stmt.to_string(),
-                            columns,
-                            rows: data_rows,
-                            elapsed_ms,
-                        }));
-                    }
-                    Err(e) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: stmt.to_string(),
-                            message: e.to_string(),
-                        }));
-                    }
-                }
+        if in_transaction {
+            if self.session_generation.load(Ordering::SeqCst) != generation {
+                // The transaction went down with the KILLed session
+                results.push(SqlResult::Exec(ExecResult::transaction_rolled_back(0)));
             } else {
-                match client.execute(stmt, &[]).await {
-                    Ok(result) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-                        let rows_affected = result.total();
-                        let message = SqlStatementClassifier::format_message(stmt, rows_affected);
-
-                        results.push(SqlResult::Exec(ExecResult {
-                            sql: stmt.to_string(),
-                            rows_affected,
-                            elapsed_ms,
-                            message: Some(message),
-                        }));
-                    }
-                    Err(e) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: stmt.to_string(),
-                            message: e.to_string(),
-                        }));
-                    }
-                }
+                let commit = !self.running.is_cancelled();
+                results.push(self.end_transaction(commit).await);
             }
         }
 
+        SqlScriptSplitter::locate_errors(script, DatabaseType::MSSQL, &mut results);
         Ok(results)
     }
 
-    pub async fn query(&self, sql: &str) -> Result<SqlResult, String> {
-        self.ensure_connected()?;
-
-        let start = Instant::now();
-        let is_query = SqlStatementClassifier::is_query_statement(sql);
-
-        let mut guard = self.client.write().unwrap();
-        let client = guard.as_mut().ok_or("Not connected")?;
+    async fn query(&self, query: &str, params: Option<Vec<SqlValue>>, _options: ExecOptions) -> Result<SqlResult, DbError> {
+        if self.client.lock().await.is_none() {
+            return Err(DbError::ConnectionError("Not connected to database".to_string()));
+        }
 
-        if is_query {
-            match client.query(sql, &[]).await {
-                Ok(stream) => {
-                    let rows = stream.into_first_result()
-                        .await
-                        .map_err(|e| e.to_string())?;
+        Ok(self.run_statement(query, params, None).await)
+    }
 
-                    let elapsed_ms = start.elapsed().as_millis();
+    fn running_server_ids(&self) -> Vec<u64> {
+        self.running.handles()
+    }
 
-                    let columns: Vec<String> = if let Some(first_row) = rows.first() {
-                        first_row.columns()
-                            .iter()
-                            .map(|col| col.name().to_string())
-                            .collect()
-                    } else {
-                        Vec::new()
-                    };
-
-                    let data_rows: Vec<Vec<Option<String>>> = rows.into_iter()
-                        .map(|row| {
-                            (0..columns.len()).map(|i| {
-                                Self::extract_value(&row, i)
-                            }).collect()
-                        })
-                        .collect();
-
-                    Ok(SqlResult::Query(QueryResult {
-                        sql: sql.to_string(),
-                        columns,
-                        rows: data_rows,
-                        elapsed_ms,
-                    }))
+    /// TDS offers no way to interrupt a statement from the client, so the session is KILLed from a
+    /// side connection, which needs the ALTER ANY CONNECTION permission. The next statement runs
+    /// on a new session.
+    async fn cancel_query(&self) -> Result<(), DbError> {
+        self.running.request_cancel();
+        let ids = self.running.handles();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let config = self
+            .config
+            .clone()
+            .ok_or_else(|| DbError::ConnectionError("No database configuration provided".to_string()))?;
+
+        self.killed.store(true, Ordering::SeqCst);
+        let killed = self.killed.clone();
+        TOKIO_HANDLE
+            .spawn(async move {
+                let result: Result<(), String> = async {
+                    let mut side = Self::open_client(&config).await?;
+                    for id in ids {
+                        side.simple_query(format!("KILL {}", id))
+                            .await
+                            .map_err(|e| e.to_string())?
+                            .into_results()
+                            .await
+                            .map_err(|e| e.to_string())?;
+                    }
+                    side.close().await.map_err(|e| e.to_string())
                 }
-                Err(e) => Ok(SqlResult::Error(SqlErrorInfo {
-                    sql: sql.to_string(),
-                    message: e.to_string(),
-                })),
-            }
-        } else {
-            match client.execute(sql, &[]).await {
-                Ok(result) => {
-                    let elapsed_ms = start.elapsed().as_millis();
-                    let rows_affected = result.total();
-                    let message = SqlStatementClassifier::format_message(sql, rows_affected);
-
-                    Ok(SqlResult::Exec(ExecResult {
-                        sql: sql.to_string(),
-                        rows_affected,
-                        elapsed_ms,
-                        message: Some(message),
-                    }))
+                .await;
+                if result.is_err() {
+                    // The session survived, keep using it
+                    killed.store(false, Ordering::SeqCst);
                 }
-                Err(e) => Ok(SqlResult::Error(SqlErrorInfo {
-                    sql: sql.to_string(),
-                    message: e.to_string(),
-                })),
-            }
-        }
+                result
+            })
+            .await
+            .map_err(|e| DbError::QueryError(format!("Failed to spawn cancel task: {}", e)))?
+            .map_err(|e| DbError::QueryError(format!("Failed to cancel query: {}", e)))
     }
 
-    fn extract_value(row: &Row, index: usize) -> Option<String> {
-        row.try_get::<&str, _>(index)
-            .ok()
-            .flatten()
-            .map(|s| s.to_string())
-            .or_else(|| {
-                row.try_get::<i32, _>(index)
-                    .ok()
-                    .flatten()
-                    .map(|v| v.to_string())
-            })
-            .or_else(|| {
-                row.try_get::<i64, _>(index)
-                    .ok()
-                    .flatten()
-                    .map(|v| v.to_string())
-            })
-            .or_else(|| {
-                row.try_get::<f64, _>(index)
-                    .ok()
-                    .flatten()
-                    .map(|v| v.to_string())
-            })
-            .or_else(|| {
-                row.try_get::<bool, _>(index)
-                    .ok()
-                    .flatten()
-                    .map(|v| v.to_string())
-            })
+    async fn begin_transaction(&self) -> Result<(), DbError> {
+        // A bare BEGIN starts a statement block in T-SQL
+        self.run_transaction_statement("BEGIN TRANSACTION").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config;
+    use std::borrow::Cow;
+    use tiberius::numeric::Numeric;
+
+    #[test]
+    fn test_extract_value() {
+        assert_eq!(MssqlDbConnection::extract_value(&ColumnData::U8(Some(7))), SqlValue::Int(7));
+        assert_eq!(MssqlDbConnection::extract_value(&ColumnData::I64(Some(-3))), SqlValue::Int(-3));
+        assert_eq!(MssqlDbConnection::extract_value(&ColumnData::Bit(Some(true))), SqlValue::Bool(true));
+        assert_eq!(
+            MssqlDbConnection::extract_value(&ColumnData::String(Some(Cow::Borrowed("héllo")))),
+            SqlValue::String("héllo".to_string())
+        );
+        assert_eq!(
+            MssqlDbConnection::extract_value(&ColumnData::Numeric(Some(Numeric::new_with_scale(12345, 2)))),
+            SqlValue::Decimal("123.45".to_string())
+        );
+        // NULL of any type
+        assert_eq!(MssqlDbConnection::extract_value(&ColumnData::I32(None)), SqlValue::Null);
+        assert_eq!(MssqlDbConnection::extract_value(&ColumnData::String(None)), SqlValue::Null);
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(MssqlDbConnection::type_name(ColumnType::Intn), "INT");
+        assert_eq!(MssqlDbConnection::type_name(ColumnType::NVarchar), "NVARCHAR");
+        assert_eq!(MssqlDbConnection::type_name(ColumnType::Datetime2), "DATETIME2");
+        assert_eq!(MssqlDbConnection::type_name(ColumnType::Guid), "UNIQUEIDENTIFIER");
+    }
+
+    #[test]
+    fn test_used_database() {
+        assert_eq!(MssqlDbConnection::used_database("USE [sales];"), Some("sales".to_string()));
+        assert_eq!(MssqlDbConnection::used_database("use  reports"), Some("reports".to_string()));
+        assert_eq!(MssqlDbConnection::used_database("USER_NAME()"), None);
+        assert_eq!(MssqlDbConnection::used_database("SELECT 1"), None);
+    }
+
+    #[test]
+    fn test_tiberius_config_address() {
+        let mut config = test_config(DatabaseType::MSSQL, &[]);
+        config.port = 14330;
+        assert_eq!(MssqlDbConnection::tiberius_config(&config).get_addr(), "db.internal:14330");
+    }
+
+    #[test]
+    fn test_server_certificate_is_validated_by_default() {
+        let config = test_config(DatabaseType::MSSQL, &[]);
+        assert!(!MssqlDbConnection::trusts_server_certificate(&config));
+        let config = test_config(DatabaseType::MSSQL, &[(SSL_TRUST_SERVER_CERT_PARAM, "false")]);
+        assert!(!MssqlDbConnection::trusts_server_certificate(&config));
+        let config = test_config(DatabaseType::MSSQL, &[(SSL_TRUST_SERVER_CERT_PARAM, "true")]);
+        assert!(MssqlDbConnection::trusts_server_certificate(&config));
+    }
+
+    #[test]
+    fn test_cancel_without_running_statement() {
+        // Nothing to KILL, so the unreachable server is never contacted
+        let connection = MssqlDbConnection::new(test_config(DatabaseType::MSSQL, &[]));
+        TOKIO_HANDLE.block_on(async {
+            assert!(connection.cancel_query().await.is_ok());
+            assert!(!connection.killed.load(Ordering::SeqCst));
+
+            let error = connection.execute("SELECT 1", ExecOptions::default()).await.unwrap_err();
+            assert!(error.to_string().contains("Not connected"));
+        });
     }
 }
@@ -0,0 +1,843 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use gpui_component::table::Column;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{ExecOptions, ExecResult, SqlResult};
+use crate::mssql::connection::MssqlDbConnection;
+use crate::plugin::DatabasePlugin;
+use crate::types::*;
+
+/// SQL Server database plugin implementation (stateless)
+///
+/// Databases are split into schemas, like PostgreSQL: the connection is bound to the
+/// database and the `database` argument of the object listing and DDL methods is a schema.
+/// Metadata is read from the `sys.*` catalog views of that database.
+pub struct MssqlPlugin;
+
+impl MssqlPlugin {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Escape a value used inside a single-quoted SQL string literal
+    fn escape_literal(value: &str) -> String {
+        value.replace('\'', "''")
+    }
+
+    async fn query_rows(&self, connection: &dyn DbConnection, sql: &str, what: &str) -> Result<Vec<Vec<SqlValue>>> {
+        let result = connection.query(sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list {}: {}", what, e))?;
+
+        match result {
+            SqlResult::Query(query_result) => Ok(query_result.rows),
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to list {}: {}", what, err.message)),
+            _ => Err(anyhow::anyhow!("Unexpected result type")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePlugin for MssqlPlugin {
+    fn name(&self) -> DatabaseType {
+        DatabaseType::MSSQL
+    }
+
+    fn pagination_clause(&self, has_order: bool, limit: usize, offset: usize) -> String {
+        // OFFSET ... FETCH is only valid after an ORDER BY
+        let order = if has_order { "" } else { " ORDER BY (SELECT NULL)" };
+        format!("{} OFFSET {} ROWS FETCH NEXT {} ROWS ONLY", order, offset, limit)
+    }
+
+    async fn create_connection(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        let mut conn = MssqlDbConnection::new(config);
+        conn.connect().await?;
+        Ok(Box::new(conn))
+    }
+
+    // === Database/Schema Level Operations ===
+
+    fn supports_schema(&self) -> bool {
+        true
+    }
+
+    async fn list_schemas(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
+        // Ids from 16384 up are the schemas of the fixed database roles
+        let rows = self.query_rows(
+            connection,
+            "SELECT name FROM sys.schemas \
+             WHERE schema_id < 16384 AND name NOT IN ('sys', 'INFORMATION_SCHEMA', 'guest') \
+             ORDER BY name",
+            "schemas",
+        ).await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| row.first().and_then(|v| v.as_text()))
+            .collect())
+    }
+
+    async fn list_databases(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
+        let rows = self.query_rows(
+            connection,
+            "SELECT name FROM sys.databases WHERE HAS_DBACCESS(name) = 1 ORDER BY name",
+            "databases",
+        ).await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| row.first().and_then(|v| v.as_text()))
+            .collect())
+    }
+
+    async fn list_databases_view(&self, connection: &dyn DbConnection) -> Result<ObjectView> {
+        use gpui::px;
+
+        let databases = self.list_databases_detailed(connection).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("collation", "Collation").width(px(220.0)),
+            Column::new("size", "Size").width(px(100.0)).text_right(),
+            Column::new("state", "State").width(px(120.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = databases.iter().map(|db| {
+            vec![
+                db.name.clone(),
+                db.collation.as_deref().unwrap_or("-").to_string(),
+                db.size.as_deref().unwrap_or("-").to_string(),
+                db.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} database(s)", databases.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_databases_detailed(&self, connection: &dyn DbConnection) -> Result<Vec<DatabaseInfo>> {
+        let rows = self.query_rows(
+            connection,
+            "SELECT d.name, d.collation_name, \
+                CAST(SUM(CAST(f.size AS BIGINT)) * 8 / 1024 AS VARCHAR(20)) + ' MB', \
+                d.state_desc \
+             FROM sys.databases d \
+             LEFT JOIN sys.master_files f ON f.database_id = d.database_id \
+             WHERE HAS_DBACCESS(d.name) = 1 \
+             GROUP BY d.name, d.collation_name, d.state_desc \
+             ORDER BY d.name",
+            "databases",
+        ).await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| {
+                Some(DatabaseInfo {
+                    name: row.first().and_then(|v| v.as_text())?,
+                    charset: None,
+                    collation: row.get(1).and_then(|v| v.as_text()),
+                    size: row.get(2).and_then(|v| v.as_text()),
+                    table_count: None,
+                    comment: row.get(3).and_then(|v| v.as_text()),
+                })
+            })
+            .collect())
+    }
+
+    // === Table Operations ===
+
+    async fn list_tables(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TableInfo>> {
+        let sql = format!(
+            "SELECT o.name, \
+                CAST(ep.value AS NVARCHAR(4000)), \
+                (SELECT SUM(p.rows) FROM sys.partitions p WHERE p.object_id = o.object_id AND p.index_id IN (0, 1)), \
+                CONVERT(VARCHAR(19), o.create_date, 120) \
+             FROM sys.tables o \
+             JOIN sys.schemas s ON s.schema_id = o.schema_id \
+             LEFT JOIN sys.extended_properties ep \
+                ON ep.class = 1 AND ep.major_id = o.object_id AND ep.minor_id = 0 AND ep.name = 'MS_Description' \
+             WHERE o.is_ms_shipped = 0 AND s.name = N'{schema}' \
+             ORDER BY o.name",
+            schema = Self::escape_literal(database),
+        );
+
+        let rows = self.query_rows(connection, &sql, "tables").await?;
+
+        Ok(rows.iter().map(|row| {
+            TableInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                comment: row.get(1).and_then(|v| v.as_text()),
+                engine: None,
+                row_count: row.get(2).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok()),
+                create_time: row.get(3).and_then(|v| v.as_text()),
+                charset: None,
+                collation: None,
+            }
+        }).collect())
+    }
+
+    async fn list_tables_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let tables = self.list_tables(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("rows", "Rows").width(px(100.0)).text_right(),
+            Column::new("created", "Created").width(px(180.0)),
+            Column::new("comment", "Comment").width(px(300.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = tables.iter().map(|table| {
+            vec![
+                table.name.clone(),
+                table.row_count.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                table.create_time.as_deref().unwrap_or("-").to_string(),
+                table.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} table(s)", tables.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_columns(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ColumnInfo>> {
+        let sql = format!(
+            "SELECT c.name, \
+                ty.name + CASE \
+                    WHEN ty.name IN ('char', 'varchar', 'binary', 'varbinary') \
+                        THEN '(' + CASE WHEN c.max_length = -1 THEN 'max' ELSE CAST(c.max_length AS VARCHAR(10)) END + ')' \
+                    WHEN ty.name IN ('nchar', 'nvarchar') \
+                        THEN '(' + CASE WHEN c.max_length = -1 THEN 'max' ELSE CAST(c.max_length / 2 AS VARCHAR(10)) END + ')' \
+                    WHEN ty.name IN ('decimal', 'numeric') \
+                        THEN '(' + CAST(c.precision AS VARCHAR(10)) + ',' + CAST(c.scale AS VARCHAR(10)) + ')' \
+                    ELSE '' END, \
+                c.is_nullable, \
+                CASE WHEN pk.column_id IS NULL THEN 0 ELSE 1 END, \
+                dc.definition, \
+                CAST(ep.value AS NVARCHAR(4000)) \
+             FROM sys.columns c \
+             JOIN sys.types ty ON ty.user_type_id = c.user_type_id \
+             LEFT JOIN sys.default_constraints dc ON dc.object_id = c.default_object_id \
+             LEFT JOIN ( \
+                SELECT ic.object_id, ic.column_id FROM sys.indexes i \
+                JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id \
+                WHERE i.is_primary_key = 1 \
+             ) pk ON pk.object_id = c.object_id AND pk.column_id = c.column_id \
+             LEFT JOIN sys.extended_properties ep \
+                ON ep.class = 1 AND ep.major_id = c.object_id AND ep.minor_id = c.column_id AND ep.name = 'MS_Description' \
+             WHERE c.object_id = OBJECT_ID(N'{object}') \
+             ORDER BY c.column_id",
+            object = Self::escape_literal(&self.qualified_table_name(database, table)),
+        );
+
+        let rows = self.query_rows(connection, &sql, "columns").await?;
+
+        Ok(rows.iter().map(|row| {
+            ColumnInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                data_type: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                is_nullable: row.get(2).and_then(|v| v.as_text()).map(|v| v == "1" || v == "true").unwrap_or(true),
+                is_primary_key: row.get(3).and_then(|v| v.as_text()).map(|v| v == "1" || v == "true").unwrap_or(false),
+                default_value: row.get(4).and_then(|v| v.as_text()),
+                comment: row.get(5).and_then(|v| v.as_text()),
+            }
+        }).collect())
+    }
+
+    async fn list_columns_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let columns_data = self.list_columns(connection, database, table).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("type", "Type").width(px(150.0)),
+            Column::new("nullable", "Nullable").width(px(80.0)),
+            Column::new("key", "Key").width(px(80.0)),
+            Column::new("default", "Default").width(px(120.0)),
+            Column::new("comment", "Comment").width(px(250.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = columns_data.iter().map(|col| {
+            vec![
+                col.name.clone(),
+                col.data_type.clone(),
+                if col.is_nullable { "YES" } else { "NO" }.to_string(),
+                if col.is_primary_key { "PRI" } else { "" }.to_string(),
+                col.default_value.as_deref().unwrap_or("").to_string(),
+                col.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} column(s)", columns_data.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_indexes(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<IndexInfo>> {
+        let sql = format!(
+            "SELECT i.name, c.name, i.is_unique, i.is_primary_key, i.type_desc \
+             FROM sys.indexes i \
+             JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id \
+             JOIN sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id \
+             WHERE i.object_id = OBJECT_ID(N'{object}') AND i.name IS NOT NULL AND ic.is_included_column = 0 \
+             ORDER BY i.name, ic.key_ordinal",
+            object = Self::escape_literal(&self.qualified_table_name(database, table)),
+        );
+
+        let rows = self.query_rows(connection, &sql, "indexes").await?;
+        let mut indexes: HashMap<String, IndexInfo> = HashMap::new();
+
+        for row in rows {
+            let index_name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+            let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+            let is_unique = row.get(2).and_then(|v| v.as_text()).map(|v| v == "1" || v == "true").unwrap_or(false);
+            let is_primary = row.get(3).and_then(|v| v.as_text()).map(|v| v == "1" || v == "true").unwrap_or(false);
+            let index_type = if is_primary {
+                Some("PRIMARY KEY".to_string())
+            } else {
+                row.get(4).and_then(|v| v.as_text())
+            };
+
+            indexes.entry(index_name.clone())
+                .or_insert_with(|| IndexInfo {
+                    name: index_name,
+                    columns: Vec::new(),
+                    is_unique,
+                    index_type,
+                })
+                .columns.push(column_name);
+        }
+
+        let mut indexes: Vec<IndexInfo> = indexes.into_values().collect();
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(indexes)
+    }
+
+    async fn list_indexes_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let indexes = self.list_indexes(connection, database, table).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("columns", "Columns").width(px(250.0)),
+            Column::new("unique", "Unique").width(px(80.0)),
+            Column::new("type", "Type").width(px(140.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = indexes.iter().map(|idx| {
+            vec![
+                idx.name.clone(),
+                idx.columns.join(", "),
+                if idx.is_unique { "YES" } else { "NO" }.to_string(),
+                idx.index_type.as_deref().unwrap_or("-").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} index(es)", indexes.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === View Operations ===
+
+    async fn list_views(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<ViewInfo>> {
+        let sql = format!(
+            "SELECT o.name, m.definition \
+             FROM sys.views o \
+             JOIN sys.schemas s ON s.schema_id = o.schema_id \
+             LEFT JOIN sys.sql_modules m ON m.object_id = o.object_id \
+             WHERE o.is_ms_shipped = 0 AND s.name = N'{schema}' \
+             ORDER BY o.name",
+            schema = Self::escape_literal(database),
+        );
+
+        let rows = self.query_rows(connection, &sql, "views").await?;
+
+        Ok(rows.iter().map(|row| {
+            ViewInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                definition: row.get(1).and_then(|v| v.as_text()),
+                comment: None,
+            }
+        }).collect())
+    }
+
+    async fn list_views_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let views = self.list_views(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("definition", "Definition").width(px(400.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = views.iter().map(|view| {
+            vec![
+                view.name.clone(),
+                view.definition.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} view(s)", views.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Function Operations ===
+
+    async fn list_functions(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<FunctionInfo>> {
+        let sql = format!(
+            "SELECT o.name, o.type_desc, m.definition \
+             FROM sys.objects o \
+             JOIN sys.schemas s ON s.schema_id = o.schema_id \
+             LEFT JOIN sys.sql_modules m ON m.object_id = o.object_id \
+             WHERE o.type IN ('FN', 'IF', 'TF', 'FS', 'FT') AND o.is_ms_shipped = 0 AND s.name = N'{schema}' \
+             ORDER BY o.name",
+            schema = Self::escape_literal(database),
+        );
+
+        let rows = self.query_rows(connection, &sql, "functions").await?;
+
+        Ok(rows.iter().map(|row| {
+            FunctionInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                return_type: row.get(1).and_then(|v| v.as_text()),
+                parameters: Vec::new(),
+                definition: row.get(2).and_then(|v| v.as_text()),
+                comment: None,
+            }
+        }).collect())
+    }
+
+    async fn list_functions_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let functions = self.list_functions(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("type", "Type").width(px(220.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = functions.iter().map(|func| {
+            vec![
+                func.name.clone(),
+                func.return_type.as_deref().unwrap_or("-").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} function(s)", functions.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Procedure Operations ===
+
+    async fn list_procedures(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<FunctionInfo>> {
+        let sql = format!(
+            "SELECT o.name, m.definition \
+             FROM sys.procedures o \
+             JOIN sys.schemas s ON s.schema_id = o.schema_id \
+             LEFT JOIN sys.sql_modules m ON m.object_id = o.object_id \
+             WHERE o.is_ms_shipped = 0 AND s.name = N'{schema}' \
+             ORDER BY o.name",
+            schema = Self::escape_literal(database),
+        );
+
+        let rows = self.query_rows(connection, &sql, "procedures").await?;
+
+        Ok(rows.iter().map(|row| {
+            FunctionInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                return_type: None,
+                parameters: Vec::new(),
+                definition: row.get(1).and_then(|v| v.as_text()),
+                comment: None,
+            }
+        }).collect())
+    }
+
+    async fn list_procedures_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let procedures = self.list_procedures(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(250.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = procedures.iter().map(|proc| {
+            vec![proc.name.clone()]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} procedure(s)", procedures.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Trigger Operations ===
+
+    async fn list_triggers(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TriggerInfo>> {
+        let sql = format!(
+            "SELECT tr.name, o.name, te.type_desc, tr.is_instead_of_trigger, m.definition \
+             FROM sys.triggers tr \
+             JOIN sys.tables o ON o.object_id = tr.parent_id \
+             JOIN sys.schemas s ON s.schema_id = o.schema_id \
+             LEFT JOIN sys.trigger_events te ON te.object_id = tr.object_id \
+             LEFT JOIN sys.sql_modules m ON m.object_id = tr.object_id \
+             WHERE tr.parent_class = 1 AND s.name = N'{schema}' \
+             ORDER BY tr.name, te.type",
+            schema = Self::escape_literal(database),
+        );
+
+        let rows = self.query_rows(connection, &sql, "triggers").await?;
+
+        // One row per trigger event, merge them into a single trigger
+        let mut triggers: Vec<TriggerInfo> = Vec::new();
+        for row in rows {
+            let name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+            let event = row.get(2).and_then(|v| v.as_text()).unwrap_or_default();
+
+            if let Some(last) = triggers.last_mut().filter(|t| t.name == name) {
+                last.event = format!("{}, {}", last.event, event);
+                continue;
+            }
+
+            let instead_of = row.get(3).and_then(|v| v.as_text()).map(|v| v == "1" || v == "true").unwrap_or(false);
+            triggers.push(TriggerInfo {
+                name,
+                table_name: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                event,
+                timing: if instead_of { "INSTEAD OF" } else { "AFTER" }.to_string(),
+                definition: row.get(4).and_then(|v| v.as_text()),
+            });
+        }
+
+        Ok(triggers)
+    }
+
+    async fn list_triggers_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let triggers = self.list_triggers(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("table", "Table").width(px(150.0)),
+            Column::new("event", "Event").width(px(160.0)),
+            Column::new("timing", "Timing").width(px(100.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = triggers.iter().map(|trigger| {
+            vec![
+                trigger.name.clone(),
+                trigger.table_name.clone(),
+                trigger.event.clone(),
+                trigger.timing.clone(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} trigger(s)", triggers.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Sequence Operations ===
+
+    async fn list_sequences(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<SequenceInfo>> {
+        let sql = format!(
+            "SELECT o.name, \
+                TRY_CAST(o.start_value AS BIGINT), \
+                TRY_CAST(o.increment AS BIGINT), \
+                TRY_CAST(o.minimum_value AS BIGINT), \
+                TRY_CAST(o.maximum_value AS BIGINT) \
+             FROM sys.sequences o \
+             JOIN sys.schemas s ON s.schema_id = o.schema_id \
+             WHERE s.name = N'{schema}' \
+             ORDER BY o.name",
+            schema = Self::escape_literal(database),
+        );
+
+        let rows = self.query_rows(connection, &sql, "sequences").await?;
+        let parse = |row: &Vec<SqlValue>, i: usize| {
+            row.get(i).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok())
+        };
+
+        Ok(rows.iter().map(|row| {
+            SequenceInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                start_value: parse(row, 1),
+                increment: parse(row, 2),
+                min_value: parse(row, 3),
+                max_value: parse(row, 4),
+            }
+        }).collect())
+    }
+
+    async fn list_sequences_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let sequences = self.list_sequences(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("start", "Start").width(px(100.0)).text_right(),
+            Column::new("increment", "Increment").width(px(100.0)).text_right(),
+            Column::new("min", "Min").width(px(160.0)).text_right(),
+            Column::new("max", "Max").width(px(160.0)).text_right(),
+        ];
+
+        let to_cell = |v: Option<i64>| v.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+        let rows: Vec<Vec<String>> = sequences.iter().map(|seq| {
+            vec![
+                seq.name.clone(),
+                to_cell(seq.start_value),
+                to_cell(seq.increment),
+                to_cell(seq.min_value),
+                to_cell(seq.max_value),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} sequence(s)", sequences.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Query Execution ===
+
+    async fn execute_query(
+        &self,
+        connection: &dyn DbConnection,
+        _database: &str,
+        query: &str,
+        params: Option<Vec<SqlValue>>,
+    ) -> Result<SqlResult> {
+        connection.query(query, params, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Query execution failed: {}", e))
+    }
+
+    async fn execute_script(
+        &self,
+        connection: &dyn DbConnection,
+        _database: &str,
+        script: &str,
+        options: ExecOptions,
+    ) -> Result<Vec<SqlResult>> {
+        connection.execute(script, options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Script execution failed: {}", e))
+    }
+
+    // === Database Switching ===
+
+    async fn switch_db(&self, connection: &dyn DbConnection, database: &str) -> Result<SqlResult> {
+        let sql = format!("USE {}", self.quote_identifier(database));
+        let results = connection
+            .execute(&sql, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to switch database: {}", e))?;
+
+        if let Some(result) = results.into_iter().next() {
+            Ok(result)
+        } else {
+            Ok(SqlResult::Exec(ExecResult {
+                sql,
+                rows_affected: 0,
+                elapsed_ms: 0,
+                message: Some("Database changed".to_string()),
+            }))
+        }
+    }
+
+    fn get_data_types(&self) -> Vec<DataTypeInfo> {
+        vec![
+            // 数值类型
+            DataTypeInfo::new("TINYINT", "Small integer (0 to 255)").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("SMALLINT", "Small integer (-32768 to 32767)").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("INT", "Standard integer (-2147483648 to 2147483647)").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("BIGINT", "Large integer").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("DECIMAL(18,2)", "Fixed-point number").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("MONEY", "Monetary value").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("REAL", "Single-precision floating-point").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("FLOAT", "Double-precision floating-point").with_category(DataTypeCategory::Numeric),
+
+            // 字符串类型
+            DataTypeInfo::new("CHAR(255)", "Fixed-length string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("VARCHAR(255)", "Variable-length string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("VARCHAR(MAX)", "Large string (2GB)").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("NCHAR(255)", "Fixed-length Unicode string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("NVARCHAR(255)", "Variable-length Unicode string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("NVARCHAR(MAX)", "Large Unicode string (2GB)").with_category(DataTypeCategory::String),
+
+            // 日期时间类型
+            DataTypeInfo::new("DATE", "Date (YYYY-MM-DD)").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("TIME", "Time of day").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("DATETIME", "Date and time (3.33 ms precision)").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("DATETIME2", "Date and time (100 ns precision)").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("DATETIMEOFFSET", "Date and time with time zone offset").with_category(DataTypeCategory::DateTime),
+
+            // 二进制类型
+            DataTypeInfo::new("BINARY(255)", "Fixed-length binary").with_category(DataTypeCategory::Binary),
+            DataTypeInfo::new("VARBINARY(255)", "Variable-length binary").with_category(DataTypeCategory::Binary),
+            DataTypeInfo::new("VARBINARY(MAX)", "Large binary (2GB)").with_category(DataTypeCategory::Binary),
+
+            // 其他类型
+            DataTypeInfo::new("BIT", "Boolean (0/1)").with_category(DataTypeCategory::Boolean),
+            DataTypeInfo::new("UNIQUEIDENTIFIER", "GUID").with_category(DataTypeCategory::Other),
+            DataTypeInfo::new("XML", "XML document").with_category(DataTypeCategory::Structured),
+        ]
+    }
+
+    // === DDL Operations ===
+
+    async fn drop_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        let query = format!("DROP TABLE IF EXISTS {}", self.qualified_table_name(database, table));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
+    async fn truncate_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        let query = format!("TRUNCATE TABLE {}", self.qualified_table_name(database, table));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
+    async fn drop_view(&self, connection: &dyn DbConnection, database: &str, view: &str) -> Result<()> {
+        let query = format!("DROP VIEW IF EXISTS {}", self.qualified_table_name(database, view));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+}
+
+impl Default for MssqlPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::TOKIO_HANDLE;
+    use crate::test_support::{rows_result, test_config, FakeConnection};
+
+    fn text(value: &str) -> SqlValue {
+        SqlValue::String(value.to_string())
+    }
+
+    #[test]
+    fn test_qualified_table_name() {
+        let plugin = MssqlPlugin::new();
+        assert_eq!(plugin.qualified_table_name("sales", "orders"), "[sales].[orders]");
+        assert_eq!(plugin.qualified_table_name("dbo", "Order]Items"), "[dbo].[Order]]Items]");
+    }
+
+    #[test]
+    fn test_pagination_needs_an_order_by() {
+        let plugin = MssqlPlugin::new();
+        assert_eq!(
+            plugin.pagination_clause(true, 50, 100),
+            " OFFSET 100 ROWS FETCH NEXT 50 ROWS ONLY"
+        );
+        assert_eq!(
+            plugin.pagination_clause(false, 50, 0),
+            " ORDER BY (SELECT NULL) OFFSET 0 ROWS FETCH NEXT 50 ROWS ONLY"
+        );
+    }
+
+    #[test]
+    fn test_table_data_query_is_schema_qualified_and_paged() {
+        let plugin = MssqlPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::MSSQL, &[]))
+            .respond("SELECT c.name", rows_result(vec![vec![text("id"), text("int"), text("0"), text("1"), SqlValue::Null, SqlValue::Null]]))
+            .respond("SELECT COUNT(*)", rows_result(vec![vec![text("250")]]));
+        let log = conn.log();
+
+        let request = TableDataRequest::new("shop", "orders")
+            .with_schema(Some("sales".to_string()))
+            .with_page(3, 50);
+        let response = TOKIO_HANDLE.block_on(plugin.query_table_data(&conn, &request)).unwrap();
+
+        assert_eq!(response.total_count, 250);
+        assert_eq!(response.primary_key_indices, vec![0]);
+        let log = log.lock().unwrap();
+        assert!(log[0].contains("OBJECT_ID(N'[sales].[orders]')"));
+        assert_eq!(log[1], "SELECT COUNT(*) FROM [sales].[orders]");
+        assert_eq!(
+            log[2],
+            "SELECT * FROM [sales].[orders] ORDER BY (SELECT NULL) OFFSET 100 ROWS FETCH NEXT 50 ROWS ONLY"
+        );
+    }
+
+    #[test]
+    fn test_database_node_lists_schemas() {
+        let plugin = MssqlPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::MSSQL, &[]))
+            .respond("SELECT name FROM sys.schemas", rows_result(vec![vec![text("dbo")], vec![text("sales")]]));
+        let node = DbNode::new("conn:shop", "shop", DbNodeType::Database, "conn".to_string());
+
+        let children = TOKIO_HANDLE.block_on(plugin.load_node_children(&conn, &node)).unwrap();
+
+        assert_eq!(children.len(), 1);
+        let folder = &children[0];
+        assert_eq!(folder.node_type, DbNodeType::SchemasFolder);
+        let schemas: Vec<&str> = folder.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(schemas, vec!["dbo", "sales"]);
+        assert_eq!(folder.children[1].object_scope().map(String::as_str), Some("sales"));
+    }
+
+    #[test]
+    fn test_tables_are_listed_per_schema() {
+        let plugin = MssqlPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::MSSQL, &[]))
+            .respond("SELECT o.name", rows_result(vec![vec![text("orders"), SqlValue::Null, text("12"), SqlValue::Null]]));
+        let log = conn.log();
+
+        let tables = TOKIO_HANDLE.block_on(plugin.list_tables(&conn, "sales")).unwrap();
+
+        // Names stay bare, the schema is a tree level of its own
+        assert_eq!(tables[0].name, "orders");
+        assert_eq!(tables[0].row_count, Some(12));
+        assert!(log.lock().unwrap()[0].contains("s.name = N'sales'"));
+    }
+
+    #[test]
+    fn test_rename_table_stays_in_schema() {
+        let plugin = MssqlPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::MSSQL, &[]));
+        let log = conn.log();
+        TOKIO_HANDLE
+            .block_on(plugin.rename_table(&conn, "sales", "orders", "purchases"))
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["EXEC sp_rename N'[sales].[orders]', N'purchases'"]
+        );
+    }
+}
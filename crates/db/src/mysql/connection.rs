@@ -1,14 +1,20 @@
+use crate::cancel::{RunningQueries, RunningQueryGuard};
 use crate::connection::{DbConnection, DbError};
-use crate::executor::{ExecOptions, ExecResult, QueryResult, SqlErrorInfo, SqlResult, SqlScriptSplitter, SqlStatementClassifier};
+use crate::executor::{ExecOptions, ExecResult, QueryResult, ResultColumn, SqlErrorInfo, SqlResult, SqlScriptSplitter, SqlStatementClassifier};
 use crate::runtime::TOKIO_HANDLE;
-use sqlx::mysql::{MySqlArguments, MySqlPoolOptions, MySqlRow};
-use sqlx::{Column, MySql, MySqlPool, Row, ValueRef};
+use crate::ssh_tunnel::SshTunnel;
+use crate::ssl::{SslMode, SslOptions};
+use crate::stream::{self, QueryStream};
+use sqlx::mysql::{MySqlArguments, MySqlConnectOptions, MySqlConnection, MySqlPoolOptions, MySqlRow, MySqlSslMode};
+use sqlx::pool::PoolConnection;
+use sqlx::{Column, Connection, Executor, MySql, MySqlPool, Row, TypeInfo, ValueRef};
 use std::time::Instant;
 use async_trait::async_trait;
 use std::sync::RwLock;
+use tokio::sync::oneshot;
 use sqlx::types::chrono;
 use sqlx::types::chrono::Utc;
-use one_core::storage::DbConnectionConfig;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
 use crate::{SqlValue};
 
 pub struct MysqlDbConnection {
@@ -17,6 +23,12 @@ pub struct MysqlDbConnection {
     pool: RwLock<Option<MySqlPool>>,
     // Track current database selected on this connection
     current_database: RwLock<Option<String>>,
+    // Pinned sessions keep their single connection for as long as they are open
+    pinned_session: bool,
+    // Connection ids of the statements in flight, targets of KILL QUERY
+    running: RunningQueries,
+    // Local end of the SSH tunnel the pool connects through, closed on drop
+    tunnel: Option<SshTunnel>,
 }
 
 impl MysqlDbConnection {
@@ -25,9 +37,47 @@ impl MysqlDbConnection {
             config: Some(config),
             pool: RwLock::new(None),
             current_database: RwLock::new(None),
+            pinned_session: false,
+            running: RunningQueries::default(),
+            tunnel: None,
         }
     }
 
+    /// Connection whose single pooled connection is never recycled, so session state survives
+    pub fn new_session(config: DbConnectionConfig) -> Self {
+        Self {
+            pinned_session: true,
+            ..Self::new(config)
+        }
+    }
+
+    /// Connect options for the database server, or the local end of its SSH tunnel
+    fn connect_options(config: &DbConnectionConfig, host: &str, port: u16) -> Result<MySqlConnectOptions, DbError> {
+        let ssl = SslOptions::from_config(config)?;
+        let mut options = MySqlConnectOptions::new()
+            .host(host)
+            .port(port)
+            .username(&config.username)
+            .password(&config.password)
+            .ssl_mode(match ssl.mode {
+                SslMode::Disable => MySqlSslMode::Disabled,
+                SslMode::Prefer => MySqlSslMode::Preferred,
+                SslMode::Require => MySqlSslMode::Required,
+                SslMode::VerifyCa => MySqlSslMode::VerifyCa,
+                SslMode::VerifyFull => MySqlSslMode::VerifyIdentity,
+            });
+        if let Some(database) = &config.database {
+            options = options.database(database);
+        }
+        if let Some(ca_cert) = &ssl.ca_cert {
+            options = options.ssl_ca(ca_cert);
+        }
+        if let (Some(cert), Some(key)) = (&ssl.client_cert, &ssl.client_key) {
+            options = options.ssl_client_cert(cert).ssl_client_key(key);
+        }
+        Ok(options)
+    }
+
     fn ensure_connected(&self) -> Result<MySqlPool, DbError> {
         self
             .pool
@@ -53,9 +103,16 @@ impl MysqlDbConnection {
             SqlValue::String(v) => query.bind(v),
             SqlValue::Bytes(v) => query.bind(v),
             SqlValue::Json(v) => query.bind(v.to_string()), // MySQL stores JSON as text
+            SqlValue::Decimal(v) => query.bind(v),
+            SqlValue::Date(v) => query.bind(v),
+            SqlValue::Time(v) => query.bind(v),
+            SqlValue::DateTime(v) => query.bind(v),
+            // MySQL has no offset-aware type, TIMESTAMP values are UTC
+            SqlValue::DateTimeTz(v) => query.bind(v.naive_utc()),
+            SqlValue::Uuid(v) => query.bind(v.to_string()),
         }
     }
-    fn extract_value(row: &MySqlRow, index: usize) -> Option<String> {
+    fn extract_value(row: &MySqlRow, index: usize) -> SqlValue {
         use sqlx::Row;
         use sqlx::TypeInfo;
         use sqlx::types::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
@@ -64,7 +121,7 @@ impl MysqlDbConnection {
         // Check if NULL
         if let Ok(val) = row.try_get_raw(index) {
             if val.is_null() {
-                return None;
+                return SqlValue::Null;
             }
         }
 
@@ -79,27 +136,33 @@ impl MysqlDbConnection {
             "TIMESTAMP" => {
                 // 带时区类型 → DateTime<Utc>
                 if let Ok(val) = row.try_get::<chrono::DateTime<Utc>, _>(index) {
-                    return Some(val.format("%Y-%m-%d %H:%M:%S").to_string());
+                    return SqlValue::DateTime(val.naive_utc());
                 }
             }
             "DATETIME" => {
                 if let Ok(val) = row.try_get::<NaiveDateTime, _>(index) {
-                    return Some(val.format("%Y-%m-%d %H:%M:%S").to_string());
+                    return SqlValue::DateTime(val);
                 }
             }
             "DATE" => {
                 if let Ok(val) = row.try_get::<NaiveDate, _>(index) {
-                    return Some(val.format("%Y-%m-%d").to_string());
+                    return SqlValue::Date(val);
                 }
             }
             "TIME" => {
                 if let Ok(val) = row.try_get::<NaiveTime, _>(index) {
-                    return Some(val.format("%H:%M:%S").to_string());
+                    return SqlValue::Time(val);
                 }
             }
             "YEAR" => {
                 if let Ok(val) = row.try_get::<i16, _>(index) {
-                    return Some(val.to_string());
+                    return SqlValue::Int(val as i64);
+                }
+            }
+            // Binary types keep their bytes, even when they happen to be valid UTF-8
+            "BINARY" | "VARBINARY" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
+                if let Ok(val) = row.try_get::<Vec<u8>, _>(index) {
+                    return SqlValue::Bytes(val);
                 }
             }
             _ => {}
@@ -108,71 +171,255 @@ impl MysqlDbConnection {
         // Try different types in order of likelihood
         // String types (VARCHAR, CHAR, TEXT, etc.)
         if let Ok(val) = row.try_get::<String, _>(index) {
-            return Some(val);
+            return SqlValue::String(val);
         }
 
         // Integer types (TINYINT, SMALLINT, MEDIUMINT, INT, BIGINT)
         if let Ok(val) = row.try_get::<i64, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val);
         }
         if let Ok(val) = row.try_get::<i32, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
         if let Ok(val) = row.try_get::<i16, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
         if let Ok(val) = row.try_get::<i8, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
 
-        // Unsigned integer types
+        // Unsigned integer types; BIGINT UNSIGNED may not fit in i64
         if let Ok(val) = row.try_get::<u64, _>(index) {
-            return Some(val.to_string());
+            return i64::try_from(val)
+                .map(SqlValue::Int)
+                .unwrap_or_else(|_| SqlValue::Decimal(val.to_string()));
         }
         if let Ok(val) = row.try_get::<u32, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
         if let Ok(val) = row.try_get::<u16, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
         if let Ok(val) = row.try_get::<u8, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
 
         // Floating point types (FLOAT, DOUBLE)
         if let Ok(val) = row.try_get::<f64, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Float(val);
         }
         if let Ok(val) = row.try_get::<f32, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Float(val as f64);
         }
 
         // DECIMAL/NUMERIC type
         if let Ok(val) = row.try_get::<BigDecimal, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Decimal(val.to_string());
         }
 
         // Boolean (BOOL, BOOLEAN which are aliases for TINYINT(1))
         if let Ok(val) = row.try_get::<bool, _>(index) {
-            return Some(if val { "1" } else { "0" }.to_string());
+            return SqlValue::Bool(val);
         }
 
-        // Binary types (BINARY, VARBINARY, BLOB)
+        // Binary types reported under another name
         if let Ok(val) = row.try_get::<Vec<u8>, _>(index) {
-            // For binary data, try to show as UTF-8 string first, otherwise hex
-            if let Ok(s) = String::from_utf8(val.clone()) {
-                return Some(s);
-            }
-            return Some(format!("0x{}", hex::encode(&val)));
+            return SqlValue::Bytes(val);
         }
 
         // JSON type
         if let Ok(val) = row.try_get::<serde_json::Value, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Json(val);
         }
 
-        // If all else fails, return column type information
-        Some(format!("<{}>", type_name))
+        // Unknown types (BIT, GEOMETRY, ...): take the raw bytes sent by the server
+        match row.try_get_unchecked::<Vec<u8>, _>(index) {
+            Ok(val) => String::from_utf8(val)
+                .map(SqlValue::String)
+                .unwrap_or_else(|e| SqlValue::Bytes(e.into_bytes())),
+            Err(_) => SqlValue::Null,
+        }
+    }
+
+    /// Extract the target database from a `USE db` statement
+    fn parse_use_database(sql: &str) -> Option<String> {
+        if !sql.to_uppercase().starts_with("USE ") {
+            return None;
+        }
+        Some(sql[4..]
+            .trim()
+            .trim_matches('`')
+            .trim_matches(';')
+            .to_string())
+    }
+
+    /// Remember the database selected by successful USE statements
+    fn track_current_database(&self, results: &[SqlResult]) {
+        for result in results {
+            if let SqlResult::Exec(exec_result) = result {
+                if let Some(db_name) = Self::parse_use_database(&exec_result.sql) {
+                    let mut db_guard = self.current_database.write().unwrap();
+                    *db_guard = Some(db_name);
+                }
+            }
+        }
+    }
+
+    /// Build the error of a failed statement, keeping the line MySQL reports for syntax errors
+    fn error_info(sql: &str, e: &sqlx::Error) -> SqlErrorInfo {
+        SqlErrorInfo::new(sql, e.to_string()).with_line_from_message(" at line ")
+    }
+
+    /// Run one statement on a single connection, either a pooled one or an open transaction
+    async fn run_statement(conn: &mut MySqlConnection, sql: &str, max_rows: Option<usize>) -> SqlResult {
+        let start = Instant::now();
+
+        // USE statements only switch the database, report which one
+        if let Some(db_name) = Self::parse_use_database(sql) {
+            return match conn.execute(sqlx::raw_sql(sql)).await {
+                Ok(_) => SqlResult::Exec(ExecResult {
+                    sql: sql.to_string(),
+                    rows_affected: 0,
+                    elapsed_ms: start.elapsed().as_millis(),
+                    message: Some(format!("Database changed to '{}'", db_name)),
+                }),
+                Err(e) => SqlResult::Error(Self::error_info(sql, &e)),
+            };
+        }
+
+        if SqlStatementClassifier::is_query_statement(sql) {
+            let mut rows = conn.fetch(sqlx::raw_sql(sql));
+            match stream::read_page(&mut rows, max_rows, Self::extract_value).await {
+                Ok(page) => SqlResult::Query(page.into_query_result(sql, start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(Self::error_info(sql, &e)),
+            }
+        } else {
+            match conn.execute(sqlx::raw_sql(sql)).await {
+                Ok(exec_result) => {
+                    let rows_affected = exec_result.rows_affected();
+                    SqlResult::Exec(ExecResult {
+                        sql: sql.to_string(),
+                        rows_affected,
+                        elapsed_ms: start.elapsed().as_millis(),
+                        message: Some(SqlStatementClassifier::format_message(sql, rows_affected)),
+                    })
+                }
+                Err(e) => SqlResult::Error(Self::error_info(sql, &e)),
+            }
+        }
+    }
+
+    /// Acquire a pooled connection and register its connection id so the statement can be killed
+    async fn acquire_tracked(
+        pool: &MySqlPool,
+        running: &RunningQueries,
+    ) -> Result<(PoolConnection<MySql>, RunningQueryGuard), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let id: u64 = conn.fetch_one("SELECT CONNECTION_ID()").await?.try_get(0)?;
+        let guard = running.track(id);
+        Ok((conn, guard))
+    }
+
+    /// Run statements one after another, stopping early on error or cancellation
+    async fn run_statements(
+        conn: &mut MySqlConnection,
+        statements: &[String],
+        options: &ExecOptions,
+        running: &RunningQueries,
+    ) -> Vec<SqlResult> {
+        let mut results = Vec::new();
+
+        for sql in statements {
+            if running.is_cancelled() {
+                break;
+            }
+
+            let result = Self::run_statement(conn, sql, options.max_rows).await;
+            let failed = matches!(result, SqlResult::Error(_));
+            results.push(result);
+
+            if failed && options.stop_on_error {
+                break;
+            }
+        }
+
+        results
+    }
+
+    /// Run a script outside a transaction and send back its results.
+    /// A query ending the script keeps its cursor open afterwards, so the rows past
+    /// the first page stay readable through `QueryResult::more_rows`.
+    async fn run_script(
+        conn: &mut MySqlConnection,
+        statements: Vec<String>,
+        options: ExecOptions,
+        running: &RunningQueries,
+        results_tx: oneshot::Sender<Result<Vec<SqlResult>, DbError>>,
+    ) {
+        let Some((last, leading)) = statements.split_last() else {
+            let _ = results_tx.send(Ok(Vec::new()));
+            return;
+        };
+
+        let mut results = Self::run_statements(conn, leading, &options, running).await;
+        let stopped = running.is_cancelled()
+            || (options.stop_on_error && matches!(results.last(), Some(SqlResult::Error(_))));
+        if stopped || !SqlStatementClassifier::is_query_statement(last) {
+            if !stopped {
+                results.push(Self::run_statement(conn, last, options.max_rows).await);
+            }
+            let _ = results_tx.send(Ok(results));
+            return;
+        }
+
+        let start = Instant::now();
+        let mut rows = conn.fetch(sqlx::raw_sql(last));
+        let pump = match stream::read_page(&mut rows, options.max_rows, Self::extract_value).await {
+            Ok(page) => {
+                let batch_size = options.max_rows.unwrap_or(usize::MAX);
+                let (result, pump) = page.into_streamed_result(last, start.elapsed().as_millis(), batch_size);
+                results.push(SqlResult::Query(result));
+                pump
+            }
+            Err(e) => {
+                results.push(SqlResult::Error(Self::error_info(last, &e)));
+                None
+            }
+        };
+        let _ = results_tx.send(Ok(results));
+
+        if let Some(pump) = pump {
+            pump.run(rows).await;
+        }
+    }
+
+    /// Run the whole script inside BEGIN/COMMIT, rolling back on error or cancellation.
+    /// DDL statements commit implicitly in MySQL and cannot be rolled back.
+    async fn execute_in_transaction(
+        conn: &mut MySqlConnection,
+        statements: Vec<String>,
+        options: ExecOptions,
+        running: &RunningQueries,
+    ) -> Result<Vec<SqlResult>, DbError> {
+        let mut tx = conn.begin().await
+            .map_err(|e| DbError::QueryError(format!("Failed to begin transaction: {}", e)))?;
+        let mut results = Self::run_statements(&mut tx, &statements, &options, running).await;
+
+        let failed = options.stop_on_error && matches!(results.last(), Some(SqlResult::Error(_)));
+        let start = Instant::now();
+        if failed || running.is_cancelled() {
+            results.push(match tx.rollback().await {
+                Ok(()) => SqlResult::Exec(ExecResult::transaction_rolled_back(start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new("ROLLBACK", e.to_string())),
+            });
+        } else {
+            results.push(match tx.commit().await {
+                Ok(()) => SqlResult::Exec(ExecResult::transaction_committed(start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new("COMMIT", e.to_string())),
+            });
+        }
+
+        Ok(results)
     }
 }
 
@@ -186,23 +433,20 @@ impl DbConnection for MysqlDbConnection {
         let config = self.config.clone();
         if let Some(conf) = config {
             let clone_conf = conf.clone();
-            let url = if let Some(db) = conf.database {
-                format!(
-                    "mysql://{}:{}@{}:{}/{}",
-                    conf.username, conf.password, conf.host, conf.port, db.clone()
-                )
-            } else {
-                format!(
-                    "mysql://{}:{}@{}:{}",
-                    conf.username, conf.password, conf.host, conf.port
-                )
+            let tunnel = SshTunnel::for_config(&conf).await?;
+            let (host, port) = match &tunnel {
+                Some(tunnel) => (tunnel.local_addr().ip().to_string(), tunnel.local_addr().port()),
+                None => (conf.host.clone(), conf.port),
             };
+            let connect_options = Self::connect_options(&conf, &host, port)?;
 
+            let pinned_session = self.pinned_session;
             let pool = TOKIO_HANDLE.spawn(async move {
-                MySqlPoolOptions::new()
-                    .max_connections(1)
-                    .connect(&url)
-                    .await
+                let mut pool_options = MySqlPoolOptions::new().max_connections(1);
+                if pinned_session {
+                    pool_options = pool_options.idle_timeout(None).max_lifetime(None);
+                }
+                pool_options.connect_with(connect_options).await
             })
             .await
             .map_err(|e| DbError::ConnectionError(format!("Failed to spawn connection task: {}", e)))?
@@ -216,6 +460,7 @@ impl DbConnection for MysqlDbConnection {
                 let mut db_guard = self.current_database.write().unwrap();
                 db_guard.clone_from(&clone_conf.database);
             }
+            self.tunnel = tunnel;
             Ok(())
         }else { 
             Err(DbError::ConnectionError("No database configuration provided".to_string()))
@@ -231,232 +476,49 @@ impl DbConnection for MysqlDbConnection {
         if let Some(pool) = pool_opt {
             pool.close().await;
         }
+        self.tunnel = None;
         Ok(())
     }
 
 
     async fn execute(&self, script: &str, options: ExecOptions) -> Result<Vec<SqlResult>, DbError> {
-        let base_pool = self.ensure_connected()?;
-        // Work off a current pool; switch pools on USE and persist DB context
-        let active_pool = base_pool.clone();
+        let pool = self.ensure_connected()?;
 
         // Split script into individual statements
-        let statements = SqlScriptSplitter::split(script);
-        let mut results = Vec::new();
-
-
-        // Execute each statement on the pool
-        for sql in statements {
-            let sql = sql.trim();
-            if sql.is_empty() {
-                continue;
-            }
-
-            // Check if this is a USE statement
-            let sql_upper = sql.to_uppercase();
-            let is_use_statement = sql_upper.starts_with("USE ");
-
-            // If it's a USE statement, execute it directly and update current database
-            if is_use_statement {
-                let start = Instant::now();
-                // Extract database name from USE statement
-                let db_name = sql.trim_start_matches("USE ")
-                    .trim_start_matches("use ")
-                    .trim()
-                    .trim_matches('`')
-                    .trim_matches(';')
-                    .to_string();
-
-                // Execute USE statement on current connection
-                let pool = active_pool.clone();
-                let sql_to_exec = sql.to_string();
-                match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&sql_to_exec).execute(&pool).await
-                }).await
-                {
-                    Ok(Ok(_exec_result)) => {
-                        // Update current database property
-                        {
-                            let mut db_guard = self.current_database.write().unwrap();
-                            *db_guard = Some(db_name.clone());
-                        }
-                        let elapsed_ms = start.elapsed().as_millis();
-                        results.push(SqlResult::Exec(ExecResult {
-                            sql: sql.to_string(),
-                            rows_affected: 0,
-                            elapsed_ms,
-                            message: Some(format!("Database changed to '{}'", db_name)),
-                        }));
-                        continue;
-                    }
-                    Ok(Err(e)) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        }));
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        }));
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
-            }
-
-            // Apply max_rows limit for non-USE statements
-            let modified_sql = if let Some(max_rows) = options.max_rows {
-                if SqlStatementClassifier::is_query_statement(sql) && !sql.to_uppercase().contains(" LIMIT ") {
-                    format!("{} LIMIT {}", sql, max_rows)
-                } else {
-                    sql.to_string()
+        let statements: Vec<String> = SqlScriptSplitter::split_for(script, DatabaseType::MySQL)
+            .into_iter()
+            .map(|sql| sql.trim().to_string())
+            .filter(|sql| !sql.is_empty())
+            .collect();
+
+        // The whole script runs on one connection, so there is a single statement to kill
+        let running = self.running.clone();
+        running.reset();
+        // Results come back before the task ends, it may go on reading the last query's rows
+        let (results_tx, results_rx) = oneshot::channel();
+        TOKIO_HANDLE.spawn(async move {
+            let (mut conn, _running_guard) = match Self::acquire_tracked(&pool, &running).await {
+                Ok(acquired) => acquired,
+                Err(e) => {
+                    let _ = results_tx.send(Err(DbError::ConnectionError(format!("Failed to acquire connection: {}", e))));
+                    return;
                 }
-            } else {
-                sql.to_string()
             };
-
-            // Determine statement type
-            let is_query = SqlStatementClassifier::is_query_statement(&modified_sql);
-
-            let start = Instant::now();
-            let result = if is_query {
-                // Execute query statement using raw_sql on the active pool - wrap in Tokio context
-                let pool = active_pool.clone();
-                let sql_to_exec = modified_sql.clone();
-                let original_sql = sql.to_string();
-
-                match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&sql_to_exec).fetch_all(&pool).await
-                }).await {
-                    Ok(Ok(rows)) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-
-                        if rows.is_empty() {
-                            SqlResult::Query(QueryResult {
-                                sql: original_sql,
-                                columns: Vec::new(),
-                                rows: Vec::new(),
-                                elapsed_ms,
-                            })
-                        } else {
-                            // Extract column names
-                            let columns: Vec<String> = rows[0]
-                                .columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect();
-
-                            // Extract row data
-                            let data_rows: Vec<Vec<Option<String>>> = rows
-                                .iter()
-                                .map(|row| {
-                                    (0..columns.len())
-                                        .map(|i| Self::extract_value(row, i))
-                                        .collect()
-                                })
-                                .collect();
-
-                            SqlResult::Query(QueryResult {
-                                sql: original_sql,
-                                columns,
-                                rows: data_rows,
-                                elapsed_ms,
-                            })
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
+            if options.transactional {
+                let _ = results_tx.send(Self::execute_in_transaction(&mut conn, statements, options, &running).await);
             } else {
-                // Execute non-query statement using raw_sql on the active pool - wrap in Tokio context
-                let pool = active_pool.clone();
-                let sql_to_exec = modified_sql.clone();
-                let original_sql = sql.to_string();
-
-                match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&sql_to_exec).execute(&pool).await
-                }).await {
-                    Ok(Ok(exec_result)) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-                        let rows_affected = exec_result.rows_affected();
-                        let message = SqlStatementClassifier::format_message(&original_sql, rows_affected);
-
-                        SqlResult::Exec(ExecResult {
-                            sql: original_sql,
-                            rows_affected,
-                            elapsed_ms,
-                            message: Some(message),
-                        })
-                    }
-                    Ok(Err(e)) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
-            };
-
-            results.push(result);
-        }
+                Self::run_script(&mut conn, statements, options, &running, results_tx).await;
+            }
+        });
+        let mut results = results_rx
+            .await
+            .map_err(|e| DbError::QueryError(format!("Query task ended unexpectedly: {}", e)))??;
 
-        // Connection will be automatically returned to the pool when dropped
+        SqlScriptSplitter::locate_errors(script, DatabaseType::MySQL, &mut results);
+        self.track_current_database(&results);
         Ok(results)
     }
 
-
     async fn query(&self, query: &str, params: Option<Vec<SqlValue>>, options: ExecOptions) -> Result<SqlResult, DbError> {
         let pool = self.ensure_connected()?;
         let start = Instant::now();
@@ -466,28 +528,16 @@ impl DbConnection for MysqlDbConnection {
         let result = if let Some(params) = params {
             // Use prepared statement with parameter binding
             if is_query {
-                // For SELECT queries with parameters - use raw_sql with parameters
-                // Build parameterized query string
+                // For SELECT queries with parameters - bind them to a prepared statement
                 let query_str = query.to_string();
-                let params_vec: Vec<String> = params.iter().map(|p| match p {
-                    SqlValue::Null => "NULL".to_string(),
-                    SqlValue::Bool(v) => v.to_string(),
-                    SqlValue::Int(v) => v.to_string(),
-                    SqlValue::Float(v) => v.to_string(),
-                    SqlValue::String(v) => format!("'{}'", v.replace("'", "''")),
-                    SqlValue::Bytes(v) => format!("0x{}", hex::encode(v)),
-                    SqlValue::Json(v) => format!("'{}'", v.to_string().replace("'", "''")),
-                }).collect();
-
-                // Simple parameter substitution (not ideal, but works for now)
-                let mut final_query = query_str.clone();
-                for (i, param_val) in params_vec.iter().enumerate() {
-                    final_query = final_query.replacen("?", param_val, 1);
-                }
+                let query_str_clone = query_str.clone();
 
                 let pool = pool.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&final_query).fetch_all(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    let bound = params.into_iter().fold(sqlx::query(&query_str_clone), Self::bind_parameter);
+                    conn.fetch_all(bound).await
                 }).await {
                     Ok(Ok(rows)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -498,17 +548,19 @@ impl DbConnection for MysqlDbConnection {
                                 columns: Vec::new(),
                                 rows: Vec::new(),
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         } else {
                             // Extract column names
-                            let columns: Vec<String> = rows[0]
+                            let columns: Vec<ResultColumn> = rows[0]
                                 .columns()
                                 .iter()
-                                .map(|col| col.name().to_string())
+                                .map(|col| ResultColumn::new(col.name(), col.type_info().name()))
                                 .collect();
 
                             // Extract row data
-                            let data_rows: Vec<Vec<Option<String>>> = rows
+                            let data_rows: Vec<Vec<SqlValue>> = rows
                                 .iter()
                                 .map(|row| {
                                     (0..columns.len())
@@ -522,40 +574,25 @@ impl DbConnection for MysqlDbConnection {
                                 columns,
                                 rows: data_rows,
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         }
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             } else {
-                // For DML/DDL queries with parameters - use raw_sql with parameters
+                // For DML/DDL queries with parameters - bind them to a prepared statement
                 let query_str = query.to_string();
-                let params_vec: Vec<String> = params.iter().map(|p| match p {
-                    SqlValue::Null => "NULL".to_string(),
-                    SqlValue::Bool(v) => v.to_string(),
-                    SqlValue::Int(v) => v.to_string(),
-                    SqlValue::Float(v) => v.to_string(),
-                    SqlValue::String(v) => format!("'{}'", v.replace("'", "''")),
-                    SqlValue::Bytes(v) => format!("0x{}", hex::encode(v)),
-                    SqlValue::Json(v) => format!("'{}'", v.to_string().replace("'", "''")),
-                }).collect();
-
-                // Simple parameter substitution
-                let mut final_query = query_str.clone();
-                for param_val in params_vec.iter() {
-                    final_query = final_query.replacen("?", param_val, 1);
-                }
+                let query_str_clone = query_str.clone();
 
                 let pool = pool.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&final_query).execute(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    let bound = params.into_iter().fold(sqlx::query(&query_str_clone), Self::bind_parameter);
+                    conn.execute(bound).await
                 }).await {
                     Ok(Ok(exec_result)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -569,14 +606,8 @@ impl DbConnection for MysqlDbConnection {
                             message: Some(message),
                         })
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             }
         } else {
@@ -585,8 +616,10 @@ impl DbConnection for MysqlDbConnection {
                 let pool = pool.clone();
                 let query_str = query.to_string();
                 let query_str_clone = query_str.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&query_str_clone).fetch_all(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    conn.fetch_all(sqlx::raw_sql(&query_str_clone)).await
                 }).await {
                     Ok(Ok(rows)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -597,17 +630,19 @@ impl DbConnection for MysqlDbConnection {
                                 columns: Vec::new(),
                                 rows: Vec::new(),
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         } else {
                             // Extract column names
-                            let columns: Vec<String> = rows[0]
+                            let columns: Vec<ResultColumn> = rows[0]
                                 .columns()
                                 .iter()
-                                .map(|col| col.name().to_string())
+                                .map(|col| ResultColumn::new(col.name(), col.type_info().name()))
                                 .collect();
 
                             // Extract row data
-                            let data_rows: Vec<Vec<Option<String>>> = rows
+                            let data_rows: Vec<Vec<SqlValue>> = rows
                                 .iter()
                                 .map(|row| {
                                     (0..columns.len())
@@ -621,24 +656,22 @@ impl DbConnection for MysqlDbConnection {
                                 columns,
                                 rows: data_rows,
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         }
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             } else {
                 let pool = pool.clone();
                 let query_str = query.to_string();
                 let query_str_clone = query_str.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&query_str_clone).execute(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    conn.execute(sqlx::raw_sql(&query_str_clone)).await
                 }).await {
                     Ok(Ok(exec_result)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -652,18 +685,72 @@ impl DbConnection for MysqlDbConnection {
                             message: Some(message),
                         })
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             }
         };
 
         Ok(result)
     }
+
+    async fn query_stream(&self, query: &str, batch_size: usize) -> Result<QueryStream, DbError> {
+        let pool = self.ensure_connected()?;
+        let sql = query.to_string();
+        let running = self.running.clone();
+        running.reset();
+
+        let (stream_tx, stream_rx) = oneshot::channel();
+        TOKIO_HANDLE.spawn(async move {
+            let (mut conn, _running_guard) = match Self::acquire_tracked(&pool, &running).await {
+                Ok(acquired) => acquired,
+                Err(e) => {
+                    let _ = stream_tx.send(Err(DbError::ConnectionError(format!("Failed to acquire connection: {}", e))));
+                    return;
+                }
+            };
+            let mut rows = conn.fetch(sqlx::raw_sql(&sql));
+            match stream::read_page(&mut rows, Some(batch_size), Self::extract_value).await {
+                Ok(page) => {
+                    let (stream, pump) = page.into_stream(batch_size);
+                    let _ = stream_tx.send(Ok(stream));
+                    if let Some(pump) = pump {
+                        pump.run(rows).await;
+                    }
+                }
+                Err(e) => {
+                    let _ = stream_tx.send(Err(DbError::QueryError(e.to_string())));
+                }
+            }
+        });
+
+        stream_rx
+            .await
+            .map_err(|e| DbError::QueryError(format!("Query task ended unexpectedly: {}", e)))?
+    }
+
+    fn running_server_ids(&self) -> Vec<u64> {
+        self.running.handles()
+    }
+
+    async fn cancel_query(&self) -> Result<(), DbError> {
+        let pool = self.ensure_connected()?;
+        self.running.request_cancel();
+        let ids = self.running.handles();
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        TOKIO_HANDLE.spawn(async move {
+            // The pool's only connection is busy with the statement, KILL it from a side connection
+            let mut conn = MySqlConnection::connect_with(&pool.connect_options()).await?;
+            for id in ids {
+                conn.execute(sqlx::raw_sql(&format!("KILL QUERY {}", id))).await?;
+            }
+            conn.close().await
+        })
+        .await
+        .map_err(|e| DbError::QueryError(format!("Failed to spawn cancel task: {}", e)))?
+        .map_err(|e| DbError::QueryError(format!("Failed to cancel query: {}", e)))
+    }
 }
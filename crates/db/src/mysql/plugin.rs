@@ -31,6 +31,12 @@ impl DatabasePlugin for MySqlPlugin {
         Ok(Box::new(conn))
     }
 
+    async fn create_session(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        let mut conn = MysqlDbConnection::new_session(config);
+        conn.connect().await?;
+        Ok(Box::new(conn))
+    }
+
     // === Database/Schema Level Operations ===
 
     async fn list_databases(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
@@ -42,7 +48,7 @@ impl DatabasePlugin for MySqlPlugin {
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter()
-                .filter_map(|row| row.first().and_then(|v| v.clone()))
+                .filter_map(|row| row.first().and_then(|v| v.as_text()))
                 .collect())
         } else {
             Err(anyhow::anyhow!("Unexpected result type"))
@@ -100,10 +106,10 @@ impl DatabasePlugin for MySqlPlugin {
         if let SqlResult::Query(query_result) = result {
             let databases: Vec<DatabaseInfo> = query_result.rows.iter()
                 .filter_map(|row| {
-                    let name = row.get(0).and_then(|v| v.clone())?;
-                    let charset = row.get(1).and_then(|v| v.clone());
-                    let collation = row.get(2).and_then(|v| v.clone());
-                    let table_count = row.get(3).and_then(|v| v.clone()).and_then(|s| s.parse::<i64>().ok());
+                    let name = row.first().and_then(|v| v.as_text())?;
+                    let charset = row.get(1).and_then(|v| v.as_text());
+                    let collation = row.get(2).and_then(|v| v.as_text());
+                    let table_count = row.get(3).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok());
                     
                     Some(DatabaseInfo {
                         name,
@@ -146,21 +152,21 @@ impl DatabasePlugin for MySqlPlugin {
 
         if let SqlResult::Query(query_result) = result {
             let tables: Vec<TableInfo> = query_result.rows.iter().map(|row| {
-                let collation = row.get(5).and_then(|v| v.clone());
+                let collation = row.get(5).and_then(|v| v.as_text());
                 // Extract charset from collation (e.g., "utf8mb4_general_ci" -> "utf8mb4")
                 let charset = collation.as_ref().and_then(|c| {
                     c.split('_').next().map(|s| s.to_string())
                 });
 
                 // Parse row count
-                let row_count = row.get(3).and_then(|v| v.clone()).and_then(|s| s.parse::<i64>().ok());
+                let row_count = row.get(3).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok());
 
                 TableInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    comment: row.get(1).and_then(|v| v.clone()).filter(|s| !s.is_empty()),
-                    engine: row.get(2).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    comment: row.get(1).and_then(|v| v.as_text()).filter(|s| !s.is_empty()),
+                    engine: row.get(2).and_then(|v| v.as_text()),
                     row_count,
-                    create_time: row.get(4).and_then(|v| v.clone()),
+                    create_time: row.get(4).and_then(|v| v.as_text()),
                     charset,
                     collation,
                 }
@@ -218,12 +224,12 @@ impl DatabasePlugin for MySqlPlugin {
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 ColumnInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    data_type: row.get(1).and_then(|v| v.clone()).unwrap_or_default(),
-                    is_nullable: row.get(2).and_then(|v| v.clone()).map(|v| v == "YES").unwrap_or(true),
-                    is_primary_key: row.get(3).and_then(|v| v.clone()).map(|v| v == "PRI").unwrap_or(false),
-                    default_value: row.get(4).and_then(|v| v.clone()),
-                    comment: row.get(5).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    data_type: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                    is_nullable: row.get(2).and_then(|v| v.as_text()).map(|v| v == "YES").unwrap_or(true),
+                    is_primary_key: row.get(3).and_then(|v| v.as_text()).map(|v| v == "PRI").unwrap_or(false),
+                    default_value: row.get(4).and_then(|v| v.as_text()),
+                    comment: row.get(5).and_then(|v| v.as_text()),
                 }
             }).collect())
         } else {
@@ -280,10 +286,10 @@ impl DatabasePlugin for MySqlPlugin {
             let mut indexes: HashMap<String, IndexInfo> = HashMap::new();
 
             for row in query_result.rows {
-                let index_name = row.get(0).and_then(|v| v.clone()).unwrap_or_default();
-                let column_name = row.get(1).and_then(|v| v.clone()).unwrap_or_default();
-                let is_unique = row.get(2).and_then(|v| v.clone()).map(|v| v == "0").unwrap_or(false);
-                let index_type = row.get(3).and_then(|v| v.clone());
+                let index_name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+                let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+                let is_unique = row.get(2).and_then(|v| v.as_text()).map(|v| v == "0").unwrap_or(false);
+                let index_type = row.get(3).and_then(|v| v.as_text());
 
                 indexes.entry(index_name.clone())
                     .or_insert_with(|| IndexInfo {
@@ -328,6 +334,51 @@ impl DatabasePlugin for MySqlPlugin {
             rows,
         })
     }
+    async fn list_foreign_keys(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ForeignKeyInfo>> {
+        let sql = format!(
+            "SELECT CONSTRAINT_NAME, COLUMN_NAME, REFERENCED_TABLE_SCHEMA, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME \
+             FROM INFORMATION_SCHEMA.KEY_COLUMN_USAGE \
+             WHERE TABLE_SCHEMA = '{}' AND TABLE_NAME = '{}' AND REFERENCED_TABLE_NAME IS NOT NULL \
+             ORDER BY CONSTRAINT_NAME, ORDINAL_POSITION",
+            database, table
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list foreign keys: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            let mut foreign_keys: Vec<ForeignKeyInfo> = Vec::new();
+
+            for row in query_result.rows {
+                let name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+                let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+                let referenced_schema = row.get(2).and_then(|v| v.as_text());
+                let referenced_table = row.get(3).and_then(|v| v.as_text()).unwrap_or_default();
+                let referenced_column = row.get(4).and_then(|v| v.as_text()).unwrap_or_default();
+
+                // Rows arrive grouped by constraint, in column order
+                match foreign_keys.last_mut() {
+                    Some(fk) if fk.name == name => {
+                        fk.columns.push(column_name);
+                        fk.referenced_columns.push(referenced_column);
+                    }
+                    _ => foreign_keys.push(ForeignKeyInfo {
+                        name,
+                        columns: vec![column_name],
+                        referenced_schema,
+                        referenced_table,
+                        referenced_columns: vec![referenced_column],
+                    }),
+                }
+            }
+
+            Ok(foreign_keys)
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
     // === View Operations ===
 
     async fn list_views(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<ViewInfo>> {
@@ -346,8 +397,8 @@ impl DatabasePlugin for MySqlPlugin {
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 ViewInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    definition: row.get(1).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    definition: row.get(1).and_then(|v| v.as_text()),
                     comment: None,
                 }
             }).collect())
@@ -399,8 +450,8 @@ impl DatabasePlugin for MySqlPlugin {
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 FunctionInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    return_type: row.get(1).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    return_type: row.get(1).and_then(|v| v.as_text()),
                     parameters: Vec::new(),
                     definition: None,
                     comment: None,
@@ -454,7 +505,7 @@ impl DatabasePlugin for MySqlPlugin {
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 FunctionInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
                     return_type: None,
                     parameters: Vec::new(),
                     definition: None,
@@ -504,10 +555,10 @@ impl DatabasePlugin for MySqlPlugin {
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 TriggerInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    table_name: row.get(1).and_then(|v| v.clone()).unwrap_or_default(),
-                    event: row.get(2).and_then(|v| v.clone()).unwrap_or_default(),
-                    timing: row.get(3).and_then(|v| v.clone()).unwrap_or_default(),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    table_name: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                    event: row.get(2).and_then(|v| v.as_text()).unwrap_or_default(),
+                    timing: row.get(3).and_then(|v| v.as_text()).unwrap_or_default(),
                     definition: None,
                 }
             }).collect())
@@ -659,6 +710,69 @@ impl DatabasePlugin for MySqlPlugin {
             DataTypeInfo::new("SET('value1','value2')", "Set of values").with_category(DataTypeCategory::Other),
         ]
     }
+
+    // === Server Sessions ===
+
+    async fn list_server_sessions(&self, connection: &dyn DbConnection) -> Result<Vec<ServerSession>> {
+        let result = connection.query("SHOW FULL PROCESSLIST", None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list server sessions: {}", e))?;
+
+        match result {
+            SqlResult::Query(query_result) => {
+                let text = |row: &Vec<SqlValue>, i: usize| row.get(i).and_then(|v| v.as_text());
+                Ok(query_result.rows.iter()
+                    .filter_map(|row| {
+                        // Id, User, Host, db, Command, Time, State, Info
+                        let id = text(row, 0)?.parse::<u64>().ok()?;
+                        let state = match (text(row, 4), text(row, 6).filter(|s| !s.is_empty())) {
+                            (Some(command), Some(state)) => Some(format!("{}: {}", command, state)),
+                            (command, state) => command.or(state),
+                        };
+                        Some(ServerSession {
+                            id,
+                            user: text(row, 1),
+                            host: text(row, 2),
+                            database: text(row, 3),
+                            state,
+                            duration_secs: text(row, 5).and_then(|s| s.parse::<i64>().ok()),
+                            query: text(row, 7),
+                        })
+                    })
+                    .collect())
+            }
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to list server sessions: {}", err.message)),
+            SqlResult::Exec(_) => Err(anyhow::anyhow!("Unexpected result type")),
+        }
+    }
+
+    async fn kill_server_session(&self, connection: &dyn DbConnection, id: u64) -> Result<()> {
+        match self.execute_query(connection, "", &format!("KILL {}", id), None).await? {
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to kill session {}: {}", id, err.message)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn tls_status(&self, connection: &dyn DbConnection) -> Result<Option<String>> {
+        let sql = "SHOW SESSION STATUS WHERE Variable_name IN ('Ssl_version', 'Ssl_cipher')";
+        match self.execute_query(connection, "", sql, None).await? {
+            SqlResult::Query(query_result) => {
+                let status = |name: &str| {
+                    query_result.rows.iter()
+                        .find(|row| row.first().and_then(|v| v.as_text()).as_deref() == Some(name))
+                        .and_then(|row| row.get(1).and_then(|v| v.as_text()))
+                        .filter(|value| !value.is_empty())
+                };
+                // An empty cipher means the session is not encrypted
+                Ok(status("Ssl_cipher").map(|cipher| match status("Ssl_version") {
+                    Some(version) => format!("{} {}", version, cipher),
+                    None => cipher,
+                }))
+            }
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to read TLS status: {}", err.message)),
+            SqlResult::Exec(_) => Err(anyhow::anyhow!("Unexpected result type")),
+        }
+    }
 }
 
 impl Default for MySqlPlugin {
@@ -1,230 +1,515 @@
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
-use oracle::Connection;
+use async_trait::async_trait;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, Timelike};
+use oracle::sql_type::{OracleType, Timestamp, ToSql};
+use oracle::{Connection, Row};
 
-use crate::{
-    ExecResult, QueryResult, SqlErrorInfo, SqlResult, SqlScriptSplitter,
-    SqlStatementClassifier,
+use crate::cancel::RunningQueries;
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{
+    ExecOptions, ExecResult, QueryResult, ResultColumn, SqlErrorInfo, SqlResult, SqlScriptSplitter,
+    SqlStatementClassifier, TransactionControl,
 };
+use crate::runtime::TOKIO_HANDLE;
+use crate::types::SqlValue;
 
-pub struct OracleConnection {
-    config: Option<OracleConfig>,
-    conn: RwLock<Option<Connection>>,
-    #[allow(dead_code)]
-    current_schema: RwLock<Option<String>>,
-}
+/// Extra parameter choosing how the target database is addressed: `service_name` or `sid`
+const CONNECT_BY_PARAM: &str = "connect_by";
+/// Extra parameter holding the service name or SID
+const SERVICE_PARAM: &str = "service_name";
 
-#[derive(Debug, Clone)]
-pub struct OracleConfig {
-    pub id: String,
-    pub name: String,
-    pub host: String,
-    pub port: u16,
-    pub username: String,
-    pub password: String,
-    pub service_name: Option<String>,
+pub struct OracleDbConnection {
+    config: Option<DbConnectionConfig>,
+    // The oracle crate is blocking; the connection is only touched from spawn_blocking.
+    // Statements share the read lock so a running one can be broken from another task.
+    conn: Arc<RwLock<Option<Connection>>>,
+    // Track current schema selected on this connection
+    current_schema: RwLock<Option<String>>,
+    // Only the cancel flag is used, break_execution needs no statement handle
+    running: RunningQueries,
 }
 
-impl OracleConnection {
-    pub fn new(config: OracleConfig) -> Self {
+impl OracleDbConnection {
+    pub fn new(config: DbConnectionConfig) -> Self {
         Self {
             config: Some(config),
-            conn: RwLock::new(None),
+            conn: Arc::new(RwLock::new(None)),
             current_schema: RwLock::new(None),
+            running: RunningQueries::default(),
         }
     }
 
-    fn ensure_connected(&self) -> Result<(), String> {
-        let guard = self.conn.read().unwrap();
-        if guard.is_none() {
-            return Err("Not connected to database".to_string());
+    /// Build an Easy Connect string for a service name, or a full descriptor for a SID
+    fn connect_string(config: &DbConnectionConfig) -> String {
+        let service = config.extra_params.get(SERVICE_PARAM).map(|s| s.trim()).unwrap_or("");
+        let by_sid = config.extra_params.get(CONNECT_BY_PARAM).map(|s| s == "sid").unwrap_or(false);
+
+        if service.is_empty() {
+            format!("//{}:{}", config.host, config.port)
+        } else if by_sid {
+            format!(
+                "(DESCRIPTION=(ADDRESS=(PROTOCOL=TCP)(HOST={})(PORT={}))(CONNECT_DATA=(SID={})))",
+                config.host, config.port, service
+            )
+        } else {
+            format!("//{}:{}/{}", config.host, config.port, service)
         }
-        Ok(())
     }
 
-    pub fn connect(&mut self) -> Result<(), String> {
-        let config = self.config.as_ref().ok_or("No configuration provided")?;
-        
-        let connect_string = if let Some(service) = &config.service_name {
-            format!("//{}:{}/{}", config.host, config.port, service)
-        } else {
-            format!("//{}:{}", config.host, config.port)
+    fn to_sql_params(params: Vec<SqlValue>) -> Vec<Box<dyn ToSql + Send>> {
+        params
+            .into_iter()
+            .map(|param| -> Box<dyn ToSql + Send> {
+                match param {
+                    SqlValue::Null => Box::new(Option::<String>::None),
+                    // Oracle SQL has no boolean type before 23ai
+                    SqlValue::Bool(v) => Box::new(v as i64),
+                    SqlValue::Int(v) => Box::new(v),
+                    SqlValue::Float(v) => Box::new(v),
+                    SqlValue::String(v) => Box::new(v),
+                    SqlValue::Bytes(v) => Box::new(v),
+                    SqlValue::Json(v) => Box::new(v.to_string()),
+                    SqlValue::Decimal(v) => Box::new(v),
+                    SqlValue::Date(v) => Self::timestamp_param(&v.and_time(Default::default()), None),
+                    // Oracle has no TIME type, times are compared as text
+                    SqlValue::Time(v) => Box::new(v.format("%H:%M:%S").to_string()),
+                    SqlValue::DateTime(v) => Self::timestamp_param(&v, None),
+                    SqlValue::DateTimeTz(v) => Self::timestamp_param(&v.naive_local(), Some(v.offset().local_minus_utc())),
+                    SqlValue::Uuid(v) => Box::new(v.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Bind a date/time as a native TIMESTAMP so it does not depend on NLS_DATE_FORMAT
+    fn timestamp_param(value: &NaiveDateTime, offset: Option<i32>) -> Box<dyn ToSql + Send> {
+        let timestamp = Timestamp::new(
+            value.year(),
+            value.month(),
+            value.day(),
+            value.hour(),
+            value.minute(),
+            value.second(),
+            value.nanosecond(),
+        )
+        .and_then(|ts| match offset {
+            Some(offset) => ts.and_tz_offset(offset),
+            None => Ok(ts),
+        });
+        match timestamp {
+            Ok(ts) => Box::new(ts),
+            Err(_) => Box::new(value.format("%Y-%m-%d %H:%M:%S").to_string()),
+        }
+    }
+
+    /// Read a cell according to the column's Oracle type
+    fn extract_value(row: &Row, index: usize, oracle_type: &OracleType) -> SqlValue {
+        let value = match oracle_type {
+            // NUMBER(p) with no scale fits an i64 up to 18 digits
+            OracleType::Number(precision, 0) if (1..=18).contains(precision) => {
+                row.get::<usize, Option<i64>>(index).ok().flatten().map(SqlValue::Int)
+            }
+            OracleType::Int64 => row.get::<usize, Option<i64>>(index).ok().flatten().map(SqlValue::Int),
+            OracleType::Number(..) | OracleType::Float(_) | OracleType::UInt64 => row
+                .get::<usize, Option<String>>(index)
+                .ok()
+                .flatten()
+                .map(SqlValue::Decimal),
+            OracleType::BinaryFloat | OracleType::BinaryDouble => {
+                row.get::<usize, Option<f64>>(index).ok().flatten().map(SqlValue::Float)
+            }
+            OracleType::Boolean => row.get::<usize, Option<bool>>(index).ok().flatten().map(SqlValue::Bool),
+            OracleType::Date | OracleType::Timestamp(_) | OracleType::TimestampLTZ(_) => row
+                .get::<usize, Option<Timestamp>>(index)
+                .ok()
+                .flatten()
+                .and_then(|ts| Self::naive_datetime(&ts))
+                .map(SqlValue::DateTime),
+            OracleType::TimestampTZ(_) => row
+                .get::<usize, Option<Timestamp>>(index)
+                .ok()
+                .flatten()
+                .and_then(|ts| {
+                    let offset = FixedOffset::east_opt(ts.tz_offset())?;
+                    let local = Self::naive_datetime(&ts)?;
+                    local.and_local_timezone(offset).single()
+                })
+                .map(|v: DateTime<FixedOffset>| SqlValue::DateTimeTz(v)),
+            OracleType::Raw(_) | OracleType::BLOB | OracleType::LongRaw => {
+                row.get::<usize, Option<Vec<u8>>>(index).ok().flatten().map(SqlValue::Bytes)
+            }
+            _ => row.get::<usize, Option<String>>(index).ok().flatten().map(SqlValue::String),
         };
+        value.unwrap_or(SqlValue::Null)
+    }
+
+    fn naive_datetime(ts: &Timestamp) -> Option<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(ts.year(), ts.month(), ts.day())?
+            .and_hms_nano_opt(ts.hour(), ts.minute(), ts.second(), ts.nanosecond())
+    }
+
+    /// Run a closure against the connection on the blocking thread pool
+    async fn with_conn<F, T>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Connection) -> Result<T, String> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        TOKIO_HANDLE
+            .spawn_blocking(move || {
+                let guard = conn.read().map_err(|e| e.to_string())?;
+                let conn = guard.as_ref().ok_or("Not connected to database")?;
+                f(conn)
+            })
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    /// Toggle autocommit; turned off while a transactional script runs
+    async fn set_autocommit(&self, autocommit: bool) -> Result<(), String> {
+        let conn = self.conn.clone();
+        TOKIO_HANDLE
+            .spawn_blocking(move || {
+                let mut guard = conn.write().map_err(|e| e.to_string())?;
+                let conn = guard.as_mut().ok_or("Not connected to database")?;
+                conn.set_autocommit(autocommit);
+                Ok(())
+            })
+            .await
+            .map_err(|e| e.to_string())?
+    }
 
-        let conn = Connection::connect(&config.username, &config.password, &connect_string)
-            .map_err(|e| format!("Failed to connect: {}", e))?;
+    /// Commit or roll back a transactional script and restore autocommit
+    async fn end_transaction(&self, commit: bool) -> SqlResult {
+        let start = Instant::now();
+        let outcome = self.with_conn(move |conn| {
+            let result = if commit { conn.commit() } else { conn.rollback() };
+            result.map_err(|e| e.to_string())
+        }).await;
+        let restored = self.set_autocommit(true).await;
+
+        match outcome.and(restored) {
+            Ok(()) if commit => SqlResult::Exec(ExecResult::transaction_committed(start.elapsed().as_millis())),
+            Ok(()) => SqlResult::Exec(ExecResult::transaction_rolled_back(start.elapsed().as_millis())),
+            Err(message) => SqlResult::Error(SqlErrorInfo::new(if commit { "COMMIT" } else { "ROLLBACK" }, message)),
+        }
+    }
+
+    /// Build the error of a failed statement, keeping the parse error offset Oracle reports
+    fn error_info(sql: &str, e: &oracle::Error) -> SqlErrorInfo {
+        // The offset is in bytes and 0 when Oracle gives none
+        let position = e
+            .db_error()
+            .map(|e| e.offset() as usize)
+            .filter(|offset| *offset > 0)
+            .and_then(|offset| sql.get(..offset))
+            .map(|before| before.chars().count() + 1);
+        SqlErrorInfo::new(sql, e.to_string()).with_position(position)
+    }
+
+    /// Execute a single statement and wrap the outcome as a SqlResult
+    async fn run_statement(
+        &self,
+        sql: &str,
+        params: Option<Vec<SqlValue>>,
+        max_rows: Option<usize>,
+    ) -> SqlResult {
+        let start = Instant::now();
+        let stmt = sql.to_string();
+        let params = Self::to_sql_params(params.unwrap_or_default());
+
+        if SqlStatementClassifier::is_query_statement(sql) {
+            let result = self.with_conn(move |conn| {
+                let refs: Vec<&dyn ToSql> = params.iter().map(|p| p.as_ref() as &dyn ToSql).collect();
+                let rows = match conn.query(&stmt, &refs) {
+                    Ok(rows) => rows,
+                    Err(e) => return Ok(Err(Self::error_info(&stmt, &e))),
+                };
+                let types: Vec<OracleType> = rows
+                    .column_info()
+                    .iter()
+                    .map(|col| col.oracle_type().clone())
+                    .collect();
+                let columns: Vec<ResultColumn> = rows
+                    .column_info()
+                    .iter()
+                    .map(|col| ResultColumn::new(col.name(), col.oracle_type().to_string()))
+                    .collect();
+
+                // Rows are fetched lazily, reading one past the limit only tells whether more exist
+                let limit = max_rows.unwrap_or(usize::MAX);
+                let mut data_rows = Vec::new();
+                let mut has_more = false;
+                for row in rows {
+                    if data_rows.len() == limit {
+                        has_more = true;
+                        break;
+                    }
+                    let row = row.map_err(|e| e.to_string())?;
+                    let row_data = types
+                        .iter()
+                        .enumerate()
+                        .map(|(i, oracle_type)| Self::extract_value(&row, i, oracle_type))
+                        .collect();
+                    data_rows.push(row_data);
+                }
+                Ok(Ok((columns, data_rows, has_more)))
+            }).await;
+
+            match result {
+                Ok(Ok((columns, rows, has_more))) => SqlResult::Query(QueryResult {
+                    sql: sql.to_string(),
+                    columns,
+                    rows,
+                    elapsed_ms: start.elapsed().as_millis(),
+                    has_more,
+                    more_rows: None,
+                }),
+                Ok(Err(error)) => SqlResult::Error(error),
+                Err(message) => SqlResult::Error(SqlErrorInfo::new(sql, message)),
+            }
+        } else {
+            let result = self.with_conn(move |conn| {
+                let refs: Vec<&dyn ToSql> = params.iter().map(|p| p.as_ref() as &dyn ToSql).collect();
+                let statement = match conn.execute(&stmt, &refs) {
+                    Ok(statement) => statement,
+                    Err(e) => return Ok(Err(Self::error_info(&stmt, &e))),
+                };
+                Ok(Ok(statement.row_count().unwrap_or(0)))
+            }).await;
+
+            match result {
+                Ok(Ok(rows_affected)) => SqlResult::Exec(ExecResult {
+                    sql: sql.to_string(),
+                    rows_affected,
+                    elapsed_ms: start.elapsed().as_millis(),
+                    message: Some(SqlStatementClassifier::format_message(sql, rows_affected)),
+                }),
+                Ok(Err(error)) => SqlResult::Error(error),
+                Err(message) => SqlResult::Error(SqlErrorInfo::new(sql, message)),
+            }
+        }
+    }
+
+    /// Extract the schema from `ALTER SESSION SET CURRENT_SCHEMA = x`
+    fn parse_current_schema(sql: &str) -> Option<String> {
+        let upper = sql.to_uppercase();
+        let pos = upper.find("CURRENT_SCHEMA")?;
+        if !upper.starts_with("ALTER SESSION") {
+            return None;
+        }
+        let value = sql[pos + "CURRENT_SCHEMA".len()..].trim_start().strip_prefix('=')?;
+        Some(value.trim().trim_end_matches(';').trim_matches('"').to_string())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.conn.read().map(|guard| guard.is_some()).unwrap_or(false)
+    }
+}
+
+#[async_trait]
+impl DbConnection for OracleDbConnection {
+    fn config(&self) -> Option<DbConnectionConfig> {
+        self.config.clone()
+    }
+
+    // Oracle before 23c has no FROM-less SELECT
+    fn ping_statement(&self) -> &'static str {
+        "SELECT 1 FROM DUAL"
+    }
+
+    async fn connect(&mut self) -> Result<(), DbError> {
+        let config = self
+            .config
+            .clone()
+            .ok_or_else(|| DbError::ConnectionError("No database configuration provided".to_string()))?;
+
+        let connect_string = Self::connect_string(&config);
+        let username = config.username.clone();
+        let password = config.password.clone();
+        let schema = config.database.clone();
+
+        let conn = TOKIO_HANDLE
+            .spawn_blocking(move || -> Result<Connection, String> {
+                let mut conn = Connection::connect(&username, &password, &connect_string)
+                    .map_err(|e| format!("Failed to connect to Oracle: {}", e))?;
+                // Match the other backends, every statement commits on its own
+                conn.set_autocommit(true);
+
+                // The tree's database level is an Oracle schema
+                if let Some(schema) = &schema {
+                    conn.execute(
+                        &format!("ALTER SESSION SET CURRENT_SCHEMA = \"{}\"", schema.replace('"', "")),
+                        &[],
+                    )
+                    .map_err(|e| format!("Failed to set schema: {}", e))?;
+                }
+                Ok(conn)
+            })
+            .await
+            .map_err(|e| DbError::ConnectionError(format!("Failed to spawn connection task: {}", e)))?
+            .map_err(DbError::ConnectionError)?;
 
         {
             let mut guard = self.conn.write().unwrap();
             *guard = Some(conn);
         }
+        {
+            let mut schema_guard = self.current_schema.write().unwrap();
+            schema_guard.clone_from(&config.database);
+        }
 
         Ok(())
     }
 
-    pub fn disconnect(&mut self) -> Result<(), String> {
-        let conn_opt = {
-            let mut guard = self.conn.write().unwrap();
-            guard.take()
-        };
-        
+    async fn disconnect(&mut self) -> Result<(), DbError> {
+        let conn_opt = self.conn.write().unwrap().take();
         if let Some(conn) = conn_opt {
-            let _ = conn.close();
+            TOKIO_HANDLE
+                .spawn_blocking(move || conn.close())
+                .await
+                .map_err(|e| DbError::ConnectionError(e.to_string()))?
+                .map_err(|e| DbError::ConnectionError(e.to_string()))?;
         }
-        
         Ok(())
     }
 
-    pub fn execute(&self, sql: &str) -> Result<Vec<SqlResult>, String> {
-        self.ensure_connected()?;
-        
-        let statements = SqlScriptSplitter::split(sql);
+    async fn execute(&self, script: &str, options: ExecOptions) -> Result<Vec<SqlResult>, DbError> {
+        if !self.is_connected() {
+            return Err(DbError::ConnectionError("Not connected to database".to_string()));
+        }
+
+        let statements = SqlScriptSplitter::split_for(script, DatabaseType::Oracle);
         let mut results = Vec::new();
 
-        for stmt in statements {
-            let stmt = stmt.trim();
-            if stmt.is_empty() {
+        // Oracle opens transactions implicitly, holding back autocommit is enough
+        let mut in_transaction = options.transactional;
+        if in_transaction {
+            self.set_autocommit(false)
+                .await
+                .map_err(|e| DbError::QueryError(format!("Failed to begin transaction: {}", e)))?;
+        }
+
+        self.running.reset();
+        for sql in statements {
+            let sql = sql.trim();
+            if sql.is_empty() {
                 continue;
             }
+            if self.running.is_cancelled() {
+                break;
+            }
 
-            let start = Instant::now();
-            let is_query = SqlStatementClassifier::is_query_statement(stmt);
+            let result = self.run_statement(sql, None, options.max_rows).await;
+            let failed = matches!(result, SqlResult::Error(_));
 
-            let mut guard = self.conn.write().unwrap();
-            let conn = guard.as_mut().ok_or("Not connected")?;
-
-            if is_query {
-                match conn.query(stmt, &[]) {
-                    Ok(rows) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-                        
-                        let column_info = rows.column_info();
-                        let columns: Vec<String> = column_info.iter()
-                            .map(|col| col.name().to_string())
-                            .collect();
-
-                        let mut data_rows = Vec::new();
-                        for row_result in rows {
-                            match row_result {
-                                Ok(row) => {
-                                    let mut row_data = Vec::new();
-                                    for i in 0..columns.len() {
-                                        let value = row.get::<usize, Option<String>>(i)
-                                            .unwrap_or(None);
-                                        row_data.push(value);
-                                    }
-                                    data_rows.push(row_data);
-                                }
-                                Err(_) => continue,
-                            }
-                        }
-
-                        results.push(SqlResult::Query(QueryResult {
-                            sql: stmt.to_string(),
-                            columns,
-                            rows: data_rows,
-                            elapsed_ms,
-                        }));
-                    }
-                    Err(e) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: stmt.to_string(),
-                            message: e.to_string(),
-                        }));
-                    }
+            if !failed {
+                if let Some(schema) = Self::parse_current_schema(sql) {
+                    let mut schema_guard = self.current_schema.write().unwrap();
+                    *schema_guard = Some(schema);
                 }
-            } else {
-                match conn.execute(stmt, &[]) {
-                    Ok(_) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-                        let message = SqlStatementClassifier::format_message(stmt, 0);
-
-                        results.push(SqlResult::Exec(ExecResult {
-                            sql: stmt.to_string(),
-                            rows_affected: 0,
-                            elapsed_ms,
-                            message: Some(message),
-                        }));
-                    }
-                    Err(e) => {
-                        results.push(SqlResult::Error(SqlErrorInfo {
-                            sql: stmt.to_string(),
-                            message: e.to_string(),
-                        }));
-                    }
+                // A typed COMMIT/ROLLBACK ends a manual transaction, go back to autocommit
+                let ends_transaction = matches!(
+                    SqlStatementClassifier::transaction_control(sql),
+                    Some(TransactionControl::Commit | TransactionControl::Rollback)
+                );
+                if ends_transaction && !options.transactional {
+                    let _ = self.set_autocommit(true).await;
+                }
+            }
+
+            results.push(result);
+            if failed && options.stop_on_error {
+                if in_transaction {
+                    results.push(self.end_transaction(false).await);
+                    in_transaction = false;
                 }
+                break;
             }
         }
 
+        if in_transaction {
+            let commit = !self.running.is_cancelled();
+            results.push(self.end_transaction(commit).await);
+        }
+
+        SqlScriptSplitter::locate_errors(script, DatabaseType::Oracle, &mut results);
         Ok(results)
     }
 
-    pub fn query(&self, sql: &str) -> Result<SqlResult, String> {
-        self.ensure_connected()?;
+    async fn query(&self, query: &str, params: Option<Vec<SqlValue>>, _options: ExecOptions) -> Result<SqlResult, DbError> {
+        if !self.is_connected() {
+            return Err(DbError::ConnectionError("Not connected to database".to_string()));
+        }
 
-        let start = Instant::now();
-        let is_query = SqlStatementClassifier::is_query_statement(sql);
-
-        let mut guard = self.conn.write().unwrap();
-        let conn = guard.as_mut().ok_or("Not connected")?;
-
-        if is_query {
-            match conn.query(sql, &[]) {
-                Ok(rows) => {
-                    let elapsed_ms = start.elapsed().as_millis();
-                    
-                    let column_info = rows.column_info();
-                    let columns: Vec<String> = column_info.iter()
-                        .map(|col| col.name().to_string())
-                        .collect();
+        Ok(self.run_statement(query, params, None).await)
+    }
 
-                    let mut data_rows = Vec::new();
-                    for row_result in rows {
-                        match row_result {
-                            Ok(row) => {
-                                let mut row_data = Vec::new();
-                                for i in 0..columns.len() {
-                                    let value = row.get::<usize, Option<String>>(i)
-                                        .unwrap_or(None);
-                                    row_data.push(value);
-                                }
-                                data_rows.push(row_data);
-                            }
-                            Err(_) => continue,
-                        }
-                    }
+    async fn cancel_query(&self) -> Result<(), DbError> {
+        self.running.request_cancel();
+        let conn = self.conn.clone();
+        TOKIO_HANDLE
+            .spawn_blocking(move || {
+                let guard = conn.read().map_err(|e| e.to_string())?;
+                let conn = guard.as_ref().ok_or("Not connected to database")?;
+                conn.break_execution().map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|e| DbError::QueryError(format!("Failed to spawn cancel task: {}", e)))?
+            .map_err(|e| DbError::QueryError(format!("Failed to cancel query: {}", e)))
+    }
 
-                    Ok(SqlResult::Query(QueryResult {
-                        sql: sql.to_string(),
-                        columns,
-                        rows: data_rows,
-                        elapsed_ms,
-                    }))
-                }
-                Err(e) => Ok(SqlResult::Error(SqlErrorInfo {
-                    sql: sql.to_string(),
-                    message: e.to_string(),
-                })),
-            }
-        } else {
-            match conn.execute(sql, &[]) {
-                Ok(_) => {
-                    let elapsed_ms = start.elapsed().as_millis();
-                    let message = SqlStatementClassifier::format_message(sql, 0);
-
-                    Ok(SqlResult::Exec(ExecResult {
-                        sql: sql.to_string(),
-                        rows_affected: 0,
-                        elapsed_ms,
-                        message: Some(message),
-                    }))
-                }
-                Err(e) => Ok(SqlResult::Error(SqlErrorInfo {
-                    sql: sql.to_string(),
-                    message: e.to_string(),
-                })),
-            }
+    async fn begin_transaction(&self) -> Result<(), DbError> {
+        // Transactions start implicitly, keep them open by holding back autocommit
+        self.set_autocommit(false).await.map_err(DbError::QueryError)
+    }
+
+    async fn commit(&self) -> Result<(), DbError> {
+        match self.end_transaction(true).await {
+            SqlResult::Error(err) => Err(DbError::QueryError(err.message)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn rollback(&self) -> Result<(), DbError> {
+        match self.end_transaction(false).await {
+            SqlResult::Error(err) => Err(DbError::QueryError(err.message)),
+            _ => Ok(()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config;
+    use one_core::storage::DatabaseType;
+
+    fn config(params: &[(&str, &str)]) -> DbConnectionConfig {
+        let mut config = test_config(DatabaseType::Oracle, params);
+        config.port = 1521;
+        config
+    }
+
+    #[test]
+    fn test_connect_string() {
+        assert_eq!(
+            OracleDbConnection::connect_string(&config(&[("service_name", " ORCLPDB1 ")])),
+            "//db.internal:1521/ORCLPDB1"
+        );
+        // Anything but sid addresses a service name
+        assert_eq!(
+            OracleDbConnection::connect_string(&config(&[("connect_by", "service"), ("service_name", "XEPDB1")])),
+            "//db.internal:1521/XEPDB1"
+        );
+        assert_eq!(
+            OracleDbConnection::connect_string(&config(&[("connect_by", "sid"), ("service_name", "ORCL")])),
+            "(DESCRIPTION=(ADDRESS=(PROTOCOL=TCP)(HOST=db.internal)(PORT=1521))(CONNECT_DATA=(SID=ORCL)))"
+        );
+        // Without a service the server's default service is used
+        assert_eq!(OracleDbConnection::connect_string(&config(&[("connect_by", "sid")])), "//db.internal:1521");
+    }
+}
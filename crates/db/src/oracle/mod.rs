@@ -1 +1,4 @@
-pub mod connection;
\ No newline at end of file
+mod connection;
+mod plugin;
+
+pub use plugin::OraclePlugin;
@@ -0,0 +1,761 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use gpui_component::table::Column;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{ExecOptions, ExecResult, SqlResult};
+use crate::oracle::connection::OracleDbConnection;
+use crate::plugin::DatabasePlugin;
+use crate::types::*;
+
+/// Oracle database plugin implementation (stateless)
+///
+/// An Oracle connection targets a single database; its schemas (users) are
+/// exposed as the tree's database level and read from the `ALL_*` dictionary views.
+pub struct OraclePlugin;
+
+impl OraclePlugin {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Escape a value used inside a single-quoted SQL string literal
+    fn escape_literal(value: &str) -> String {
+        value.replace('\'', "''")
+    }
+
+    async fn query_rows(&self, connection: &dyn DbConnection, sql: &str, what: &str) -> Result<Vec<Vec<SqlValue>>> {
+        let result = connection.query(sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list {}: {}", what, e))?;
+
+        match result {
+            SqlResult::Query(query_result) => Ok(query_result.rows),
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to list {}: {}", what, err.message)),
+            _ => Err(anyhow::anyhow!("Unexpected result type")),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePlugin for OraclePlugin {
+    fn name(&self) -> DatabaseType {
+        DatabaseType::Oracle
+    }
+
+    fn pagination_clause(&self, _has_order: bool, limit: usize, offset: usize) -> String {
+        if offset == 0 {
+            format!(" FETCH FIRST {} ROWS ONLY", limit)
+        } else {
+            format!(" OFFSET {} ROWS FETCH NEXT {} ROWS ONLY", offset, limit)
+        }
+    }
+
+    async fn create_connection(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        let mut conn = OracleDbConnection::new(config);
+        conn.connect().await?;
+        Ok(Box::new(conn))
+    }
+
+    // === Database/Schema Level Operations ===
+
+    async fn list_databases(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
+        let rows = self.query_rows(
+            connection,
+            "SELECT USERNAME FROM ALL_USERS \
+             WHERE ORACLE_MAINTAINED = 'N' OR USERNAME = USER \
+             ORDER BY USERNAME",
+            "schemas",
+        ).await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| row.first().and_then(|v| v.as_text()))
+            .collect())
+    }
+
+    async fn list_databases_view(&self, connection: &dyn DbConnection) -> Result<ObjectView> {
+        use gpui::px;
+
+        let databases = self.list_databases_detailed(connection).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("charset", "Charset").width(px(120.0)),
+            Column::new("tables", "Tables").width(px(80.0)).text_right(),
+            Column::new("created", "Created").width(px(180.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = databases.iter().map(|db| {
+            vec![
+                db.name.clone(),
+                db.charset.as_deref().unwrap_or("-").to_string(),
+                db.table_count.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                db.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} schema(s)", databases.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_databases_detailed(&self, connection: &dyn DbConnection) -> Result<Vec<DatabaseInfo>> {
+        let rows = self.query_rows(
+            connection,
+            "SELECT u.USERNAME, \
+                (SELECT COUNT(*) FROM ALL_TABLES t WHERE t.OWNER = u.USERNAME), \
+                TO_CHAR(u.CREATED, 'YYYY-MM-DD HH24:MI:SS'), \
+                (SELECT VALUE FROM NLS_DATABASE_PARAMETERS WHERE PARAMETER = 'NLS_CHARACTERSET') \
+             FROM ALL_USERS u \
+             WHERE u.ORACLE_MAINTAINED = 'N' OR u.USERNAME = USER \
+             ORDER BY u.USERNAME",
+            "schemas",
+        ).await?;
+
+        Ok(rows.iter()
+            .filter_map(|row| {
+                Some(DatabaseInfo {
+                    name: row.first().and_then(|v| v.as_text())?,
+                    charset: row.get(3).and_then(|v| v.as_text()),
+                    collation: None,
+                    size: None,
+                    table_count: row.get(1).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok()),
+                    comment: row.get(2).and_then(|v| v.as_text()),
+                })
+            })
+            .collect())
+    }
+
+    // === Table Operations ===
+
+    async fn list_tables(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TableInfo>> {
+        let sql = format!(
+            "SELECT t.TABLE_NAME, c.COMMENTS, t.TABLESPACE_NAME, t.NUM_ROWS, \
+                TO_CHAR(o.CREATED, 'YYYY-MM-DD HH24:MI:SS') \
+             FROM ALL_TABLES t \
+             LEFT JOIN ALL_TAB_COMMENTS c ON c.OWNER = t.OWNER AND c.TABLE_NAME = t.TABLE_NAME \
+             LEFT JOIN ALL_OBJECTS o \
+                ON o.OWNER = t.OWNER AND o.OBJECT_NAME = t.TABLE_NAME AND o.OBJECT_TYPE = 'TABLE' \
+             WHERE t.OWNER = '{}' AND t.NESTED = 'NO' AND t.SECONDARY = 'N' AND t.DROPPED = 'NO' \
+             ORDER BY t.TABLE_NAME",
+            Self::escape_literal(database)
+        );
+
+        let rows = self.query_rows(connection, &sql, "tables").await?;
+
+        Ok(rows.iter().map(|row| {
+            TableInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                comment: row.get(1).and_then(|v| v.as_text()),
+                engine: row.get(2).and_then(|v| v.as_text()),
+                row_count: row.get(3).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok()),
+                create_time: row.get(4).and_then(|v| v.as_text()),
+                charset: None,
+                collation: None,
+            }
+        }).collect())
+    }
+
+    async fn list_tables_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let tables = self.list_tables(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("tablespace", "Tablespace").width(px(120.0)),
+            Column::new("rows", "Rows").width(px(100.0)).text_right(),
+            Column::new("created", "Created").width(px(180.0)),
+            Column::new("comment", "Comment").width(px(300.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = tables.iter().map(|table| {
+            vec![
+                table.name.clone(),
+                table.engine.as_deref().unwrap_or("-").to_string(),
+                table.row_count.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                table.create_time.as_deref().unwrap_or("-").to_string(),
+                table.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} table(s)", tables.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_columns(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ColumnInfo>> {
+        let sql = format!(
+            "SELECT c.COLUMN_NAME, \
+                c.DATA_TYPE || CASE \
+                    WHEN c.DATA_TYPE IN ('VARCHAR2', 'NVARCHAR2', 'CHAR', 'NCHAR') THEN '(' || c.CHAR_LENGTH || ')' \
+                    WHEN c.DATA_TYPE = 'RAW' THEN '(' || c.DATA_LENGTH || ')' \
+                    WHEN c.DATA_TYPE = 'NUMBER' AND c.DATA_PRECISION IS NOT NULL \
+                        THEN '(' || c.DATA_PRECISION || ',' || c.DATA_SCALE || ')' \
+                    ELSE '' END, \
+                c.NULLABLE, \
+                CASE WHEN EXISTS ( \
+                    SELECT 1 FROM ALL_CONSTRAINTS k \
+                    JOIN ALL_CONS_COLUMNS kc ON kc.OWNER = k.OWNER AND kc.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+                    WHERE k.CONSTRAINT_TYPE = 'P' AND k.OWNER = c.OWNER \
+                        AND k.TABLE_NAME = c.TABLE_NAME AND kc.COLUMN_NAME = c.COLUMN_NAME \
+                ) THEN 1 ELSE 0 END, \
+                c.DATA_DEFAULT, \
+                cc.COMMENTS \
+             FROM ALL_TAB_COLUMNS c \
+             LEFT JOIN ALL_COL_COMMENTS cc \
+                ON cc.OWNER = c.OWNER AND cc.TABLE_NAME = c.TABLE_NAME AND cc.COLUMN_NAME = c.COLUMN_NAME \
+             WHERE c.OWNER = '{}' AND c.TABLE_NAME = '{}' \
+             ORDER BY c.COLUMN_ID",
+            Self::escape_literal(database),
+            Self::escape_literal(table)
+        );
+
+        let rows = self.query_rows(connection, &sql, "columns").await?;
+
+        Ok(rows.iter().map(|row| {
+            ColumnInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                data_type: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                is_nullable: row.get(2).and_then(|v| v.as_text()).map(|v| v == "Y").unwrap_or(true),
+                is_primary_key: row.get(3).and_then(|v| v.as_text()).map(|v| v == "1").unwrap_or(false),
+                // DATA_DEFAULT keeps the trailing whitespace of the DDL
+                default_value: row.get(4).and_then(|v| v.as_text()).map(|v| v.trim().to_string()),
+                comment: row.get(5).and_then(|v| v.as_text()),
+            }
+        }).collect())
+    }
+
+    async fn list_columns_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let columns_data = self.list_columns(connection, database, table).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("type", "Type").width(px(150.0)),
+            Column::new("nullable", "Nullable").width(px(80.0)),
+            Column::new("key", "Key").width(px(80.0)),
+            Column::new("default", "Default").width(px(120.0)),
+            Column::new("comment", "Comment").width(px(250.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = columns_data.iter().map(|col| {
+            vec![
+                col.name.clone(),
+                col.data_type.clone(),
+                if col.is_nullable { "YES" } else { "NO" }.to_string(),
+                if col.is_primary_key { "PRI" } else { "" }.to_string(),
+                col.default_value.as_deref().unwrap_or("").to_string(),
+                col.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} column(s)", columns_data.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_indexes(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<IndexInfo>> {
+        let sql = format!(
+            "SELECT i.INDEX_NAME, ic.COLUMN_NAME, i.UNIQUENESS, i.INDEX_TYPE, \
+                CASE WHEN EXISTS ( \
+                    SELECT 1 FROM ALL_CONSTRAINTS k \
+                    WHERE k.OWNER = i.TABLE_OWNER AND k.INDEX_NAME = i.INDEX_NAME AND k.CONSTRAINT_TYPE = 'P' \
+                ) THEN 1 ELSE 0 END \
+             FROM ALL_INDEXES i \
+             JOIN ALL_IND_COLUMNS ic ON ic.INDEX_OWNER = i.OWNER AND ic.INDEX_NAME = i.INDEX_NAME \
+             WHERE i.TABLE_OWNER = '{}' AND i.TABLE_NAME = '{}' \
+             ORDER BY i.INDEX_NAME, ic.COLUMN_POSITION",
+            Self::escape_literal(database),
+            Self::escape_literal(table)
+        );
+
+        let rows = self.query_rows(connection, &sql, "indexes").await?;
+        let mut indexes: HashMap<String, IndexInfo> = HashMap::new();
+
+        for row in rows {
+            let index_name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+            let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+            let is_unique = row.get(2).and_then(|v| v.as_text()).map(|v| v == "UNIQUE").unwrap_or(false);
+            let is_primary = row.get(4).and_then(|v| v.as_text()).map(|v| v == "1").unwrap_or(false);
+            let index_type = if is_primary {
+                Some("PRIMARY KEY".to_string())
+            } else {
+                row.get(3).and_then(|v| v.as_text())
+            };
+
+            indexes.entry(index_name.clone())
+                .or_insert_with(|| IndexInfo {
+                    name: index_name,
+                    columns: Vec::new(),
+                    is_unique,
+                    index_type,
+                })
+                .columns.push(column_name);
+        }
+
+        let mut indexes: Vec<IndexInfo> = indexes.into_values().collect();
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(indexes)
+    }
+
+    async fn list_indexes_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let indexes = self.list_indexes(connection, database, table).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("columns", "Columns").width(px(250.0)),
+            Column::new("unique", "Unique").width(px(80.0)),
+            Column::new("type", "Type").width(px(140.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = indexes.iter().map(|idx| {
+            vec![
+                idx.name.clone(),
+                idx.columns.join(", "),
+                if idx.is_unique { "YES" } else { "NO" }.to_string(),
+                idx.index_type.as_deref().unwrap_or("-").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} index(es)", indexes.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === View Operations ===
+
+    async fn list_views(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<ViewInfo>> {
+        let sql = format!(
+            "SELECT v.VIEW_NAME, v.TEXT, c.COMMENTS \
+             FROM ALL_VIEWS v \
+             LEFT JOIN ALL_TAB_COMMENTS c ON c.OWNER = v.OWNER AND c.TABLE_NAME = v.VIEW_NAME \
+             WHERE v.OWNER = '{}' \
+             ORDER BY v.VIEW_NAME",
+            Self::escape_literal(database)
+        );
+
+        let rows = self.query_rows(connection, &sql, "views").await?;
+
+        Ok(rows.iter().map(|row| {
+            ViewInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                definition: row.get(1).and_then(|v| v.as_text()),
+                comment: row.get(2).and_then(|v| v.as_text()),
+            }
+        }).collect())
+    }
+
+    async fn list_views_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let views = self.list_views(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("definition", "Definition").width(px(400.0)),
+            Column::new("comment", "Comment").width(px(250.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = views.iter().map(|view| {
+            vec![
+                view.name.clone(),
+                view.definition.as_deref().unwrap_or("").to_string(),
+                view.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} view(s)", views.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Function Operations ===
+
+    async fn list_functions(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<FunctionInfo>> {
+        let sql = format!(
+            "SELECT o.OBJECT_NAME, \
+                (SELECT a.DATA_TYPE FROM ALL_ARGUMENTS a \
+                 WHERE a.OWNER = o.OWNER AND a.OBJECT_NAME = o.OBJECT_NAME \
+                    AND a.PACKAGE_NAME IS NULL AND a.POSITION = 0 AND ROWNUM = 1), \
+                o.STATUS \
+             FROM ALL_OBJECTS o \
+             WHERE o.OWNER = '{}' AND o.OBJECT_TYPE = 'FUNCTION' \
+             ORDER BY o.OBJECT_NAME",
+            Self::escape_literal(database)
+        );
+
+        let rows = self.query_rows(connection, &sql, "functions").await?;
+
+        Ok(rows.iter().map(|row| {
+            FunctionInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                return_type: row.get(1).and_then(|v| v.as_text()),
+                parameters: Vec::new(),
+                definition: None,
+                comment: row.get(2).and_then(|v| v.as_text()),
+            }
+        }).collect())
+    }
+
+    async fn list_functions_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let functions = self.list_functions(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("return_type", "Return Type").width(px(150.0)),
+            Column::new("status", "Status").width(px(100.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = functions.iter().map(|func| {
+            vec![
+                func.name.clone(),
+                func.return_type.as_deref().unwrap_or("-").to_string(),
+                func.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} function(s)", functions.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Procedure Operations ===
+
+    async fn list_procedures(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<FunctionInfo>> {
+        let sql = format!(
+            "SELECT OBJECT_NAME, STATUS FROM ALL_OBJECTS \
+             WHERE OWNER = '{}' AND OBJECT_TYPE = 'PROCEDURE' \
+             ORDER BY OBJECT_NAME",
+            Self::escape_literal(database)
+        );
+
+        let rows = self.query_rows(connection, &sql, "procedures").await?;
+
+        Ok(rows.iter().map(|row| {
+            FunctionInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                return_type: None,
+                parameters: Vec::new(),
+                definition: None,
+                comment: row.get(1).and_then(|v| v.as_text()),
+            }
+        }).collect())
+    }
+
+    async fn list_procedures_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let procedures = self.list_procedures(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(250.0)),
+            Column::new("status", "Status").width(px(100.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = procedures.iter().map(|proc| {
+            vec![
+                proc.name.clone(),
+                proc.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} procedure(s)", procedures.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Trigger Operations ===
+
+    async fn list_triggers(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TriggerInfo>> {
+        let sql = format!(
+            "SELECT TRIGGER_NAME, TABLE_NAME, TRIGGERING_EVENT, TRIGGER_TYPE, DESCRIPTION \
+             FROM ALL_TRIGGERS \
+             WHERE OWNER = '{}' \
+             ORDER BY TRIGGER_NAME",
+            Self::escape_literal(database)
+        );
+
+        let rows = self.query_rows(connection, &sql, "triggers").await?;
+
+        Ok(rows.iter().map(|row| {
+            // TRIGGER_TYPE reads like "BEFORE EACH ROW" or "AFTER STATEMENT"
+            let timing = row.get(3).and_then(|v| v.as_text()).unwrap_or_default()
+                .replace(" EACH ROW", "")
+                .replace(" STATEMENT", "")
+                .replace(" EVENT", "");
+
+            TriggerInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                table_name: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                event: row.get(2).and_then(|v| v.as_text()).unwrap_or_default(),
+                timing,
+                definition: row.get(4).and_then(|v| v.as_text()),
+            }
+        }).collect())
+    }
+
+    async fn list_triggers_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let triggers = self.list_triggers(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("table", "Table").width(px(150.0)),
+            Column::new("event", "Event").width(px(160.0)),
+            Column::new("timing", "Timing").width(px(100.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = triggers.iter().map(|trigger| {
+            vec![
+                trigger.name.clone(),
+                trigger.table_name.clone(),
+                trigger.event.clone(),
+                trigger.timing.clone(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} trigger(s)", triggers.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Sequence Operations ===
+
+    async fn list_sequences(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<SequenceInfo>> {
+        let sql = format!(
+            "SELECT SEQUENCE_NAME, LAST_NUMBER, INCREMENT_BY, MIN_VALUE, MAX_VALUE \
+             FROM ALL_SEQUENCES \
+             WHERE SEQUENCE_OWNER = '{}' \
+             ORDER BY SEQUENCE_NAME",
+            Self::escape_literal(database)
+        );
+
+        let rows = self.query_rows(connection, &sql, "sequences").await?;
+        // MAX_VALUE defaults to 28 digits and does not fit in i64
+        let parse = |row: &Vec<SqlValue>, i: usize| {
+            row.get(i).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok())
+        };
+
+        Ok(rows.iter().map(|row| {
+            SequenceInfo {
+                name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                start_value: parse(row, 1),
+                increment: parse(row, 2),
+                min_value: parse(row, 3),
+                max_value: parse(row, 4),
+            }
+        }).collect())
+    }
+
+    async fn list_sequences_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let sequences = self.list_sequences(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("last", "Last Number").width(px(120.0)).text_right(),
+            Column::new("increment", "Increment").width(px(100.0)).text_right(),
+            Column::new("min", "Min").width(px(160.0)).text_right(),
+            Column::new("max", "Max").width(px(160.0)).text_right(),
+        ];
+
+        let to_cell = |v: Option<i64>| v.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+        let rows: Vec<Vec<String>> = sequences.iter().map(|seq| {
+            vec![
+                seq.name.clone(),
+                to_cell(seq.start_value),
+                to_cell(seq.increment),
+                to_cell(seq.min_value),
+                to_cell(seq.max_value),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} sequence(s)", sequences.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Query Execution ===
+
+    async fn execute_query(
+        &self,
+        connection: &dyn DbConnection,
+        _database: &str,
+        query: &str,
+        params: Option<Vec<SqlValue>>,
+    ) -> Result<SqlResult> {
+        connection.query(query, params, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Query execution failed: {}", e))
+    }
+
+    async fn execute_script(
+        &self,
+        connection: &dyn DbConnection,
+        _database: &str,
+        script: &str,
+        options: ExecOptions,
+    ) -> Result<Vec<SqlResult>> {
+        connection.execute(script, options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Script execution failed: {}", e))
+    }
+
+    // === Database Switching ===
+
+    async fn switch_db(&self, connection: &dyn DbConnection, database: &str) -> Result<SqlResult> {
+        let sql = format!("ALTER SESSION SET CURRENT_SCHEMA = {}", self.quote_identifier(database));
+        let results = connection
+            .execute(&sql, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to switch schema: {}", e))?;
+
+        if let Some(result) = results.into_iter().next() {
+            Ok(result)
+        } else {
+            Ok(SqlResult::Exec(ExecResult {
+                sql,
+                rows_affected: 0,
+                elapsed_ms: 0,
+                message: Some("Schema changed".to_string()),
+            }))
+        }
+    }
+
+    fn get_data_types(&self) -> Vec<DataTypeInfo> {
+        vec![
+            // 数值类型
+            DataTypeInfo::new("NUMBER", "Variable precision number").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("NUMBER(10)", "Integer number").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("NUMBER(10,2)", "Fixed-point number").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("BINARY_FLOAT", "Single-precision floating-point").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("BINARY_DOUBLE", "Double-precision floating-point").with_category(DataTypeCategory::Numeric),
+
+            // 字符串类型
+            DataTypeInfo::new("CHAR(255)", "Fixed-length string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("VARCHAR2(255)", "Variable-length string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("NVARCHAR2(255)", "Variable-length Unicode string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("CLOB", "Character large object").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("NCLOB", "Unicode character large object").with_category(DataTypeCategory::String),
+
+            // 日期时间类型
+            DataTypeInfo::new("DATE", "Date and time (second precision)").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("TIMESTAMP", "Date and time with fractional seconds").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("TIMESTAMP WITH TIME ZONE", "Timestamp with time zone").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("INTERVAL DAY TO SECOND", "Time interval").with_category(DataTypeCategory::DateTime),
+
+            // 二进制类型
+            DataTypeInfo::new("RAW(255)", "Variable-length binary").with_category(DataTypeCategory::Binary),
+            DataTypeInfo::new("BLOB", "Binary large object").with_category(DataTypeCategory::Binary),
+
+            // 其他类型
+            DataTypeInfo::new("NUMBER(1)", "Boolean stored as 0/1").with_category(DataTypeCategory::Boolean),
+            DataTypeInfo::new("XMLTYPE", "XML document").with_category(DataTypeCategory::Structured),
+        ]
+    }
+
+    // === DDL Operations ===
+
+    async fn drop_database(&self, _connection: &dyn DbConnection, _database: &str) -> Result<()> {
+        Err(anyhow::anyhow!("Oracle schemas are users, drop them with DROP USER ... CASCADE"))
+    }
+
+    async fn drop_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        let query = format!("DROP TABLE {}", self.qualified_table_name(database, table));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
+    async fn truncate_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        let query = format!("TRUNCATE TABLE {}", self.qualified_table_name(database, table));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
+    async fn drop_view(&self, connection: &dyn DbConnection, database: &str, view: &str) -> Result<()> {
+        let query = format!("DROP VIEW {}", self.qualified_table_name(database, view));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+}
+
+impl Default for OraclePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::TOKIO_HANDLE;
+    use crate::test_support::{test_config, FakeConnection};
+
+    #[test]
+    fn test_pagination_clause() {
+        let plugin = OraclePlugin::new();
+        assert_eq!(plugin.pagination_clause(false, 100, 0), " FETCH FIRST 100 ROWS ONLY");
+        assert_eq!(plugin.pagination_clause(true, 50, 200), " OFFSET 200 ROWS FETCH NEXT 50 ROWS ONLY");
+    }
+
+    #[test]
+    fn test_qualified_table_name() {
+        let plugin = OraclePlugin::new();
+        assert_eq!(plugin.quote_identifier("ORDERS"), "\"ORDERS\"");
+        // Quoting keeps the case, a lower-case name is a different object than the upper-case default
+        assert_eq!(plugin.qualified_table_name("HR", "employees"), "\"HR\".\"employees\"");
+    }
+
+    #[test]
+    fn test_generated_statements() {
+        let plugin = OraclePlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::Oracle, &[]));
+        let log = conn.log();
+        TOKIO_HANDLE.block_on(async {
+            plugin.truncate_table(&conn, "HR", "EMPLOYEES").await.unwrap();
+            plugin.switch_db(&conn, "SALES").await.unwrap();
+            assert!(plugin.drop_database(&conn, "HR").await.is_err());
+        });
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "TRUNCATE TABLE \"HR\".\"EMPLOYEES\"",
+                "ALTER SESSION SET CURRENT_SCHEMA = \"SALES\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_literal() {
+        assert_eq!(OraclePlugin::escape_literal("O'BRIEN"), "O''BRIEN");
+    }
+}
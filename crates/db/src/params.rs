@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use one_core::storage::DatabaseType;
+
+use crate::connection::DbError;
+use crate::executor::{ScriptStatement, SplitOptions, SqlScriptSplitter};
+use crate::types::SqlValue;
+
+/// Placeholder syntaxes recognised in editor scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    /// `?`, numbered by position across the script
+    Positional,
+    /// `$1`, `$2`, ...
+    Numbered,
+    /// `:name`
+    Named,
+    /// `${name}`
+    Variable,
+}
+
+/// A placeholder found in a SQL text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub kind: PlaceholderKind,
+    /// Key its value is looked up by: `?1`, `$1`, `:name` or `${name}`
+    pub key: String,
+    /// Byte range of the placeholder in the text it was found in
+    pub range: Range<usize>,
+}
+
+/// Find the placeholders of a SQL text, skipping string literals, quoted identifiers,
+/// comments, `::` casts and dollar-quoted bodies.
+/// `dialect` decides what `#` and `?` mean: a MySQL comment, and PostgreSQL operators.
+pub fn find_placeholders(sql: &str, dialect: DatabaseType) -> Vec<Placeholder> {
+    scan_placeholders(sql, 0, dialect)
+}
+
+/// Distinct placeholders of a script in order of first appearance, the values a caller has to ask for
+pub fn script_parameters(script: &str, dialect: DatabaseType) -> Vec<Placeholder> {
+    let mut seen = std::collections::HashSet::new();
+    find_placeholders(script, dialect)
+        .into_iter()
+        .filter(|p| seen.insert(p.key.clone()))
+        .collect()
+}
+
+fn scan_placeholders(sql: &str, positional_before: usize, dialect: DatabaseType) -> Vec<Placeholder> {
+    let bytes = sql.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut placeholders = Vec::new();
+    let mut positional = positional_before;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match b {
+            b'\'' | b'"' | b'`' => {
+                i = sql[i + 1..].find(b as char).map_or(bytes.len(), |end| i + 1 + end + 1);
+            }
+            b'-' if next == Some(b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end + 1);
+            }
+            b'#' if dialect == DatabaseType::MySQL => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end + 1);
+            }
+            b'/' if next == Some(b'*') => {
+                i = sql[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+            }
+            // PostgreSQL binds `$1`, its `?`, `?|` and `?&` are jsonb operators
+            b'?' if dialect != DatabaseType::PostgreSQL && !matches!(next, Some(b'|' | b'&')) => {
+                positional += 1;
+                placeholders.push(Placeholder {
+                    kind: PlaceholderKind::Positional,
+                    key: format!("?{}", positional),
+                    range: i..i + 1,
+                });
+                i += 1;
+            }
+            b'$' if next.is_some_and(|n| n.is_ascii_digit()) => {
+                let end = (i + 1..bytes.len()).find(|&j| !bytes[j].is_ascii_digit()).unwrap_or(bytes.len());
+                placeholders.push(Placeholder {
+                    kind: PlaceholderKind::Numbered,
+                    key: sql[i..end].to_string(),
+                    range: i..end,
+                });
+                i = end;
+            }
+            b'$' if next == Some(b'{') => match sql[i + 2..].find('}') {
+                Some(len) if len > 0 && sql[i + 2..i + 2 + len].bytes().all(|c| is_ident(c) || c == b'.') => {
+                    let end = i + 2 + len + 1;
+                    placeholders.push(Placeholder {
+                        kind: PlaceholderKind::Variable,
+                        key: sql[i..end].to_string(),
+                        range: i..end,
+                    });
+                    i = end;
+                }
+                _ => i += 1,
+            },
+            b'$' => {
+                // Dollar-quoted body: $$...$$ or $tag$...$tag$
+                let tag_end = (i + 1..bytes.len()).find(|&j| !is_ident(bytes[j]));
+                match tag_end {
+                    Some(j) if bytes[j] == b'$' && !(i > 0 && is_ident(bytes[i - 1])) => {
+                        let tag = &sql[i..=j];
+                        i = sql[j + 1..].find(tag).map_or(bytes.len(), |end| j + 1 + end + tag.len());
+                    }
+                    _ => i += 1,
+                }
+            }
+            b':' if next == Some(b':') => i += 2,
+            b':' if next.is_some_and(|n| n.is_ascii_alphabetic() || n == b'_')
+                && !(i > 0 && is_ident(bytes[i - 1])) =>
+            {
+                let end = (i + 1..bytes.len()).find(|&j| !is_ident(bytes[j])).unwrap_or(bytes.len());
+                placeholders.push(Placeholder {
+                    kind: PlaceholderKind::Named,
+                    key: sql[i..end].to_string(),
+                    range: i..end,
+                });
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    placeholders
+}
+
+/// How a driver spells its bind parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamStyle {
+    /// `?` (MySQL, SQLite)
+    Question,
+    /// `$1` (PostgreSQL)
+    Dollar,
+    /// `@P1` (SQL Server)
+    AtP,
+    /// `:1` (Oracle)
+    Colon,
+}
+
+impl ParamStyle {
+    pub fn for_database(database_type: DatabaseType) -> Self {
+        match database_type {
+            DatabaseType::MySQL | DatabaseType::SQLite => ParamStyle::Question,
+            DatabaseType::PostgreSQL => ParamStyle::Dollar,
+            DatabaseType::MSSQL => ParamStyle::AtP,
+            DatabaseType::Oracle => ParamStyle::Colon,
+        }
+    }
+
+    fn marker(self, position: usize) -> String {
+        match self {
+            ParamStyle::Question => "?".to_string(),
+            ParamStyle::Dollar => format!("${}", position),
+            ParamStyle::AtP => format!("@P{}", position),
+            ParamStyle::Colon => format!(":{}", position),
+        }
+    }
+}
+
+/// A statement rewritten to the driver's placeholder syntax, with its parameters in bind order
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundStatement {
+    pub sql: String,
+    pub params: Vec<SqlValue>,
+    /// Byte range of the statement in the script
+    pub range: Range<usize>,
+}
+
+/// Split a script into statements and replace every placeholder with the bind parameters of `dialect`.
+/// `values` is keyed by `Placeholder::key`; a placeholder without a value is an error.
+pub fn bind_script(
+    script: &str,
+    values: &HashMap<String, SqlValue>,
+    dialect: DatabaseType,
+) -> Result<Vec<BoundStatement>, DbError> {
+    let style = ParamStyle::for_database(dialect);
+    let mut positional = 0;
+    let mut statements = Vec::new();
+
+    for ScriptStatement { sql: statement, range } in SqlScriptSplitter::split_with(script, SplitOptions::new(dialect)) {
+        let placeholders = scan_placeholders(&statement, positional, dialect);
+        positional += placeholders.iter().filter(|p| p.kind == PlaceholderKind::Positional).count();
+
+        let mut sql = String::with_capacity(statement.len());
+        let mut params = Vec::with_capacity(placeholders.len());
+        let mut last = 0;
+        for placeholder in &placeholders {
+            let value = values
+                .get(&placeholder.key)
+                .ok_or_else(|| DbError::new(format!("No value given for parameter {}", placeholder.key)))?;
+            params.push(value.clone());
+            sql.push_str(&statement[last..placeholder.range.start]);
+            sql.push_str(&style.marker(params.len()));
+            last = placeholder.range.end;
+        }
+        sql.push_str(&statement[last..]);
+
+        statements.push(BoundStatement { sql, params, range });
+    }
+
+    Ok(statements)
+}
+
+/// Type a parameter value typed as text is bound as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ParamType {
+    #[default]
+    Text,
+    Integer,
+    Decimal,
+    Float,
+    Boolean,
+    Date,
+    DateTime,
+    Null,
+}
+
+impl ParamType {
+    pub fn all() -> &'static [ParamType] {
+        &[
+            ParamType::Text,
+            ParamType::Integer,
+            ParamType::Decimal,
+            ParamType::Float,
+            ParamType::Boolean,
+            ParamType::Date,
+            ParamType::DateTime,
+            ParamType::Null,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ParamType::Text => "Text",
+            ParamType::Integer => "Integer",
+            ParamType::Decimal => "Decimal",
+            ParamType::Float => "Float",
+            ParamType::Boolean => "Boolean",
+            ParamType::Date => "Date",
+            ParamType::DateTime => "DateTime",
+            ParamType::Null => "NULL",
+        }
+    }
+
+    /// Convert the text entered for a parameter into the value to bind
+    pub fn parse(&self, text: &str) -> Result<SqlValue, DbError> {
+        let trimmed = text.trim();
+        let invalid = || DbError::new(format!("'{}' is not a valid {} value", text, self.label()));
+        match self {
+            ParamType::Text => Ok(SqlValue::String(text.to_string())),
+            ParamType::Integer => trimmed.parse().map(SqlValue::Int).map_err(|_| invalid()),
+            ParamType::Decimal => trimmed
+                .parse::<sqlx::types::BigDecimal>()
+                .map(|_| SqlValue::Decimal(trimmed.to_string()))
+                .map_err(|_| invalid()),
+            ParamType::Float => trimmed.parse().map(SqlValue::Float).map_err(|_| invalid()),
+            ParamType::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "t" | "1" | "yes" => Ok(SqlValue::Bool(true)),
+                "false" | "f" | "0" | "no" => Ok(SqlValue::Bool(false)),
+                _ => Err(invalid()),
+            },
+            ParamType::Date => NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                .map(SqlValue::Date)
+                .map_err(|_| invalid()),
+            ParamType::DateTime => NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f")
+                .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S%.f"))
+                .map(SqlValue::DateTime)
+                .map_err(|_| invalid()),
+            ParamType::Null => Ok(SqlValue::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(sql: &str) -> Vec<String> {
+        keys_for(sql, DatabaseType::MySQL)
+    }
+
+    fn keys_for(sql: &str, dialect: DatabaseType) -> Vec<String> {
+        find_placeholders(sql, dialect).into_iter().map(|p| p.key).collect()
+    }
+
+    #[test]
+    fn test_find_each_placeholder_kind() {
+        assert_eq!(
+            keys("SELECT * FROM t WHERE a = ? AND b = $2 AND c = :name AND d = ${env.var} AND e = ?"),
+            vec!["?1", "$2", ":name", "${env.var}", "?2"]
+        );
+    }
+
+    #[test]
+    fn test_placeholders_ignored_in_literals_comments_and_casts() {
+        let sql = "SELECT '?', \"a:b\", `$1`, x::int, $$ :body $$ -- :comment\n FROM t /* ? */ WHERE y = :id";
+        assert_eq!(keys(sql), vec![":id"]);
+        assert_eq!(keys("SELECT 1 # :comment\nFROM t WHERE y = :id"), vec![":id"]);
+    }
+
+    #[test]
+    fn test_postgres_operators_are_not_placeholders() {
+        let postgres = |sql| keys_for(sql, DatabaseType::PostgreSQL);
+        assert_eq!(postgres("SELECT data #>> '{a}', flags # 4 FROM t WHERE id = :id"), vec![":id"]);
+        assert_eq!(postgres("SELECT * FROM t WHERE doc ? 'key' AND doc ?| array['a'] AND doc ?& array['b'] AND id = $1"), vec!["$1"]);
+        assert_eq!(keys_for("SELECT ?|, ?", DatabaseType::SQLite), vec!["?1"]);
+    }
+
+    #[test]
+    fn test_script_parameters_are_distinct() {
+        let params = script_parameters("SELECT :id, :id, $1; SELECT $1, ?", DatabaseType::MySQL);
+        let keys: Vec<_> = params.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(keys, vec![":id", "$1", "?1"]);
+    }
+
+    #[test]
+    fn test_bind_script_rewrites_to_driver_style() {
+        let values = HashMap::from([
+            (":id".to_string(), SqlValue::Int(7)),
+            (":name".to_string(), SqlValue::String("a".to_string())),
+            ("$1".to_string(), SqlValue::Null),
+        ]);
+        let bound = bind_script("SELECT * FROM t WHERE id = :id AND name = :name; DELETE FROM t WHERE id = $1 OR id = :id", &values, DatabaseType::PostgreSQL).unwrap();
+
+        assert_eq!(bound[0].sql, "SELECT * FROM t WHERE id = $1 AND name = $2");
+        assert_eq!(bound[0].params, vec![SqlValue::Int(7), SqlValue::String("a".to_string())]);
+        assert_eq!(bound[1].sql, "DELETE FROM t WHERE id = $1 OR id = $2");
+        assert_eq!(bound[1].params, vec![SqlValue::Null, SqlValue::Int(7)]);
+        assert_eq!(bound[1].range, 49..88);
+
+        let positional = HashMap::from([("?1".to_string(), SqlValue::Int(1)), ("?2".to_string(), SqlValue::Int(2))]);
+        let bound = bind_script("SELECT ?; SELECT ?", &positional, DatabaseType::MySQL).unwrap();
+        assert_eq!(bound[1].sql, "SELECT ?");
+        assert_eq!(bound[1].params, vec![SqlValue::Int(2)]);
+    }
+
+    #[test]
+    fn test_bind_script_requires_every_value() {
+        let err = bind_script("SELECT :missing", &HashMap::new(), DatabaseType::MySQL).unwrap_err();
+        assert!(err.to_string().contains(":missing"));
+    }
+
+    #[test]
+    fn test_param_type_parse() {
+        assert_eq!(ParamType::Integer.parse(" 42 ").unwrap(), SqlValue::Int(42));
+        assert_eq!(ParamType::Boolean.parse("yes").unwrap(), SqlValue::Bool(true));
+        assert_eq!(ParamType::Decimal.parse("1.50").unwrap(), SqlValue::Decimal("1.50".to_string()));
+        assert_eq!(ParamType::Text.parse(" x ").unwrap(), SqlValue::String(" x ".to_string()));
+        assert!(ParamType::DateTime.parse("2024-05-06T07:08:09").is_ok());
+        assert!(ParamType::Integer.parse("abc").is_err());
+    }
+}
@@ -0,0 +1,404 @@
+use one_core::storage::DatabaseType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::connection::DbError;
+use crate::executor::{QueryResult, SqlStatementClassifier};
+use crate::types::SqlValue;
+
+/// One operation of a query plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlanNode {
+    /// Operation, e.g. `Seq Scan` or `Nested loop`
+    pub operation: String,
+    /// Table the operation reads
+    pub relation: Option<String>,
+    /// Index used, join condition, filter and similar details
+    pub detail: Option<String>,
+    /// Estimated cost, in the database's own units and including the children
+    pub cost: Option<f64>,
+    /// Estimated rows
+    pub rows: Option<f64>,
+    /// Rows actually produced over all loops, only for analyzed plans
+    pub actual_rows: Option<f64>,
+    /// Milliseconds spent over all loops, only for analyzed plans
+    pub time_ms: Option<f64>,
+    /// Reads every row of a table
+    pub full_scan: bool,
+    pub children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    fn new(operation: impl Into<String>) -> Self {
+        Self {
+            operation: operation.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A statement's plan as returned by `parse_plan`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub roots: Vec<PlanNode>,
+    /// Planning time in milliseconds when the database reports it
+    pub planning_ms: Option<f64>,
+    /// Execution time in milliseconds, only for analyzed plans
+    pub execution_ms: Option<f64>,
+}
+
+/// The statement asking `dialect` for the plan of `sql`, `None` when the plan can't be read back.
+///
+/// `analyze` runs the statement to measure actual rows and timing. Only PostgreSQL queries
+/// are analyzed, so explaining never modifies data.
+pub fn explain_statement(dialect: DatabaseType, sql: &str, analyze: bool) -> Option<String> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if sql.is_empty() {
+        return None;
+    }
+    match dialect {
+        DatabaseType::MySQL => Some(format!("EXPLAIN FORMAT=JSON {}", sql)),
+        DatabaseType::PostgreSQL if analyze && SqlStatementClassifier::is_query_statement(sql) => {
+            Some(format!("EXPLAIN (FORMAT JSON, ANALYZE) {}", sql))
+        }
+        DatabaseType::PostgreSQL => Some(format!("EXPLAIN (FORMAT JSON) {}", sql)),
+        DatabaseType::SQLite => Some(format!("EXPLAIN QUERY PLAN {}", sql)),
+        _ => None,
+    }
+}
+
+/// Read the result of an `explain_statement` into plan nodes
+pub fn parse_plan(dialect: DatabaseType, result: &QueryResult) -> Result<QueryPlan, DbError> {
+    match dialect {
+        DatabaseType::MySQL => Ok(QueryPlan {
+            roots: mysql_children(&plan_json(result)?),
+            ..Default::default()
+        }),
+        DatabaseType::PostgreSQL => Ok(postgres_plan(&plan_json(result)?)),
+        DatabaseType::SQLite => Ok(sqlite_plan(result)),
+        _ => Err(DbError::new("Query plans are not supported for this database")),
+    }
+}
+
+/// The JSON document in the first cell of the result
+fn plan_json(result: &QueryResult) -> Result<Value, DbError> {
+    match result.rows.first().and_then(|row| row.first()) {
+        Some(SqlValue::Json(value)) => Ok(value.clone()),
+        Some(SqlValue::String(text)) => serde_json::from_str(text)
+            .map_err(|e| DbError::QueryError(format!("Failed to read the plan: {}", e))),
+        _ => Err(DbError::QueryError("The database returned no plan".to_string())),
+    }
+}
+
+/// Number written as a JSON number or, like MySQL costs, as a string
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => Some(items.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", ")),
+        _ => None,
+    }
+}
+
+/// `label: value` pairs for the fields present, joined into one detail line
+fn details(node: &Value, fields: &[(&str, &str)]) -> Option<String> {
+    let parts: Vec<String> = fields
+        .iter()
+        .filter_map(|(field, label)| text(node.get(*field)).map(|value| format!("{}: {}", label, value)))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// Operations nested in an object of MySQL's `FORMAT=JSON` plan
+fn mysql_children(object: &Value) -> Vec<PlanNode> {
+    let Some(fields) = object.as_object() else {
+        return Vec::new();
+    };
+    let wrap = |operation: &str, value: &Value| PlanNode {
+        children: mysql_children(value),
+        ..PlanNode::new(operation)
+    };
+
+    let mut children = Vec::new();
+    for (key, value) in fields {
+        match key.as_str() {
+            "query_block" => children.push(mysql_query_block(value)),
+            "table" => children.push(mysql_table(value)),
+            "nested_loop" => children.push(PlanNode {
+                children: value.as_array().into_iter().flatten().flat_map(mysql_children).collect(),
+                ..PlanNode::new("Nested loop")
+            }),
+            "ordering_operation" => children.push(PlanNode {
+                detail: (value.get("using_filesort") == Some(&Value::Bool(true))).then(|| "using filesort".to_string()),
+                ..wrap("Sort", value)
+            }),
+            "grouping_operation" => children.push(wrap("Group", value)),
+            "duplicates_removal" => children.push(wrap("Distinct", value)),
+            "windowing" => children.push(wrap("Window", value)),
+            "materialized_from_subquery" => children.push(wrap("Materialize", value)),
+            "union_result" => children.push(PlanNode {
+                relation: text(value.get("table_name")),
+                children: value
+                    .get("query_specifications")
+                    .and_then(|specs| specs.as_array())
+                    .into_iter()
+                    .flatten()
+                    .flat_map(mysql_children)
+                    .collect(),
+                ..PlanNode::new("Union")
+            }),
+            key if key.ends_with("_subqueries") => {
+                children.extend(value.as_array().into_iter().flatten().flat_map(mysql_children));
+            }
+            _ => {}
+        }
+    }
+    children
+}
+
+fn mysql_query_block(block: &Value) -> PlanNode {
+    let operation = match block.get("select_id").and_then(|id| id.as_u64()) {
+        Some(id) => format!("Query block #{}", id),
+        None => "Query block".to_string(),
+    };
+    PlanNode {
+        detail: text(block.get("message")),
+        cost: number(block.pointer("/cost_info/query_cost")),
+        children: mysql_children(block),
+        ..PlanNode::new(operation)
+    }
+}
+
+fn mysql_table(table: &Value) -> PlanNode {
+    let access_type = table.get("access_type").and_then(|t| t.as_str()).unwrap_or_default();
+    let operation = match access_type {
+        "ALL" => "Full table scan".to_string(),
+        "index" => "Full index scan".to_string(),
+        "range" => "Index range scan".to_string(),
+        "ref" | "eq_ref" | "ref_or_null" | "fulltext" => "Index lookup".to_string(),
+        "const" | "system" => "Constant lookup".to_string(),
+        "" => "Table".to_string(),
+        other => format!("Table access ({})", other),
+    };
+    PlanNode {
+        relation: text(table.get("table_name")),
+        detail: details(table, &[("key", "index"), ("ref", "ref"), ("attached_condition", "filter")]),
+        cost: number(table.pointer("/cost_info/prefix_cost")),
+        rows: number(table.get("rows_examined_per_scan")),
+        full_scan: access_type == "ALL",
+        children: mysql_children(table),
+        ..PlanNode::new(operation)
+    }
+}
+
+/// PostgreSQL's `FORMAT JSON` plan: an array holding one object with the root `Plan`
+fn postgres_plan(json: &Value) -> QueryPlan {
+    let entry = json.get(0).unwrap_or(json);
+    QueryPlan {
+        roots: entry.get("Plan").map(postgres_node).into_iter().collect(),
+        planning_ms: number(entry.get("Planning Time")),
+        execution_ms: number(entry.get("Execution Time")),
+    }
+}
+
+fn postgres_node(node: &Value) -> PlanNode {
+    let operation = text(node.get("Node Type")).unwrap_or_else(|| "Plan".to_string());
+    // PostgreSQL reports actual rows and time per loop
+    let loops = number(node.get("Actual Loops")).unwrap_or(1.0);
+    PlanNode {
+        relation: text(node.get("Relation Name"))
+            .or_else(|| text(node.get("CTE Name")))
+            .or_else(|| text(node.get("Function Name"))),
+        detail: details(
+            node,
+            &[
+                ("Join Type", "join"),
+                ("Index Name", "index"),
+                ("Index Cond", "index cond"),
+                ("Hash Cond", "hash cond"),
+                ("Merge Cond", "merge cond"),
+                ("Sort Key", "sort key"),
+                ("Group Key", "group key"),
+                ("Filter", "filter"),
+            ],
+        ),
+        cost: number(node.get("Total Cost")),
+        rows: number(node.get("Plan Rows")),
+        actual_rows: number(node.get("Actual Rows")).map(|rows| rows * loops),
+        time_ms: number(node.get("Actual Total Time")).map(|ms| ms * loops),
+        full_scan: operation == "Seq Scan",
+        children: node
+            .get("Plans")
+            .and_then(|plans| plans.as_array())
+            .into_iter()
+            .flatten()
+            .map(postgres_node)
+            .collect(),
+        operation,
+    }
+}
+
+/// SQLite's `EXPLAIN QUERY PLAN` rows: `id, parent, notused, detail`
+fn sqlite_plan(result: &QueryResult) -> QueryPlan {
+    let entries: Vec<(i64, i64, String)> = result
+        .rows
+        .iter()
+        .filter_map(|row| match row.as_slice() {
+            [SqlValue::Int(id), SqlValue::Int(parent), _, detail, ..] => Some((*id, *parent, detail.as_text()?)),
+            _ => None,
+        })
+        .collect();
+
+    fn children_of(parent: i64, entries: &[(i64, i64, String)]) -> Vec<PlanNode> {
+        entries
+            .iter()
+            .filter(|(id, entry_parent, _)| *entry_parent == parent && *id != parent)
+            .map(|(id, _, detail)| {
+                // `SCAN t` reads the whole table, `SCAN t USING INDEX i` only the index
+                let full_scan = detail.starts_with("SCAN ") && !detail.contains(" USING ") && detail != "SCAN CONSTANT ROW";
+                PlanNode {
+                    full_scan,
+                    children: children_of(*id, entries),
+                    ..PlanNode::new(detail.clone())
+                }
+            })
+            .collect()
+    }
+
+    QueryPlan {
+        roots: children_of(0, &entries),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ResultColumn;
+
+    fn result(columns: &[&str], rows: Vec<Vec<SqlValue>>) -> QueryResult {
+        QueryResult {
+            sql: String::new(),
+            columns: columns.iter().map(|name| ResultColumn::new(*name, "TEXT")).collect(),
+            rows,
+            elapsed_ms: 0,
+            has_more: false,
+            more_rows: None,
+        }
+    }
+
+    #[test]
+    fn test_explain_statement() {
+        assert_eq!(
+            explain_statement(DatabaseType::MySQL, "SELECT 1;", false).as_deref(),
+            Some("EXPLAIN FORMAT=JSON SELECT 1")
+        );
+        assert_eq!(
+            explain_statement(DatabaseType::PostgreSQL, "SELECT 1", true).as_deref(),
+            Some("EXPLAIN (FORMAT JSON, ANALYZE) SELECT 1")
+        );
+        // Analyzing would run the DELETE
+        assert_eq!(
+            explain_statement(DatabaseType::PostgreSQL, "DELETE FROM t", true).as_deref(),
+            Some("EXPLAIN (FORMAT JSON) DELETE FROM t")
+        );
+        assert_eq!(explain_statement(DatabaseType::MSSQL, "SELECT 1", false), None);
+        assert_eq!(explain_statement(DatabaseType::SQLite, " ; ", false), None);
+    }
+
+    #[test]
+    fn test_parse_mysql_plan() {
+        let json = r#"{
+          "query_block": {
+            "select_id": 1,
+            "cost_info": {"query_cost": "12.50"},
+            "ordering_operation": {
+              "using_filesort": true,
+              "nested_loop": [
+                {"table": {"table_name": "u", "access_type": "ALL", "rows_examined_per_scan": 100,
+                           "cost_info": {"prefix_cost": "10.25"}, "attached_condition": "(`u`.`age` > 3)"}},
+                {"table": {"table_name": "o", "access_type": "ref", "key": "idx_user", "rows_examined_per_scan": 2}}
+              ]
+            }
+          }
+        }"#;
+        let plan = parse_plan(DatabaseType::MySQL, &result(&["EXPLAIN"], vec![vec![SqlValue::String(json.to_string())]])).unwrap();
+
+        let block = &plan.roots[0];
+        assert_eq!(block.operation, "Query block #1");
+        assert_eq!(block.cost, Some(12.5));
+        let sort = &block.children[0];
+        assert_eq!(sort.detail.as_deref(), Some("using filesort"));
+        let tables = &sort.children[0].children;
+        assert_eq!(tables[0].relation.as_deref(), Some("u"));
+        assert!(tables[0].full_scan);
+        assert_eq!(tables[0].rows, Some(100.0));
+        assert_eq!(tables[0].cost, Some(10.25));
+        assert_eq!(tables[0].detail.as_deref(), Some("filter: (`u`.`age` > 3)"));
+        assert_eq!(tables[1].operation, "Index lookup");
+        assert!(!tables[1].full_scan);
+    }
+
+    #[test]
+    fn test_parse_postgres_plan() {
+        let json: Value = serde_json::from_str(r#"[{
+          "Plan": {
+            "Node Type": "Hash Join", "Join Type": "Inner", "Total Cost": 35.5, "Plan Rows": 10,
+            "Actual Total Time": 0.5, "Actual Rows": 8, "Actual Loops": 1,
+            "Plans": [
+              {"Node Type": "Seq Scan", "Relation Name": "users", "Total Cost": 20.0, "Plan Rows": 100,
+               "Actual Total Time": 0.25, "Actual Rows": 100, "Actual Loops": 2, "Filter": "(age > 3)"},
+              {"Node Type": "Index Scan", "Relation Name": "orders", "Index Name": "orders_pkey"}
+            ]
+          },
+          "Planning Time": 0.1,
+          "Execution Time": 0.75
+        }]"#).unwrap();
+        let plan = parse_plan(DatabaseType::PostgreSQL, &result(&["QUERY PLAN"], vec![vec![SqlValue::Json(json)]])).unwrap();
+
+        assert_eq!(plan.planning_ms, Some(0.1));
+        assert_eq!(plan.execution_ms, Some(0.75));
+        let join = &plan.roots[0];
+        assert_eq!(join.detail.as_deref(), Some("join: Inner"));
+        assert_eq!(join.actual_rows, Some(8.0));
+        let scan = &join.children[0];
+        assert!(scan.full_scan);
+        assert_eq!(scan.time_ms, Some(0.5));
+        assert_eq!(scan.actual_rows, Some(200.0));
+        assert_eq!(scan.detail.as_deref(), Some("filter: (age > 3)"));
+        assert!(!join.children[1].full_scan);
+        assert_eq!(join.children[1].detail.as_deref(), Some("index: orders_pkey"));
+    }
+
+    #[test]
+    fn test_parse_sqlite_plan() {
+        let row = |id: i64, parent: i64, detail: &str| {
+            vec![SqlValue::Int(id), SqlValue::Int(parent), SqlValue::Int(0), SqlValue::String(detail.to_string())]
+        };
+        let rows = vec![
+            row(2, 0, "SCAN u"),
+            row(5, 0, "SEARCH o USING INDEX idx_user (user_id=?)"),
+            row(9, 0, "USE TEMP B-TREE FOR ORDER BY"),
+            row(11, 9, "SCAN t USING COVERING INDEX idx_t"),
+        ];
+        let plan = parse_plan(DatabaseType::SQLite, &result(&["id", "parent", "notused", "detail"], rows)).unwrap();
+
+        assert_eq!(plan.roots.len(), 3);
+        assert!(plan.roots[0].full_scan);
+        assert!(!plan.roots[1].full_scan);
+        assert_eq!(plan.roots[2].children[0].operation, "SCAN t USING COVERING INDEX idx_t");
+        assert!(!plan.roots[2].children[0].full_scan);
+    }
+
+    #[test]
+    fn test_parse_plan_without_rows() {
+        assert!(parse_plan(DatabaseType::MySQL, &result(&["EXPLAIN"], Vec::new())).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use crate::executor::{ExecOptions, SqlResult};
 use crate::types::*;
 use anyhow::Result;
 use async_trait::async_trait;
+use gpui_component::table::Column;
 use one_core::storage::{DatabaseType, DbConnectionConfig};
 
 /// Database plugin trait for supporting multiple database types
@@ -14,25 +15,67 @@ pub trait DatabasePlugin: Send + Sync {
     fn identifier_quote(&self) -> &str {
         match self.name() {
             DatabaseType::MySQL => "`",
-            DatabaseType::PostgreSQL => "\"",
-            DatabaseType::MSSQL => "",
-            DatabaseType::Oracle => "",
+            DatabaseType::PostgreSQL | DatabaseType::SQLite | DatabaseType::Oracle => "\"",
+            DatabaseType::MSSQL => "[",
         }
     }
 
     fn quote_identifier(&self, identifier: &str) -> String {
-        let quote = self.identifier_quote();
-        format!("{}{}{}", quote, identifier, quote)
+        match self.identifier_quote() {
+            // SQL Server brackets open and close with different characters
+            "[" => format!("[{}]", identifier.replace(']', "]]")),
+            quote => format!("{}{}{}", quote, identifier, quote),
+        }
+    }
+
+    /// Fully qualified table reference used by generated data queries
+    fn qualified_table_name(&self, database: &str, table: &str) -> String {
+        format!("{}.{}", self.quote_identifier(database), self.quote_identifier(table))
+    }
+
+    /// Pagination clause appended after the ORDER BY clause of data queries
+    fn pagination_clause(&self, _has_order: bool, limit: usize, offset: usize) -> String {
+        format!(" LIMIT {} OFFSET {}", limit, offset)
     }
 
     async fn create_connection(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError>;
 
+    /// Create a connection pinned to one physical session, for callers that keep
+    /// session state such as an open transaction between calls.
+    /// Backends that already hold a single client can reuse `create_connection`.
+    async fn create_session(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        self.create_connection(config).await
+    }
+
     // === Database/Schema Level Operations ===
     async fn list_databases(&self, connection: &dyn DbConnection) -> Result<Vec<String>>;
     
     async fn list_databases_view(&self, connection: &dyn DbConnection) -> Result<ObjectView>;
     async fn list_databases_detailed(&self, connection: &dyn DbConnection) -> Result<Vec<DatabaseInfo>>;
 
+    /// Whether databases are split into schemas that show up as their own tree level.
+    /// When true, the `database` argument of the object listing and DDL methods is a schema name.
+    fn supports_schema(&self) -> bool {
+        false
+    }
+
+    /// List the user visible schemas of the database the connection is bound to
+    async fn list_schemas(&self, _connection: &dyn DbConnection) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_schemas_view(&self, connection: &dyn DbConnection) -> Result<ObjectView> {
+        use gpui::px;
+
+        let schemas = self.list_schemas(connection).await?;
+
+        Ok(ObjectView {
+            title: format!("{} schema(s)", schemas.len()),
+            columns: vec![Column::new("name", "Name").width(px(200.0))],
+            rows: schemas.into_iter().map(|schema| vec![schema]).collect(),
+        })
+    }
+
     // === Table Operations ===
     async fn list_tables(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TableInfo>>;
     
@@ -42,6 +85,11 @@ pub trait DatabasePlugin: Send + Sync {
     async fn list_indexes(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<IndexInfo>>;
     
     async fn list_indexes_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView>;
+
+    /// List the foreign keys declared on a table, for plugins that can report them
+    async fn list_foreign_keys(&self, _connection: &dyn DbConnection, _database: &str, _table: &str) -> Result<Vec<ForeignKeyInfo>> {
+        Ok(Vec::new())
+    }
     
     
 
@@ -105,10 +153,13 @@ pub trait DatabasePlugin: Send + Sync {
     // === Tree Building ===
     async fn build_database_tree(&self, connection: &dyn DbConnection, node: &DbNode) -> Result<Vec<DbNode>> {
         let mut nodes = Vec::new();
-        let database = &node.name;
         let id = &node.id;
-        let mut metadata: HashMap<String, String> = HashMap::new();
-        metadata.insert("database".to_string(), database.to_string());
+        // Schema nodes already carry their database and schema, a database node starts the chain
+        let metadata: HashMap<String, String> = match node.node_type {
+            DbNodeType::Schema => node.metadata.clone().unwrap_or_default(),
+            _ => HashMap::from([("database".to_string(), node.name.clone())]),
+        };
+        let database = metadata.get("schema").or_else(|| metadata.get("database")).unwrap();
         // Tables folder
         let tables = self.list_tables(connection, database).await?;
         let table_count = tables.len();
@@ -204,9 +255,44 @@ pub trait DatabasePlugin: Send + Sync {
                     })
                     .collect())
             }
-            DbNodeType::Database => {
+            DbNodeType::Database if self.supports_schema() => {
+                let schemas = self.list_schemas(connection).await?;
+                let folder_id = format!("{}:schemas_folder", id);
+                let mut schemas_folder = DbNode::new(
+                    folder_id.clone(),
+                    format!("Schemas ({})", schemas.len()),
+                    DbNodeType::SchemasFolder,
+                    node.connection_id.clone()
+                ).with_parent_context(id);
+
+                if !schemas.is_empty() {
+                    schemas_folder.children = schemas
+                        .into_iter()
+                        .map(|schema| {
+                            let metadata = HashMap::from([
+                                ("database".to_string(), node.name.clone()),
+                                ("schema".to_string(), schema.clone()),
+                            ]);
+                            DbNode::new(
+                                format!("{}:{}", folder_id, schema),
+                                schema,
+                                DbNodeType::Schema,
+                                node.connection_id.clone()
+                            )
+                            .with_children_flag(true)
+                            .with_parent_context(folder_id.clone())
+                            .with_metadata(metadata)
+                        })
+                        .collect();
+                    schemas_folder.has_children = true;
+                    schemas_folder.children_loaded = true;
+                }
+                Ok(vec![schemas_folder])
+            }
+            DbNodeType::Database | DbNodeType::Schema => {
                 self.build_database_tree(connection, node).await
             }
+            DbNodeType::SchemasFolder |
             DbNodeType::TablesFolder | DbNodeType::ViewsFolder |
             DbNodeType::FunctionsFolder | DbNodeType::ProceduresFolder |
             DbNodeType::TriggersFolder | DbNodeType::SequencesFolder => {
@@ -217,8 +303,7 @@ pub trait DatabasePlugin: Send + Sync {
                 }
             }
             DbNodeType::Table => {
-                let metadata = node.metadata.as_ref().unwrap();
-                let db = metadata.get("database").unwrap();
+                let db = node.object_scope().unwrap();
                 let table = &node.name;
                 let mut children = Vec::new();
 
@@ -343,10 +428,8 @@ pub trait DatabasePlugin: Send + Sync {
         connection: &dyn DbConnection,
         request: &TableDataRequest,
     ) -> Result<TableDataResponse> {
-        let quote = self.identifier_quote();
-
         // Get column metadata
-        let columns_info = self.list_columns(connection, &request.database, &request.table).await?;
+        let columns_info = self.list_columns(connection, request.scope(), &request.table).await?;
         let columns: Vec<TableColumnMeta> = columns_info
             .iter()
             .enumerate()
@@ -380,7 +463,7 @@ pub trait DatabasePlugin: Send + Sync {
                 .filters
                 .iter()
                 .map(|f| {
-                    let col = format!("{}{}{}", quote, f.column, quote);
+                    let col = self.quote_identifier(&f.column);
                     match f.operator {
                         FilterOperator::IsNull => format!("{} IS NULL", col),
                         FilterOperator::IsNotNull => format!("{} IS NOT NULL", col),
@@ -415,7 +498,7 @@ pub trait DatabasePlugin: Send + Sync {
                         SortDirection::Asc => "ASC",
                         SortDirection::Desc => "DESC",
                     };
-                    format!("{}{}{} {}", quote, s.column, quote, dir)
+                    format!("{} {}", self.quote_identifier(&s.column), dir)
                 })
                 .collect();
             format!(" ORDER BY {}", sorts.join(", "))
@@ -424,20 +507,17 @@ pub trait DatabasePlugin: Send + Sync {
         // Calculate offset
         let offset = (request.page.saturating_sub(1)) * request.page_size;
 
+        let table_name = self.qualified_table_name(request.scope(), &request.table);
+
         // Build count query
-        let count_sql = format!(
-            "SELECT COUNT(*) FROM {}{}{}.{}{}{}{}",
-            quote, request.database, quote,
-            quote, request.table, quote,
-            where_clause
-        );
+        let count_sql = format!("SELECT COUNT(*) FROM {}{}", table_name, where_clause);
 
         // Get total count
         let total_count = match self.execute_query(connection, &request.database, &count_sql, None).await? {
             SqlResult::Query(result) => {
                 result.rows.first()
                     .and_then(|r| r.first())
-                    .and_then(|v| v.as_ref())
+                    .and_then(|v| v.as_text())
                     .and_then(|s| s.parse::<usize>().ok())
                     .unwrap_or(0)
             }
@@ -446,13 +526,11 @@ pub trait DatabasePlugin: Send + Sync {
 
         // Build data query with pagination
         let data_sql = format!(
-            "SELECT * FROM {}{}{}.{}{}{}{}{} LIMIT {} OFFSET {}",
-            quote, request.database, quote,
-            quote, request.table, quote,
+            "SELECT * FROM {}{}{}{}",
+            table_name,
             where_clause,
             order_clause,
-            request.page_size,
-            offset
+            self.pagination_clause(!order_clause.is_empty(), request.page_size, offset)
         );
 
         // Execute data query
@@ -516,12 +594,18 @@ pub trait DatabasePlugin: Send + Sync {
                 self.quote_identifier(old_name),
                 self.quote_identifier(new_name)
             ),
-            DatabaseType::PostgreSQL => format!(
+            // sp_rename takes the schema-qualified old name and a bare new name
+            DatabaseType::MSSQL => format!(
+                "EXEC sp_rename N'{}', N'{}'",
+                self.qualified_table_name(database, old_name).replace('\'', "''"),
+                new_name.replace('\'', "''")
+            ),
+            // The new name of a schema-qualified table stays in the same schema (or attached database)
+            DatabaseType::SQLite | DatabaseType::PostgreSQL | DatabaseType::Oracle => format!(
                 "ALTER TABLE {} RENAME TO {}",
-                self.quote_identifier(old_name),
+                self.qualified_table_name(database, old_name),
                 self.quote_identifier(new_name)
             ),
-            DatabaseType::MSSQL | DatabaseType::Oracle => todo!(),
         };
         self.execute_query(connection, database, &query, None).await?;
         Ok(())
@@ -533,4 +617,21 @@ pub trait DatabasePlugin: Send + Sync {
         self.execute_query(connection, database, &query, None).await?;
         Ok(())
     }
+
+    // === Server Sessions ===
+    /// Sessions connected to the server, for plugins whose database has a process list
+    async fn list_server_sessions(&self, _connection: &dyn DbConnection) -> Result<Vec<ServerSession>> {
+        Ok(Vec::new())
+    }
+
+    /// Terminate a session listed by `list_server_sessions`
+    async fn kill_server_session(&self, _connection: &dyn DbConnection, _id: u64) -> Result<()> {
+        Err(anyhow::anyhow!("Killing sessions is not supported for {:?}", self.name()))
+    }
+
+    // === Connection Security ===
+    /// TLS protocol and cipher the connection negotiated, `None` when it is not encrypted
+    async fn tls_status(&self, _connection: &dyn DbConnection) -> Result<Option<String>> {
+        Err(anyhow::anyhow!("TLS status is not available for {:?}", self.name()))
+    }
 }
@@ -1,19 +1,34 @@
+use crate::cancel::{RunningQueries, RunningQueryGuard};
 use crate::connection::{DbConnection, DbError};
-use crate::executor::{ExecOptions, ExecResult, QueryResult, SqlErrorInfo, SqlResult, SqlScriptSplitter, SqlStatementClassifier};
+use crate::executor::{ExecOptions, ExecResult, QueryResult, ResultColumn, SqlErrorInfo, SqlResult, SqlScriptSplitter, SqlStatementClassifier};
 use crate::runtime::TOKIO_HANDLE;
-use sqlx::{Column, PgPool, Row, ValueRef};
+use crate::ssh_tunnel::SshTunnel;
+use crate::ssl::{SslMode, SslOptions};
+use crate::stream::{self, QueryStream};
+use sqlx::pool::PoolConnection;
+use sqlx::{Column, Connection, Executor, PgPool, Postgres, Row, TypeInfo, ValueRef};
 use std::time::Instant;
 use async_trait::async_trait;
-use sqlx::postgres::{PgArguments, PgPoolOptions, PgRow};
+use sqlx::postgres::{PgArguments, PgConnectOptions, PgConnection, PgDatabaseError, PgErrorPosition, PgPoolOptions, PgRow, PgSslMode};
 use std::sync::RwLock;
-use one_core::storage::DbConnectionConfig;
+use tokio::sync::oneshot;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
 use crate::{ SqlValue};
 
+/// Extra parameter holding a comma separated schema search path applied to every session
+const SEARCH_PATH_PARAM: &str = "search_path";
+
 pub struct PostgresDbConnection {
     config: Option<DbConnectionConfig>,
     pool: RwLock<Option<PgPool>>,    
     // Track database name for visibility; Postgres cannot switch without reconnecting
     current_database: RwLock<Option<String>>,
+    // Pinned sessions run on exactly one connection that is never recycled
+    pinned_session: bool,
+    // Backend pids of the statements in flight, targets of pg_cancel_backend
+    running: RunningQueries,
+    // Local end of the SSH tunnel the pool connects through, closed on drop
+    tunnel: Option<SshTunnel>,
 }
 
 impl PostgresDbConnection {
@@ -22,9 +37,71 @@ impl PostgresDbConnection {
             config: Some(config),
             pool: RwLock::new(None),
             current_database: RwLock::new(None),
+            pinned_session: false,
+            running: RunningQueries::default(),
+            tunnel: None,
+        }
+    }
+
+    /// Connection backed by a single connection that is never recycled, so session state survives
+    pub fn new_session(config: DbConnectionConfig) -> Self {
+        Self {
+            pinned_session: true,
+            ..Self::new(config)
+        }
+    }
+
+    /// Build `SET search_path TO ...` from the configured schema list, quoting bare names
+    fn search_path_statement(config: &DbConnectionConfig) -> Option<String> {
+        let schemas: Vec<String> = config
+            .extra_params
+            .get(SEARCH_PATH_PARAM)?
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                if s.starts_with('"') {
+                    s.to_string()
+                } else {
+                    format!("\"{}\"", s.replace('"', "\"\""))
+                }
+            })
+            .collect();
+
+        if schemas.is_empty() {
+            None
+        } else {
+            Some(format!("SET search_path TO {}", schemas.join(", ")))
         }
     }
 
+    /// Connect options for the database server, or the local end of its SSH tunnel
+    fn connect_options(config: &DbConnectionConfig, host: &str, port: u16) -> Result<PgConnectOptions, DbError> {
+        let ssl = SslOptions::from_config(config)?;
+        let mut options = PgConnectOptions::new()
+            .host(host)
+            .port(port)
+            .username(&config.username)
+            .password(&config.password)
+            .ssl_mode(match ssl.mode {
+                SslMode::Disable => PgSslMode::Disable,
+                SslMode::Prefer => PgSslMode::Prefer,
+                SslMode::Require => PgSslMode::Require,
+                SslMode::VerifyCa => PgSslMode::VerifyCa,
+                SslMode::VerifyFull => PgSslMode::VerifyFull,
+            });
+        if let Some(database) = &config.database {
+            options = options.database(database);
+        }
+        if let Some(ca_cert) = &ssl.ca_cert {
+            options = options.ssl_root_cert(ca_cert);
+        }
+        if let (Some(cert), Some(key)) = (&ssl.client_cert, &ssl.client_key) {
+            options = options.ssl_client_cert(cert).ssl_client_key(key);
+        }
+        Ok(options)
+    }
+
     fn ensure_connected(&self) -> Result<PgPool, DbError> {
         self
             .pool
@@ -50,20 +127,29 @@ impl PostgresDbConnection {
             SqlValue::String(v) => query.bind(v),
             SqlValue::Bytes(v) => query.bind(v),
             SqlValue::Json(v) => query.bind(v), // PostgreSQL has native JSON support
+            SqlValue::Decimal(v) => match v.parse::<sqlx::types::BigDecimal>() {
+                Ok(decimal) => query.bind(decimal),
+                Err(_) => query.bind(v),
+            },
+            SqlValue::Date(v) => query.bind(v),
+            SqlValue::Time(v) => query.bind(v),
+            SqlValue::DateTime(v) => query.bind(v),
+            SqlValue::DateTimeTz(v) => query.bind(v),
+            SqlValue::Uuid(v) => query.bind(v),
         }
     }
 
     /// Extract value from PostgreSQL row, handling all PostgreSQL types
-    fn extract_value(row: &PgRow, index: usize) -> Option<String> {
+    fn extract_value(row: &PgRow, index: usize) -> SqlValue {
         use sqlx::Row;
         use sqlx::TypeInfo;
         use sqlx::types::chrono::{NaiveDate, NaiveDateTime, NaiveTime, DateTime, Utc};
-        use sqlx::types::BigDecimal;
+        use sqlx::types::{BigDecimal, Uuid};
 
         // Check if NULL
         if let Ok(val) = row.try_get_raw(index) {
             if val.is_null() {
-                return None;
+                return SqlValue::Null;
             }
         }
 
@@ -76,22 +162,27 @@ impl PostgresDbConnection {
         match type_name.as_str() {
             "TIMESTAMPTZ" => {
                 if let Ok(val) = row.try_get::<DateTime<Utc>, _>(index) {
-                    return Some(val.format("%Y-%m-%d %H:%M:%S%z").to_string());
+                    return SqlValue::DateTimeTz(val.fixed_offset());
                 }
             }
             "TIMESTAMP" => {
                 if let Ok(val) = row.try_get::<NaiveDateTime, _>(index) {
-                    return Some(val.format("%Y-%m-%d %H:%M:%S").to_string());
+                    return SqlValue::DateTime(val);
                 }
             }
             "DATE" => {
                 if let Ok(val) = row.try_get::<NaiveDate, _>(index) {
-                    return Some(val.format("%Y-%m-%d").to_string());
+                    return SqlValue::Date(val);
                 }
             }
             "TIME" => {
                 if let Ok(val) = row.try_get::<NaiveTime, _>(index) {
-                    return Some(val.format("%H:%M:%S").to_string());
+                    return SqlValue::Time(val);
+                }
+            }
+            "UUID" => {
+                if let Ok(val) = row.try_get::<Uuid, _>(index) {
+                    return SqlValue::Uuid(val);
                 }
             }
             _ => {}
@@ -100,53 +191,222 @@ impl PostgresDbConnection {
         // Try different types
         // String types (VARCHAR, CHAR, TEXT, etc.)
         if let Ok(val) = row.try_get::<String, _>(index) {
-            return Some(val);
+            return SqlValue::String(val);
         }
 
         // Integer types (SMALLINT, INTEGER, BIGINT)
         if let Ok(val) = row.try_get::<i64, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val);
         }
         if let Ok(val) = row.try_get::<i32, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
         if let Ok(val) = row.try_get::<i16, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val as i64);
         }
 
         // Boolean
         if let Ok(val) = row.try_get::<bool, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Bool(val);
         }
 
         // Float types (REAL, DOUBLE PRECISION)
         if let Ok(val) = row.try_get::<f64, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Float(val);
         }
         if let Ok(val) = row.try_get::<f32, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Float(val as f64);
         }
 
         // DECIMAL/NUMERIC type
         if let Ok(val) = row.try_get::<BigDecimal, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Decimal(val.to_string());
         }
 
         // Binary data (BYTEA)
         if let Ok(val) = row.try_get::<Vec<u8>, _>(index) {
-            if let Ok(s) = String::from_utf8(val.clone()) {
-                return Some(s);
-            }
-            return Some(format!("\\x{}", hex::encode(&val)));
+            return SqlValue::Bytes(val);
         }
 
         // JSON/JSONB types
         if let Ok(val) = row.try_get::<serde_json::Value, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Json(val);
+        }
+
+        // Types without a decoder (NAME, OID, arrays, ranges, ...): raw_sql results use the
+        // text protocol, so the raw bytes are the server's text rendering
+        match row.try_get_unchecked::<Vec<u8>, _>(index) {
+            Ok(val) => String::from_utf8(val)
+                .map(SqlValue::String)
+                .unwrap_or_else(|e| SqlValue::Bytes(e.into_bytes())),
+            Err(_) => SqlValue::Null,
+        }
+    }
+
+    /// Statements that would need a different physical connection, which psql handles client side
+    fn unsupported_statement_message(sql: &str) -> Option<&'static str> {
+        if sql.starts_with("\\c ") || sql.starts_with("\\connect ") {
+            return Some("\\c command is not supported. PostgreSQL cannot switch databases on an existing connection. Please reconnect to switch databases.");
+        }
+        if sql.to_uppercase().starts_with("USE ") {
+            return Some("USE statement is not supported in PostgreSQL. PostgreSQL cannot switch databases on an existing connection. Please reconnect to switch databases.");
+        }
+        None
+    }
+
+    /// Build the error of a failed statement, keeping the position PostgreSQL reports
+    fn error_info(sql: &str, e: &sqlx::Error) -> SqlErrorInfo {
+        let position = e
+            .as_database_error()
+            .and_then(|e| e.try_downcast_ref::<PgDatabaseError>())
+            .and_then(|e| match e.position() {
+                Some(PgErrorPosition::Original(position)) => Some(position),
+                _ => None,
+            });
+        SqlErrorInfo::new(sql, e.to_string()).with_position(position)
+    }
+
+    /// Run one statement on a single connection, either a pooled one or an open transaction
+    async fn run_statement(conn: &mut PgConnection, sql: &str, max_rows: Option<usize>) -> SqlResult {
+        if let Some(message) = Self::unsupported_statement_message(sql) {
+            return SqlResult::Error(SqlErrorInfo::new(sql, message));
+        }
+
+        let start = Instant::now();
+        if SqlStatementClassifier::is_query_statement(sql) {
+            let mut rows = conn.fetch(sqlx::raw_sql(sql));
+            match stream::read_page(&mut rows, max_rows, Self::extract_value).await {
+                Ok(page) => SqlResult::Query(page.into_query_result(sql, start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(Self::error_info(sql, &e)),
+            }
+        } else {
+            match conn.execute(sqlx::raw_sql(sql)).await {
+                Ok(exec_result) => {
+                    let rows_affected = exec_result.rows_affected();
+                    SqlResult::Exec(ExecResult {
+                        sql: sql.to_string(),
+                        rows_affected,
+                        elapsed_ms: start.elapsed().as_millis(),
+                        message: Some(SqlStatementClassifier::format_message(sql, rows_affected)),
+                    })
+                }
+                Err(e) => SqlResult::Error(Self::error_info(sql, &e)),
+            }
+        }
+    }
+
+    /// Acquire a pooled connection and register its backend pid so the statement can be cancelled
+    async fn acquire_tracked(
+        pool: &PgPool,
+        running: &RunningQueries,
+    ) -> Result<(PoolConnection<Postgres>, RunningQueryGuard), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let pid: i32 = conn.fetch_one("SELECT pg_backend_pid()").await?.try_get(0)?;
+        let guard = running.track(pid as u64);
+        Ok((conn, guard))
+    }
+
+    /// Run statements one after another, stopping early on error or cancellation
+    async fn run_statements(
+        conn: &mut PgConnection,
+        statements: &[String],
+        options: &ExecOptions,
+        running: &RunningQueries,
+    ) -> Vec<SqlResult> {
+        let mut results = Vec::new();
+
+        for sql in statements {
+            if running.is_cancelled() {
+                break;
+            }
+
+            let result = Self::run_statement(conn, sql, options.max_rows).await;
+            let failed = matches!(result, SqlResult::Error(_));
+            results.push(result);
+
+            if failed && options.stop_on_error {
+                break;
+            }
+        }
+
+        results
+    }
+
+    /// Run a script outside a transaction and send back its results.
+    /// A query ending the script keeps its cursor open afterwards, so the rows past
+    /// the first page stay readable through `QueryResult::more_rows`.
+    async fn run_script(
+        conn: &mut PgConnection,
+        statements: Vec<String>,
+        options: ExecOptions,
+        running: &RunningQueries,
+        results_tx: oneshot::Sender<Result<Vec<SqlResult>, DbError>>,
+    ) {
+        let Some((last, leading)) = statements.split_last() else {
+            let _ = results_tx.send(Ok(Vec::new()));
+            return;
+        };
+
+        let mut results = Self::run_statements(conn, leading, &options, running).await;
+        let stopped = running.is_cancelled()
+            || (options.stop_on_error && matches!(results.last(), Some(SqlResult::Error(_))));
+        if stopped || !SqlStatementClassifier::is_query_statement(last) || Self::unsupported_statement_message(last).is_some() {
+            if !stopped {
+                results.push(Self::run_statement(conn, last, options.max_rows).await);
+            }
+            let _ = results_tx.send(Ok(results));
+            return;
+        }
+
+        let start = Instant::now();
+        let mut rows = conn.fetch(sqlx::raw_sql(last));
+        let pump = match stream::read_page(&mut rows, options.max_rows, Self::extract_value).await {
+            Ok(page) => {
+                let batch_size = options.max_rows.unwrap_or(usize::MAX);
+                let (result, pump) = page.into_streamed_result(last, start.elapsed().as_millis(), batch_size);
+                results.push(SqlResult::Query(result));
+                pump
+            }
+            Err(e) => {
+                results.push(SqlResult::Error(Self::error_info(last, &e)));
+                None
+            }
+        };
+        let _ = results_tx.send(Ok(results));
+
+        if let Some(pump) = pump {
+            pump.run(rows).await;
+        }
+    }
+
+    /// Run the whole script inside BEGIN/COMMIT, rolling back on error or cancellation.
+    /// PostgreSQL aborts the transaction on the first error, so without `stop_on_error`
+    /// the remaining statements fail and the final COMMIT is turned into a rollback by the server.
+    async fn execute_in_transaction(
+        conn: &mut PgConnection,
+        statements: Vec<String>,
+        options: ExecOptions,
+        running: &RunningQueries,
+    ) -> Result<Vec<SqlResult>, DbError> {
+        let mut tx = conn.begin().await
+            .map_err(|e| DbError::QueryError(format!("Failed to begin transaction: {}", e)))?;
+        let mut results = Self::run_statements(&mut tx, &statements, &options, running).await;
+
+        let failed = options.stop_on_error && matches!(results.last(), Some(SqlResult::Error(_)));
+        let start = Instant::now();
+        if failed || running.is_cancelled() {
+            results.push(match tx.rollback().await {
+                Ok(()) => SqlResult::Exec(ExecResult::transaction_rolled_back(start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new("ROLLBACK", e.to_string())),
+            });
+        } else {
+            results.push(match tx.commit().await {
+                Ok(()) => SqlResult::Exec(ExecResult::transaction_committed(start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new("COMMIT", e.to_string())),
+            });
         }
 
-        // If all else fails, return column type information
-        Some(format!("<{}>", type_name))
+        Ok(results)
     }
 }
 
@@ -160,22 +420,32 @@ impl DbConnection for PostgresDbConnection {
         let config = self.config.clone();
         if let Some(conf) = config {
             let clone_conf = conf.clone();
-            let url = if let Some(db) = conf.database {
-                format!(
-                    "postgresql://{}:{}@{}:{}/{}",
-                    conf.username, conf.password, conf.host, conf.port, db
-                )
-            } else {
-                format!(
-                    "postgresql://{}:{}@{}:{}",
-                    conf.username, conf.password, conf.host, conf.port
-                )
+            let tunnel = SshTunnel::for_config(&conf).await?;
+            let (host, port) = match &tunnel {
+                Some(tunnel) => (tunnel.local_addr().ip().to_string(), tunnel.local_addr().port()),
+                None => (conf.host.clone(), conf.port),
             };
+            let search_path = Self::search_path_statement(&conf);
+            let connect_options = Self::connect_options(&conf, &host, port)?;
 
+            let pinned_session = self.pinned_session;
             let pool = TOKIO_HANDLE.spawn(async move {
-                PgPoolOptions::new()
-                    .max_connections(5)
-                    .connect(&url)
+                let mut pool_options = PgPoolOptions::new().max_connections(1);
+                if pinned_session {
+                    pool_options = pool_options.idle_timeout(None).max_lifetime(None);
+                }
+                pool_options
+                    // Every pooled session needs the search path, not just the first one
+                    .after_connect(move |conn, _meta| {
+                        let search_path = search_path.clone();
+                        Box::pin(async move {
+                            if let Some(sql) = search_path {
+                                conn.execute(sql.as_str()).await?;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .connect_with(connect_options)
                     .await
             })
             .await
@@ -189,6 +459,7 @@ impl DbConnection for PostgresDbConnection {
                 let mut db_guard = self.current_database.write().unwrap();
                 db_guard.clone_from(&clone_conf.database);
             }
+            self.tunnel = tunnel;
             Ok(())
         }else { 
             Err(DbError::ConnectionError("No database configuration provided".to_string()))
@@ -204,6 +475,7 @@ impl DbConnection for PostgresDbConnection {
         if let Some(pool) = pool_opt {
             pool.close().await;
         }
+        self.tunnel = None;
         Ok(())
     }
 
@@ -212,192 +484,39 @@ impl DbConnection for PostgresDbConnection {
         let pool = self.ensure_connected()?;
 
         // Split script into individual statements
-        let statements = SqlScriptSplitter::split(script);
-        let mut results = Vec::new();
-
-        // PostgreSQL doesn't have USE statement, database is set at connection level
-
-
-        // Execute each statement on the pool
-        for sql in statements {
-            let sql = sql.trim();
-            if sql.is_empty() {
-                continue;
-            }
-
-            // Check if this is a \c (psql connect) command or USE statement - not supported
-            let sql_trimmed = sql.trim_start();
-            let sql_upper = sql_trimmed.to_uppercase();
-
-            if sql_trimmed.starts_with("\\c ") || sql_trimmed.starts_with("\\connect ") {
-                results.push(SqlResult::Error(SqlErrorInfo {
-                    sql: sql.to_string(),
-                    message: "\\c command is not supported. PostgreSQL cannot switch databases on an existing connection. Please reconnect to switch databases.".to_string(),
-                }));
-
-                if options.stop_on_error {
-                    break;
-                }
-                continue;
-            }
-
-            if sql_upper.starts_with("USE ") {
-                results.push(SqlResult::Error(SqlErrorInfo {
-                    sql: sql.to_string(),
-                    message: "USE statement is not supported in PostgreSQL. PostgreSQL cannot switch databases on an existing connection. Please reconnect to switch databases.".to_string(),
-                }));
-
-                if options.stop_on_error {
-                    break;
+        let statements: Vec<String> = SqlScriptSplitter::split_for(script, DatabaseType::PostgreSQL)
+            .into_iter()
+            .map(|sql| sql.trim().to_string())
+            .filter(|sql| !sql.is_empty())
+            .collect();
+
+        // The whole script runs on one backend, so there is a single pid to cancel
+        let running = self.running.clone();
+        running.reset();
+        // Results come back before the task ends, it may go on reading the last query's rows
+        let (results_tx, results_rx) = oneshot::channel();
+        TOKIO_HANDLE.spawn(async move {
+            let (mut conn, _running_guard) = match Self::acquire_tracked(&pool, &running).await {
+                Ok(acquired) => acquired,
+                Err(e) => {
+                    let _ = results_tx.send(Err(DbError::ConnectionError(format!("Failed to acquire connection: {}", e))));
+                    return;
                 }
-                continue;
-            }
-
-            // Apply max_rows limit
-            let modified_sql = if let Some(max_rows) = options.max_rows {
-                if SqlStatementClassifier::is_query_statement(sql) && !sql.to_uppercase().contains(" LIMIT ") {
-                    format!("{} LIMIT {}", sql, max_rows)
-                } else {
-                    sql.to_string()
-                }
-            } else {
-                sql.to_string()
             };
-
-            // Determine statement type
-            let is_query = SqlStatementClassifier::is_query_statement(&modified_sql);
-
-            let start = Instant::now();
-            let result = if is_query {
-                // Execute query using raw_sql on pool - wrap in Tokio context
-                let pool = pool.clone();
-                let sql_to_exec = modified_sql.clone();
-                let original_sql = sql.to_string();
-
-                match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&sql_to_exec).fetch_all(&pool).await
-                }).await {
-                    Ok(Ok(rows)) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-
-                        if rows.is_empty() {
-                            SqlResult::Query(QueryResult {
-                                sql: original_sql,
-                                columns: Vec::new(),
-                                rows: Vec::new(),
-                                elapsed_ms,
-                            })
-                        } else {
-                            // Extract column names
-                            let columns: Vec<String> = rows[0]
-                                .columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect();
-
-                            // Extract row data
-                            let data_rows: Vec<Vec<Option<String>>> = rows
-                                .iter()
-                                .map(|row| {
-                                    (0..columns.len())
-                                        .map(|i| Self::extract_value(row, i))
-                                        .collect()
-                                })
-                                .collect();
-
-                            SqlResult::Query(QueryResult {
-                                sql: original_sql,
-                                columns,
-                                rows: data_rows,
-                                elapsed_ms,
-                            })
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
+            if options.transactional {
+                let _ = results_tx.send(Self::execute_in_transaction(&mut conn, statements, options, &running).await);
             } else {
-                // Execute non-query using raw_sql on pool - wrap in Tokio context
-                let pool = pool.clone();
-                let sql_to_exec = modified_sql.clone();
-                let original_sql = sql.to_string();
-
-                match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&sql_to_exec).execute(&pool).await
-                }).await {
-                    Ok(Ok(exec_result)) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-                        let rows_affected = exec_result.rows_affected();
-                        let message = SqlStatementClassifier::format_message(&original_sql, rows_affected);
-
-                        SqlResult::Exec(ExecResult {
-                            sql: original_sql,
-                            rows_affected,
-                            elapsed_ms,
-                            message: Some(message),
-                        })
-                    }
-                    Ok(Err(e)) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
-            };
-
-            results.push(result);
-        }
+                Self::run_script(&mut conn, statements, options, &running, results_tx).await;
+            }
+        });
+        let mut results = results_rx
+            .await
+            .map_err(|e| DbError::QueryError(format!("Query task ended unexpectedly: {}", e)))??;
 
-        // Connection will be automatically returned to the pool when dropped
+        SqlScriptSplitter::locate_errors(script, DatabaseType::PostgreSQL, &mut results);
         Ok(results)
     }
 
-
     async fn query(&self, query: &str, params: Option<Vec<SqlValue>>, options: ExecOptions) -> Result<SqlResult, DbError> {
         let pool = self.ensure_connected()?;
         let query = query.trim();
@@ -412,28 +531,16 @@ impl DbConnection for PostgresDbConnection {
         let result = if let Some(params) = params {
             // Use prepared statement with parameter binding
             if is_query {
-                // For SELECT queries with parameters - use raw_sql with parameters
-                // Build parameterized query string
+                // For SELECT queries with parameters - bind them to a prepared statement
                 let query_str = query.to_string();
-                let params_vec: Vec<String> = params.iter().enumerate().map(|(i, p)| match p {
-                    SqlValue::Null => "NULL".to_string(),
-                    SqlValue::Bool(v) => v.to_string(),
-                    SqlValue::Int(v) => v.to_string(),
-                    SqlValue::Float(v) => v.to_string(),
-                    SqlValue::String(v) => format!("'{}'", v.replace("'", "''")),
-                    SqlValue::Bytes(v) => format!("'\\x{}'", hex::encode(v)),
-                    SqlValue::Json(v) => format!("'{}'", v.to_string().replace("'", "''")),
-                }).collect();
-
-                // PostgreSQL uses $1, $2, etc. for parameters
-                let mut final_query = query_str.clone();
-                for (i, param_val) in params_vec.iter().enumerate() {
-                    final_query = final_query.replace(&format!("${}", i + 1), param_val);
-                }
+                let query_str_clone = query_str.clone();
 
                 let pool = pool.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&final_query).fetch_all(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    let bound = params.into_iter().fold(sqlx::query(&query_str_clone), Self::bind_parameter);
+                    conn.fetch_all(bound).await
                 }).await {
                     Ok(Ok(rows)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -444,17 +551,19 @@ impl DbConnection for PostgresDbConnection {
                                 columns: Vec::new(),
                                 rows: Vec::new(),
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         } else {
                             // Extract column names
-                            let columns: Vec<String> = rows[0]
+                            let columns: Vec<ResultColumn> = rows[0]
                                 .columns()
                                 .iter()
-                                .map(|col| col.name().to_string())
+                                .map(|col| ResultColumn::new(col.name(), col.type_info().name()))
                                 .collect();
 
                             // Extract row data
-                            let data_rows: Vec<Vec<Option<String>>> = rows
+                            let data_rows: Vec<Vec<SqlValue>> = rows
                                 .iter()
                                 .map(|row| {
                                     (0..columns.len())
@@ -468,40 +577,25 @@ impl DbConnection for PostgresDbConnection {
                                 columns,
                                 rows: data_rows,
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         }
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             } else {
-                // For DML/DDL queries with parameters - use raw_sql with parameters
+                // For DML/DDL queries with parameters - bind them to a prepared statement
                 let query_str = query.to_string();
-                let params_vec: Vec<String> = params.iter().enumerate().map(|(i, p)| match p {
-                    SqlValue::Null => "NULL".to_string(),
-                    SqlValue::Bool(v) => v.to_string(),
-                    SqlValue::Int(v) => v.to_string(),
-                    SqlValue::Float(v) => v.to_string(),
-                    SqlValue::String(v) => format!("'{}'", v.replace("'", "''")),
-                    SqlValue::Bytes(v) => format!("'\\x{}'", hex::encode(v)),
-                    SqlValue::Json(v) => format!("'{}'", v.to_string().replace("'", "''")),
-                }).collect();
-
-                // PostgreSQL uses $1, $2, etc. for parameters
-                let mut final_query = query_str.clone();
-                for (i, param_val) in params_vec.iter().enumerate() {
-                    final_query = final_query.replace(&format!("${}", i + 1), param_val);
-                }
+                let query_str_clone = query_str.clone();
 
                 let pool = pool.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&final_query).execute(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    let bound = params.into_iter().fold(sqlx::query(&query_str_clone), Self::bind_parameter);
+                    conn.execute(bound).await
                 }).await {
                     Ok(Ok(exec_result)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -515,14 +609,8 @@ impl DbConnection for PostgresDbConnection {
                             message: Some(message),
                         })
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             }
         } else {
@@ -531,8 +619,10 @@ impl DbConnection for PostgresDbConnection {
                 let pool = pool.clone();
                 let query_str = query.to_string();
                 let query_str_clone = query_str.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&query_str_clone).fetch_all(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    conn.fetch_all(sqlx::raw_sql(&query_str_clone)).await
                 }).await {
                     Ok(Ok(rows)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -543,17 +633,19 @@ impl DbConnection for PostgresDbConnection {
                                 columns: Vec::new(),
                                 rows: Vec::new(),
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         } else {
                             // Extract column names
-                            let columns: Vec<String> = rows[0]
+                            let columns: Vec<ResultColumn> = rows[0]
                                 .columns()
                                 .iter()
-                                .map(|col| col.name().to_string())
+                                .map(|col| ResultColumn::new(col.name(), col.type_info().name()))
                                 .collect();
 
                             // Extract row data
-                            let data_rows: Vec<Vec<Option<String>>> = rows
+                            let data_rows: Vec<Vec<SqlValue>> = rows
                                 .iter()
                                 .map(|row| {
                                     (0..columns.len())
@@ -567,24 +659,22 @@ impl DbConnection for PostgresDbConnection {
                                 columns,
                                 rows: data_rows,
                                 elapsed_ms,
+                                has_more: false,
+                                more_rows: None,
                             })
                         }
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             } else {
                 let pool = pool.clone();
                 let query_str = query.to_string();
                 let query_str_clone = query_str.clone();
+                let running = self.running.clone();
                 match TOKIO_HANDLE.spawn(async move {
-                    sqlx::raw_sql(&query_str_clone).execute(&pool).await
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    conn.execute(sqlx::raw_sql(&query_str_clone)).await
                 }).await {
                     Ok(Ok(exec_result)) => {
                         let elapsed_ms = start.elapsed().as_millis();
@@ -598,18 +688,111 @@ impl DbConnection for PostgresDbConnection {
                             message: Some(message),
                         })
                     }
-                    Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    }),
-                    Err(e) => SqlResult::Error(SqlErrorInfo {
-                        sql: query.to_string(),
-                        message: e.to_string(),
-                    })
+                    Ok(Err(e)) => SqlResult::Error(Self::error_info(query, &e)),
+                    Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string()))
                 }
             }
         };
 
         Ok(result)
     }
+
+    async fn query_stream(&self, query: &str, batch_size: usize) -> Result<QueryStream, DbError> {
+        let pool = self.ensure_connected()?;
+        let sql = query.to_string();
+        let running = self.running.clone();
+        running.reset();
+
+        let (stream_tx, stream_rx) = oneshot::channel();
+        TOKIO_HANDLE.spawn(async move {
+            let (mut conn, _running_guard) = match Self::acquire_tracked(&pool, &running).await {
+                Ok(acquired) => acquired,
+                Err(e) => {
+                    let _ = stream_tx.send(Err(DbError::ConnectionError(format!("Failed to acquire connection: {}", e))));
+                    return;
+                }
+            };
+            let mut rows = conn.fetch(sqlx::raw_sql(&sql));
+            match stream::read_page(&mut rows, Some(batch_size), Self::extract_value).await {
+                Ok(page) => {
+                    let (stream, pump) = page.into_stream(batch_size);
+                    let _ = stream_tx.send(Ok(stream));
+                    if let Some(pump) = pump {
+                        pump.run(rows).await;
+                    }
+                }
+                Err(e) => {
+                    let _ = stream_tx.send(Err(DbError::QueryError(e.to_string())));
+                }
+            }
+        });
+
+        stream_rx
+            .await
+            .map_err(|e| DbError::QueryError(format!("Query task ended unexpectedly: {}", e)))?
+    }
+
+    fn running_server_ids(&self) -> Vec<u64> {
+        self.running.handles()
+    }
+
+    async fn cancel_query(&self) -> Result<(), DbError> {
+        let pool = self.ensure_connected()?;
+        self.running.request_cancel();
+        let pids = self.running.handles();
+        if pids.is_empty() {
+            return Ok(());
+        }
+
+        TOKIO_HANDLE.spawn(async move {
+            // A pinned session has no spare pooled connection, cancel from a side connection
+            let mut conn = PgConnection::connect_with(&pool.connect_options()).await?;
+            for pid in pids {
+                sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(pid as i32)
+                    .execute(&mut conn)
+                    .await?;
+            }
+            conn.close().await
+        })
+        .await
+        .map_err(|e| DbError::QueryError(format!("Failed to spawn cancel task: {}", e)))?
+        .map_err(|e| DbError::QueryError(format!("Failed to cancel query: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config;
+    use one_core::storage::DatabaseType;
+
+    fn search_path(value: &str) -> Option<String> {
+        PostgresDbConnection::search_path_statement(&test_config(DatabaseType::PostgreSQL, &[("search_path", value)]))
+    }
+
+    #[test]
+    fn test_search_path_statement() {
+        assert_eq!(
+            search_path("sales, \"Audit\" ,public"),
+            Some("SET search_path TO \"sales\", \"Audit\", \"public\"".to_string())
+        );
+        assert_eq!(search_path(" , "), None);
+        assert_eq!(
+            PostgresDbConnection::search_path_statement(&test_config(DatabaseType::PostgreSQL, &[])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_connect_options_use_ssl_settings() {
+        let config = test_config(DatabaseType::PostgreSQL, &[(crate::ssl::SSL_MODE_PARAM, "verify-full")]);
+        let options = PostgresDbConnection::connect_options(&config, "127.0.0.1", 40022).unwrap();
+        assert_eq!(options.get_host(), "127.0.0.1");
+        assert_eq!(options.get_port(), 40022);
+        assert!(matches!(options.get_ssl_mode(), PgSslMode::VerifyFull));
+
+        let default = PostgresDbConnection::connect_options(&test_config(DatabaseType::PostgreSQL, &[]), "db.internal", 5432).unwrap();
+        assert!(matches!(default.get_ssl_mode(), PgSslMode::Prefer));
+    }
 }
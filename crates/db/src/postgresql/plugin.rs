@@ -12,12 +12,20 @@ use crate::postgresql::connection::PostgresDbConnection;
 use crate::types::*;
 
 /// PostgreSQL database plugin implementation (stateless)
+///
+/// A connection is bound to one database, so object listing works per schema:
+/// the `database` argument of the listing and DDL methods names a schema of that database.
 pub struct PostgresPlugin;
 
 impl PostgresPlugin {
     pub fn new() -> Self {
         Self
     }
+
+    /// Escape a value used inside a single-quoted SQL string literal
+    fn escape_literal(value: &str) -> String {
+        value.replace('\'', "''")
+    }
 }
 
 #[async_trait]
@@ -31,8 +39,36 @@ impl DatabasePlugin for PostgresPlugin {
         Ok(Box::new(conn))
     }
 
+    async fn create_session(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        let mut conn = PostgresDbConnection::new_session(config);
+        conn.connect().await?;
+        Ok(Box::new(conn))
+    }
+
     // === Database/Schema Level Operations ===
 
+    fn supports_schema(&self) -> bool {
+        true
+    }
+
+    async fn list_schemas(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
+        let result = connection.query(
+            "SELECT nspname FROM pg_namespace \
+             WHERE nspname NOT LIKE 'pg\\_%' AND nspname <> 'information_schema' \
+             ORDER BY nspname",
+            None,
+            ExecOptions::default()
+        ).await.map_err(|e| anyhow::anyhow!("Failed to list schemas: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            Ok(query_result.rows.iter()
+                .filter_map(|row| row.first().and_then(|v| v.as_text()))
+                .collect())
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
     async fn list_databases(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
         let result = connection.query(
             "SELECT datname FROM pg_database WHERE datistemplate = false ORDER BY datname",
@@ -42,7 +78,7 @@ impl DatabasePlugin for PostgresPlugin {
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter()
-                .filter_map(|row| row.first().and_then(|v| v.clone()))
+                .filter_map(|row| row.first().and_then(|v| v.as_text()))
                 .collect())
         } else {
             Err(anyhow::anyhow!("Unexpected result type"))
@@ -88,7 +124,7 @@ impl DatabasePlugin for PostgresPlugin {
                 pg_encoding_to_char(d.encoding) as charset,
                 d.datcollate as collation,
                 pg_size_pretty(pg_database_size(d.datname)) as size,
-                (SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'public') as table_count,
+                (SELECT COUNT(*) FROM pg_tables WHERE schemaname NOT LIKE 'pg\\_%' AND schemaname <> 'information_schema') as table_count,
                 shobj_description(d.oid, 'pg_database') as comment
             FROM pg_database d
             WHERE d.datistemplate = false 
@@ -100,12 +136,12 @@ impl DatabasePlugin for PostgresPlugin {
         if let SqlResult::Query(query_result) = result {
             let databases: Vec<DatabaseInfo> = query_result.rows.iter()
                 .filter_map(|row| {
-                    let name = row.get(0).and_then(|v| v.clone())?;
-                    let charset = row.get(1).and_then(|v| v.clone());
-                    let collation = row.get(2).and_then(|v| v.clone());
-                    let size = row.get(3).and_then(|v| v.clone());
-                    let table_count = row.get(4).and_then(|v| v.clone()).and_then(|s| s.parse::<i64>().ok());
-                    let comment = row.get(5).and_then(|v| v.clone());
+                    let name = row.first().and_then(|v| v.as_text())?;
+                    let charset = row.get(1).and_then(|v| v.as_text());
+                    let collation = row.get(2).and_then(|v| v.as_text());
+                    let size = row.get(3).and_then(|v| v.as_text());
+                    let table_count = row.get(4).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok());
+                    let comment = row.get(5).and_then(|v| v.as_text());
                     
                     Some(DatabaseInfo {
                         name,
@@ -126,29 +162,33 @@ impl DatabasePlugin for PostgresPlugin {
 
     // === Table Operations ===
 
-    async fn list_tables(&self, connection: &dyn DbConnection, _database: &str) -> Result<Vec<TableInfo>> {
+    async fn list_tables(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TableInfo>> {
         // Query to get all tables with their description/metadata
         // PostgreSQL stores table comments in pg_description
-        let sql = "SELECT \
+        let sql = format!(
+            "SELECT \
                 t.tablename, \
                 obj_description((quote_ident(t.schemaname) || '.' || quote_ident(t.tablename))::regclass) AS table_comment, \
-                (SELECT reltuples::bigint FROM pg_class WHERE relname = t.tablename AND relnamespace = 'public'::regnamespace) AS row_count \
+                (SELECT reltuples::bigint FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE c.relname = t.tablename AND n.nspname = t.schemaname) AS row_count \
              FROM pg_tables t \
-             WHERE t.schemaname = 'public' \
-             ORDER BY t.tablename";
+             WHERE t.schemaname = '{}' \
+             ORDER BY t.tablename",
+            Self::escape_literal(database)
+        );
 
-        let result = connection.query(sql, None, ExecOptions::default())
+        let result = connection.query(&sql, None, ExecOptions::default())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to list tables: {}", e))?;
 
         if let SqlResult::Query(query_result) = result {
             let tables: Vec<TableInfo> = query_result.rows.iter().map(|row| {
                 // Parse row count
-                let row_count = row.get(2).and_then(|v| v.clone()).and_then(|s| s.parse::<i64>().ok());
+                let row_count = row.get(2).and_then(|v| v.as_text()).and_then(|s| s.parse::<i64>().ok());
 
                 TableInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    comment: row.get(1).and_then(|v| v.clone()).filter(|s| !s.is_empty()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    comment: row.get(1).and_then(|v| v.as_text()).filter(|s| !s.is_empty()),
                     engine: None, // PostgreSQL doesn't have engine concept like MySQL
                     row_count,
                     create_time: None, // Would require additional query to pg_stat_user_tables
@@ -189,18 +229,20 @@ impl DatabasePlugin for PostgresPlugin {
         })
     }
 
-    async fn list_columns(&self, connection: &dyn DbConnection, _database: &str, table: &str) -> Result<Vec<ColumnInfo>> {
+    async fn list_columns(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ColumnInfo>> {
         let sql = format!(
             "SELECT column_name, data_type, is_nullable, column_default, \
              (SELECT COUNT(*) FROM information_schema.key_column_usage kcu \
               WHERE kcu.table_name = c.table_name AND kcu.column_name = c.column_name \
-              AND kcu.table_schema = 'public' AND EXISTS \
+              AND kcu.table_schema = c.table_schema AND EXISTS \
               (SELECT 1 FROM information_schema.table_constraints tc \
-               WHERE tc.constraint_name = kcu.constraint_name AND tc.constraint_type = 'PRIMARY KEY')) > 0 AS is_primary \
+               WHERE tc.constraint_name = kcu.constraint_name AND tc.constraint_schema = kcu.constraint_schema \
+               AND tc.constraint_type = 'PRIMARY KEY')) > 0 AS is_primary \
              FROM information_schema.columns c \
-             WHERE table_schema = 'public' AND table_name = '{}' \
+             WHERE table_schema = '{}' AND table_name = '{}' \
              ORDER BY ordinal_position",
-            table
+            Self::escape_literal(database),
+            Self::escape_literal(table)
         );
 
         let result = connection.query(&sql, None, ExecOptions::default())
@@ -210,11 +252,11 @@ impl DatabasePlugin for PostgresPlugin {
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 ColumnInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    data_type: row.get(1).and_then(|v| v.clone()).unwrap_or_default(),
-                    is_nullable: row.get(2).and_then(|v| v.clone()).map(|v| v == "YES").unwrap_or(true),
-                    is_primary_key: row.get(4).and_then(|v| v.clone()).map(|v| v == "t" || v == "true" || v == "1").unwrap_or(false),
-                    default_value: row.get(3).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    data_type: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                    is_nullable: row.get(2).and_then(|v| v.as_text()).map(|v| v == "YES").unwrap_or(true),
+                    is_primary_key: row.get(4).and_then(|v| v.as_text()).map(|v| v == "t" || v == "true" || v == "1").unwrap_or(false),
+                    default_value: row.get(3).and_then(|v| v.as_text()),
                     comment: None,
                 }
             }).collect())
@@ -253,18 +295,20 @@ impl DatabasePlugin for PostgresPlugin {
         })
     }
 
-    async fn list_indexes(&self, connection: &dyn DbConnection, _database: &str, table: &str) -> Result<Vec<IndexInfo>> {
+    async fn list_indexes(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<IndexInfo>> {
         let sql = format!(
             "SELECT i.relname AS index_name, \
              a.attname AS column_name, \
              ix.indisunique AS is_unique \
              FROM pg_class t \
+             JOIN pg_namespace n ON n.oid = t.relnamespace \
              JOIN pg_index ix ON t.oid = ix.indrelid \
              JOIN pg_class i ON i.oid = ix.indexrelid \
              JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = ANY(ix.indkey) \
-             WHERE t.relname = '{}' AND t.relkind = 'r' \
+             WHERE n.nspname = '{}' AND t.relname = '{}' AND t.relkind IN ('r', 'p') \
              ORDER BY i.relname, a.attnum",
-            table
+            Self::escape_literal(database),
+            Self::escape_literal(table)
         );
 
         let result = connection.query(&sql, None, ExecOptions::default())
@@ -275,9 +319,9 @@ impl DatabasePlugin for PostgresPlugin {
             let mut indexes: HashMap<String, IndexInfo> = HashMap::new();
 
             for row in query_result.rows {
-                let index_name = row.get(0).and_then(|v| v.clone()).unwrap_or_default();
-                let column_name = row.get(1).and_then(|v| v.clone()).unwrap_or_default();
-                let is_unique = row.get(2).and_then(|v| v.clone()).map(|v| v == "t" || v == "true").unwrap_or(false);
+                let index_name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+                let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+                let is_unique = row.get(2).and_then(|v| v.as_text()).map(|v| v == "t" || v == "true").unwrap_or(false);
 
                 indexes.entry(index_name.clone())
                     .or_insert_with(|| IndexInfo {
@@ -324,20 +368,76 @@ impl DatabasePlugin for PostgresPlugin {
     }
 
 
+    async fn list_foreign_keys(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ForeignKeyInfo>> {
+        let sql = format!(
+            "SELECT con.conname, a.attname, fn.nspname, ft.relname, fa.attname \
+             FROM pg_constraint con \
+             JOIN pg_class t ON t.oid = con.conrelid \
+             JOIN pg_namespace n ON n.oid = t.relnamespace \
+             JOIN pg_class ft ON ft.oid = con.confrelid \
+             JOIN pg_namespace fn ON fn.oid = ft.relnamespace \
+             CROSS JOIN LATERAL unnest(con.conkey, con.confkey) WITH ORDINALITY AS k(attnum, fattnum, ord) \
+             JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum \
+             JOIN pg_attribute fa ON fa.attrelid = con.confrelid AND fa.attnum = k.fattnum \
+             WHERE con.contype = 'f' AND n.nspname = '{}' AND t.relname = '{}' \
+             ORDER BY con.conname, k.ord",
+            Self::escape_literal(database),
+            Self::escape_literal(table)
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list foreign keys: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            let mut foreign_keys: Vec<ForeignKeyInfo> = Vec::new();
+
+            for row in query_result.rows {
+                let name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+                let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+                let referenced_schema = row.get(2).and_then(|v| v.as_text());
+                let referenced_table = row.get(3).and_then(|v| v.as_text()).unwrap_or_default();
+                let referenced_column = row.get(4).and_then(|v| v.as_text()).unwrap_or_default();
+
+                // Rows arrive grouped by constraint, in column order
+                match foreign_keys.last_mut() {
+                    Some(fk) if fk.name == name => {
+                        fk.columns.push(column_name);
+                        fk.referenced_columns.push(referenced_column);
+                    }
+                    _ => foreign_keys.push(ForeignKeyInfo {
+                        name,
+                        columns: vec![column_name],
+                        referenced_schema,
+                        referenced_table,
+                        referenced_columns: vec![referenced_column],
+                    }),
+                }
+            }
+
+            Ok(foreign_keys)
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
     // === View Operations ===
 
-    async fn list_views(&self, connection: &dyn DbConnection, _database: &str) -> Result<Vec<ViewInfo>> {
-        let sql = "SELECT table_name, view_definition FROM information_schema.views WHERE table_schema = 'public' ORDER BY table_name";
+    async fn list_views(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<ViewInfo>> {
+        let sql = format!(
+            "SELECT table_name, view_definition FROM information_schema.views WHERE table_schema = '{}' ORDER BY table_name",
+            Self::escape_literal(database)
+        );
 
-        let result = connection.query(sql, None, ExecOptions::default())
+        let result = connection.query(&sql, None, ExecOptions::default())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to list views: {}", e))?;
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 ViewInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    definition: row.get(1).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    definition: row.get(1).and_then(|v| v.as_text()),
                     comment: None,
                 }
             }).collect())
@@ -372,18 +472,21 @@ impl DatabasePlugin for PostgresPlugin {
 
     // === Function Operations ===
 
-    async fn list_functions(&self, connection: &dyn DbConnection, _database: &str) -> Result<Vec<FunctionInfo>> {
-        let sql = "SELECT routine_name, data_type FROM information_schema.routines WHERE routine_schema = 'public' AND routine_type = 'FUNCTION' ORDER BY routine_name";
+    async fn list_functions(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<FunctionInfo>> {
+        let sql = format!(
+            "SELECT routine_name, data_type FROM information_schema.routines WHERE routine_schema = '{}' AND routine_type = 'FUNCTION' ORDER BY routine_name",
+            Self::escape_literal(database)
+        );
 
-        let result = connection.query(sql, None, ExecOptions::default())
+        let result = connection.query(&sql, None, ExecOptions::default())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to list functions: {}", e))?;
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 FunctionInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    return_type: row.get(1).and_then(|v| v.clone()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    return_type: row.get(1).and_then(|v| v.as_text()),
                     parameters: Vec::new(),
                     definition: None,
                     comment: None,
@@ -421,17 +524,20 @@ impl DatabasePlugin for PostgresPlugin {
 
     // === Procedure Operations ===
 
-    async fn list_procedures(&self, connection: &dyn DbConnection, _database: &str) -> Result<Vec<FunctionInfo>> {
-        let sql = "SELECT routine_name FROM information_schema.routines WHERE routine_schema = 'public' AND routine_type = 'PROCEDURE' ORDER BY routine_name";
+    async fn list_procedures(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<FunctionInfo>> {
+        let sql = format!(
+            "SELECT routine_name FROM information_schema.routines WHERE routine_schema = '{}' AND routine_type = 'PROCEDURE' ORDER BY routine_name",
+            Self::escape_literal(database)
+        );
 
-        let result = connection.query(sql, None, ExecOptions::default())
+        let result = connection.query(&sql, None, ExecOptions::default())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to list procedures: {}", e))?;
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 FunctionInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
                     return_type: None,
                     parameters: Vec::new(),
                     definition: None,
@@ -466,23 +572,26 @@ impl DatabasePlugin for PostgresPlugin {
 
     // === Trigger Operations ===
 
-    async fn list_triggers(&self, connection: &dyn DbConnection, _database: &str) -> Result<Vec<TriggerInfo>> {
-        let sql = "SELECT trigger_name, event_object_table, event_manipulation, action_timing \
-                   FROM information_schema.triggers \
-                   WHERE trigger_schema = 'public' \
-                   ORDER BY trigger_name";
+    async fn list_triggers(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TriggerInfo>> {
+        let sql = format!(
+            "SELECT trigger_name, event_object_table, event_manipulation, action_timing \
+             FROM information_schema.triggers \
+             WHERE trigger_schema = '{}' \
+             ORDER BY trigger_name",
+            Self::escape_literal(database)
+        );
 
-        let result = connection.query(sql, None, ExecOptions::default())
+        let result = connection.query(&sql, None, ExecOptions::default())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to list triggers: {}", e))?;
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 TriggerInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    table_name: row.get(1).and_then(|v| v.clone()).unwrap_or_default(),
-                    event: row.get(2).and_then(|v| v.clone()).unwrap_or_default(),
-                    timing: row.get(3).and_then(|v| v.clone()).unwrap_or_default(),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    table_name: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                    event: row.get(2).and_then(|v| v.as_text()).unwrap_or_default(),
+                    timing: row.get(3).and_then(|v| v.as_text()).unwrap_or_default(),
                     definition: None,
                 }
             }).collect())
@@ -522,24 +631,27 @@ impl DatabasePlugin for PostgresPlugin {
 
     // === Sequence Operations ===
 
-    async fn list_sequences(&self, connection: &dyn DbConnection, _database: &str) -> Result<Vec<SequenceInfo>> {
-        let sql = "SELECT sequence_name, start_value::bigint, increment::bigint, min_value::bigint, max_value::bigint \
-                   FROM information_schema.sequences \
-                   WHERE sequence_schema = 'public' \
-                   ORDER BY sequence_name";
+    async fn list_sequences(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<SequenceInfo>> {
+        let sql = format!(
+            "SELECT sequence_name, start_value::bigint, increment::bigint, min_value::bigint, max_value::bigint \
+             FROM information_schema.sequences \
+             WHERE sequence_schema = '{}' \
+             ORDER BY sequence_name",
+            Self::escape_literal(database)
+        );
 
-        let result = connection.query(sql, None, ExecOptions::default())
+        let result = connection.query(&sql, None, ExecOptions::default())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to list sequences: {}", e))?;
 
         if let SqlResult::Query(query_result) = result {
             Ok(query_result.rows.iter().map(|row| {
                 SequenceInfo {
-                    name: row.get(0).and_then(|v| v.clone()).unwrap_or_default(),
-                    start_value: row.get(1).and_then(|v| v.clone()).and_then(|s| s.parse().ok()),
-                    increment: row.get(2).and_then(|v| v.clone()).and_then(|s| s.parse().ok()),
-                    min_value: row.get(3).and_then(|v| v.clone()).and_then(|s| s.parse().ok()),
-                    max_value: row.get(4).and_then(|v| v.clone()).and_then(|s| s.parse().ok()),
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    start_value: row.get(1).and_then(|v| v.as_text()).and_then(|s| s.parse().ok()),
+                    increment: row.get(2).and_then(|v| v.as_text()).and_then(|s| s.parse().ok()),
+                    min_value: row.get(3).and_then(|v| v.as_text()).and_then(|s| s.parse().ok()),
+                    max_value: row.get(4).and_then(|v| v.as_text()).and_then(|s| s.parse().ok()),
                 }
             }).collect())
         } else {
@@ -620,6 +732,26 @@ impl DatabasePlugin for PostgresPlugin {
         }))
     }
 
+    // === DDL Operations ===
+
+    async fn drop_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        let query = format!("DROP TABLE IF EXISTS {}", self.qualified_table_name(database, table));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
+    async fn truncate_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        let query = format!("TRUNCATE TABLE {}", self.qualified_table_name(database, table));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
+    async fn drop_view(&self, connection: &dyn DbConnection, database: &str, view: &str) -> Result<()> {
+        let query = format!("DROP VIEW IF EXISTS {}", self.qualified_table_name(database, view));
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+
     fn get_data_types(&self) -> Vec<DataTypeInfo> {
         vec![
             // 数值类型
@@ -664,6 +796,59 @@ impl DatabasePlugin for PostgresPlugin {
             DataTypeInfo::new("MACADDR", "MAC address").with_category(DataTypeCategory::Other),
         ]
     }
+
+    // === Server Sessions ===
+
+    async fn list_server_sessions(&self, connection: &dyn DbConnection) -> Result<Vec<ServerSession>> {
+        let sql = "SELECT pid, usename, client_addr::text, datname, state, \
+                   EXTRACT(EPOCH FROM now() - COALESCE(query_start, backend_start))::bigint, query \
+                   FROM pg_stat_activity \
+                   WHERE backend_type = 'client backend' \
+                   ORDER BY pid";
+        let result = connection.query(sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list server sessions: {}", e))?;
+
+        match result {
+            SqlResult::Query(query_result) => {
+                let text = |row: &Vec<SqlValue>, i: usize| row.get(i).and_then(|v| v.as_text());
+                Ok(query_result.rows.iter()
+                    .filter_map(|row| {
+                        Some(ServerSession {
+                            id: text(row, 0)?.parse::<u64>().ok()?,
+                            user: text(row, 1),
+                            host: text(row, 2),
+                            database: text(row, 3),
+                            state: text(row, 4),
+                            duration_secs: text(row, 5).and_then(|s| s.parse::<i64>().ok()),
+                            query: text(row, 6).filter(|s| !s.is_empty()),
+                        })
+                    })
+                    .collect())
+            }
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to list server sessions: {}", err.message)),
+            SqlResult::Exec(_) => Err(anyhow::anyhow!("Unexpected result type")),
+        }
+    }
+
+    /// Terminates the whole backend, rolling back its transaction, not just the running statement
+    async fn kill_server_session(&self, connection: &dyn DbConnection, id: u64) -> Result<()> {
+        match self.execute_query(connection, "", &format!("SELECT pg_terminate_backend({})", id), None).await? {
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to kill session {}: {}", id, err.message)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn tls_status(&self, connection: &dyn DbConnection) -> Result<Option<String>> {
+        let sql = "SELECT version, cipher FROM pg_stat_ssl WHERE pid = pg_backend_pid() AND ssl";
+        match self.execute_query(connection, "", sql, None).await? {
+            SqlResult::Query(query_result) => Ok(query_result.rows.first().map(|row| {
+                row.iter().filter_map(|v| v.as_text()).collect::<Vec<_>>().join(" ")
+            })),
+            SqlResult::Error(err) => Err(anyhow::anyhow!("Failed to read TLS status: {}", err.message)),
+            SqlResult::Exec(_) => Err(anyhow::anyhow!("Unexpected result type")),
+        }
+    }
 }
 
 impl Default for PostgresPlugin {
@@ -671,3 +856,117 @@ impl Default for PostgresPlugin {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::TOKIO_HANDLE;
+    use crate::test_support::{rows_result, test_config, FakeConnection};
+
+    fn text(value: &str) -> SqlValue {
+        SqlValue::String(value.to_string())
+    }
+
+    #[test]
+    fn test_qualified_table_name() {
+        let plugin = PostgresPlugin::new();
+        assert_eq!(plugin.quote_identifier("Order Items"), "\"Order Items\"");
+        assert_eq!(plugin.qualified_table_name("sales", "orders"), "\"sales\".\"orders\"");
+    }
+
+    #[test]
+    fn test_table_data_query_is_schema_qualified_and_paged() {
+        let plugin = PostgresPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::PostgreSQL, &[]))
+            .respond("SELECT column_name", rows_result(vec![vec![text("id"), text("integer"), text("NO"), SqlValue::Null, text("t")]]))
+            .respond("SELECT COUNT(*)", rows_result(vec![vec![text("250")]]));
+        let log = conn.log();
+
+        let request = TableDataRequest::new("shop", "orders")
+            .with_schema(Some("sales".to_string()))
+            .with_page(3, 50);
+        let response = TOKIO_HANDLE.block_on(plugin.query_table_data(&conn, &request)).unwrap();
+
+        assert_eq!(response.total_count, 250);
+        assert_eq!(response.primary_key_indices, vec![0]);
+        let log = log.lock().unwrap();
+        // The columns are looked up in the schema, not in the database the connection is bound to
+        assert!(log[0].contains("table_schema = 'sales' AND table_name = 'orders'"));
+        assert_eq!(log[1], "SELECT COUNT(*) FROM \"sales\".\"orders\"");
+        assert_eq!(log[2], "SELECT * FROM \"sales\".\"orders\" LIMIT 50 OFFSET 100");
+    }
+
+    #[test]
+    fn test_rename_table_stays_in_schema() {
+        let plugin = PostgresPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::PostgreSQL, &[]));
+        let log = conn.log();
+        TOKIO_HANDLE
+            .block_on(plugin.rename_table(&conn, "sales", "orders", "purchases"))
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["ALTER TABLE \"sales\".\"orders\" RENAME TO \"purchases\""]
+        );
+    }
+
+    #[test]
+    fn test_database_node_lists_schemas() {
+        let plugin = PostgresPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::PostgreSQL, &[]))
+            .respond("SELECT nspname", rows_result(vec![vec![text("public")], vec![text("sales")]]));
+        let node = DbNode::new("conn:shop", "shop", DbNodeType::Database, "conn".to_string());
+
+        let children = TOKIO_HANDLE.block_on(plugin.load_node_children(&conn, &node)).unwrap();
+
+        assert_eq!(children.len(), 1);
+        let folder = &children[0];
+        assert_eq!(folder.node_type, DbNodeType::SchemasFolder);
+        let schemas: Vec<&str> = folder.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(schemas, vec!["public", "sales"]);
+        let sales = &folder.children[1];
+        assert_eq!(sales.object_scope().map(String::as_str), Some("sales"));
+        assert_eq!(
+            sales.metadata.as_ref().and_then(|m| m.get("database")).map(String::as_str),
+            Some("shop")
+        );
+    }
+
+    #[test]
+    fn test_server_sessions_from_pg_stat_activity() {
+        let plugin = PostgresPlugin::new();
+        let conn = FakeConnection::new(test_config(DatabaseType::PostgreSQL, &[]))
+            .respond("SELECT pid", rows_result(vec![
+                vec![text("4242"), text("app"), text("10.0.0.5/32"), text("shop"), text("active"), text("12"), text("SELECT pg_sleep(60)")],
+                vec![text("4243"), text("app"), SqlValue::Null, text("shop"), text("idle"), text("300"), text("")],
+            ]));
+        let log = conn.log();
+
+        let sessions = TOKIO_HANDLE.block_on(plugin.list_server_sessions(&conn)).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, 4242);
+        assert_eq!(sessions[0].duration_secs, Some(12));
+        assert_eq!(sessions[0].query.as_deref(), Some("SELECT pg_sleep(60)"));
+        assert_eq!(sessions[1].host, None);
+        assert_eq!(sessions[1].query, None);
+
+        TOKIO_HANDLE.block_on(plugin.kill_server_session(&conn, 4242)).unwrap();
+        assert_eq!(log.lock().unwrap().last().unwrap(), "SELECT pg_terminate_backend(4242)");
+    }
+
+    #[test]
+    fn test_tls_status_from_pg_stat_ssl() {
+        let plugin = PostgresPlugin::new();
+        let encrypted = FakeConnection::new(test_config(DatabaseType::PostgreSQL, &[]))
+            .respond("SELECT version, cipher", rows_result(vec![vec![text("TLSv1.3"), text("TLS_AES_256_GCM_SHA384")]]));
+        assert_eq!(
+            TOKIO_HANDLE.block_on(plugin.tls_status(&encrypted)).unwrap().as_deref(),
+            Some("TLSv1.3 TLS_AES_256_GCM_SHA384")
+        );
+
+        let plain = FakeConnection::new(test_config(DatabaseType::PostgreSQL, &[]))
+            .respond("SELECT version, cipher", rows_result(vec![]));
+        assert_eq!(TOKIO_HANDLE.block_on(plugin.tls_status(&plain)).unwrap(), None);
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{ExecOptions, SqlResult, SqlStatementClassifier, TransactionControl};
+use crate::params::bind_script;
+use crate::runtime::TOKIO_HANDLE;
+use crate::types::SqlValue;
+
+/// Transaction bookkeeping for a pinned session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionState {
+    /// Whether a transaction is currently open on the session
+    pub open: bool,
+    /// Statements run inside the open transaction that are not committed yet
+    pub pending_statements: usize,
+}
+
+impl TransactionState {
+    pub fn begin(&mut self) {
+        self.open = true;
+        self.pending_statements = 0;
+    }
+
+    pub fn end(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Track the statements of a script that ran successfully on the session
+    pub fn observe(&mut self, results: &[SqlResult]) {
+        for result in results {
+            // Queries change nothing and failed statements have no effect
+            let SqlResult::Exec(exec_result) = result else {
+                continue;
+            };
+
+            match SqlStatementClassifier::transaction_control(&exec_result.sql) {
+                Some(TransactionControl::Begin) => self.begin(),
+                Some(TransactionControl::Commit | TransactionControl::Rollback) => self.end(),
+                None if self.open => self.pending_statements += 1,
+                None => {}
+            }
+        }
+    }
+}
+
+/// Handle to an in-flight query, kept by whoever started it so it can be cancelled from elsewhere
+#[derive(Clone)]
+pub struct RunningQuery {
+    connection: Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RunningQuery {
+    pub fn new(connection: Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>) -> Self {
+        Self {
+            connection,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether `cancel` was called, so partial results can be reported as such
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub async fn cancel(&self) -> Result<(), DbError> {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.connection.read().await.cancel_query().await
+    }
+}
+
+/// A connection owned by a single consumer, such as an editor tab, instead of the shared pool.
+/// Everything run through it shares one physical session, so transactions stay open between calls.
+#[derive(Clone)]
+pub struct DbSession {
+    id: String,
+    connection_id: String,
+    database: Option<String>,
+    connection: Arc<RwLock<Box<dyn DbConnection + Send + Sync>>>,
+    transaction: Arc<std::sync::RwLock<TransactionState>>,
+    closed: Arc<AtomicBool>,
+    /// Script being executed, shown in the connection status view
+    running_sql: Arc<std::sync::RwLock<Option<String>>>,
+    last_active: Arc<std::sync::RwLock<Instant>>,
+}
+
+/// Marks a session busy with a script until dropped, however the execution ends
+struct ActivityGuard<'a> {
+    session: &'a DbSession,
+}
+
+impl Drop for ActivityGuard<'_> {
+    fn drop(&mut self) {
+        *self.session.running_sql.write().unwrap() = None;
+        *self.session.last_active.write().unwrap() = Instant::now();
+    }
+}
+
+impl DbSession {
+    pub(crate) fn new(
+        id: String,
+        connection_id: String,
+        database: Option<String>,
+        connection: Box<dyn DbConnection + Send + Sync>,
+    ) -> Self {
+        Self {
+            id,
+            connection_id,
+            database,
+            connection: Arc::new(RwLock::new(connection)),
+            transaction: Arc::new(std::sync::RwLock::new(TransactionState::default())),
+            closed: Arc::new(AtomicBool::new(false)),
+            running_sql: Arc::new(std::sync::RwLock::new(None)),
+            last_active: Arc::new(std::sync::RwLock::new(Instant::now())),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn connection_id(&self) -> &str {
+        &self.connection_id
+    }
+
+    pub fn database(&self) -> Option<&str> {
+        self.database.as_deref()
+    }
+
+    pub fn connection(&self) -> Arc<RwLock<Box<dyn DbConnection + Send + Sync>>> {
+        self.connection.clone()
+    }
+
+    pub fn transaction(&self) -> TransactionState {
+        *self.transaction.read().unwrap()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Script the session is executing, `None` while idle
+    pub fn running_sql(&self) -> Option<String> {
+        self.running_sql.read().unwrap().clone()
+    }
+
+    /// Time since the last execution ended
+    pub fn idle_for(&self) -> Duration {
+        self.last_active.read().unwrap().elapsed()
+    }
+
+    /// Server session ids of the running statements, see `DbConnection::running_server_ids`
+    pub fn running_server_ids(&self) -> Vec<u64> {
+        self.connection
+            .try_read()
+            .map(|conn| conn.running_server_ids())
+            .unwrap_or_default()
+    }
+
+    fn start_activity(&self, script: &str) -> ActivityGuard<'_> {
+        *self.running_sql.write().unwrap() = Some(script.to_string());
+        ActivityGuard { session: self }
+    }
+
+    /// Execute a script and keep track of transaction statements it contains
+    pub async fn execute(&self, script: &str, options: ExecOptions) -> Result<Vec<SqlResult>, DbError> {
+        let _activity = self.start_activity(script);
+        let conn = self.connection.read().await;
+        let results = conn.execute(script, options).await?;
+        self.transaction.write().unwrap().observe(&results);
+        Ok(results)
+    }
+
+    /// Execute a script with placeholders, one statement at a time with its values bound as parameters.
+    /// `values` is keyed by `Placeholder::key`, see `script_parameters`.
+    pub async fn execute_with_params(
+        &self,
+        script: &str,
+        values: &HashMap<String, SqlValue>,
+        options: ExecOptions,
+    ) -> Result<Vec<SqlResult>, DbError> {
+        let _activity = self.start_activity(script);
+        let conn = self.connection.read().await;
+        let dialect = conn
+            .config()
+            .map(|config| config.database_type)
+            .ok_or_else(|| DbError::ConnectionError("Session has no connection config".to_string()))?;
+
+        let mut results = Vec::new();
+        for statement in bind_script(script, values, dialect)? {
+            let params = (!statement.params.is_empty()).then_some(statement.params);
+            let mut result = conn.query(&statement.sql, params, options.clone()).await?;
+            if let SqlResult::Error(error) = &mut result {
+                error.range = Some(statement.range);
+            }
+            let failed = matches!(result, SqlResult::Error(_));
+            results.push(result);
+            if failed && options.stop_on_error {
+                break;
+            }
+        }
+
+        self.transaction.write().unwrap().observe(&results);
+        Ok(results)
+    }
+
+    pub async fn begin(&self) -> Result<(), DbError> {
+        self.connection.read().await.begin_transaction().await?;
+        self.transaction.write().unwrap().begin();
+        Ok(())
+    }
+
+    pub async fn commit(&self) -> Result<(), DbError> {
+        self.connection.read().await.commit().await?;
+        self.transaction.write().unwrap().end();
+        Ok(())
+    }
+
+    pub async fn rollback(&self) -> Result<(), DbError> {
+        self.connection.read().await.rollback().await?;
+        self.transaction.write().unwrap().end();
+        Ok(())
+    }
+
+    /// Disconnect; the server rolls back whatever transaction is still open
+    pub(crate) async fn close(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let connection = self.connection.clone();
+        let result = TOKIO_HANDLE
+            .spawn(async move { connection.write().await.disconnect().await })
+            .await;
+        match result {
+            Ok(Err(e)) => tracing::warn!("Failed to close session {}: {}", self.id, e),
+            Err(e) => tracing::warn!("Failed to close session {}: {}", self.id, e),
+            Ok(Ok(())) => {}
+        }
+        self.transaction.write().unwrap().end();
+    }
+}
@@ -2,20 +2,28 @@ use std::sync::RwLock;
 use std::time::Instant;
 
 use async_trait::async_trait;
-use sqlx::sqlite::{SqlitePoolOptions, SqliteRow};
-use sqlx::{Column, Row, SqlitePool};
-use one_core::storage::DbConnectionConfig;
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{SqliteArguments, SqliteConnection, SqlitePoolOptions, SqliteRow};
+use sqlx::{Column, Connection, Executor, Row, Sqlite, SqlitePool, TypeInfo};
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+use tokio::sync::oneshot;
+use crate::cancel::{RunningQueries, RunningQueryGuard};
 use crate::connection::{DbConnection, DbError};
 use crate::executor::{
-    ExecOptions, ExecResult, QueryResult, SqlErrorInfo, SqlResult, SqlScriptSplitter,
+    ExecOptions, ExecResult, QueryResult, ResultColumn, SqlErrorInfo, SqlResult, SqlScriptSplitter,
     SqlStatementClassifier,
 };
 use crate::runtime::TOKIO_HANDLE;
+use crate::stream::{self, QueryStream};
 use crate::types::{SqlValue};
 
 pub struct SqliteDbConnection {
     config: Option<DbConnectionConfig>,
     pool: RwLock<Option<SqlitePool>>,
+    // Pinned sessions keep their single connection for as long as they are open
+    pinned_session: bool,
+    // Database handles of the statements in flight, targets of sqlite3_interrupt
+    running: RunningQueries,
 }
 
 impl SqliteDbConnection {
@@ -23,6 +31,16 @@ impl SqliteDbConnection {
         Self {
             config: Some(config),
             pool: RwLock::new(None),
+            pinned_session: false,
+            running: RunningQueries::default(),
+        }
+    }
+
+    /// Connection whose single pooled connection is never recycled, so session state survives
+    pub fn new_session(config: DbConnectionConfig) -> Self {
+        Self {
+            pinned_session: true,
+            ..Self::new(config)
         }
     }
 
@@ -35,13 +53,35 @@ impl SqliteDbConnection {
             .ok_or_else(|| DbError::ConnectionError("Not connected to database".to_string()))
     }
 
-    fn extract_value(row: &SqliteRow, index: usize) -> Option<String> {
+    fn bind_parameter<'q>(
+        query: sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>,
+        param: SqlValue,
+    ) -> sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>> {
+        match param {
+            SqlValue::Null => query.bind(None::<i64>),
+            SqlValue::Bool(v) => query.bind(v),
+            SqlValue::Int(v) => query.bind(v),
+            SqlValue::Float(v) => query.bind(v),
+            SqlValue::String(v) => query.bind(v),
+            SqlValue::Bytes(v) => query.bind(v),
+            // SQLite keeps JSON, decimals and UUIDs as text
+            SqlValue::Json(v) => query.bind(v.to_string()),
+            SqlValue::Decimal(v) => query.bind(v),
+            SqlValue::Date(v) => query.bind(v),
+            SqlValue::Time(v) => query.bind(v),
+            SqlValue::DateTime(v) => query.bind(v),
+            SqlValue::DateTimeTz(v) => query.bind(v),
+            SqlValue::Uuid(v) => query.bind(v.to_string()),
+        }
+    }
+
+    fn extract_value(row: &SqliteRow, index: usize) -> SqlValue {
         use sqlx::types::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-        use sqlx::{TypeInfo, ValueRef};
+        use sqlx::ValueRef;
 
         if let Ok(val) = row.try_get_raw(index) {
             if val.is_null() {
-                return None;
+                return SqlValue::Null;
             }
         }
 
@@ -51,46 +91,193 @@ impl SqliteDbConnection {
         match type_name.as_str() {
             "DATETIME" | "TIMESTAMP" => {
                 if let Ok(val) = row.try_get::<NaiveDateTime, _>(index) {
-                    return Some(val.format("%Y-%m-%d %H:%M:%S").to_string());
+                    return SqlValue::DateTime(val);
                 }
             }
             "DATE" => {
                 if let Ok(val) = row.try_get::<NaiveDate, _>(index) {
-                    return Some(val.format("%Y-%m-%d").to_string());
+                    return SqlValue::Date(val);
                 }
             }
             "TIME" => {
                 if let Ok(val) = row.try_get::<NaiveTime, _>(index) {
-                    return Some(val.format("%H:%M:%S").to_string());
+                    return SqlValue::Time(val);
                 }
             }
             _ => {}
         }
 
         if let Ok(val) = row.try_get::<String, _>(index) {
-            return Some(val);
+            return SqlValue::String(val);
         }
 
         if let Ok(val) = row.try_get::<i64, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Int(val);
         }
 
         if let Ok(val) = row.try_get::<f64, _>(index) {
-            return Some(val.to_string());
+            return SqlValue::Float(val);
         }
 
         if let Ok(val) = row.try_get::<bool, _>(index) {
-            return Some(if val { "1" } else { "0" }.to_string());
+            return SqlValue::Bool(val);
         }
 
         if let Ok(val) = row.try_get::<Vec<u8>, _>(index) {
-            if let Ok(s) = String::from_utf8(val.clone()) {
-                return Some(s);
+            return SqlValue::Bytes(val);
+        }
+
+        // Storage classes always decode above; keep whatever bytes SQLite holds
+        row.try_get_unchecked::<Vec<u8>, _>(index)
+            .map(SqlValue::Bytes)
+            .unwrap_or(SqlValue::Null)
+    }
+
+    /// Run one statement on a single connection, either a pooled one or an open transaction
+    async fn run_statement(
+        conn: &mut SqliteConnection,
+        sql: &str,
+        max_rows: Option<usize>,
+    ) -> SqlResult {
+        let start = Instant::now();
+
+        if SqlStatementClassifier::is_query_statement(sql) {
+            let mut rows = conn.fetch(sqlx::raw_sql(sql));
+            match stream::read_page(&mut rows, max_rows, Self::extract_value).await {
+                Ok(page) => SqlResult::Query(page.into_query_result(sql, start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new(sql, e.to_string())),
+            }
+        } else {
+            match conn.execute(sqlx::raw_sql(sql)).await {
+                Ok(exec_result) => {
+                    let rows_affected = exec_result.rows_affected();
+                    SqlResult::Exec(ExecResult {
+                        sql: sql.to_string(),
+                        rows_affected,
+                        elapsed_ms: start.elapsed().as_millis(),
+                        message: Some(SqlStatementClassifier::format_message(sql, rows_affected)),
+                    })
+                }
+                Err(e) => SqlResult::Error(SqlErrorInfo::new(sql, e.to_string())),
+            }
+        }
+    }
+
+    /// Acquire a pooled connection and register its database handle so the statement can be interrupted
+    async fn acquire_tracked(
+        pool: &SqlitePool,
+        running: &RunningQueries,
+    ) -> Result<(PoolConnection<Sqlite>, RunningQueryGuard), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        let handle = conn.lock_handle().await?.as_raw_handle().as_ptr() as usize as u64;
+        let guard = running.track(handle);
+        Ok((conn, guard))
+    }
+
+    /// Run statements one after another, stopping early on error or cancellation
+    async fn run_statements(
+        conn: &mut SqliteConnection,
+        statements: &[String],
+        options: &ExecOptions,
+        running: &RunningQueries,
+    ) -> Vec<SqlResult> {
+        let mut results = Vec::new();
+
+        for sql in statements {
+            if running.is_cancelled() {
+                break;
+            }
+
+            let result = Self::run_statement(conn, sql, options.max_rows).await;
+            let failed = matches!(result, SqlResult::Error(_));
+            results.push(result);
+
+            if failed && options.stop_on_error {
+                break;
             }
-            return Some(format!("0x{}", hex::encode(&val)));
         }
 
-        Some(format!("<{}>", type_name))
+        results
+    }
+
+    /// Run a script outside a transaction and send back its results.
+    /// A query ending the script keeps its cursor open afterwards, so the rows past
+    /// the first page stay readable through `QueryResult::more_rows`.
+    async fn run_script(
+        conn: &mut SqliteConnection,
+        statements: Vec<String>,
+        options: ExecOptions,
+        running: &RunningQueries,
+        results_tx: oneshot::Sender<Result<Vec<SqlResult>, DbError>>,
+    ) {
+        let Some((last, leading)) = statements.split_last() else {
+            let _ = results_tx.send(Ok(Vec::new()));
+            return;
+        };
+
+        let mut results = Self::run_statements(conn, leading, &options, running).await;
+        let stopped = running.is_cancelled()
+            || (options.stop_on_error && matches!(results.last(), Some(SqlResult::Error(_))));
+        if stopped || !SqlStatementClassifier::is_query_statement(last) {
+            if !stopped {
+                results.push(Self::run_statement(conn, last, options.max_rows).await);
+            }
+            let _ = results_tx.send(Ok(results));
+            return;
+        }
+
+        let start = Instant::now();
+        let mut rows = conn.fetch(sqlx::raw_sql(last));
+        let pump = match stream::read_page(&mut rows, options.max_rows, Self::extract_value).await {
+            Ok(page) => {
+                let batch_size = options.max_rows.unwrap_or(usize::MAX);
+                let (result, pump) = page.into_streamed_result(last, start.elapsed().as_millis(), batch_size);
+                results.push(SqlResult::Query(result));
+                pump
+            }
+            Err(e) => {
+                results.push(SqlResult::Error(SqlErrorInfo::new(last.clone(), e.to_string())));
+                None
+            }
+        };
+        let _ = results_tx.send(Ok(results));
+
+        if let Some(pump) = pump {
+            pump.run(rows).await;
+        }
+    }
+
+    /// Run the whole script inside BEGIN/COMMIT.
+    /// With `stop_on_error` the first failure rolls everything back, and so does a cancellation.
+    async fn execute_in_transaction(
+        conn: &mut SqliteConnection,
+        statements: Vec<String>,
+        options: ExecOptions,
+        running: &RunningQueries,
+    ) -> Result<Vec<SqlResult>, DbError> {
+        let mut tx = conn
+            .begin()
+            .await
+            .map_err(|e| DbError::QueryError(format!("Failed to begin transaction: {}", e)))?;
+        let mut results = Self::run_statements(&mut tx, &statements, &options, running).await;
+
+        let failed = options.stop_on_error && matches!(results.last(), Some(SqlResult::Error(_)));
+        let start = Instant::now();
+        if failed || running.is_cancelled() {
+            results.push(match tx.rollback().await {
+                Ok(()) => SqlResult::Exec(ExecResult::transaction_rolled_back(
+                    start.elapsed().as_millis(),
+                )),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new("ROLLBACK", e.to_string())),
+            });
+        } else {
+            results.push(match tx.commit().await {
+                Ok(()) => SqlResult::Exec(ExecResult::transaction_committed(start.elapsed().as_millis())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new("COMMIT", e.to_string())),
+            });
+        }
+
+        Ok(results)
     }
 }
 
@@ -116,12 +303,14 @@ impl DbConnection for SqliteDbConnection {
 
         let url = format!("sqlite://{}", database_path);
 
+        let pinned_session = self.pinned_session;
         let pool = TOKIO_HANDLE
             .spawn(async move {
-                SqlitePoolOptions::new()
-                    .max_connections(1)
-                    .connect(&url)
-                    .await
+                let mut pool_options = SqlitePoolOptions::new().max_connections(1);
+                if pinned_session {
+                    pool_options = pool_options.idle_timeout(None).max_lifetime(None);
+                }
+                pool_options.connect(&url).await
             })
             .await
             .map_err(|e| {
@@ -156,161 +345,42 @@ impl DbConnection for SqliteDbConnection {
         options: ExecOptions,
     ) -> Result<Vec<SqlResult>, DbError> {
         let pool = self.ensure_connected()?;
-        let statements = SqlScriptSplitter::split(script);
-        let mut results = Vec::new();
-
-        for sql in statements {
-            let sql = sql.trim();
-            if sql.is_empty() {
-                continue;
-            }
-
-            let modified_sql = if let Some(max_rows) = options.max_rows {
-                if SqlStatementClassifier::is_query_statement(sql)
-                    && !sql.to_uppercase().contains(" LIMIT ")
-                {
-                    format!("{} LIMIT {}", sql, max_rows)
-                } else {
-                    sql.to_string()
+        let statements: Vec<String> = SqlScriptSplitter::split_for(script, DatabaseType::SQLite)
+            .into_iter()
+            .map(|sql| sql.trim().to_string())
+            .filter(|sql| !sql.is_empty())
+            .collect();
+
+        let running = self.running.clone();
+        running.reset();
+        // Results come back before the task ends, it may go on reading the last query's rows
+        let (results_tx, results_rx) = oneshot::channel();
+        TOKIO_HANDLE.spawn(async move {
+            let (mut conn, _running_guard) = match Self::acquire_tracked(&pool, &running).await {
+                Ok(acquired) => acquired,
+                Err(e) => {
+                    let _ = results_tx.send(Err(DbError::ConnectionError(format!("Failed to acquire connection: {}", e))));
+                    return;
                 }
-            } else {
-                sql.to_string()
             };
-
-            let is_query = SqlStatementClassifier::is_query_statement(&modified_sql);
-            let start = Instant::now();
-
-            let result = if is_query {
-                let pool = pool.clone();
-                let sql_to_exec = modified_sql.clone();
-                let original_sql = sql.to_string();
-
-                match TOKIO_HANDLE
-                    .spawn(async move { sqlx::raw_sql(&sql_to_exec).fetch_all(&pool).await })
-                    .await
-                {
-                    Ok(Ok(rows)) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-
-                        if rows.is_empty() {
-                            SqlResult::Query(QueryResult {
-                                sql: original_sql,
-                                columns: Vec::new(),
-                                rows: Vec::new(),
-                                elapsed_ms,
-                            })
-                        } else {
-                            let columns: Vec<String> = rows[0]
-                                .columns()
-                                .iter()
-                                .map(|col| col.name().to_string())
-                                .collect();
-
-                            let data_rows: Vec<Vec<Option<String>>> = rows
-                                .iter()
-                                .map(|row| {
-                                    (0..columns.len())
-                                        .map(|i| Self::extract_value(row, i))
-                                        .collect()
-                                })
-                                .collect();
-
-                            SqlResult::Query(QueryResult {
-                                sql: original_sql,
-                                columns,
-                                rows: data_rows,
-                                elapsed_ms,
-                            })
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
+            if options.transactional {
+                let _ = results_tx.send(Self::execute_in_transaction(&mut conn, statements, options, &running).await);
             } else {
-                let pool = pool.clone();
-                let sql_to_exec = modified_sql.clone();
-                let original_sql = sql.to_string();
-
-                match TOKIO_HANDLE
-                    .spawn(async move { sqlx::raw_sql(&sql_to_exec).execute(&pool).await })
-                    .await
-                {
-                    Ok(Ok(exec_result)) => {
-                        let elapsed_ms = start.elapsed().as_millis();
-                        let rows_affected = exec_result.rows_affected();
-                        let message =
-                            SqlStatementClassifier::format_message(&original_sql, rows_affected);
-
-                        SqlResult::Exec(ExecResult {
-                            sql: original_sql,
-                            rows_affected,
-                            elapsed_ms,
-                            message: Some(message),
-                        })
-                    }
-                    Ok(Err(e)) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                    Err(e) => {
-                        let result = SqlResult::Error(SqlErrorInfo {
-                            sql: sql.to_string(),
-                            message: e.to_string(),
-                        });
-
-                        results.push(result);
-
-                        if options.stop_on_error {
-                            break;
-                        }
-                        continue;
-                    }
-                }
-            };
-
-            results.push(result);
-        }
+                Self::run_script(&mut conn, statements, options, &running, results_tx).await;
+            }
+        });
+        let mut results = results_rx
+            .await
+            .map_err(|e| DbError::QueryError(format!("Query task ended unexpectedly: {}", e)))??;
 
+        SqlScriptSplitter::locate_errors(script, DatabaseType::SQLite, &mut results);
         Ok(results)
     }
 
     async fn query(
         &self,
         query: &str,
-        _params: Option<Vec<SqlValue>>,
+        params: Option<Vec<SqlValue>>,
         _options: ExecOptions,
     ) -> Result<SqlResult, DbError> {
         let pool = self.ensure_connected()?;
@@ -319,11 +389,21 @@ impl DbConnection for SqliteDbConnection {
 
         let result = if is_query {
             let pool = pool.clone();
+            let running = self.running.clone();
             let query_str = query.to_string();
             let query_str_clone = query_str.clone();
 
             match TOKIO_HANDLE
-                .spawn(async move { sqlx::raw_sql(&query_str_clone).fetch_all(&pool).await })
+                .spawn(async move {
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    match params {
+                        Some(params) => {
+                            let bound = params.into_iter().fold(sqlx::query(&query_str_clone), Self::bind_parameter);
+                            conn.fetch_all(bound).await
+                        }
+                        None => conn.fetch_all(sqlx::raw_sql(&query_str_clone)).await,
+                    }
+                })
                 .await
             {
                 Ok(Ok(rows)) => {
@@ -335,15 +415,17 @@ impl DbConnection for SqliteDbConnection {
                             columns: Vec::new(),
                             rows: Vec::new(),
                             elapsed_ms,
+                            has_more: false,
+                            more_rows: None,
                         })
                     } else {
-                        let columns: Vec<String> = rows[0]
+                        let columns: Vec<ResultColumn> = rows[0]
                             .columns()
                             .iter()
-                            .map(|col| col.name().to_string())
+                            .map(|col| ResultColumn::new(col.name(), col.type_info().name()))
                             .collect();
 
-                        let data_rows: Vec<Vec<Option<String>>> = rows
+                        let data_rows: Vec<Vec<SqlValue>> = rows
                             .iter()
                             .map(|row| {
                                 (0..columns.len())
@@ -357,25 +439,31 @@ impl DbConnection for SqliteDbConnection {
                             columns,
                             rows: data_rows,
                             elapsed_ms,
+                            has_more: false,
+                            more_rows: None,
                         })
                     }
                 }
-                Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                    sql: query.to_string(),
-                    message: e.to_string(),
-                }),
-                Err(e) => SqlResult::Error(SqlErrorInfo {
-                    sql: query.to_string(),
-                    message: e.to_string(),
-                }),
+                Ok(Err(e)) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string())),
             }
         } else {
             let pool = pool.clone();
+            let running = self.running.clone();
             let query_str = query.to_string();
             let query_str_clone = query_str.clone();
 
             match TOKIO_HANDLE
-                .spawn(async move { sqlx::raw_sql(&query_str_clone).execute(&pool).await })
+                .spawn(async move {
+                    let (mut conn, _running_guard) = Self::acquire_tracked(&pool, &running).await?;
+                    match params {
+                        Some(params) => {
+                            let bound = params.into_iter().fold(sqlx::query(&query_str_clone), Self::bind_parameter);
+                            conn.execute(bound).await
+                        }
+                        None => conn.execute(sqlx::raw_sql(&query_str_clone)).await,
+                    }
+                })
                 .await
             {
                 Ok(Ok(exec_result)) => {
@@ -391,17 +479,364 @@ impl DbConnection for SqliteDbConnection {
                         message: Some(message),
                     })
                 }
-                Ok(Err(e)) => SqlResult::Error(SqlErrorInfo {
-                    sql: query.to_string(),
-                    message: e.to_string(),
-                }),
-                Err(e) => SqlResult::Error(SqlErrorInfo {
-                    sql: query.to_string(),
-                    message: e.to_string(),
-                }),
+                Ok(Err(e)) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string())),
+                Err(e) => SqlResult::Error(SqlErrorInfo::new(query, e.to_string())),
             }
         };
 
         Ok(result)
     }
+
+    async fn query_stream(&self, query: &str, batch_size: usize) -> Result<QueryStream, DbError> {
+        let pool = self.ensure_connected()?;
+        let sql = query.to_string();
+        let running = self.running.clone();
+        running.reset();
+
+        let (stream_tx, stream_rx) = oneshot::channel();
+        TOKIO_HANDLE.spawn(async move {
+            let (mut conn, _running_guard) = match Self::acquire_tracked(&pool, &running).await {
+                Ok(acquired) => acquired,
+                Err(e) => {
+                    let _ = stream_tx.send(Err(DbError::ConnectionError(format!("Failed to acquire connection: {}", e))));
+                    return;
+                }
+            };
+            let mut rows = conn.fetch(sqlx::raw_sql(&sql));
+            match stream::read_page(&mut rows, Some(batch_size), Self::extract_value).await {
+                Ok(page) => {
+                    let (stream, pump) = page.into_stream(batch_size);
+                    let _ = stream_tx.send(Ok(stream));
+                    if let Some(pump) = pump {
+                        pump.run(rows).await;
+                    }
+                }
+                Err(e) => {
+                    let _ = stream_tx.send(Err(DbError::QueryError(e.to_string())));
+                }
+            }
+        });
+
+        stream_rx
+            .await
+            .map_err(|e| DbError::QueryError(format!("Query task ended unexpectedly: {}", e)))?
+    }
+
+    async fn cancel_query(&self) -> Result<(), DbError> {
+        self.ensure_connected()?;
+        self.running.request_cancel();
+        self.running.with_handles(|handles| {
+            for handle in handles {
+                // SAFETY: a handle stays registered only while its connection is checked out of the
+                // pool, and the lock held here keeps it registered; sqlite3_interrupt is thread-safe.
+                unsafe {
+                    libsqlite3_sys::sqlite3_interrupt(*handle as usize as *mut libsqlite3_sys::sqlite3);
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config;
+    use crate::types::FieldType;
+    use one_core::storage::DatabaseType;
+
+    fn connect_in_memory() -> SqliteDbConnection {
+        let mut conn = SqliteDbConnection::new_session(test_config(DatabaseType::SQLite, &[]));
+        TOKIO_HANDLE.block_on(conn.connect()).unwrap();
+        TOKIO_HANDLE
+            .block_on(conn.execute(
+                "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+                ExecOptions::default(),
+            ))
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_ping_fails_once_the_connection_is_closed() {
+        let conn = connect_in_memory();
+        TOKIO_HANDLE.block_on(conn.ping()).unwrap();
+
+        // Closed under the connection, as when the server drops it: queries still return `Ok`
+        TOKIO_HANDLE.block_on(conn.ensure_connected().unwrap().close());
+        let result = TOKIO_HANDLE.block_on(conn.query("SELECT 1", None, ExecOptions::default())).unwrap();
+        assert!(matches!(result, SqlResult::Error(_)));
+        assert!(TOKIO_HANDLE.block_on(conn.ping()).is_err());
+    }
+
+    fn count_items(conn: &SqliteDbConnection) -> String {
+        let result = TOKIO_HANDLE
+            .block_on(conn.query("SELECT COUNT(*) FROM items", None, ExecOptions::default()))
+            .unwrap();
+        match result {
+            SqlResult::Query(q) => q.rows[0][0].to_string(),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    fn transactional(stop_on_error: bool) -> ExecOptions {
+        ExecOptions {
+            stop_on_error,
+            transactional: true,
+            max_rows: None,
+        }
+    }
+
+    #[test]
+    fn test_transactional_script_commits() {
+        let conn = connect_in_memory();
+        let results = TOKIO_HANDLE
+            .block_on(conn.execute(
+                "INSERT INTO items VALUES (1, 'a'); INSERT INTO items VALUES (2, 'b');",
+                transactional(true),
+            ))
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(&results[2], SqlResult::Exec(r) if r.sql == "COMMIT"));
+        assert_eq!(count_items(&conn), "2");
+    }
+
+    #[test]
+    fn test_transactional_script_rolls_back_on_error() {
+        let conn = connect_in_memory();
+        let results = TOKIO_HANDLE
+            .block_on(conn.execute(
+                "INSERT INTO items VALUES (1, 'a'); INSERT INTO items VALUES (1, 'dup'); INSERT INTO items VALUES (3, 'c');",
+                transactional(true),
+            ))
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[1], SqlResult::Error(_)));
+        assert!(matches!(&results[2], SqlResult::Exec(r) if r.is_rollback()));
+        assert_eq!(count_items(&conn), "0");
+    }
+
+    #[test]
+    fn test_transactional_script_without_stop_keeps_successful_statements() {
+        let conn = connect_in_memory();
+        let results = TOKIO_HANDLE
+            .block_on(conn.execute(
+                "INSERT INTO items VALUES (1, 'a'); INSERT INTO items VALUES (1, 'dup'); INSERT INTO items VALUES (3, 'c');",
+                transactional(false),
+            ))
+            .unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[3], SqlResult::Exec(r) if r.sql == "COMMIT"));
+        assert_eq!(count_items(&conn), "2");
+    }
+
+    #[test]
+    fn test_manual_transaction_spans_execute_calls() {
+        let conn = connect_in_memory();
+        TOKIO_HANDLE.block_on(conn.begin_transaction()).unwrap();
+        TOKIO_HANDLE
+            .block_on(conn.execute("INSERT INTO items VALUES (1, 'a')", ExecOptions::default()))
+            .unwrap();
+        TOKIO_HANDLE
+            .block_on(conn.execute("INSERT INTO items VALUES (2, 'b')", ExecOptions::default()))
+            .unwrap();
+        assert_eq!(count_items(&conn), "2");
+
+        TOKIO_HANDLE.block_on(conn.rollback()).unwrap();
+        assert_eq!(count_items(&conn), "0");
+    }
+
+    #[test]
+    fn test_non_transactional_script_keeps_statements_before_error() {
+        let conn = connect_in_memory();
+        let results = TOKIO_HANDLE
+            .block_on(conn.execute(
+                "INSERT INTO items VALUES (1, 'a'); INSERT INTO items VALUES (1, 'dup'); INSERT INTO items VALUES (3, 'c');",
+                ExecOptions::default(),
+            ))
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(count_items(&conn), "1");
+    }
+
+    #[test]
+    fn test_cancel_query_interrupts_running_script() {
+        let conn = std::sync::Arc::new(connect_in_memory());
+        let running = conn.clone();
+        let task = TOKIO_HANDLE.spawn(async move {
+            running
+                .execute(
+                    "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 100000000) SELECT COUNT(*) FROM c; INSERT INTO items VALUES (1, 'a');",
+                    ExecOptions {
+                        stop_on_error: false,
+                        transactional: false,
+                        max_rows: None,
+                    },
+                )
+                .await
+        });
+
+        // Give the counting query time to start
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        TOKIO_HANDLE.block_on(conn.cancel_query()).unwrap();
+        let results = TOKIO_HANDLE.block_on(task).unwrap().unwrap();
+
+        // The interrupted query fails and the rest of the script is skipped
+        assert_eq!(results.len(), 1);
+        assert!(matches!(&results[0], SqlResult::Error(e) if e.message.contains("interrupted")));
+        assert_eq!(count_items(&conn), "0");
+    }
+
+    #[test]
+    fn test_query_returns_typed_cells_and_column_types() {
+        let conn = connect_in_memory();
+        TOKIO_HANDLE
+            .block_on(conn.execute(
+                "CREATE TABLE typed (n INTEGER, r REAL, t TEXT, b BLOB, d DATETIME); \
+                 INSERT INTO typed VALUES (42, 1.5, 'x', X'DEAD', '2024-05-06 07:08:09'), (NULL, NULL, NULL, NULL, NULL);",
+                ExecOptions::default(),
+            ))
+            .unwrap();
+
+        let result = TOKIO_HANDLE
+            .block_on(conn.query("SELECT n, r, t, b, d FROM typed", None, ExecOptions::default()))
+            .unwrap();
+        let SqlResult::Query(q) = result else {
+            panic!("unexpected result: {:?}", result);
+        };
+
+        let field_types: Vec<FieldType> = q.columns.iter().map(|c| c.field_type).collect();
+        assert_eq!(
+            field_types,
+            vec![FieldType::Integer, FieldType::Decimal, FieldType::LongText, FieldType::Binary, FieldType::DateTime]
+        );
+        assert_eq!(q.rows[0][0], SqlValue::Int(42));
+        assert_eq!(q.rows[0][1], SqlValue::Float(1.5));
+        assert_eq!(q.rows[0][2], SqlValue::String("x".to_string()));
+        assert_eq!(q.rows[0][3].to_string(), "0xdead");
+        assert_eq!(q.rows[0][4].to_string(), "2024-05-06 07:08:09");
+        assert!(q.rows[1].iter().all(SqlValue::is_null));
+    }
+
+    fn insert_items(conn: &SqliteDbConnection, count: usize) {
+        let values: Vec<String> = (1..=count).map(|i| format!("({}, 'item{}')", i, i)).collect();
+        TOKIO_HANDLE
+            .block_on(conn.execute(
+                &format!("INSERT INTO items (id, name) VALUES {}", values.join(", ")),
+                ExecOptions::default(),
+            ))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_query_past_max_rows_streams_remaining_rows() {
+        let conn = connect_in_memory();
+        insert_items(&conn, 5);
+
+        let options = ExecOptions {
+            max_rows: Some(2),
+            ..ExecOptions::default()
+        };
+        let results = TOKIO_HANDLE
+            .block_on(conn.execute("SELECT 1; SELECT id FROM items ORDER BY id", options))
+            .unwrap();
+        let SqlResult::Query(q) = &results[1] else {
+            panic!("unexpected result: {:?}", results[1]);
+        };
+        assert_eq!(q.rows, vec![vec![SqlValue::Int(1)], vec![SqlValue::Int(2)]]);
+        assert!(q.has_more);
+
+        let stream = q.more_rows.clone().expect("remaining rows should be streamed");
+        let mut ids = Vec::new();
+        while let Some(batch) = TOKIO_HANDLE.block_on(stream.next_batch()) {
+            ids.extend(batch.unwrap().into_iter().map(|row| row[0].to_string()));
+        }
+        assert_eq!(ids, vec!["3", "4", "5"]);
+        assert!(stream.is_finished());
+
+        // The connection is free again once the stream is drained
+        assert_eq!(count_items(&conn), "5");
+    }
+
+    #[test]
+    fn test_query_stream_reads_in_batches() {
+        let conn = connect_in_memory();
+        insert_items(&conn, 5);
+
+        let stream = TOKIO_HANDLE
+            .block_on(conn.query_stream("SELECT id, name FROM items ORDER BY id", 2))
+            .unwrap();
+        assert_eq!(stream.columns().len(), 2);
+
+        let mut sizes = Vec::new();
+        while let Some(batch) = TOKIO_HANDLE.block_on(stream.next_batch()) {
+            sizes.push(batch.unwrap().len());
+        }
+        assert_eq!(sizes, vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn test_closed_stream_releases_connection() {
+        let conn = connect_in_memory();
+        insert_items(&conn, 10);
+
+        let stream = TOKIO_HANDLE
+            .block_on(conn.query_stream("SELECT id FROM items", 2))
+            .unwrap();
+        assert!(TOKIO_HANDLE.block_on(stream.next_batch()).is_some());
+        TOKIO_HANDLE.block_on(stream.close());
+
+        assert!(TOKIO_HANDLE.block_on(stream.next_batch()).is_none());
+        assert_eq!(count_items(&conn), "10");
+    }
+
+    #[test]
+    fn test_query_binds_parameters() {
+        let conn = connect_in_memory();
+        TOKIO_HANDLE
+            .block_on(conn.query(
+                "INSERT INTO items (id, name) VALUES (?, ?)",
+                Some(vec![SqlValue::Int(1), SqlValue::String("it's; -- not sql".to_string())]),
+                ExecOptions::default(),
+            ))
+            .unwrap();
+
+        let result = TOKIO_HANDLE
+            .block_on(conn.query("SELECT name FROM items WHERE id = ?", Some(vec![SqlValue::Int(1)]), ExecOptions::default()))
+            .unwrap();
+        let SqlResult::Query(q) = result else {
+            panic!("unexpected result: {:?}", result);
+        };
+        assert_eq!(q.rows, vec![vec![SqlValue::String("it's; -- not sql".to_string())]]);
+    }
+
+    #[test]
+    fn test_session_executes_script_with_placeholders() {
+        let session = crate::session::DbSession::new(
+            "session".to_string(),
+            "test".to_string(),
+            None,
+            Box::new(connect_in_memory()),
+        );
+        let values = std::collections::HashMap::from([
+            (":name".to_string(), SqlValue::String("a".to_string())),
+            ("$1".to_string(), SqlValue::Int(7)),
+        ]);
+
+        let results = TOKIO_HANDLE
+            .block_on(session.execute_with_params(
+                "INSERT INTO items (id, name) VALUES ($1, :name); SELECT id FROM items WHERE name = :name",
+                &values,
+                ExecOptions::default(),
+            ))
+            .unwrap();
+        let SqlResult::Query(q) = &results[1] else {
+            panic!("unexpected result: {:?}", results[1]);
+        };
+        assert_eq!(q.rows, vec![vec![SqlValue::Int(7)]]);
+    }
 }
@@ -1,3 +1,5 @@
 mod connection;
+mod plugin;
 
 pub use connection::SqliteDbConnection;
+pub use plugin::SqlitePlugin;
@@ -0,0 +1,884 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use gpui_component::table::Column;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{ExecOptions, ExecResult, SqlResult};
+use crate::plugin::DatabasePlugin;
+use crate::sqlite::connection::SqliteDbConnection;
+use crate::types::*;
+
+/// SQLite database plugin implementation (stateless)
+///
+/// A SQLite connection is a single file. The attached schemas (`main`, `temp`
+/// and anything added with `ATTACH DATABASE`) are exposed as databases.
+pub struct SqlitePlugin;
+
+impl SqlitePlugin {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Escape a value used inside a single-quoted SQL string literal
+    fn escape_literal(value: &str) -> String {
+        value.replace('\'', "''")
+    }
+
+    /// Split a statement into words, keeping quoted identifiers whole
+    fn split_words(sql: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut quote: Option<char> = None;
+
+        for c in sql.chars() {
+            match quote {
+                Some(close) => {
+                    word.push(c);
+                    if c == close {
+                        quote = None;
+                    }
+                }
+                None if c.is_whitespace() => {
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                }
+                None => {
+                    quote = match c {
+                        '"' | '`' | '\'' => Some(c),
+                        '[' => Some(']'),
+                        _ => None,
+                    };
+                    word.push(c);
+                }
+            }
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+        words
+    }
+
+    /// Parse timing and event from a `CREATE TRIGGER` statement
+    ///
+    /// `CREATE [TEMP] TRIGGER [IF NOT EXISTS] name [BEFORE | AFTER | INSTEAD OF] event ...`,
+    /// the event is the word after the timing, which defaults to `BEFORE`.
+    fn parse_trigger_header(sql: &str) -> (String, String) {
+        let mut words = Self::split_words(sql)
+            .into_iter()
+            .map(|word| word.to_uppercase())
+            .skip_while(|word| word != "TRIGGER")
+            .skip(1)
+            .peekable();
+
+        if words.peek().map(String::as_str) == Some("IF") {
+            // NOT EXISTS
+            words.nth(2);
+        }
+        // Trigger name
+        words.next();
+
+        let timing = match words.peek().map(String::as_str) {
+            Some("BEFORE" | "AFTER") => words.next().unwrap_or_default(),
+            Some("INSTEAD") => {
+                words.nth(1);
+                "INSTEAD OF".to_string()
+            }
+            _ => "BEFORE".to_string(),
+        };
+        let event = words
+            .next()
+            .filter(|word| matches!(word.as_str(), "INSERT" | "UPDATE" | "DELETE"))
+            .unwrap_or_default();
+
+        (timing, event)
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabasePlugin for SqlitePlugin {
+    fn name(&self) -> DatabaseType {
+        DatabaseType::SQLite
+    }
+
+    async fn create_connection(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        let mut conn = SqliteDbConnection::new(config);
+        conn.connect().await?;
+        Ok(Box::new(conn))
+    }
+
+    async fn create_session(&self, config: DbConnectionConfig) -> Result<Box<dyn DbConnection + Send + Sync>, DbError> {
+        let mut conn = SqliteDbConnection::new_session(config);
+        conn.connect().await?;
+        Ok(Box::new(conn))
+    }
+
+    // === Database/Schema Level Operations ===
+
+    async fn list_databases(&self, connection: &dyn DbConnection) -> Result<Vec<String>> {
+        let result = connection.query(
+            "SELECT name FROM pragma_database_list ORDER BY seq",
+            None,
+            ExecOptions::default()
+        ).await.map_err(|e| anyhow::anyhow!("Failed to list databases: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            Ok(query_result.rows.iter()
+                .filter_map(|row| row.first().and_then(|v| v.as_text()))
+                .collect())
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    async fn list_databases_view(&self, connection: &dyn DbConnection) -> Result<ObjectView> {
+        use gpui::px;
+
+        let databases = self.list_databases_detailed(connection).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("charset", "Encoding").width(px(120.0)),
+            Column::new("tables", "Tables").width(px(80.0)).text_right(),
+            Column::new("file", "File").width(px(400.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = databases.iter().map(|db| {
+            vec![
+                db.name.clone(),
+                db.charset.as_deref().unwrap_or("-").to_string(),
+                db.table_count.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                db.comment.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} database(s)", databases.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_databases_detailed(&self, connection: &dyn DbConnection) -> Result<Vec<DatabaseInfo>> {
+        let result = connection.query(
+            "SELECT name, file FROM pragma_database_list ORDER BY seq",
+            None,
+            ExecOptions::default()
+        ).await.map_err(|e| anyhow::anyhow!("Failed to list databases: {}", e))?;
+
+        let encoding = match connection.query("SELECT encoding FROM pragma_encoding", None, ExecOptions::default()).await {
+            Ok(SqlResult::Query(r)) => r.rows.first().and_then(|row| row.first()).and_then(|v| v.as_text()),
+            _ => None,
+        };
+
+        if let SqlResult::Query(query_result) = result {
+            let mut databases = Vec::new();
+            for row in &query_result.rows {
+                let Some(name) = row.first().and_then(|v| v.as_text()) else {
+                    continue;
+                };
+                let file = row.get(1).and_then(|v| v.as_text()).filter(|s| !s.is_empty());
+
+                let count_sql = format!(
+                    "SELECT COUNT(*) FROM {}.sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'",
+                    self.quote_identifier(&name)
+                );
+                let table_count = match connection.query(&count_sql, None, ExecOptions::default()).await {
+                    Ok(SqlResult::Query(r)) => r.rows.first()
+                        .and_then(|row| row.first())
+                        .and_then(|v| v.as_text())
+                        .and_then(|s| s.parse::<i64>().ok()),
+                    _ => None,
+                };
+
+                databases.push(DatabaseInfo {
+                    name,
+                    charset: encoding.clone(),
+                    collation: None,
+                    size: None,
+                    table_count,
+                    comment: file,
+                });
+            }
+            Ok(databases)
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    // === Table Operations ===
+
+    async fn list_tables(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TableInfo>> {
+        let sql = format!(
+            "SELECT name FROM {}.sqlite_master \
+             WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\' \
+             ORDER BY name",
+            self.quote_identifier(database)
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list tables: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            Ok(query_result.rows.iter().map(|row| {
+                TableInfo {
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    comment: None,
+                    engine: None,
+                    row_count: None,
+                    create_time: None,
+                    charset: None,
+                    collation: None,
+                }
+            }).collect())
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    async fn list_tables_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let tables = self.list_tables(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(300.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = tables.iter().map(|table| {
+            vec![table.name.clone()]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} table(s)", tables.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_columns(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ColumnInfo>> {
+        let sql = format!(
+            "SELECT name, type, \"notnull\", dflt_value, pk \
+             FROM pragma_table_info('{}', '{}') \
+             ORDER BY cid",
+            Self::escape_literal(table),
+            Self::escape_literal(database)
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list columns: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            Ok(query_result.rows.iter().map(|row| {
+                ColumnInfo {
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    data_type: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                    is_nullable: row.get(2).and_then(|v| v.as_text()).map(|v| v == "0").unwrap_or(true),
+                    is_primary_key: row.get(4).and_then(|v| v.as_text()).map(|v| v != "0").unwrap_or(false),
+                    default_value: row.get(3).and_then(|v| v.as_text()),
+                    comment: None,
+                }
+            }).collect())
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    async fn list_columns_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let columns_data = self.list_columns(connection, database, table).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("type", "Type").width(px(150.0)),
+            Column::new("nullable", "Nullable").width(px(80.0)),
+            Column::new("key", "Key").width(px(80.0)),
+            Column::new("default", "Default").width(px(120.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = columns_data.iter().map(|col| {
+            vec![
+                col.name.clone(),
+                col.data_type.clone(),
+                if col.is_nullable { "YES" } else { "NO" }.to_string(),
+                if col.is_primary_key { "PRI" } else { "" }.to_string(),
+                col.default_value.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} column(s)", columns_data.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_indexes(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<IndexInfo>> {
+        let database = Self::escape_literal(database);
+        let sql = format!(
+            "SELECT il.name, ii.name, il.\"unique\", il.origin \
+             FROM pragma_index_list('{}', '{}') AS il \
+             JOIN pragma_index_info(il.name, '{}') AS ii \
+             ORDER BY il.name, ii.seqno",
+            Self::escape_literal(table),
+            database,
+            database
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list indexes: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            let mut indexes: HashMap<String, IndexInfo> = HashMap::new();
+
+            for row in query_result.rows {
+                let index_name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+                // Expression indexes have no column name
+                let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_else(|| "<expr>".to_string());
+                let is_unique = row.get(2).and_then(|v| v.as_text()).map(|v| v == "1").unwrap_or(false);
+                let index_type = row.get(3).and_then(|v| v.as_text()).map(|origin| match origin.as_str() {
+                    "pk" => "PRIMARY KEY".to_string(),
+                    "u" => "UNIQUE".to_string(),
+                    _ => "INDEX".to_string(),
+                });
+
+                indexes.entry(index_name.clone())
+                    .or_insert_with(|| IndexInfo {
+                        name: index_name,
+                        columns: Vec::new(),
+                        is_unique,
+                        index_type: index_type.clone(),
+                    })
+                    .columns.push(column_name);
+            }
+
+            let mut indexes: Vec<IndexInfo> = indexes.into_values().collect();
+            indexes.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(indexes)
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    async fn list_indexes_view(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let indexes = self.list_indexes(connection, database, table).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("columns", "Columns").width(px(250.0)),
+            Column::new("unique", "Unique").width(px(80.0)),
+            Column::new("type", "Type").width(px(120.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = indexes.iter().map(|idx| {
+            vec![
+                idx.name.clone(),
+                idx.columns.join(", "),
+                if idx.is_unique { "YES" } else { "NO" }.to_string(),
+                idx.index_type.as_deref().unwrap_or("-").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} index(es)", indexes.len()),
+            columns,
+            rows,
+        })
+    }
+
+    async fn list_foreign_keys(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<Vec<ForeignKeyInfo>> {
+        // Foreign keys are unnamed in the pragma output, name them after their id.
+        // "to" is NULL when the key references the parent's primary key implicitly
+        let sql = format!(
+            "SELECT 'fk_' || id, \"from\", NULL, \"table\", COALESCE(\"to\", '') \
+             FROM pragma_foreign_key_list('{}', '{}') \
+             ORDER BY id, seq",
+            Self::escape_literal(table),
+            Self::escape_literal(database)
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list foreign keys: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            let mut foreign_keys: Vec<ForeignKeyInfo> = Vec::new();
+
+            for row in query_result.rows {
+                let name = row.first().and_then(|v| v.as_text()).unwrap_or_default();
+                let column_name = row.get(1).and_then(|v| v.as_text()).unwrap_or_default();
+                let referenced_schema = row.get(2).and_then(|v| v.as_text());
+                let referenced_table = row.get(3).and_then(|v| v.as_text()).unwrap_or_default();
+                let referenced_column = row.get(4).and_then(|v| v.as_text()).unwrap_or_default();
+
+                // Rows arrive grouped by constraint, in column order
+                match foreign_keys.last_mut() {
+                    Some(fk) if fk.name == name => {
+                        fk.columns.push(column_name);
+                        fk.referenced_columns.push(referenced_column);
+                    }
+                    _ => foreign_keys.push(ForeignKeyInfo {
+                        name,
+                        columns: vec![column_name],
+                        referenced_schema,
+                        referenced_table,
+                        referenced_columns: vec![referenced_column],
+                    }),
+                }
+            }
+
+            Ok(foreign_keys)
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    // === View Operations ===
+
+    async fn list_views(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<ViewInfo>> {
+        let sql = format!(
+            "SELECT name, sql FROM {}.sqlite_master \
+             WHERE type = 'view' \
+             ORDER BY name",
+            self.quote_identifier(database)
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list views: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            Ok(query_result.rows.iter().map(|row| {
+                ViewInfo {
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    definition: row.get(1).and_then(|v| v.as_text()),
+                    comment: None,
+                }
+            }).collect())
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    async fn list_views_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let views = self.list_views(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+            Column::new("definition", "Definition").width(px(400.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = views.iter().map(|view| {
+            vec![
+                view.name.clone(),
+                view.definition.as_deref().unwrap_or("").to_string(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} view(s)", views.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Function Operations ===
+    // SQLite has no stored functions or procedures; user functions are
+    // registered by the host application and are not visible in the schema.
+
+    async fn list_functions(&self, _connection: &dyn DbConnection, _database: &str) -> Result<Vec<FunctionInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_functions_view(&self, _connection: &dyn DbConnection, _database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+        ];
+
+        Ok(ObjectView {
+            title: "0 function(s)".to_string(),
+            columns,
+            rows: vec![],
+        })
+    }
+
+    // === Procedure Operations ===
+
+    async fn list_procedures(&self, _connection: &dyn DbConnection, _database: &str) -> Result<Vec<FunctionInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_procedures_view(&self, _connection: &dyn DbConnection, _database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+        ];
+
+        Ok(ObjectView {
+            title: "0 procedure(s)".to_string(),
+            columns,
+            rows: vec![],
+        })
+    }
+
+    // === Trigger Operations ===
+
+    async fn list_triggers(&self, connection: &dyn DbConnection, database: &str) -> Result<Vec<TriggerInfo>> {
+        let sql = format!(
+            "SELECT name, tbl_name, sql FROM {}.sqlite_master \
+             WHERE type = 'trigger' \
+             ORDER BY name",
+            self.quote_identifier(database)
+        );
+
+        let result = connection.query(&sql, None, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list triggers: {}", e))?;
+
+        if let SqlResult::Query(query_result) = result {
+            Ok(query_result.rows.iter().map(|row| {
+                let definition = row.get(2).and_then(|v| v.as_text());
+                let (timing, event) = definition
+                    .as_deref()
+                    .map(Self::parse_trigger_header)
+                    .unwrap_or_default();
+
+                TriggerInfo {
+                    name: row.first().and_then(|v| v.as_text()).unwrap_or_default(),
+                    table_name: row.get(1).and_then(|v| v.as_text()).unwrap_or_default(),
+                    event,
+                    timing,
+                    definition,
+                }
+            }).collect())
+        } else {
+            Err(anyhow::anyhow!("Unexpected result type"))
+        }
+    }
+
+    async fn list_triggers_view(&self, connection: &dyn DbConnection, database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let triggers = self.list_triggers(connection, database).await?;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(180.0)),
+            Column::new("table", "Table").width(px(150.0)),
+            Column::new("event", "Event").width(px(100.0)),
+            Column::new("timing", "Timing").width(px(100.0)),
+        ];
+
+        let rows: Vec<Vec<String>> = triggers.iter().map(|trigger| {
+            vec![
+                trigger.name.clone(),
+                trigger.table_name.clone(),
+                trigger.event.clone(),
+                trigger.timing.clone(),
+            ]
+        }).collect();
+
+        Ok(ObjectView {
+            title: format!("{} trigger(s)", triggers.len()),
+            columns,
+            rows,
+        })
+    }
+
+    // === Sequence Operations ===
+    // SQLite tracks AUTOINCREMENT counters in sqlite_sequence but has no sequence objects
+
+    async fn list_sequences(&self, _connection: &dyn DbConnection, _database: &str) -> Result<Vec<SequenceInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_sequences_view(&self, _connection: &dyn DbConnection, _database: &str) -> Result<ObjectView> {
+        use gpui::px;
+
+        let columns = vec![
+            Column::new("name", "Name").width(px(200.0)),
+        ];
+
+        Ok(ObjectView {
+            title: "0 sequence(s)".to_string(),
+            columns,
+            rows: vec![],
+        })
+    }
+
+    // === Query Execution ===
+
+    async fn execute_query(
+        &self,
+        connection: &dyn DbConnection,
+        _database: &str,
+        query: &str,
+        params: Option<Vec<SqlValue>>,
+    ) -> Result<SqlResult> {
+        connection.query(query, params, ExecOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Query execution failed: {}", e))
+    }
+
+    async fn execute_script(
+        &self,
+        connection: &dyn DbConnection,
+        _database: &str,
+        script: &str,
+        options: ExecOptions,
+    ) -> Result<Vec<SqlResult>> {
+        connection.execute(script, options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Script execution failed: {}", e))
+    }
+
+    // === Database Switching ===
+
+    async fn switch_db(&self, _connection: &dyn DbConnection, database: &str) -> Result<SqlResult> {
+        // All attached schemas share one connection, objects are addressed as schema.table
+        Ok(SqlResult::Exec(ExecResult {
+            sql: format!("-- switch to {}", database),
+            rows_affected: 0,
+            elapsed_ms: 0,
+            message: Some(format!("Using schema '{}'", database)),
+        }))
+    }
+
+    fn get_data_types(&self) -> Vec<DataTypeInfo> {
+        vec![
+            // 数值类型
+            DataTypeInfo::new("INTEGER", "Signed integer (1-8 bytes)").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("REAL", "8-byte floating-point").with_category(DataTypeCategory::Numeric),
+            DataTypeInfo::new("NUMERIC", "Numeric affinity").with_category(DataTypeCategory::Numeric),
+
+            // 字符串类型
+            DataTypeInfo::new("TEXT", "Text string").with_category(DataTypeCategory::String),
+            DataTypeInfo::new("VARCHAR(255)", "Text affinity").with_category(DataTypeCategory::String),
+
+            // 日期时间类型
+            DataTypeInfo::new("DATE", "Date stored as TEXT (YYYY-MM-DD)").with_category(DataTypeCategory::DateTime),
+            DataTypeInfo::new("DATETIME", "Date and time stored as TEXT").with_category(DataTypeCategory::DateTime),
+
+            // 二进制类型
+            DataTypeInfo::new("BLOB", "Binary data stored as input").with_category(DataTypeCategory::Binary),
+
+            // 其他类型
+            DataTypeInfo::new("BOOLEAN", "Boolean stored as INTEGER 0/1").with_category(DataTypeCategory::Boolean),
+        ]
+    }
+
+    // === DDL Operations ===
+
+    async fn drop_database(&self, _connection: &dyn DbConnection, _database: &str) -> Result<()> {
+        Err(anyhow::anyhow!("SQLite databases are files and cannot be dropped with SQL"))
+    }
+
+    async fn truncate_table(&self, connection: &dyn DbConnection, database: &str, table: &str) -> Result<()> {
+        // SQLite has no TRUNCATE; a DELETE without WHERE uses the truncate optimization
+        let query = format!(
+            "DELETE FROM {}.{}",
+            self.quote_identifier(database),
+            self.quote_identifier(table)
+        );
+        self.execute_query(connection, database, &query, None).await?;
+        Ok(())
+    }
+}
+
+impl Default for SqlitePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::TOKIO_HANDLE;
+    use crate::test_support::test_config;
+
+    /// In-memory database with an `orders` table, a unique index on it and one row
+    fn open_database(plugin: &SqlitePlugin) -> Box<dyn DbConnection + Send + Sync> {
+        TOKIO_HANDLE.block_on(async {
+            let conn = plugin
+                .create_connection(test_config(DatabaseType::SQLite, &[]))
+                .await
+                .unwrap();
+            conn.execute(
+                "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer TEXT NOT NULL, status TEXT DEFAULT 'new', placed_at TEXT);\
+                 CREATE UNIQUE INDEX idx_orders_customer_placed ON orders (customer, placed_at);\
+                 INSERT INTO orders (customer, placed_at) VALUES ('ada', '2024-01-01');",
+                ExecOptions::default(),
+            )
+            .await
+            .unwrap();
+            conn
+        })
+    }
+
+    fn table_names(plugin: &SqlitePlugin, conn: &dyn DbConnection) -> Vec<String> {
+        let tables = TOKIO_HANDLE.block_on(plugin.list_tables(conn, "main")).unwrap();
+        tables.into_iter().map(|t| t.name).collect()
+    }
+
+    #[test]
+    fn test_list_tables() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        TOKIO_HANDLE
+            .block_on(conn.execute(
+                "CREATE TABLE customers (id INTEGER PRIMARY KEY AUTOINCREMENT);\
+                 CREATE TABLE sqlite1 (id INTEGER)",
+                ExecOptions::default(),
+            ))
+            .unwrap();
+
+        // Only the internal sqlite_ tables are hidden, `_` is not a wildcard here
+        assert_eq!(table_names(&plugin, &*conn), vec!["customers", "orders", "sqlite1"]);
+        let databases = TOKIO_HANDLE.block_on(plugin.list_databases_detailed(&*conn)).unwrap();
+        assert_eq!(databases[0].table_count, Some(3));
+        assert!(table_names(&plugin, &*conn).iter().all(|name| !name.starts_with("sqlite_")));
+    }
+
+    #[test]
+    fn test_list_columns() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        let columns = TOKIO_HANDLE.block_on(plugin.list_columns(&*conn, "main", "orders")).unwrap();
+
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "customer", "status", "placed_at"]);
+        assert!(columns[0].is_primary_key);
+        assert_eq!(columns[0].data_type, "INTEGER");
+        assert!(!columns[1].is_nullable);
+        assert!(columns[3].is_nullable);
+        assert_eq!(columns[2].default_value.as_deref(), Some("'new'"));
+    }
+
+    #[test]
+    fn test_list_indexes() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        let indexes = TOKIO_HANDLE.block_on(plugin.list_indexes(&*conn, "main", "orders")).unwrap();
+
+        assert_eq!(indexes.len(), 1);
+        let index = &indexes[0];
+        assert_eq!(index.name, "idx_orders_customer_placed");
+        assert_eq!(index.columns, vec!["customer", "placed_at"]);
+        assert!(index.is_unique);
+        assert_eq!(index.index_type.as_deref(), Some("INDEX"));
+    }
+
+    #[test]
+    fn test_list_foreign_keys() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        TOKIO_HANDLE
+            .block_on(conn.execute(
+                "CREATE TABLE order_items (order_id INTEGER REFERENCES orders (id), line INTEGER, sku TEXT);\
+                 CREATE TABLE shipments (customer TEXT, placed_at TEXT, \
+                 FOREIGN KEY (customer, placed_at) REFERENCES orders (customer, placed_at));",
+                ExecOptions::default(),
+            ))
+            .unwrap();
+
+        let foreign_keys = TOKIO_HANDLE.block_on(plugin.list_foreign_keys(&*conn, "main", "order_items")).unwrap();
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].columns, vec!["order_id"]);
+        assert_eq!(foreign_keys[0].referenced_table, "orders");
+        assert_eq!(foreign_keys[0].referenced_columns, vec!["id"]);
+
+        let foreign_keys = TOKIO_HANDLE.block_on(plugin.list_foreign_keys(&*conn, "main", "shipments")).unwrap();
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].columns, vec!["customer", "placed_at"]);
+        assert_eq!(foreign_keys[0].referenced_columns, vec!["customer", "placed_at"]);
+
+        assert!(TOKIO_HANDLE.block_on(plugin.list_foreign_keys(&*conn, "main", "orders")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rename_table() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        TOKIO_HANDLE
+            .block_on(plugin.rename_table(&*conn, "main", "orders", "purchases"))
+            .unwrap();
+
+        assert_eq!(table_names(&plugin, &*conn), vec!["purchases"]);
+        // The index moves with the table
+        let indexes = TOKIO_HANDLE.block_on(plugin.list_indexes(&*conn, "main", "purchases")).unwrap();
+        assert_eq!(indexes.len(), 1);
+    }
+
+    #[test]
+    fn test_rename_table_in_attached_database() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        for sql in ["ATTACH DATABASE ':memory:' AS archive", "CREATE TABLE archive.orders (id INTEGER)"] {
+            TOKIO_HANDLE.block_on(conn.execute(sql, ExecOptions::default())).unwrap();
+        }
+        TOKIO_HANDLE
+            .block_on(plugin.rename_table(&*conn, "archive", "orders", "purchases"))
+            .unwrap();
+
+        // Unqualified, the rename would have hit the orders table in main
+        assert_eq!(table_names(&plugin, &*conn), vec!["orders"]);
+        let archived = TOKIO_HANDLE.block_on(plugin.list_tables(&*conn, "archive")).unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].name, "purchases");
+    }
+
+    #[test]
+    fn test_truncate_table() {
+        let plugin = SqlitePlugin::new();
+        let conn = open_database(&plugin);
+        TOKIO_HANDLE.block_on(plugin.truncate_table(&*conn, "main", "orders")).unwrap();
+
+        let result = TOKIO_HANDLE
+            .block_on(conn.query("SELECT COUNT(*) FROM orders", None, ExecOptions::default()))
+            .unwrap();
+        match result {
+            SqlResult::Query(q) => assert_eq!(q.rows[0][0], SqlValue::Int(0)),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // Only the rows go, the table stays
+        assert_eq!(table_names(&plugin, &*conn), vec!["orders"]);
+    }
+
+    #[test]
+    fn test_parse_trigger_header() {
+        let sql = "CREATE TRIGGER log_orders AFTER UPDATE OF status ON orders BEGIN SELECT 1; END";
+        assert_eq!(
+            SqlitePlugin::parse_trigger_header(sql),
+            ("AFTER".to_string(), "UPDATE".to_string())
+        );
+        let sql = "CREATE TRIGGER t INSTEAD OF INSERT ON order_view BEGIN SELECT 1; END";
+        assert_eq!(
+            SqlitePlugin::parse_trigger_header(sql),
+            ("INSTEAD OF".to_string(), "INSERT".to_string())
+        );
+        // Event words inside the trigger name are not the event
+        let sql = "CREATE TRIGGER insert_audit AFTER UPDATE ON t BEGIN SELECT 1; END";
+        assert_eq!(
+            SqlitePlugin::parse_trigger_header(sql),
+            ("AFTER".to_string(), "UPDATE".to_string())
+        );
+        let sql = "CREATE TEMP TRIGGER IF NOT EXISTS \"after insert\" DELETE ON t BEGIN SELECT 1; END";
+        assert_eq!(
+            SqlitePlugin::parse_trigger_header(sql),
+            ("BEFORE".to_string(), "DELETE".to_string())
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use crate::connection::DbError;
+use crate::runtime::TOKIO_HANDLE;
+use one_core::storage::{DbConnectionConfig, SshAuthMethod, SshParams};
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Connection form fields configuring the tunnel, stored in `extra_params`
+pub const SSH_HOST_PARAM: &str = "ssh_host";
+pub const SSH_PORT_PARAM: &str = "ssh_port";
+pub const SSH_USERNAME_PARAM: &str = "ssh_username";
+pub const SSH_AUTH_PARAM: &str = "ssh_auth";
+pub const SSH_PASSWORD_PARAM: &str = "ssh_password";
+pub const SSH_KEY_PATH_PARAM: &str = "ssh_key_path";
+pub const SSH_PASSPHRASE_PARAM: &str = "ssh_passphrase";
+pub const SSH_KNOWN_HOSTS_PARAM: &str = "ssh_known_hosts";
+pub const SSH_KEEPALIVE_PARAM: &str = "ssh_keepalive";
+
+/// Values of `SSH_AUTH_PARAM`
+pub const SSH_AUTH_PASSWORD: &str = "password";
+pub const SSH_AUTH_PRIVATE_KEY: &str = "private_key";
+
+pub const DEFAULT_SSH_PORT: u16 = 22;
+pub const DEFAULT_SSH_KEEPALIVE: Duration = Duration::from_secs(30);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// How long the forwarding thread sleeps when no connection has data to move
+const IDLE_POLL: Duration = Duration::from_millis(5);
+
+/// SSH server a database connection is tunnelled through
+#[derive(Debug, Clone)]
+pub struct SshTunnelConfig {
+    pub server: SshParams,
+    /// OpenSSH known_hosts file the server's host key must be listed in
+    pub known_hosts: PathBuf,
+    pub keepalive_interval: Duration,
+}
+
+impl SshTunnelConfig {
+    /// Tunnel settings of a connection, `None` when it connects directly
+    pub fn from_config(config: &DbConnectionConfig) -> Result<Option<Self>, DbError> {
+        let param = |name: &str| {
+            config
+                .extra_params
+                .get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let invalid = |message: String| DbError::ConnectionError(format!("Invalid SSH settings: {}", message));
+
+        let Some(host) = param(SSH_HOST_PARAM) else {
+            return Ok(None);
+        };
+        let port = match param(SSH_PORT_PARAM) {
+            Some(port) => port.parse().map_err(|_| invalid(format!("port '{}'", port)))?,
+            None => DEFAULT_SSH_PORT,
+        };
+        let username = param(SSH_USERNAME_PARAM).ok_or_else(|| invalid("username is required".to_string()))?;
+
+        let auth_method = match param(SSH_AUTH_PARAM).unwrap_or(SSH_AUTH_PASSWORD) {
+            SSH_AUTH_PASSWORD => SshAuthMethod::Password {
+                password: param(SSH_PASSWORD_PARAM).unwrap_or_default().to_string(),
+            },
+            SSH_AUTH_PRIVATE_KEY => SshAuthMethod::PrivateKey {
+                key_path: param(SSH_KEY_PATH_PARAM)
+                    .ok_or_else(|| invalid("private key file is required".to_string()))?
+                    .to_string(),
+                passphrase: param(SSH_PASSPHRASE_PARAM).map(|p| p.to_string()),
+            },
+            other => return Err(invalid(format!("unknown authentication method '{}'", other))),
+        };
+
+        let known_hosts = match param(SSH_KNOWN_HOSTS_PARAM) {
+            Some(path) => expand_home(path),
+            None => expand_home("~/.ssh/known_hosts"),
+        };
+        let keepalive_interval = match param(SSH_KEEPALIVE_PARAM) {
+            Some(secs) => Duration::from_secs(secs.parse().map_err(|_| invalid(format!("keepalive interval '{}'", secs)))?),
+            None => DEFAULT_SSH_KEEPALIVE,
+        };
+
+        Ok(Some(Self {
+            server: SshParams {
+                host: host.to_string(),
+                port,
+                username: username.to_string(),
+                auth_method,
+            },
+            known_hosts,
+            keepalive_interval,
+        }))
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Local port forwarded to a database host through an SSH server.
+///
+/// Connections accepted on `local_addr` each get their own channel on one SSH session.
+/// Dropping the tunnel stops forwarding and closes the session.
+pub struct SshTunnel {
+    local_addr: SocketAddr,
+    closed: Arc<AtomicBool>,
+}
+
+impl SshTunnel {
+    /// Tunnel for a connection config, `None` when no SSH server is configured
+    pub async fn for_config(config: &DbConnectionConfig) -> Result<Option<Self>, DbError> {
+        match SshTunnelConfig::from_config(config)? {
+            Some(tunnel_config) => Self::open(tunnel_config, config.host.clone(), config.port).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Connect and authenticate to the SSH server, then forward a local port to `remote_host:remote_port`
+    pub async fn open(config: SshTunnelConfig, remote_host: String, remote_port: u16) -> Result<Self, DbError> {
+        TOKIO_HANDLE
+            .spawn_blocking(move || {
+                let session = connect_session(&config)?;
+                let forwarder = SshForwarder {
+                    session,
+                    remote_host,
+                    remote_port,
+                    keepalive_interval: config.keepalive_interval,
+                };
+                Self::start(forwarder)
+                    .map_err(|e| DbError::ConnectionError(format!("Failed to listen for the SSH tunnel: {}", e)))
+            })
+            .await
+            .map_err(|e| DbError::ConnectionError(format!("Failed to spawn SSH tunnel task: {}", e)))?
+    }
+
+    fn start<F: Forwarder>(forwarder: F) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let thread_closed = closed.clone();
+        thread::Builder::new()
+            .name(format!("ssh-tunnel-{}", local_addr.port()))
+            .spawn(move || forward(listener, forwarder, thread_closed))?;
+
+        Ok(Self { local_addr, closed })
+    }
+
+    /// Address the database driver connects to instead of the database host
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+fn connect_session(config: &SshTunnelConfig) -> Result<Session, DbError> {
+    let server = &config.server;
+    let error = |action: &str, e: &dyn std::fmt::Display| {
+        DbError::ConnectionError(format!("Failed to {} SSH server {}:{}: {}", action, server.host, server.port, e))
+    };
+
+    let addr = (server.host.as_str(), server.port)
+        .to_socket_addrs()
+        .map_err(|e| error("resolve", &e))?
+        .next()
+        .ok_or_else(|| error("resolve", &"no address"))?;
+    let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| error("connect to", &e))?;
+
+    let mut session = Session::new().map_err(|e| error("start a session with", &e))?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(CONNECT_TIMEOUT.as_millis() as u32);
+    session.handshake().map_err(|e| error("complete the handshake with", &e))?;
+    verify_host_key(&session, config)?;
+
+    let result = match &server.auth_method {
+        SshAuthMethod::Password { password } => session.userauth_password(&server.username, password),
+        SshAuthMethod::PrivateKey { key_path, passphrase } => {
+            session.userauth_pubkey_file(&server.username, None, &expand_home(key_path), passphrase.as_deref())
+        }
+    };
+    result.map_err(|e| error("authenticate with", &e))?;
+    if !session.authenticated() {
+        return Err(error("authenticate with", &"server rejected the credentials"));
+    }
+
+    // Forwarding polls the channels, reads must not wait for the timeout
+    session.set_timeout(0);
+    session.set_keepalive(true, config.keepalive_interval.as_secs().max(1) as u32);
+    Ok(session)
+}
+
+/// Only servers whose host key is in the known_hosts file are trusted
+fn verify_host_key(session: &Session, config: &SshTunnelConfig) -> Result<(), DbError> {
+    let server = &config.server;
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| DbError::ConnectionError(format!("SSH server {} sent no host key", server.host)))?;
+
+    let mut known_hosts = session
+        .known_hosts()
+        .map_err(|e| DbError::ConnectionError(format!("Failed to read known hosts: {}", e)))?;
+    if config.known_hosts.exists() {
+        known_hosts
+            .read_file(&config.known_hosts, KnownHostFileKind::OpenSSH)
+            .map_err(|e| DbError::ConnectionError(format!("Failed to read {}: {}", config.known_hosts.display(), e)))?;
+    }
+
+    match known_hosts.check_port(&server.host, server.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound => Err(DbError::ConnectionError(format!(
+            "Host key of SSH server {} is not in {}, connect once with ssh to verify and add it",
+            server.host,
+            config.known_hosts.display()
+        ))),
+        CheckResult::Mismatch => Err(DbError::ConnectionError(format!(
+            "Host key of SSH server {} does not match the one in {}",
+            server.host,
+            config.known_hosts.display()
+        ))),
+        CheckResult::Failure => Err(DbError::ConnectionError(format!(
+            "Failed to check the host key of SSH server {}",
+            server.host
+        ))),
+    }
+}
+
+/// Opens the remote end of forwarded connections
+trait Forwarder: Send + 'static {
+    type Channel: Read + Write;
+
+    /// Channel to the database for a newly accepted local connection, reads and writes must not block
+    fn open_channel(&mut self) -> io::Result<Self::Channel>;
+
+    /// Keep the transport alive, returns the time until the next call
+    fn keepalive(&mut self) -> io::Result<Duration>;
+}
+
+struct SshForwarder {
+    session: Session,
+    remote_host: String,
+    remote_port: u16,
+    keepalive_interval: Duration,
+}
+
+impl Forwarder for SshForwarder {
+    type Channel = ssh2::Channel;
+
+    fn open_channel(&mut self) -> io::Result<ssh2::Channel> {
+        self.session.set_blocking(true);
+        let channel = self.session.channel_direct_tcpip(&self.remote_host, self.remote_port, None);
+        self.session.set_blocking(false);
+        channel.map_err(io::Error::from)
+    }
+
+    fn keepalive(&mut self) -> io::Result<Duration> {
+        match self.session.keepalive_send() {
+            Ok(secs) => Ok(Duration::from_secs(secs.max(1) as u64)),
+            Err(e) => match io::Error::from(e) {
+                e if e.kind() == ErrorKind::WouldBlock => Ok(Duration::from_secs(1)),
+                e => Err(e),
+            },
+        }
+        .map(|next| next.min(self.keepalive_interval))
+    }
+}
+
+/// Accepts local connections and moves bytes to and from their channels until the tunnel is dropped
+fn forward<F: Forwarder>(listener: TcpListener, mut forwarder: F, closed: Arc<AtomicBool>) {
+    let mut links: Vec<Link<F::Channel>> = Vec::new();
+    let mut next_keepalive = Instant::now();
+
+    while !closed.load(Ordering::SeqCst) {
+        let mut busy = false;
+        match listener.accept() {
+            Ok((local, _)) => {
+                busy = true;
+                match local.set_nonblocking(true).and_then(|_| forwarder.open_channel()) {
+                    Ok(remote) => links.push(Link::new(local, remote)),
+                    Err(e) => tracing::warn!("Failed to open SSH tunnel channel: {}", e),
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => {
+                tracing::error!("SSH tunnel stopped accepting connections: {}", e);
+                break;
+            }
+        }
+
+        links.retain_mut(|link| match link.pump() {
+            Ok(moved) => {
+                busy |= moved;
+                !link.is_done()
+            }
+            Err(e) => {
+                tracing::debug!("SSH tunnel connection closed: {}", e);
+                false
+            }
+        });
+
+        if Instant::now() >= next_keepalive {
+            match forwarder.keepalive() {
+                Ok(next) => next_keepalive = Instant::now() + next,
+                Err(e) => {
+                    tracing::error!("SSH tunnel keepalive failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        if !busy {
+            thread::sleep(IDLE_POLL);
+        }
+    }
+}
+
+/// A forwarded connection and the bytes still to be written to either side
+struct Link<C> {
+    local: TcpStream,
+    remote: C,
+    to_remote: Vec<u8>,
+    to_local: Vec<u8>,
+    local_eof: bool,
+    remote_eof: bool,
+}
+
+impl<C: Read + Write> Link<C> {
+    fn new(local: TcpStream, remote: C) -> Self {
+        Self {
+            local,
+            remote,
+            to_remote: Vec::new(),
+            to_local: Vec::new(),
+            local_eof: false,
+            remote_eof: false,
+        }
+    }
+
+    /// Move whatever is ready in both directions, returns whether anything moved
+    fn pump(&mut self) -> io::Result<bool> {
+        let sent = transfer(&mut self.local, &mut self.remote, &mut self.to_remote, &mut self.local_eof)?;
+        let received = transfer(&mut self.remote, &mut self.local, &mut self.to_local, &mut self.remote_eof)?;
+        Ok(sent || received)
+    }
+
+    /// Either side hung up and what it sent has been passed on
+    fn is_done(&self) -> bool {
+        (self.local_eof && self.to_remote.is_empty()) || (self.remote_eof && self.to_local.is_empty())
+    }
+}
+
+fn transfer(from: &mut impl Read, to: &mut impl Write, pending: &mut Vec<u8>, eof: &mut bool) -> io::Result<bool> {
+    let mut moved = false;
+    if pending.is_empty() && !*eof {
+        let mut buf = [0u8; 16 * 1024];
+        match from.read(&mut buf) {
+            Ok(0) => *eof = true,
+            Ok(n) => {
+                pending.extend_from_slice(&buf[..n]);
+                moved = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+    }
+    if !pending.is_empty() {
+        match to.write(pending) {
+            Ok(n) => {
+                pending.drain(..n);
+                moved = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config;
+    use one_core::storage::DatabaseType;
+
+    /// Stands in for the SSH server: channels are plain TCP connections to the target
+    struct DirectForwarder {
+        target: SocketAddr,
+        keepalives: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Forwarder for DirectForwarder {
+        type Channel = TcpStream;
+
+        fn open_channel(&mut self) -> io::Result<TcpStream> {
+            let stream = TcpStream::connect(self.target)?;
+            stream.set_nonblocking(true)?;
+            Ok(stream)
+        }
+
+        fn keepalive(&mut self) -> io::Result<Duration> {
+            self.keepalives.fetch_add(1, Ordering::SeqCst);
+            Ok(Duration::from_millis(20))
+        }
+    }
+
+    fn echo_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                thread::spawn(move || {
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n) = stream.read(&mut buf) {
+                        if n == 0 || stream.write_all(&buf[..n]).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    fn round_trip(addr: SocketAddr, message: &[u8]) -> Vec<u8> {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        // Write while reading, the echo would stall once the socket buffers are full
+        let mut writer = stream.try_clone().unwrap();
+        let message_copy = message.to_vec();
+        thread::spawn(move || writer.write_all(&message_copy));
+        let mut received = vec![0u8; message.len()];
+        stream.read_exact(&mut received).unwrap();
+        received
+    }
+
+    #[test]
+    fn test_tunnel_config_from_extra_params() {
+        let direct = test_config(DatabaseType::MySQL, &[(SSH_PORT_PARAM, "2222")]);
+        assert!(SshTunnelConfig::from_config(&direct).unwrap().is_none());
+
+        let password = test_config(
+            DatabaseType::MySQL,
+            &[(SSH_HOST_PARAM, "bastion"), (SSH_USERNAME_PARAM, "ops"), (SSH_PASSWORD_PARAM, "secret")],
+        );
+        let tunnel = SshTunnelConfig::from_config(&password).unwrap().unwrap();
+        assert_eq!(tunnel.server.port, DEFAULT_SSH_PORT);
+        assert_eq!(tunnel.keepalive_interval, DEFAULT_SSH_KEEPALIVE);
+        assert!(matches!(tunnel.server.auth_method, SshAuthMethod::Password { ref password } if password == "secret"));
+
+        let key = test_config(
+            DatabaseType::PostgreSQL,
+            &[
+                (SSH_HOST_PARAM, "bastion"),
+                (SSH_PORT_PARAM, "2222"),
+                (SSH_USERNAME_PARAM, "ops"),
+                (SSH_AUTH_PARAM, SSH_AUTH_PRIVATE_KEY),
+                (SSH_KEY_PATH_PARAM, "/keys/id_ed25519"),
+                (SSH_KNOWN_HOSTS_PARAM, "/keys/known_hosts"),
+                (SSH_KEEPALIVE_PARAM, "10"),
+            ],
+        );
+        let tunnel = SshTunnelConfig::from_config(&key).unwrap().unwrap();
+        assert_eq!(tunnel.server.port, 2222);
+        assert_eq!(tunnel.known_hosts, PathBuf::from("/keys/known_hosts"));
+        assert_eq!(tunnel.keepalive_interval, Duration::from_secs(10));
+        assert!(matches!(
+            tunnel.server.auth_method,
+            SshAuthMethod::PrivateKey { ref key_path, passphrase: None } if key_path == "/keys/id_ed25519"
+        ));
+
+        let missing_key = test_config(
+            DatabaseType::MySQL,
+            &[(SSH_HOST_PARAM, "bastion"), (SSH_USERNAME_PARAM, "ops"), (SSH_AUTH_PARAM, SSH_AUTH_PRIVATE_KEY)],
+        );
+        assert!(SshTunnelConfig::from_config(&missing_key).is_err());
+        let missing_user = test_config(DatabaseType::MySQL, &[(SSH_HOST_PARAM, "bastion")]);
+        assert!(SshTunnelConfig::from_config(&missing_user).is_err());
+    }
+
+    #[test]
+    fn test_tunnel_forwards_each_connection() {
+        let keepalives = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tunnel = SshTunnel::start(DirectForwarder { target: echo_server(), keepalives: keepalives.clone() }).unwrap();
+
+        assert_eq!(round_trip(tunnel.local_addr(), b"SELECT 1"), b"SELECT 1");
+        let large = vec![7u8; 256 * 1024];
+        assert_eq!(round_trip(tunnel.local_addr(), &large), large);
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(keepalives.load(Ordering::SeqCst) >= 2);
+
+        let addr = tunnel.local_addr();
+        drop(tunnel);
+        thread::sleep(Duration::from_millis(50));
+        assert!(TcpStream::connect(addr).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::connection::DbError;
+use crate::ssh_tunnel::SSH_HOST_PARAM;
+use one_core::storage::DbConnectionConfig;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Connection form fields configuring TLS, stored in `extra_params`
+pub const SSL_MODE_PARAM: &str = "ssl_mode";
+pub const SSL_CA_PARAM: &str = "ssl_ca";
+pub const SSL_CERT_PARAM: &str = "ssl_cert";
+pub const SSL_KEY_PARAM: &str = "ssl_key";
+/// SQL Server only: `true` accepts the server certificate without validating it
+pub const SSL_TRUST_SERVER_CERT_PARAM: &str = "trust_server_certificate";
+
+/// How strictly the connection requires and verifies TLS, named after libpq's `sslmode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SslMode {
+    /// Plain connection
+    Disable,
+    /// TLS when the server offers it, plain otherwise
+    #[default]
+    Prefer,
+    /// TLS without verifying the server certificate
+    Require,
+    /// TLS with a server certificate signed by the CA
+    VerifyCa,
+    /// Like `VerifyCa`, and the certificate must name the host
+    VerifyFull,
+}
+
+impl SslMode {
+    pub const ALL: [SslMode; 5] = [
+        SslMode::Disable,
+        SslMode::Prefer,
+        SslMode::Require,
+        SslMode::VerifyCa,
+        SslMode::VerifyFull,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.as_str() == value)
+    }
+}
+
+impl fmt::Display for SslMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// TLS settings of a connection
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SslOptions {
+    pub mode: SslMode,
+    /// CA certificate the server certificate is verified against, system roots when unset
+    pub ca_cert: Option<PathBuf>,
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+}
+
+impl SslOptions {
+    pub fn from_config(config: &DbConnectionConfig) -> Result<Self, DbError> {
+        let param = |name: &str| {
+            config
+                .extra_params
+                .get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let invalid = |message: String| DbError::ConnectionError(format!("Invalid SSL settings: {}", message));
+
+        let mode = match param(SSL_MODE_PARAM) {
+            Some(mode) => SslMode::parse(mode).ok_or_else(|| invalid(format!("unknown mode '{}'", mode)))?,
+            None => SslMode::default(),
+        };
+        let options = Self {
+            mode,
+            ca_cert: param(SSL_CA_PARAM).map(PathBuf::from),
+            client_cert: param(SSL_CERT_PARAM).map(PathBuf::from),
+            client_key: param(SSL_KEY_PARAM).map(PathBuf::from),
+        };
+
+        if options.client_cert.is_some() != options.client_key.is_some() {
+            return Err(invalid("client certificate and key must be set together".to_string()));
+        }
+        if options.mode == SslMode::Disable && (options.ca_cert.is_some() || options.client_cert.is_some()) {
+            return Err(invalid("certificates are set but SSL is disabled".to_string()));
+        }
+        // Through a tunnel the driver connects to 127.0.0.1, which no server certificate names
+        if options.mode == SslMode::VerifyFull && param(SSH_HOST_PARAM).is_some() {
+            return Err(invalid(
+                "verify-full cannot check the host name through an SSH tunnel, use verify-ca".to_string(),
+            ));
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_config;
+    use one_core::storage::DatabaseType;
+
+    #[test]
+    fn test_ssl_options_from_extra_params() {
+        let default = test_config(DatabaseType::MySQL, &[]);
+        assert_eq!(SslOptions::from_config(&default).unwrap(), SslOptions::default());
+
+        let verified = test_config(
+            DatabaseType::PostgreSQL,
+            &[
+                (SSL_MODE_PARAM, "verify-full"),
+                (SSL_CA_PARAM, "/certs/ca.pem"),
+                (SSL_CERT_PARAM, "/certs/client.pem"),
+                (SSL_KEY_PARAM, "/certs/client.key"),
+            ],
+        );
+        let options = SslOptions::from_config(&verified).unwrap();
+        assert_eq!(options.mode, SslMode::VerifyFull);
+        assert_eq!(options.ca_cert, Some(PathBuf::from("/certs/ca.pem")));
+        assert_eq!(options.client_key, Some(PathBuf::from("/certs/client.key")));
+
+        let unknown = test_config(DatabaseType::MySQL, &[(SSL_MODE_PARAM, "always")]);
+        assert!(SslOptions::from_config(&unknown).is_err());
+        let cert_without_key = test_config(DatabaseType::MySQL, &[(SSL_CERT_PARAM, "/certs/client.pem")]);
+        assert!(SslOptions::from_config(&cert_without_key).is_err());
+        let disabled_with_ca = test_config(DatabaseType::MySQL, &[(SSL_MODE_PARAM, "disable"), (SSL_CA_PARAM, "/certs/ca.pem")]);
+        assert!(SslOptions::from_config(&disabled_with_ca).is_err());
+    }
+
+    #[test]
+    fn test_verify_full_is_rejected_through_ssh_tunnel() {
+        let tunnelled = |mode: &str| {
+            test_config(
+                DatabaseType::PostgreSQL,
+                &[(SSH_HOST_PARAM, "bastion"), (SSL_MODE_PARAM, mode), (SSL_CA_PARAM, "/certs/ca.pem")],
+            )
+        };
+        let error = SslOptions::from_config(&tunnelled("verify-full")).unwrap_err();
+        assert!(error.to_string().contains("SSH tunnel"));
+        assert_eq!(SslOptions::from_config(&tunnelled("verify-ca")).unwrap().mode, SslMode::VerifyCa);
+    }
+}
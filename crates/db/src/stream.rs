@@ -0,0 +1,245 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+use sqlx::{Column, Row, TypeInfo};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::connection::DbError;
+use crate::executor::{QueryResult, ResultColumn};
+use crate::types::SqlValue;
+
+/// Rows delivered together by a `QueryStream`
+pub type RowBatch = Vec<Vec<SqlValue>>;
+
+type BatchReceiver = mpsc::Receiver<Result<RowBatch, DbError>>;
+
+/// Rows of a query that are read from the server batch by batch, as the consumer asks for them.
+///
+/// A background task keeps the statement's connection until every row has been read or the
+/// stream is closed, so close streams you no longer need before running anything else on a
+/// single-connection session.
+#[derive(Clone)]
+pub struct QueryStream {
+    columns: Vec<ResultColumn>,
+    batches: Arc<Mutex<Option<BatchReceiver>>>,
+    finished: Arc<AtomicBool>,
+}
+
+impl QueryStream {
+    fn channel(columns: Vec<ResultColumn>, capacity: usize) -> (Self, mpsc::Sender<Result<RowBatch, DbError>>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let stream = Self {
+            columns,
+            batches: Arc::new(Mutex::new(Some(receiver))),
+            finished: Arc::new(AtomicBool::new(false)),
+        };
+        (stream, sender)
+    }
+
+    /// Stream over rows that are already in memory, for drivers that cannot read incrementally
+    pub fn from_rows(columns: Vec<ResultColumn>, rows: RowBatch, batch_size: usize) -> Self {
+        let batch_size = batch_size.max(1);
+        let (stream, sender) = Self::channel(columns, rows.len().div_ceil(batch_size));
+        let mut rows = rows.into_iter().peekable();
+        while rows.peek().is_some() {
+            let batch: RowBatch = rows.by_ref().take(batch_size).collect();
+            let _ = sender.try_send(Ok(batch));
+        }
+        stream
+    }
+
+    pub fn columns(&self) -> &[ResultColumn] {
+        &self.columns
+    }
+
+    /// Whether every row has been handed out, or the stream failed or was closed
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Next batch of rows, `None` once the result is exhausted
+    pub async fn next_batch(&self) -> Option<Result<RowBatch, DbError>> {
+        if self.is_finished() {
+            return None;
+        }
+
+        let mut batches = self.batches.lock().await;
+        let next = match batches.as_mut() {
+            Some(receiver) => receiver.recv().await,
+            None => None,
+        };
+        if !matches!(next, Some(Ok(_))) {
+            self.finished.store(true, Ordering::SeqCst);
+            *batches = None;
+        }
+        next
+    }
+
+    /// Stop reading: the task feeding the stream ends and gives its connection back.
+    /// The server may still send the rest of the result, cancel the query first to cut it short.
+    pub async fn close(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+        self.batches.lock().await.take();
+    }
+}
+
+impl fmt::Debug for QueryStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryStream")
+            .field("columns", &self.columns.len())
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// Column metadata of a sqlx row
+pub(crate) fn result_columns<R: Row>(row: &R) -> Vec<ResultColumn> {
+    row.columns()
+        .iter()
+        .map(|col| ResultColumn::new(col.name(), col.type_info().name()))
+        .collect()
+}
+
+/// First rows of a sqlx result, read without rewriting the statement
+pub(crate) struct Page<R> {
+    columns: Vec<ResultColumn>,
+    rows: RowBatch,
+    /// The row read past the limit, proof that the result continues
+    overflow: Option<Vec<SqlValue>>,
+    extract: fn(&R, usize) -> SqlValue,
+}
+
+/// Read up to `limit` rows from `rows`, peeking at one more to know whether the result goes on
+pub(crate) async fn read_page<R, S>(
+    rows: &mut S,
+    limit: Option<usize>,
+    extract: fn(&R, usize) -> SqlValue,
+) -> Result<Page<R>, sqlx::Error>
+where
+    R: Row,
+    S: Stream<Item = Result<R, sqlx::Error>> + Unpin,
+{
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut columns = Vec::new();
+    let mut data = Vec::new();
+    let mut overflow = None;
+
+    while let Some(row) = rows.next().await {
+        let row = row?;
+        if columns.is_empty() {
+            columns = result_columns(&row);
+        }
+        let values = (0..columns.len()).map(|i| extract(&row, i)).collect();
+        if data.len() < limit {
+            data.push(values);
+        } else {
+            overflow = Some(values);
+            break;
+        }
+    }
+
+    Ok(Page {
+        columns,
+        rows: data,
+        overflow,
+        extract,
+    })
+}
+
+impl<R: Row> Page<R> {
+    /// Result holding only this page; rows past it are dropped and `has_more` records that they exist
+    pub fn into_query_result(self, sql: &str, elapsed_ms: u128) -> QueryResult {
+        QueryResult {
+            sql: sql.to_string(),
+            has_more: self.overflow.is_some(),
+            columns: self.columns,
+            rows: self.rows,
+            elapsed_ms,
+            more_rows: None,
+        }
+    }
+
+    /// Result holding this page, with the remaining rows readable through `QueryResult::more_rows`.
+    /// Run the returned pump on the statement's row stream to feed them.
+    pub fn into_streamed_result(self, sql: &str, elapsed_ms: u128, batch_size: usize) -> (QueryResult, Option<RowPump<R>>) {
+        let Some(overflow) = self.overflow else {
+            return (self.into_query_result(sql, elapsed_ms), None);
+        };
+
+        let (stream, sender) = QueryStream::channel(self.columns.clone(), 1);
+        let pump = RowPump {
+            sender,
+            pending: vec![overflow],
+            batch_size,
+            extract: self.extract,
+        };
+        let result = QueryResult {
+            sql: sql.to_string(),
+            columns: self.columns,
+            rows: self.rows,
+            elapsed_ms,
+            has_more: true,
+            more_rows: Some(stream),
+        };
+        (result, Some(pump))
+    }
+
+    /// Stream handing out this page as its first batch, followed by whatever the pump reads
+    pub fn into_stream(self, batch_size: usize) -> (QueryStream, Option<RowPump<R>>) {
+        let (stream, sender) = QueryStream::channel(self.columns, 1);
+        if !self.rows.is_empty() {
+            let _ = sender.try_send(Ok(self.rows));
+        }
+        let pump = self.overflow.map(|overflow| RowPump {
+            sender,
+            pending: vec![overflow],
+            batch_size,
+            extract: self.extract,
+        });
+        (stream, pump)
+    }
+}
+
+/// Feeds the rest of a result into a `QueryStream`, one batch ahead of the consumer
+pub(crate) struct RowPump<R> {
+    sender: mpsc::Sender<Result<RowBatch, DbError>>,
+    pending: RowBatch,
+    batch_size: usize,
+    extract: fn(&R, usize) -> SqlValue,
+}
+
+impl<R: Row> RowPump<R> {
+    /// Read `rows` until they run out, fail, or the stream is closed
+    pub async fn run<S>(mut self, mut rows: S)
+    where
+        S: Stream<Item = Result<R, sqlx::Error>> + Unpin,
+    {
+        let batch_size = self.batch_size.max(1);
+        loop {
+            match rows.next().await {
+                Some(Ok(row)) => {
+                    let values = (0..row.len()).map(|i| (self.extract)(&row, i)).collect();
+                    self.pending.push(values);
+                    if self.pending.len() >= batch_size {
+                        let batch = std::mem::take(&mut self.pending);
+                        if self.sender.send(Ok(batch)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    let _ = self.sender.send(Err(DbError::QueryError(e.to_string()))).await;
+                    return;
+                }
+                None => {
+                    if !self.pending.is_empty() {
+                        let _ = self.sender.send(Ok(self.pending)).await;
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
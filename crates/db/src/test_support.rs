@@ -0,0 +1,117 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use one_core::storage::{DatabaseType, DbConnectionConfig};
+
+use crate::connection::{DbConnection, DbError};
+use crate::executor::{ExecOptions, ExecResult, QueryResult, ResultColumn, SqlResult};
+use crate::types::SqlValue;
+
+/// Id of every config built by `test_config`
+pub(crate) const TEST_CONFIG_ID: &str = "test";
+
+/// Stored connection of `database_type` with `params` as its extra params.
+/// SQLite ones open a fresh in-memory database, the others point at a host that is never contacted.
+pub(crate) fn test_config(database_type: DatabaseType, params: &[(&str, &str)]) -> DbConnectionConfig {
+    let sqlite = database_type == DatabaseType::SQLite;
+    DbConnectionConfig {
+        id: TEST_CONFIG_ID.to_string(),
+        database_type,
+        name: TEST_CONFIG_ID.to_string(),
+        host: if sqlite { String::new() } else { "db.internal".to_string() },
+        port: 0,
+        username: if sqlite { String::new() } else { "app".to_string() },
+        password: String::new(),
+        database: sqlite.then(|| ":memory:".to_string()),
+        workspace_id: None,
+        extra_params: params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    }
+}
+
+/// Connection that records what it is asked to do and answers queries from canned results
+pub(crate) struct FakeConnection {
+    config: DbConnectionConfig,
+    /// `connect`, `disconnect` and every statement run, in order
+    log: Arc<Mutex<Vec<String>>>,
+    /// Result of queries starting with the prefix; other statements succeed without rows
+    responses: Vec<(String, SqlResult)>,
+}
+
+impl FakeConnection {
+    pub(crate) fn new(config: DbConnectionConfig) -> Self {
+        Self {
+            config,
+            log: Arc::default(),
+            responses: Vec::new(),
+        }
+    }
+
+    pub(crate) fn respond(mut self, prefix: &str, result: SqlResult) -> Self {
+        self.responses.push((prefix.to_string(), result));
+        self
+    }
+
+    /// Shared with the connection, so it can be read after the connection moved into a session or pool
+    pub(crate) fn log(&self) -> Arc<Mutex<Vec<String>>> {
+        self.log.clone()
+    }
+
+    fn record(&self, entry: &str) {
+        self.log.lock().unwrap().push(entry.to_string());
+    }
+
+    fn result_for(&self, sql: &str) -> SqlResult {
+        self.responses
+            .iter()
+            .find(|(prefix, _)| sql.starts_with(prefix.as_str()))
+            .map(|(_, result)| result.clone())
+            .unwrap_or_else(|| {
+                SqlResult::Exec(ExecResult {
+                    sql: sql.to_string(),
+                    rows_affected: 0,
+                    elapsed_ms: 0,
+                    message: None,
+                })
+            })
+    }
+}
+
+#[async_trait]
+impl DbConnection for FakeConnection {
+    fn config(&self) -> Option<DbConnectionConfig> {
+        Some(self.config.clone())
+    }
+
+    async fn connect(&mut self) -> Result<(), DbError> {
+        self.record("connect");
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), DbError> {
+        self.record("disconnect");
+        Ok(())
+    }
+
+    async fn execute(&self, script: &str, _options: ExecOptions) -> Result<Vec<SqlResult>, DbError> {
+        self.record(script);
+        Ok(vec![self.result_for(script)])
+    }
+
+    async fn query(&self, query: &str, _params: Option<Vec<SqlValue>>, _options: ExecOptions) -> Result<SqlResult, DbError> {
+        self.record(query);
+        Ok(self.result_for(query))
+    }
+}
+
+/// Query result holding `rows`, for code that reads cells by position
+pub(crate) fn rows_result(rows: Vec<Vec<SqlValue>>) -> SqlResult {
+    let width = rows.first().map_or(0, |row| row.len());
+    SqlResult::Query(QueryResult {
+        sql: String::new(),
+        columns: (0..width).map(|i| ResultColumn::new(format!("column{}", i + 1), "TEXT")).collect(),
+        rows,
+        elapsed_ms: 0,
+        has_more: false,
+        more_rows: None,
+    })
+}
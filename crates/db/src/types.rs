@@ -1,18 +1,128 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
 use gpui_component::table::Column;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-/// SQL value type for parameter binding
-#[derive(Debug, Clone)]
+/// A typed SQL value, used for parameter binding and for the cells of query results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SqlValue {
     Null,
     Bool(bool),
     Int(i64),
     Float(f64),
+    /// Exact numeric kept as its text so no precision is lost (DECIMAL, NUMERIC, unsigned BIGINT)
+    Decimal(String),
     String(String),
     Bytes(Vec<u8>),
     Json(serde_json::Value),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
+    /// Timestamp carrying its UTC offset (TIMESTAMPTZ, DATETIMEOFFSET)
+    DateTimeTz(DateTime<FixedOffset>),
+    Uuid(Uuid),
+}
+
+impl SqlValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, SqlValue::Null)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, SqlValue::Int(_) | SqlValue::Float(_) | SqlValue::Decimal(_))
+    }
+
+    /// Text of the value for code that reads results as strings, such as metadata queries.
+    /// NULL gives `None` and bytes are decoded as UTF-8 rather than rendered as hex.
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            SqlValue::Null => None,
+            SqlValue::String(v) => Some(v.clone()),
+            SqlValue::Bytes(v) => Some(String::from_utf8_lossy(v).into_owned()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// Ordering used when sorting a result grid: NULL first, numbers by value,
+    /// dates chronologically, anything else by its text
+    pub fn cmp_for_sort(&self, other: &SqlValue) -> Ordering {
+        use SqlValue::*;
+        match (self, other) {
+            (Null, Null) => Ordering::Equal,
+            (Null, _) => Ordering::Less,
+            (_, Null) => Ordering::Greater,
+            (Int(a), Int(b)) => a.cmp(b),
+            (a, b) if a.is_numeric() && b.is_numeric() => {
+                a.as_f64().unwrap_or(f64::NAN).total_cmp(&b.as_f64().unwrap_or(f64::NAN))
+            }
+            (Bool(a), Bool(b)) => a.cmp(b),
+            (Date(a), Date(b)) => a.cmp(b),
+            (Time(a), Time(b)) => a.cmp(b),
+            (DateTime(a), DateTime(b)) => a.cmp(b),
+            (DateTimeTz(a), DateTimeTz(b)) => a.cmp(b),
+            (Bytes(a), Bytes(b)) => a.cmp(b),
+            (String(a), String(b)) => a.cmp(b),
+            (a, b) => a.to_string().cmp(&b.to_string()),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            SqlValue::Int(v) => Some(*v as f64),
+            SqlValue::Float(v) => Some(*v),
+            SqlValue::Decimal(v) => v.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// JSON form for exports: numbers and booleans stay native.
+    /// Decimals become numbers only when that does not lose digits.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match self {
+            SqlValue::Null => Value::Null,
+            SqlValue::Bool(v) => Value::Bool(*v),
+            SqlValue::Int(v) => Value::from(*v),
+            SqlValue::Float(v) => serde_json::Number::from_f64(*v).map(Value::Number).unwrap_or(Value::Null),
+            SqlValue::Decimal(v) => v
+                .parse::<i64>()
+                .ok()
+                .map(Value::from)
+                .or_else(|| {
+                    v.parse::<f64>()
+                        .ok()
+                        .filter(|f| f.to_string() == *v)
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Value::Number)
+                })
+                .unwrap_or_else(|| Value::String(v.clone())),
+            SqlValue::Json(v) => v.clone(),
+            other => Value::String(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SqlValue {
+    /// Rendering used by result grids and text exports
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlValue::Null => write!(f, "NULL"),
+            SqlValue::Bool(v) => write!(f, "{}", v),
+            SqlValue::Int(v) => write!(f, "{}", v),
+            SqlValue::Float(v) => write!(f, "{}", v),
+            SqlValue::Decimal(v) | SqlValue::String(v) => write!(f, "{}", v),
+            SqlValue::Bytes(v) => write!(f, "0x{}", hex::encode(v)),
+            SqlValue::Json(v) => write!(f, "{}", v),
+            SqlValue::Date(v) => write!(f, "{}", v.format("%Y-%m-%d")),
+            SqlValue::Time(v) => write!(f, "{}", v.format("%H:%M:%S")),
+            SqlValue::DateTime(v) => write!(f, "{}", v.format("%Y-%m-%d %H:%M:%S")),
+            SqlValue::DateTimeTz(v) => write!(f, "{}", v.format("%Y-%m-%d %H:%M:%S%z")),
+            SqlValue::Uuid(v) => write!(f, "{}", v),
+        }
+    }
 }
 
 /// Database tree node types for hierarchical display
@@ -20,6 +130,8 @@ pub enum SqlValue {
 pub enum DbNodeType {
     Connection,
     Database,
+    SchemasFolder,
+    Schema,
     TablesFolder,
     Table,
     ColumnsFolder,
@@ -43,6 +155,8 @@ impl fmt::Display for DbNodeType {
         match self {
             DbNodeType::Connection => write!(f, "Connection"),
             DbNodeType::Database => write!(f, "Database"),
+            DbNodeType::SchemasFolder => write!(f, "Schemas"),
+            DbNodeType::Schema => write!(f, "Schema"),
             DbNodeType::TablesFolder => write!(f, "Tables"),
             DbNodeType::Table => write!(f, "Table"),
             DbNodeType::ColumnsFolder => write!(f, "Columns"),
@@ -135,6 +249,20 @@ impl DbNode {
         self
     }
 
+    /// Database this node belongs to, the node itself for database nodes
+    pub fn database(&self) -> Option<&String> {
+        match self.node_type {
+            DbNodeType::Database => Some(&self.name),
+            _ => self.metadata.as_ref()?.get("database"),
+        }
+    }
+
+    /// Namespace holding this node's objects: the schema when present, otherwise the database
+    pub fn object_scope(&self) -> Option<&String> {
+        let metadata = self.metadata.as_ref()?;
+        metadata.get("schema").or_else(|| metadata.get("database"))
+    }
+
     pub fn sort_children(&mut self) {
         self.children.sort();
     }
@@ -178,6 +306,30 @@ pub struct IndexInfo {
     pub index_type: Option<String>,
 }
 
+/// Foreign key of a table, columns in constraint order
+#[derive(Debug, Clone)]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_schema: Option<String>,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+}
+
+/// A session in the server's process list
+#[derive(Debug, Clone)]
+pub struct ServerSession {
+    /// Id the server kills the session by: MySQL connection id, PostgreSQL backend pid
+    pub id: u64,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub database: Option<String>,
+    pub state: Option<String>,
+    /// Seconds spent in the current state or statement
+    pub duration_secs: Option<i64>,
+    pub query: Option<String>,
+}
+
 /// Table information with description/metadata
 #[derive(Debug, Clone)]
 pub struct TableInfo {
@@ -455,7 +607,7 @@ pub struct ObjectView {
 // === Table Data Query Types ===
 
 /// Abstract data type for UI rendering
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FieldType {
     /// Integer numbers (INT, BIGINT, SMALLINT, etc.)
     Integer,
@@ -477,6 +629,8 @@ pub enum FieldType {
     Binary,
     /// JSON data
     Json,
+    /// UUID / GUID
+    Uuid,
     /// Unknown or unsupported type
     Unknown,
 }
@@ -486,28 +640,37 @@ impl FieldType {
     pub fn from_db_type(db_type: &str) -> Self {
         let upper = db_type.to_uppercase();
         let base_type = upper.split('(').next().unwrap_or(&upper).trim();
+        let base_type = base_type.strip_suffix(" UNSIGNED").unwrap_or(base_type);
 
         match base_type {
-            // Integer types
-            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "TINYINT" | "MEDIUMINT" | "SERIAL" | "BIGSERIAL" | "SMALLSERIAL" => Self::Integer,
+            // Integer types (INT2/INT4/INT8 are the PostgreSQL driver names)
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "TINYINT" | "MEDIUMINT" | "SERIAL" | "BIGSERIAL" | "SMALLSERIAL"
+            | "INT2" | "INT4" | "INT8" => Self::Integer,
             // Decimal types
-            "DECIMAL" | "NUMERIC" | "FLOAT" | "DOUBLE" | "REAL" | "DOUBLE PRECISION" | "MONEY" => Self::Decimal,
+            "DECIMAL" | "NUMERIC" | "NUMBER" | "FLOAT" | "DOUBLE" | "REAL" | "DOUBLE PRECISION" | "MONEY" | "SMALLMONEY"
+            | "FLOAT4" | "FLOAT8" | "BINARY_FLOAT" | "BINARY_DOUBLE" => Self::Decimal,
             // Boolean
             "BOOL" | "BOOLEAN" | "BIT" => Self::Boolean,
             // Date/Time
             "DATE" => Self::Date,
             "TIME" => Self::Time,
-            "DATETIME" | "TIMESTAMP" | "TIMESTAMPTZ" => Self::DateTime,
+            "DATETIME" | "DATETIME2" | "SMALLDATETIME" | "DATETIMEOFFSET" | "TIMESTAMP" | "TIMESTAMPTZ" => Self::DateTime,
             // Text types
-            "CHAR" | "VARCHAR" | "NCHAR" | "NVARCHAR" | "CHARACTER VARYING" | "CHARACTER" => Self::Text,
+            "CHAR" | "VARCHAR" | "NCHAR" | "NVARCHAR" | "CHARACTER VARYING" | "CHARACTER" | "VARCHAR2" | "NVARCHAR2" | "BPCHAR" => Self::Text,
             "TEXT" | "LONGTEXT" | "MEDIUMTEXT" | "TINYTEXT" | "CLOB" | "NTEXT" => Self::LongText,
             // Binary
-            "BLOB" | "LONGBLOB" | "MEDIUMBLOB" | "TINYBLOB" | "BINARY" | "VARBINARY" | "BYTEA" | "IMAGE" => Self::Binary,
+            "BLOB" | "LONGBLOB" | "MEDIUMBLOB" | "TINYBLOB" | "BINARY" | "VARBINARY" | "BYTEA" | "IMAGE" | "RAW" => Self::Binary,
             // JSON
             "JSON" | "JSONB" => Self::Json,
+            "UUID" | "UNIQUEIDENTIFIER" => Self::Uuid,
             _ => Self::Unknown,
         }
     }
+
+    /// Numeric columns are right-aligned in result grids
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer | Self::Decimal)
+    }
 }
 
 /// Column metadata for table data display
@@ -594,6 +757,8 @@ pub struct SortCondition {
 pub struct TableDataRequest {
     /// Database name
     pub database: String,
+    /// Schema name, for backends that group tables into schemas
+    pub schema: Option<String>,
     /// Table name
     pub table: String,
     /// Page number (1-based)
@@ -614,6 +779,7 @@ impl TableDataRequest {
     pub fn new(database: impl Into<String>, table: impl Into<String>) -> Self {
         Self {
             database: database.into(),
+            schema: None,
             table: table.into(),
             page: 1,
             page_size: 100,
@@ -624,6 +790,16 @@ impl TableDataRequest {
         }
     }
 
+    pub fn with_schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Namespace the table lives in: the schema when set, otherwise the database
+    pub fn scope(&self) -> &str {
+        self.schema.as_deref().unwrap_or(&self.database)
+    }
+
     pub fn with_page(mut self, page: usize, page_size: usize) -> Self {
         self.page = page;
         self.page_size = page_size;
@@ -658,8 +834,8 @@ impl TableDataRequest {
 pub struct TableDataResponse {
     /// Column metadata
     pub columns: Vec<TableColumnMeta>,
-    /// Row data (each cell is Option<String>, None means NULL)
-    pub rows: Vec<Vec<Option<String>>>,
+    /// Row data as typed cells, NULL is `SqlValue::Null`
+    pub rows: Vec<Vec<SqlValue>>,
     /// Total row count (for pagination)
     pub total_count: usize,
     /// Current page
@@ -0,0 +1,389 @@
+use std::any::Any;
+use std::time::Duration;
+
+use db::{DbSession, GlobalDbState, PooledConnectionState, ServerSession, SessionPurpose};
+use gpui::prelude::FluentBuilder;
+use gpui::{div, px, AnyElement, App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::tag::Tag;
+use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, Sizable, Size, StyledExt};
+use one_core::tab_container::{TabContent, TabContentType};
+
+/// 独占会话在刷新时的快照
+struct SessionRow {
+    session: DbSession,
+    running_sql: Option<String>,
+    idle_for: Duration,
+    server_ids: Vec<u64>,
+}
+
+impl From<DbSession> for SessionRow {
+    fn from(session: DbSession) -> Self {
+        Self {
+            running_sql: session.running_sql(),
+            idle_for: session.idle_for(),
+            server_ids: session.running_server_ids(),
+            session,
+        }
+    }
+}
+
+/// 一个连接下本应用打开的连接和服务器上的进程
+struct ConnectionSessions {
+    connection_id: String,
+    name: String,
+    pooled: Vec<PooledConnectionState>,
+    sessions: Vec<SessionRow>,
+    server: Result<Vec<ServerSession>, String>,
+}
+
+impl ConnectionSessions {
+    /// 池连接正在执行的语句，按服务器进程 id 在进程列表中查找
+    fn running_query(&self, server_ids: &[u64]) -> Option<String> {
+        let server = self.server.as_ref().ok()?;
+        server
+            .iter()
+            .find(|session| server_ids.contains(&session.id))
+            .and_then(|session| session.query.clone())
+    }
+}
+
+/// 连接状态：连接池中的连接、编辑器的独占会话以及服务器进程列表
+pub struct ConnectionSessionsPanel {
+    connection_ids: Vec<String>,
+    connections: Vec<ConnectionSessions>,
+    loading: bool,
+    status: String,
+}
+
+impl ConnectionSessionsPanel {
+    pub fn new(connection_ids: Vec<String>, cx: &mut Context<Self>) -> Self {
+        let mut panel = Self {
+            connection_ids,
+            connections: Vec::new(),
+            loading: false,
+            status: String::new(),
+        };
+        panel.load(cx);
+        panel
+    }
+
+    pub fn load(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        let global_state = cx.global::<GlobalDbState>().clone();
+        let connection_ids = self.connection_ids.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db::spawn_result(async move {
+                let mut connections = Vec::new();
+                for connection_id in connection_ids {
+                    let name = global_state.get_config(&connection_id).await
+                        .map(|config| config.name)
+                        .unwrap_or_else(|| connection_id.clone());
+
+                    let server = async {
+                        let (plugin, conn_arc) = global_state.get_plugin_and_connection(&connection_id, SessionPurpose::Metadata).await?;
+                        let conn = conn_arc.read().await;
+                        plugin.list_server_sessions(&**conn).await
+                    }.await.map_err(|e| e.to_string());
+
+                    // 进程列表查完再取池状态，这样用于查询的元数据连接不会显示为占用
+                    let pooled = global_state.connection_pool.connection_states(Some(&connection_id)).await;
+                    let sessions = global_state.sessions_for(&connection_id).await
+                        .into_iter()
+                        .map(SessionRow::from)
+                        .collect();
+
+                    connections.push(ConnectionSessions { connection_id, name, pooled, sessions, server });
+                }
+                Ok(connections)
+            }).await;
+
+            _ = this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(connections) => this.connections = connections,
+                    Err(e) => this.status = format!("加载失败: {}", e),
+                }
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    /// 执行一个操作，完成后刷新列表
+    fn run_action(&mut self, action: impl std::future::Future<Output = anyhow::Result<()>> + Send + 'static, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let result = db::spawn_result(action).await;
+            _ = this.update(cx, |this, cx| {
+                this.status = match result {
+                    Ok(()) => String::new(),
+                    Err(e) => format!("操作失败: {}", e),
+                };
+                this.load(cx);
+            });
+        }).detach();
+    }
+
+    fn close_pooled(&mut self, connection_id: String, state: &PooledConnectionState, cx: &mut Context<Self>) {
+        let global_state = cx.global::<GlobalDbState>().clone();
+        let database = state.database.clone();
+        let purpose = state.purpose.clone();
+        self.run_action(async move {
+            global_state.close_pooled_connection(&connection_id, database.as_deref(), &purpose).await;
+            Ok(())
+        }, cx);
+    }
+
+    fn close_session(&mut self, session: DbSession, cx: &mut Context<Self>) {
+        let global_state = cx.global::<GlobalDbState>().clone();
+        self.run_action(async move {
+            global_state.close_session(&session).await;
+            Ok(())
+        }, cx);
+    }
+
+    fn kill_server_session(&mut self, connection_id: String, id: u64, cx: &mut Context<Self>) {
+        let global_state = cx.global::<GlobalDbState>().clone();
+        self.run_action(async move {
+            let (plugin, conn_arc) = global_state.get_plugin_and_connection(&connection_id, SessionPurpose::Metadata).await?;
+            let conn = conn_arc.read().await;
+            plugin.kill_server_session(&**conn, id).await
+        }, cx);
+    }
+
+    fn section_title(title: &'static str, count: usize, cx: &App) -> impl IntoElement {
+        div()
+            .pt_2()
+            .text_sm()
+            .font_semibold()
+            .text_color(cx.theme().muted_foreground)
+            .child(format!("{} ({})", title, count))
+    }
+
+    fn row(cx: &App) -> gpui::Div {
+        h_flex()
+            .gap_3()
+            .px_2()
+            .py_1()
+            .items_center()
+            .text_sm()
+            .rounded(cx.theme().radius)
+            .bg(cx.theme().secondary)
+    }
+
+    fn cell(width: f32, text: impl Into<SharedString>) -> impl IntoElement {
+        div().w(px(width)).flex_shrink_0().truncate().child(text.into())
+    }
+
+    fn query_cell(query: Option<String>, cx: &App) -> impl IntoElement {
+        let query = query.map(|sql| sql.split_whitespace().collect::<Vec<_>>().join(" "));
+        div()
+            .flex_1()
+            .truncate()
+            .when_none(&query, |el| el.text_color(cx.theme().muted_foreground).child("-"))
+            .when_some(query, |el, query| el.child(query))
+    }
+
+    fn render_connection(&self, ix: usize, connection: &ConnectionSessions, cx: &mut Context<Self>) -> impl IntoElement {
+        let pooled = connection.pooled.iter().enumerate().map(|(row_ix, state)| {
+            let connection_id = connection.connection_id.clone();
+            let close_state = state.clone();
+            let query = connection.running_query(&state.server_ids);
+
+            Self::row(cx)
+                .child(Self::cell(120., state.purpose.to_string()))
+                .child(Self::cell(120., state.database.clone().unwrap_or_else(|| "-".to_string())))
+                .child(if !state.connected {
+                    Tag::danger().small().child("连接失败")
+                } else if state.in_use {
+                    Tag::warning().small().child("使用中")
+                } else {
+                    Tag::success().small().child("空闲")
+                })
+                .child(Self::cell(80., format_duration(state.idle_for)))
+                .child(match &state.last_error {
+                    Some(error) if !state.connected => div().flex_1().truncate().text_color(cx.theme().danger).child(error.clone()),
+                    _ => div().flex_1().child(Self::query_cell(query, cx)),
+                })
+                .child(
+                    Button::new(("close-pooled", ix * 1000 + row_ix))
+                        .with_size(Size::XSmall)
+                        .ghost()
+                        .label("关闭")
+                        .on_click(cx.listener(move |this, _, _, cx| this.close_pooled(connection_id.clone(), &close_state, cx))),
+                )
+        }).collect::<Vec<_>>();
+
+        let sessions = connection.sessions.iter().enumerate().map(|(row_ix, row)| {
+            let session = row.session.clone();
+            let query = row.running_sql.clone().or_else(|| connection.running_query(&row.server_ids));
+            let transaction_open = row.session.transaction().open;
+
+            Self::row(cx)
+                .child(Self::cell(120., row.session.id().to_string()))
+                .child(Self::cell(120., row.session.database().unwrap_or("-").to_string()))
+                .child(if row.running_sql.is_some() {
+                    Tag::warning().small().child("执行中")
+                } else if transaction_open {
+                    Tag::info().small().child("事务中")
+                } else {
+                    Tag::success().small().child("空闲")
+                })
+                .child(Self::cell(80., if row.running_sql.is_some() { "-".to_string() } else { format_duration(row.idle_for) }))
+                .child(Self::query_cell(query, cx))
+                .child(
+                    Button::new(("close-session", ix * 1000 + row_ix))
+                        .with_size(Size::XSmall)
+                        .ghost()
+                        .label("关闭")
+                        .on_click(cx.listener(move |this, _, _, cx| this.close_session(session.clone(), cx))),
+                )
+        }).collect::<Vec<_>>();
+
+        let server = match &connection.server {
+            Ok(server) if server.is_empty() => div()
+                .px_2()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("该数据库不支持查看进程列表")
+                .into_any_element(),
+            Ok(server) => v_flex()
+                .gap_1()
+                .children(server.iter().map(|process| {
+                    let connection_id = connection.connection_id.clone();
+                    let id = process.id;
+                    let label = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+                    Self::row(cx)
+                        .child(Self::cell(60., id.to_string()))
+                        .child(Self::cell(100., label(&process.user)))
+                        .child(Self::cell(140., label(&process.host)))
+                        .child(Self::cell(100., label(&process.database)))
+                        .child(Self::cell(120., label(&process.state)))
+                        .child(Self::cell(60., process.duration_secs.map(|secs| format!("{}s", secs)).unwrap_or_default()))
+                        .child(Self::query_cell(process.query.clone(), cx))
+                        .child(
+                            Button::new(("kill-server-session", id as usize))
+                                .with_size(Size::XSmall)
+                                .ghost()
+                                .label("终止")
+                                .on_click(cx.listener(move |this, _, _, cx| this.kill_server_session(connection_id.clone(), id, cx))),
+                        )
+                }))
+                .into_any_element(),
+            Err(e) => div()
+                .px_2()
+                .text_sm()
+                .text_color(cx.theme().danger)
+                .child(format!("获取进程列表失败: {}", e))
+                .into_any_element(),
+        };
+
+        v_flex()
+            .gap_1()
+            .child(div().text_base().font_semibold().child(connection.name.clone()))
+            .child(Self::section_title("连接池", connection.pooled.len(), cx))
+            .children(pooled)
+            .child(Self::section_title("独占会话", connection.sessions.len(), cx))
+            .children(sessions)
+            .child(Self::section_title("服务器进程", connection.server.as_ref().map(|s| s.len()).unwrap_or(0), cx))
+            .child(server)
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+impl Render for ConnectionSessionsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let connections = self.connections.iter().enumerate()
+            .map(|(ix, connection)| self.render_connection(ix, connection, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .p_2()
+                    .items_center()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(
+                        Button::new("refresh-sessions")
+                            .with_size(Size::Small)
+                            .ghost()
+                            .icon(IconName::Loader)
+                            .label("刷新")
+                            .loading(self.loading)
+                            .on_click(cx.listener(|this, _, _, cx| this.load(cx))),
+                    )
+                    .child(div().flex_1())
+                    .when(!self.status.is_empty(), |el| {
+                        el.child(div().text_sm().text_color(cx.theme().danger).child(self.status.clone()))
+                    }),
+            )
+            .child(
+                v_flex()
+                    .id("connection-sessions-list")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .p_2()
+                    .gap_4()
+                    .children(connections),
+            )
+    }
+}
+
+/// 连接会话标签页
+#[derive(Clone)]
+pub struct ConnectionSessionsTabContent {
+    panel: Entity<ConnectionSessionsPanel>,
+}
+
+impl ConnectionSessionsTabContent {
+    pub fn new(connection_ids: Vec<String>, cx: &mut App) -> Self {
+        let panel = cx.new(|cx| ConnectionSessionsPanel::new(connection_ids, cx));
+        Self { panel }
+    }
+}
+
+impl TabContent for ConnectionSessionsTabContent {
+    fn title(&self) -> SharedString {
+        SharedString::from("会话")
+    }
+
+    fn icon(&self) -> Option<IconName> {
+        Some(IconName::Inspector)
+    }
+
+    fn render_content(&self, _window: &mut Window, _cx: &mut App) -> AnyElement {
+        self.panel.clone().into_any_element()
+    }
+
+    fn on_activate(&self, _window: &mut Window, cx: &mut App) {
+        self.panel.update(cx, |panel, cx| panel.load(cx));
+    }
+
+    fn content_type(&self) -> TabContentType {
+        TabContentType::Custom("connection-sessions".to_string())
+    }
+
+    // 连接由所在的数据库标签页决定，恢复时不需要额外状态
+    fn save_state(&self, _cx: &App) -> Option<serde_json::Value> {
+        Some(serde_json::Value::Null)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -7,11 +7,14 @@ use gpui_component::{
     v_flex, ActiveTheme, Sizable,
 };
 
-use db::{DataExporter, DataFormat, ExportConfig, GlobalDbState};
+use one_core::storage::DatabaseType;
+use db::{DataExporter, DataFormat, ExportConfig, GlobalDbState, SessionPurpose};
+use uuid::Uuid;
 
 pub struct DataExportView {
     connection_id: String,
     database: Entity<InputState>,
+    schema: Entity<InputState>,
     pub tables: Entity<InputState>,
     format: Entity<DataFormat>,
     include_schema: Entity<bool>,
@@ -28,6 +31,7 @@ impl DataExportView {
     pub fn new(
         connection_id: impl Into<String>,
         database: String,
+        schema: Option<String>,
         window: &mut Window,
         cx: &mut App,
     ) -> Entity<Self> {
@@ -37,6 +41,11 @@ impl DataExportView {
                 state.set_value(database, window, cx);
                 state
             });
+            let schema_input = cx.new(|cx| {
+                let mut state = InputState::new(window, cx);
+                state.set_value(schema.unwrap_or_default(), window, cx);
+                state
+            });
             let tables_input = cx.new(|cx| InputState::new(window, cx));
             let where_input = cx.new(|cx| InputState::new(window, cx));
             let limit_input = cx.new(|cx| InputState::new(window, cx));
@@ -44,6 +53,7 @@ impl DataExportView {
             Self {
                 connection_id: connection_id.into(),
                 database: database_input,
+                schema: schema_input,
                 tables: tables_input,
                 format: cx.new(|_| DataFormat::Sql),
                 include_schema: cx.new(|_| true),
@@ -91,6 +101,7 @@ impl DataExportView {
         let connection_id = self.connection_id.clone();
 
         let database = self.database.read(cx).text().to_string();
+        let schema = self.schema.read(cx).text().to_string().trim().to_string();
         let tables_str = self.tables.read(cx).text().to_string();
         let format = *self.format.read(cx);
         let include_schema = *self.include_schema.read(cx);
@@ -142,7 +153,13 @@ impl DataExportView {
 
         cx.spawn(async move |cx| {
             let config = match global_state.get_config(&connection_id).await {
-                Some(cfg) => cfg,
+                // SQLite 的 database 是文件路径，其余数据库连接到所选的库
+                Some(mut cfg) => {
+                    if cfg.database_type != DatabaseType::SQLite && !database.is_empty() {
+                        cfg.database = Some(database.clone());
+                    }
+                    cfg
+                }
                 None => {
                     cx.update(|cx| {
                         status.update(cx, |s, cx| {
@@ -154,20 +171,13 @@ impl DataExportView {
                 }
             };
 
-            let plugin = match global_state.db_manager.get_plugin(&config.database_type) {
-                Ok(p) => p,
-                Err(e) => {
-                    cx.update(|cx| {
-                        status.update(cx, |s, cx| {
-                            *s = format!("Error: {}", e);
-                            cx.notify();
-                        });
-                    }).ok();
-                    return;
-                }
-            };
-
-            let connection = match plugin.create_connection(config).await {
+            // 后台任务使用自己的池连接，不占用编辑器和对象树的连接，结束后即断开
+            let purpose = SessionPurpose::Background(Uuid::new_v4().to_string());
+            let connection = match global_state
+                .connection_pool
+                .get_connection(config, purpose.clone(), &global_state.db_manager)
+                .await
+            {
                 Ok(c) => c,
                 Err(e) => {
                     cx.update(|cx| {
@@ -183,6 +193,7 @@ impl DataExportView {
             let export_config = ExportConfig {
                 format,
                 database,
+                schema: if schema.is_empty() { None } else { Some(schema) },
                 tables,
                 include_schema,
                 include_data,
@@ -190,7 +201,11 @@ impl DataExportView {
                 limit,
             };
 
-            match DataExporter::export(connection.as_ref(), export_config).await {
+            let result = DataExporter::export(&**connection.read().await, export_config).await;
+            drop(connection);
+            global_state.release_connections(&connection_id, &purpose).await;
+
+            match result {
                 Ok(result) => {
                     // 写入文件
                     if let Err(e) = std::fs::write(&output_path_str, result.output) {
@@ -237,6 +252,7 @@ impl Clone for DataExportView {
         Self {
             connection_id: self.connection_id.clone(),
             database: self.database.clone(),
+            schema: self.schema.clone(),
             tables: self.tables.clone(),
             format: self.format.clone(),
             include_schema: self.include_schema.clone(),
@@ -274,6 +290,14 @@ impl Render for DataExportView {
                     .child(div().w_24().child("Database:"))
                     .child(Input::new(&self.database).w_64()),
             )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().w_24().child("Schema:"))
+                    .child(Input::new(&self.schema).w_64())
+                    .child(div().text_xs().text_color(cx.theme().muted_foreground).child("(Optional)")),
+            )
             .child(
                 h_flex()
                     .gap_2()
@@ -7,11 +7,14 @@ use gpui_component::{
     v_flex, ActiveTheme, Sizable,
 };
 
-use db::{DataFormat, DataImporter, GlobalDbState, ImportConfig};
+use one_core::storage::DatabaseType;
+use db::{DataFormat, DataImporter, GlobalDbState, ImportConfig, SessionPurpose};
+use uuid::Uuid;
 
 pub struct DataImportView {
     connection_id: String,
     database: Entity<InputState>,
+    schema: Entity<InputState>,
     table: Entity<InputState>,
     format: Entity<DataFormat>,
     stop_on_error: Entity<bool>,
@@ -27,6 +30,7 @@ impl DataImportView {
     pub fn new(
         connection_id: impl Into<String>,
         database: String,
+        schema: Option<String>,
         window: &mut Window,
         cx: &mut App,
     ) -> Entity<Self> {
@@ -36,11 +40,17 @@ impl DataImportView {
                 state.set_value(database, window, cx);
                 state
             });
+            let schema_input = cx.new(|cx| {
+                let mut state = InputState::new(window, cx);
+                state.set_value(schema.unwrap_or_default(), window, cx);
+                state
+            });
             let table_input = cx.new(|cx| InputState::new(window, cx));
 
             Self {
                 connection_id: connection_id.into(),
                 database: database_input,
+                schema: schema_input,
                 table: table_input,
                 format: cx.new(|_| DataFormat::Sql),
                 stop_on_error: cx.new(|_| true),
@@ -95,6 +105,7 @@ impl DataImportView {
         let connection_id = self.connection_id.clone();
 
         let database = self.database.read(cx).text().to_string();
+        let schema = self.schema.read(cx).text().to_string().trim().to_string();
         let table = self.table.read(cx).text().to_string();
         let format = *self.format.read(cx);
         let stop_on_error = *self.stop_on_error.read(cx);
@@ -119,7 +130,13 @@ impl DataImportView {
 
         cx.spawn(async move |cx| {
             let config = match global_state.get_config(&connection_id).await {
-                Some(cfg) => cfg,
+                // SQLite 的 database 是文件路径，其余数据库连接到所选的库
+                Some(mut cfg) => {
+                    if cfg.database_type != DatabaseType::SQLite && !database.is_empty() {
+                        cfg.database = Some(database.clone());
+                    }
+                    cfg
+                }
                 None => {
                     cx.update(|cx| {
                         status.update(cx, |s, cx| {
@@ -131,12 +148,13 @@ impl DataImportView {
                 }
             };
 
-            let plugin = match global_state.db_manager.get_plugin(&config.database_type) {
-                Ok(p) => p,
+            // 读取文件
+            let data = match std::fs::read_to_string(&file_path_str) {
+                Ok(d) => d,
                 Err(e) => {
                     cx.update(|cx| {
                         status.update(cx, |s, cx| {
-                            *s = format!("Error: {}", e);
+                            *s = format!("File read error: {}", e);
                             cx.notify();
                         });
                     }).ok();
@@ -144,7 +162,13 @@ impl DataImportView {
                 }
             };
 
-            let connection = match plugin.create_connection(config).await {
+            // 后台任务使用自己的池连接，不占用编辑器和对象树的连接，结束后即断开
+            let purpose = SessionPurpose::Background(Uuid::new_v4().to_string());
+            let connection = match global_state
+                .connection_pool
+                .get_connection(config, purpose.clone(), &global_state.db_manager)
+                .await
+            {
                 Ok(c) => c,
                 Err(e) => {
                     cx.update(|cx| {
@@ -157,30 +181,21 @@ impl DataImportView {
                 }
             };
 
-            // 读取文件
-            let data = match std::fs::read_to_string(&file_path_str) {
-                Ok(d) => d,
-                Err(e) => {
-                    cx.update(|cx| {
-                        status.update(cx, |s, cx| {
-                            *s = format!("File read error: {}", e);
-                            cx.notify();
-                        });
-                    }).ok();
-                    return;
-                }
-            };
-
             let import_config = ImportConfig {
                 format,
                 database,
+                schema: if schema.is_empty() { None } else { Some(schema) },
                 table: if table.is_empty() { None } else { Some(table) },
                 stop_on_error,
                 use_transaction,
                 truncate_before_import: truncate_before,
             };
 
-            match DataImporter::import(connection.as_ref(), import_config, data).await {
+            let result = DataImporter::import(&**connection.read().await, import_config, data).await;
+            drop(connection);
+            global_state.release_connections(&connection_id, &purpose).await;
+
+            match result {
                 Ok(result) => {
                     cx.update(|cx| {
                         status.update(cx, |s, cx| {
@@ -224,6 +239,7 @@ impl Clone for DataImportView {
         Self {
             connection_id: self.connection_id.clone(),
             database: self.database.clone(),
+            schema: self.schema.clone(),
             table: self.table.clone(),
             format: self.format.clone(),
             stop_on_error: self.stop_on_error.clone(),
@@ -260,6 +276,14 @@ impl Render for DataImportView {
                     .child(div().w_24().child("Database:"))
                     .child(Input::new(&self.database).w_64()),
             )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().w_24().child("Schema:"))
+                    .child(Input::new(&self.schema).w_64())
+                    .child(div().text_xs().text_color(cx.theme().muted_foreground).child("(Optional)")),
+            )
             .child(
                 h_flex()
                     .gap_2()
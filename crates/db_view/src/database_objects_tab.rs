@@ -4,7 +4,7 @@ use gpui::{
     div, AnyElement, App, AppContext, Context, Entity, Focusable, FocusHandle, IntoElement,
     ParentElement, SharedString, Styled, Window,
 };
-use db::{DbNode, DbNodeType, ObjectView};
+use db::{DbNode, DbNodeType, ObjectView, SessionPurpose};
 use gpui_component::{
     table::{Table, TableState},
     v_flex, ActiveTheme, Size,
@@ -70,7 +70,7 @@ impl DatabaseObjectsPanel {
         });
     }
 
-    fn load_data_for_node(&self, node: DbNode, config: DbConnectionConfig, cx: &mut App) {
+    fn load_data_for_node(&self, node: DbNode, mut config: DbConnectionConfig, cx: &mut App) {
         let loaded_data = self.loaded_data.clone();
         let table_state = self.table_state.clone();
 
@@ -78,10 +78,14 @@ impl DatabaseObjectsPanel {
             let global_state = cx.update(|cx| cx.global::<db::GlobalDbState>().clone()).ok()?;
 
             let plugin = global_state.db_manager.get_plugin(&config.database_type).ok()?;
+            // 支持 schema 的数据库只能看到连接所在库的 schema
+            if let Some(database) = node.database().filter(|_| plugin.supports_schema()) {
+                config.database = Some(database.clone());
+            }
 
             let conn_arc = global_state
                 .connection_pool
-                .get_connection(config, &global_state.db_manager)
+                .get_connection(config, SessionPurpose::Metadata, &global_state.db_manager)
                 .await
                 .ok()?;
 
@@ -91,35 +95,36 @@ impl DatabaseObjectsPanel {
                 DbNodeType::Connection => {
                     plugin.list_databases_view(&**conn).await.ok()
                 },
-                DbNodeType::Database | DbNodeType::TablesFolder => {
-                    let mut database = &node.name;
-                    if node.metadata.is_some() {
-                        database = node.metadata.as_ref()?.get("database").or(Some(&node.name))?;
-                    }
+                // 支持 schema 的数据库在数据库层级展示 schema 列表
+                DbNodeType::Database | DbNodeType::SchemasFolder if plugin.supports_schema() => {
+                    plugin.list_schemas_view(&**conn).await.ok()
+                }
+                DbNodeType::Database | DbNodeType::Schema | DbNodeType::TablesFolder => {
+                    let database = node.object_scope().unwrap_or(&node.name);
                     plugin.list_tables_view(&**conn, database).await.ok()
                 }
                 DbNodeType::Table => {
-                    let database = node.metadata.as_ref()?.get("database")?;
+                    let database = node.object_scope()?;
                     plugin.list_columns_view(&**conn, database, &node.name).await.ok()
                 }
                 DbNodeType::ViewsFolder => {
-                    let database = node.metadata.as_ref()?.get("database").or(Some(&node.name))?;
+                    let database = node.object_scope().unwrap_or(&node.name);
                     plugin.list_views_view(&**conn, database).await.ok()
                 }
                 DbNodeType::FunctionsFolder => {
-                    let database = node.metadata.as_ref()?.get("database").or(Some(&node.name))?;
+                    let database = node.object_scope().unwrap_or(&node.name);
                     plugin.list_functions_view(&**conn, database).await.ok()
                 }
                 DbNodeType::ProceduresFolder => {
-                    let database = node.metadata.as_ref()?.get("database").or(Some(&node.name))?;
+                    let database = node.object_scope().unwrap_or(&node.name);
                     plugin.list_procedures_view(&**conn, database).await.ok()
                 }
                 DbNodeType::TriggersFolder => {
-                    let database = node.metadata.as_ref()?.get("database").or(Some(&node.name))?;
+                    let database = node.object_scope().unwrap_or(&node.name);
                     plugin.list_triggers_view(&**conn, database).await.ok()
                 }
                 DbNodeType::SequencesFolder => {
-                    let database = node.metadata.as_ref()?.get("database").or(Some(&node.name))?;
+                    let database = node.object_scope().unwrap_or(&node.name);
                     plugin.list_sequences_view(&**conn, database).await.ok()
                 }
                 _ => None,
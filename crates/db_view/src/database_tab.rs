@@ -1,9 +1,12 @@
 use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
 
-use db::{DbNode, DbNodeType, GlobalDbState};
+use db::{DbNode, DbNodeType, GlobalDbState, SessionPurpose};
 use gpui::{div, px, prelude::FluentBuilder, AnyElement, App, AppContext, Context, Entity, FontWeight, Hsla, IntoElement, ParentElement, SharedString, Styled, Subscription, Window};
 use gpui_component::{button::ButtonVariants, h_flex, resizable::{h_resizable, resizable_panel}, v_flex, ActiveTheme, IconName, WindowExt};
-use one_core::{gpui_tokio::Tokio, storage::StoredConnection, tab_container::{TabContainer, TabContent, TabContentType, TabItem}};
+use one_core::{gpui_tokio::Tokio, storage::StoredConnection, tab_container::{TabContainer, TabContent, TabContentType, TabItem, TabLayout, TabState}};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 // 字符集选择项
@@ -54,7 +57,7 @@ impl gpui_component::select::SelectItem for CollationItem {
     }
 }
 
-use crate::{database_objects_tab::DatabaseObjectsPanel, db_tree_view::{DbTreeView, DbTreeViewEvent}};
+use crate::{connection_sessions_panel::ConnectionSessionsTabContent, database_objects_tab::DatabaseObjectsPanel, db_tree_view::{DbTreeView, DbTreeViewEvent}, query_history_panel::QueryHistoryTabContent, sql_drafts::{unclaimed_drafts, SqlDraft}, sql_editor_view::SqlEditorTabContent, table_data_tab::{TableDataState, TableDataTabContent}};
 
 // Event handler for database tree view events
 struct DatabaseEventHandler {
@@ -215,11 +218,14 @@ impl DatabaseEventHandler {
         window: &mut Window,
         cx: &mut App,
     ) {
-        use crate::sql_editor_view::SqlEditorTabContent;
-
         let connection_id = node.connection_id.clone();
-        // 获取数据库名：如果是数据库节点则用 name，否则用 parent_context
-        let database = node.name.clone();
+        // 数据库节点用 name，schema 节点用其所属数据库
+        let database = node
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("database"))
+            .cloned()
+            .unwrap_or_else(|| node.name.clone());
         let sql_editor = SqlEditorTabContent::new_with_config(
             format!("{} - Query", database),
             connection_id,
@@ -243,13 +249,15 @@ impl DatabaseEventHandler {
         window: &mut Window,
         cx: &mut App,
     ) {
-        use crate::table_data_tab::TableDataTabContent;
-
         let connection_id = node.connection_id.clone();
         let table = node.name.clone();
-        let metadata = &node.metadata.unwrap();
+        let metadata = node.metadata.as_ref().unwrap();
         let database = metadata.get("database").unwrap();
-        let tab_id = format!("table-data-{}.{}", database, table);
+        let schema = metadata.get("schema").cloned();
+        let tab_id = match &schema {
+            Some(schema) => format!("table-data-{}.{}.{}", database, schema, table),
+            None => format!("table-data-{}.{}", database, table),
+        };
 
         let config = Tokio::block_on(cx, async move {
             global_state.get_config(&connection_id).await
@@ -266,6 +274,7 @@ impl DatabaseEventHandler {
                     move |window, cx| {
                         let table_data = TableDataTabContent::new(
                             database_clone,
+                            schema,
                             table_clone,
                             config_id,
                             window,
@@ -288,13 +297,15 @@ impl DatabaseEventHandler {
         window: &mut Window,
         cx: &mut App,
     ) {
-        use crate::table_data_tab::TableDataTabContent;
-
         let connection_id = node.connection_id.clone();
         let view = node.name.clone();
-        let metadata = &node.metadata.unwrap();
+        let metadata = node.metadata.as_ref().unwrap();
         let database = metadata.get("database").unwrap();
-        let tab_id = format!("view-data-{}.{}", database, view);
+        let schema = metadata.get("schema").cloned();
+        let tab_id = match &schema {
+            Some(schema) => format!("view-data-{}.{}.{}", database, schema, view),
+            None => format!("view-data-{}.{}", database, view),
+        };
 
         let config = Tokio::block_on(cx, async move {
             global_state.get_config(&connection_id).await
@@ -312,6 +323,7 @@ impl DatabaseEventHandler {
                     move |window, cx| {
                         let view_data = TableDataTabContent::new(
                             database_clone,
+                            schema,
                             view_clone,
                             config_id,
                             window,
@@ -384,8 +396,14 @@ impl DatabaseEventHandler {
         use gpui_component::WindowExt;
 
         let connection_id = node.connection_id.clone();
-        // 获取数据库名：如果是数据库节点则用 name，否则用 parent_context
-        let database = node.parent_context.clone().unwrap_or_else(|| node.name.clone());
+        // 数据库节点用 name，其余节点从元数据中取所属数据库和 schema
+        let database = node
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("database"))
+            .cloned()
+            .unwrap_or_else(|| node.name.clone());
+        let schema = node.metadata.as_ref().and_then(|m| m.get("schema")).cloned();
 
         eprintln!("Opening import dialog for database: {}", database);
 
@@ -397,6 +415,7 @@ impl DatabaseEventHandler {
             let import_view = DataImportView::new(
                 config.id,
                 database.clone(),
+                schema,
                 window,
                 cx,
             );
@@ -427,8 +446,14 @@ impl DatabaseEventHandler {
         use gpui_component::WindowExt;
 
         let connection_id = node.connection_id.clone();
-        // 获取数据库名：如果是数据库节点则用 name，否则用 parent_context
-        let database = node.parent_context.clone().unwrap_or_else(|| node.name.clone());
+        // 数据库节点用 name，其余节点从元数据中取所属数据库和 schema
+        let database = node
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("database"))
+            .cloned()
+            .unwrap_or_else(|| node.name.clone());
+        let schema = node.metadata.as_ref().and_then(|m| m.get("schema")).cloned();
         // 如果是表节点，预填表名
         let table_name = if node.node_type == db::DbNodeType::Table {
             Some(node.name.clone())
@@ -444,6 +469,7 @@ impl DatabaseEventHandler {
             let export_view = DataExportView::new(
                 config.id,
                 database.clone(),
+                schema,
                 window,
                 cx,
             );
@@ -467,15 +493,55 @@ impl DatabaseEventHandler {
         }
     }
 
-    /// 处理关闭连接事件
+    /// 处理关闭连接事件，编辑器中还有未提交的事务时先确认
     fn handle_close_connection(
         node: DbNode,
-        _global_state: GlobalDbState,
-        _window: &mut Window,
-        _cx: &mut App,
+        global_state: GlobalDbState,
+        window: &mut Window,
+        cx: &mut App,
     ) {
-        eprintln!("Close connection: {}", node.connection_id);
-        // TODO: 实现关闭连接逻辑
+        let connection_id = node.connection_id.clone();
+        let open_sessions = Tokio::block_on(cx, {
+            let global_state = global_state.clone();
+            let connection_id = connection_id.clone();
+            async move { global_state.sessions_with_open_transaction(&connection_id).await }
+        });
+
+        let close = Rc::new(move |cx: &mut App| {
+            let global_state = global_state.clone();
+            let connection_id = connection_id.clone();
+            cx.spawn(async move |_cx| {
+                global_state.close_connection(&connection_id).await;
+                eprintln!("Connection closed: {}", connection_id);
+            }).detach();
+        });
+
+        if open_sessions.is_empty() {
+            close(cx);
+            return;
+        }
+
+        let pending: usize = open_sessions.iter().map(|s| s.transaction().pending_statements).sum();
+        window.open_dialog(cx, move |dialog, _window, _cx| {
+            let close = close.clone();
+            dialog
+                .title("关闭连接")
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(format!(
+                            "有 {} 个查询标签页存在未提交的事务（共 {} 条语句）。",
+                            open_sessions.len(),
+                            pending
+                        ))
+                        .child("关闭连接将回滚这些事务。")
+                )
+                .on_ok(move |_, _, cx| {
+                    close(cx);
+                    true
+                })
+        });
     }
 
     /// 处理编辑连接事件
@@ -688,7 +754,7 @@ impl DatabaseEventHandler {
 
                     cx.spawn(async move |_cx| {
                         let result = db::spawn_result(async move {
-                            let (plugin, conn_arc) = global_state.get_plugin_and_connection(&config_id).await?;
+                            let (plugin, conn_arc) = global_state.get_plugin_and_connection(&config_id, SessionPurpose::Metadata).await?;
                             let conn = conn_arc.read().await;
                             
                             // 构建 ALTER DATABASE 语句
@@ -761,7 +827,7 @@ impl DatabaseEventHandler {
                     
                     cx.spawn(async move |cx| {
                         let result = db::spawn_result(async move {
-                            let (plugin, conn_arc) = state.get_plugin_and_connection(&conn_id).await?;
+                            let (plugin, conn_arc) = state.get_plugin_and_connection(&conn_id, SessionPurpose::Metadata).await?;
                             let conn = conn_arc.read().await;
                             plugin.drop_database(&**conn, &db_name).await
                         }).await;
@@ -784,6 +850,15 @@ impl DatabaseEventHandler {
         });
     }
 
+    /// 对象所在作用域的树节点 id：有 schema 时为 schema 节点，否则为数据库节点
+    fn scope_node_id(connection_id: &str, metadata: Option<&HashMap<String, String>>) -> String {
+        let database = metadata.and_then(|m| m.get("database")).map(|s| s.as_str()).unwrap_or("");
+        match metadata.and_then(|m| m.get("schema")) {
+            Some(schema) => format!("{}:{}:schemas_folder:{}", connection_id, database, schema),
+            None => format!("{}:{}", connection_id, database),
+        }
+    }
+
     /// 处理删除表事件
     fn handle_delete_table(
         node: DbNode,
@@ -820,13 +895,14 @@ impl DatabaseEventHandler {
                     let state = state.clone();
                     let tbl_name_log = tbl_name.clone();
                     let tree = tree.clone();
-                    let db_node_id = format!("{}:{}", conn_id, meta.as_ref().and_then(|m| m.get("database")).unwrap_or(&String::new()));
+                    let db_node_id = Self::scope_node_id(&conn_id, meta.as_ref());
                     
                     cx.spawn(async move |cx| {
                         let result = db::spawn_result(async move {
-                            let (plugin, conn_arc) = state.get_plugin_and_connection(&conn_id).await?;
+                            let bound_database = meta.as_ref().and_then(|m| m.get("database")).map(|s| s.as_str());
+                            let (plugin, conn_arc) = state.get_plugin_and_database_connection(&conn_id, bound_database, SessionPurpose::Metadata).await?;
                             let conn = conn_arc.read().await;
-                            let database = meta.as_ref().and_then(|m| m.get("database")).map(|s| s.as_str()).unwrap_or("");
+                            let database = meta.as_ref().and_then(|m| m.get("schema").or_else(|| m.get("database"))).map(|s| s.as_str()).unwrap_or("");
                             plugin.drop_table(&**conn, database, &tbl_name).await
                         }).await;
 
@@ -896,9 +972,10 @@ impl DatabaseEventHandler {
                     
                     cx.spawn(async move |_cx| {
                         let result = db::spawn_result(async move {
-                            let (plugin, conn_arc) = state.get_plugin_and_connection(&conn_id).await?;
+                            let bound_database = meta.as_ref().and_then(|m| m.get("database")).map(|s| s.as_str());
+                            let (plugin, conn_arc) = state.get_plugin_and_database_connection(&conn_id, bound_database, SessionPurpose::Metadata).await?;
                             let conn = conn_arc.read().await;
-                            let database = meta.as_ref().and_then(|m| m.get("database")).map(|s| s.as_str()).unwrap_or("");
+                            let database = meta.as_ref().and_then(|m| m.get("schema").or_else(|| m.get("database"))).map(|s| s.as_str()).unwrap_or("");
                             plugin.truncate_table(&**conn, database, &tbl_name).await
                         }).await;
 
@@ -951,13 +1028,14 @@ impl DatabaseEventHandler {
                     let state = state.clone();
                     let v_name_log = v_name.clone();
                     let tree = tree.clone();
-                    let db_node_id = format!("{}:{}", conn_id, meta.as_ref().and_then(|m| m.get("database")).unwrap_or(&String::new()));
+                    let db_node_id = Self::scope_node_id(&conn_id, meta.as_ref());
                     
                     cx.spawn(async move |cx| {
                         let result = db::spawn_result(async move {
-                            let (plugin, conn_arc) = state.get_plugin_and_connection(&conn_id).await?;
+                            let bound_database = meta.as_ref().and_then(|m| m.get("database")).map(|s| s.as_str());
+                            let (plugin, conn_arc) = state.get_plugin_and_database_connection(&conn_id, bound_database, SessionPurpose::Metadata).await?;
                             let conn = conn_arc.read().await;
-                            let database = meta.as_ref().and_then(|m| m.get("database")).map(|s| s.as_str()).unwrap_or("");
+                            let database = meta.as_ref().and_then(|m| m.get("schema").or_else(|| m.get("database"))).map(|s| s.as_str()).unwrap_or("");
                             plugin.drop_view(&**conn, database, &v_name).await
                         }).await;
 
@@ -980,6 +1058,17 @@ impl DatabaseEventHandler {
     }
 }
 
+/// 退出时保存的数据库标签页状态：连接、展开的树节点和打开的子标签页
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseTabState {
+    pub tab_name: Option<String>,
+    pub connection_ids: Vec<i64>,
+    #[serde(default)]
+    pub expanded_nodes: Vec<String>,
+    #[serde(default)]
+    pub tabs: TabLayout,
+}
+
 // Database connection tab content - using TabContainer architecture
 pub struct DatabaseTabContent {
     connections: Vec<StoredConnection>,
@@ -998,6 +1087,24 @@ impl DatabaseTabContent {
         Self::new_with_name(None, connections, window, cx)
     }
     pub fn new_with_name(tab_name: Option<String>, connections: Vec<StoredConnection>, window: &mut Window, cx: &mut App) -> Self {
+        Self::create(tab_name, connections, None, window, cx)
+    }
+
+    /// 按保存的状态重建标签页，连接已被删除时返回 None。
+    /// 恢复的子标签页和树节点在显示时才连接数据库
+    pub fn restore(state: DatabaseTabState, connections: &[StoredConnection], window: &mut Window, cx: &mut App) -> Option<Self> {
+        let restored: Vec<StoredConnection> = state
+            .connection_ids
+            .iter()
+            .filter_map(|id| connections.iter().find(|conn| conn.id == Some(*id)).cloned())
+            .collect();
+        if restored.is_empty() {
+            return None;
+        }
+        Some(Self::create(state.tab_name.clone(), restored, Some(state), window, cx))
+    }
+
+    fn create(tab_name: Option<String>, connections: Vec<StoredConnection>, state: Option<DatabaseTabState>, window: &mut Window, cx: &mut App) -> Self {
         // Create database tree view
         let db_tree_view = cx.new(|cx| {
             DbTreeView::new(&connections, window, cx)
@@ -1021,6 +1128,32 @@ impl DatabaseTabContent {
             container.add_and_activate_tab(tab, cx);
         });
 
+        if let Some(state) = state {
+            let connection_ids: Vec<String> = connections
+                .iter()
+                .filter_map(|c| c.id.map(|id| id.to_string()))
+                .collect();
+            let history_container = tab_container.clone();
+            tab_container.update(cx, |container, cx| {
+                container.restore_layout(state.tabs, |tab, window, cx| {
+                    Self::restore_tab(tab, &connection_ids, &history_container, window, cx)
+                }, window, cx);
+            });
+            db_tree_view.update(cx, |tree, cx| tree.restore_expanded_nodes(state.expanded_nodes, cx));
+        }
+
+        // 恢复上次退出时未保存的编辑器内容
+        for conn in &connections {
+            let Some(id) = conn.id else { continue };
+            for draft in unclaimed_drafts(&id.to_string()) {
+                let tab_id = format!("query-draft-{}", draft.id);
+                let sql_editor = SqlEditorTabContent::from_draft(draft, window, cx);
+                tab_container.update(cx, |container, cx| {
+                    container.add_and_activate_tab(TabItem::new(tab_id, sql_editor), cx);
+                });
+            }
+        }
+
         let status_msg = cx.new(|_| "Ready".to_string());
         let is_connected = cx.new(|_| true);
 
@@ -1054,6 +1187,33 @@ impl DatabaseTabContent {
         }
     }
 
+    fn restore_tab(
+        tab: &TabState,
+        connection_ids: &[String],
+        tab_container: &Entity<TabContainer>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<TabItem> {
+        match &tab.content_type {
+            TabContentType::SqlEditor => {
+                let draft: SqlDraft = serde_json::from_value(tab.data.clone()).ok()?;
+                Some(TabItem::new(tab.id.clone(), SqlEditorTabContent::from_draft(draft, window, cx)))
+            }
+            TabContentType::TableData(_) => {
+                let table: TableDataState = serde_json::from_value(tab.data.clone()).ok()?;
+                Some(TabItem::new(tab.id.clone(), TableDataTabContent::restore(table, window, cx)))
+            }
+            TabContentType::Custom(kind) if kind == "query-history" => {
+                let content = QueryHistoryTabContent::new(connection_ids.to_vec(), tab_container.clone(), window, cx);
+                Some(TabItem::new(tab.id.clone(), content))
+            }
+            TabContentType::Custom(kind) if kind == "connection-sessions" => {
+                Some(TabItem::new(tab.id.clone(), ConnectionSessionsTabContent::new(connection_ids.to_vec(), cx)))
+            }
+            _ => None,
+        }
+    }
+
     fn render_connection_status(&self, cx: &mut App) -> AnyElement {
         let status_text = self.status_msg.read(cx).clone();
         let is_error = status_text.contains("Failed") || status_text.contains("failed");
@@ -1179,6 +1339,9 @@ impl DatabaseTabContent {
         let db_tree_view = self.db_tree_view.clone();
         let tab_container = self.tab_container.clone();
         let first_conn = self.connections.first().cloned();
+        let connections = self.connections.clone();
+        let sessions_container = self.tab_container.clone();
+        let sessions_connections = self.connections.clone();
 
         h_flex()
             .w_full()
@@ -1203,6 +1366,42 @@ impl DatabaseTabContent {
                     .ghost()
                     .tooltip("新建查询")
             )
+            .child(
+                Button::new("query-history")
+                    .icon(IconName::Calendar)
+                    .child("历史记录")
+                    .ghost()
+                    .tooltip("查询历史")
+                    .on_click(move |_, window, cx| {
+                        let connection_ids = connections.iter()
+                            .filter_map(|c| c.id.map(|id| id.to_string()))
+                            .collect::<Vec<_>>();
+                        let history_container = tab_container.clone();
+                        tab_container.update(cx, |container, cx| {
+                            container.activate_or_add_tab_lazy("query-history", |window, cx| {
+                                let content = QueryHistoryTabContent::new(connection_ids, history_container, window, cx);
+                                TabItem::new("query-history", content)
+                            }, window, cx);
+                        });
+                    })
+            )
+            .child(
+                Button::new("connection-sessions")
+                    .icon(IconName::Inspector)
+                    .child("会话")
+                    .ghost()
+                    .tooltip("连接会话和服务器进程")
+                    .on_click(move |_, window, cx| {
+                        let connection_ids = sessions_connections.iter()
+                            .filter_map(|c| c.id.map(|id| id.to_string()))
+                            .collect::<Vec<_>>();
+                        sessions_container.update(cx, |container, cx| {
+                            container.activate_or_add_tab_lazy("connection-sessions", |_window, cx| {
+                                TabItem::new("connection-sessions", ConnectionSessionsTabContent::new(connection_ids, cx))
+                            }, window, cx);
+                        });
+                    })
+            )
             .child(
                 Button::new("new-table")
                     .icon(IconName::Table)
@@ -1292,6 +1491,16 @@ impl TabContent for DatabaseTabContent {
         TabContentType::Custom(format!("database-{}", name))
     }
 
+    fn save_state(&self, cx: &App) -> Option<serde_json::Value> {
+        let state = DatabaseTabState {
+            tab_name: self.tab_name.clone(),
+            connection_ids: self.connections.iter().filter_map(|c| c.id).collect(),
+            expanded_nodes: self.db_tree_view.read(cx).expanded_node_ids(),
+            tabs: self.tab_container.read(cx).save_layout(cx),
+        };
+        serde_json::to_value(state).ok()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -4,11 +4,21 @@ use gpui_component::{
     form::{field, v_form},
     h_flex,
     input::{Input, InputEvent, InputState},
-    select::{Select, SelectItem, SelectState},
+    select::{Select, SelectEvent, SelectItem, SelectState},
     tab::{Tab, TabBar},
-    v_flex, ActiveTheme, Disableable, Sizable, Size, StyledExt,
+    v_flex, ActiveTheme, Disableable, IndexPath, Sizable, Size, StyledExt,
+};
+use db::{
+    PoolOptions, DEFAULT_SSH_KEEPALIVE, DEFAULT_SSH_PORT, IDLE_TIMEOUT_PARAM, MAX_CONNECTIONS_PARAM, PING_INTERVAL_PARAM,
+    SSH_AUTH_PARAM, SSH_AUTH_PASSWORD, SSH_AUTH_PRIVATE_KEY, SSH_HOST_PARAM, SSH_KEEPALIVE_PARAM, SSH_KEY_PATH_PARAM,
+    SSH_KNOWN_HOSTS_PARAM, SSH_PASSPHRASE_PARAM, SSH_PASSWORD_PARAM, SSH_PORT_PARAM, SSH_USERNAME_PARAM, SSL_CA_PARAM,
+    SSL_CERT_PARAM, SSL_KEY_PARAM, SSL_MODE_PARAM, SSL_TRUST_SERVER_CERT_PARAM, SslMode,
 };
 use one_core::storage::{DatabaseType, DbConnectionConfig, StoredConnection, Workspace};
+use std::collections::HashMap;
+
+/// Fields mapped onto `DbConnectionConfig` directly, everything else goes to `extra_params`
+const BASE_FIELDS: [&str; 6] = ["name", "host", "port", "username", "password", "database"];
 
 /// Workspace select item for dropdown
 #[derive(Clone, Debug)]
@@ -45,6 +55,34 @@ impl SelectItem for WorkspaceSelectItem {
     }
 }
 
+/// Option item for select fields
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldOption {
+    pub value: String,
+    pub label: String,
+}
+
+impl FieldOption {
+    pub fn new(value: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            label: label.into(),
+        }
+    }
+}
+
+impl SelectItem for FieldOption {
+    type Value = String;
+
+    fn title(&self) -> SharedString {
+        self.label.clone().into()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.value
+    }
+}
+
 /// Represents a tab group containing multiple fields
 #[derive(Clone, Debug)]
 pub struct TabGroup {
@@ -89,6 +127,7 @@ pub enum FormFieldType {
     Text,
     Number,
     Password,
+    Select(Vec<FieldOption>),
 }
 
 impl FormField {
@@ -124,6 +163,91 @@ impl FormField {
     }
 }
 
+/// Connection pool settings, shown on the advanced tab of every database type
+fn pool_fields() -> Vec<FormField> {
+    let defaults = PoolOptions::default();
+    vec![
+        FormField::new(IDLE_TIMEOUT_PARAM, "空闲超时（秒）", FormFieldType::Number)
+            .optional()
+            .placeholder(defaults.idle_timeout.as_secs().to_string()),
+        FormField::new(MAX_CONNECTIONS_PARAM, "最大连接数", FormFieldType::Number)
+            .optional()
+            .placeholder(defaults.max_connections.to_string()),
+        FormField::new(PING_INTERVAL_PARAM, "检测间隔（秒）", FormFieldType::Number)
+            .optional()
+            .placeholder(defaults.ping_interval.as_secs().to_string()),
+    ]
+}
+
+/// TLS settings, certificate paths are read when connecting
+fn ssl_fields() -> Vec<FormField> {
+    let mode_label = |mode: SslMode| match mode {
+        SslMode::Disable => "禁用",
+        SslMode::Prefer => "优先（服务器支持时加密）",
+        SslMode::Require => "必须（不验证证书）",
+        SslMode::VerifyCa => "验证 CA",
+        SslMode::VerifyFull => "验证 CA 和主机名（不支持 SSH 隧道）",
+    };
+    vec![
+        FormField::new(SSL_MODE_PARAM, "SSL 模式", FormFieldType::Select(
+            SslMode::ALL.iter().map(|mode| FieldOption::new(mode.as_str(), mode_label(*mode))).collect(),
+        ))
+            .optional()
+            .default(SslMode::default().as_str()),
+        FormField::new(SSL_CA_PARAM, "CA 证书", FormFieldType::Text)
+            .optional()
+            .placeholder("ca.pem，留空则使用系统证书"),
+        FormField::new(SSL_CERT_PARAM, "客户端证书", FormFieldType::Text)
+            .optional()
+            .placeholder("client-cert.pem"),
+        FormField::new(SSL_KEY_PARAM, "客户端私钥", FormFieldType::Text)
+            .optional()
+            .placeholder("client-key.pem"),
+    ]
+}
+
+/// SQL Server TLS settings, the server certificate is validated unless trusted explicitly
+fn mssql_ssl_fields() -> Vec<FormField> {
+    vec![
+        FormField::new(SSL_TRUST_SERVER_CERT_PARAM, "服务器证书", FormFieldType::Select(vec![
+            FieldOption::new("false", "验证证书"),
+            FieldOption::new("true", "信任服务器证书（不验证）"),
+        ]))
+            .optional()
+            .default("false"),
+    ]
+}
+
+/// SSH tunnel settings, the connection goes direct while the host is empty
+fn ssh_fields() -> Vec<FormField> {
+    vec![
+        FormField::new(SSH_HOST_PARAM, "SSH 主机", FormFieldType::Text)
+            .optional()
+            .placeholder("留空则直接连接"),
+        FormField::new(SSH_PORT_PARAM, "SSH 端口", FormFieldType::Number)
+            .optional()
+            .placeholder(DEFAULT_SSH_PORT.to_string()),
+        FormField::new(SSH_USERNAME_PARAM, "SSH 用户名", FormFieldType::Text).optional(),
+        FormField::new(SSH_AUTH_PARAM, "认证方式", FormFieldType::Select(vec![
+            FieldOption::new(SSH_AUTH_PASSWORD, "密码"),
+            FieldOption::new(SSH_AUTH_PRIVATE_KEY, "私钥"),
+        ]))
+            .optional()
+            .default(SSH_AUTH_PASSWORD),
+        FormField::new(SSH_PASSWORD_PARAM, "SSH 密码", FormFieldType::Password).optional(),
+        FormField::new(SSH_KEY_PATH_PARAM, "私钥文件", FormFieldType::Text)
+            .optional()
+            .placeholder("~/.ssh/id_ed25519"),
+        FormField::new(SSH_PASSPHRASE_PARAM, "私钥密码", FormFieldType::Password).optional(),
+        FormField::new(SSH_KNOWN_HOSTS_PARAM, "known_hosts 文件", FormFieldType::Text)
+            .optional()
+            .placeholder("~/.ssh/known_hosts"),
+        FormField::new(SSH_KEEPALIVE_PARAM, "保活间隔（秒）", FormFieldType::Number)
+            .optional()
+            .placeholder(DEFAULT_SSH_KEEPALIVE.as_secs().to_string()),
+    ]
+}
+
 /// Database connection form configuration for different database types
 pub struct DbFormConfig {
     pub db_type: DatabaseType,
@@ -159,9 +283,9 @@ impl DbFormConfig {
                         .placeholder("database name (optional)")
                         .default("ai_app"),
                 ]),
-                TabGroup::new("advanced", "高级"),
-                TabGroup::new("ssl", "SSL"),
-                TabGroup::new("ssh", "SSH"),
+                TabGroup::new("advanced", "高级").fields(pool_fields()),
+                TabGroup::new("ssl", "SSL").fields(ssl_fields()),
+                TabGroup::new("ssh", "SSH").fields(ssh_fields()),
                 TabGroup::new("http", "HTTP"),
                 TabGroup::new("notes", "备注"),
             ],
@@ -193,10 +317,108 @@ impl DbFormConfig {
                         .optional()
                         .placeholder("database name (optional)"),
                 ]),
-                TabGroup::new("advanced", "高级"),
+                TabGroup::new("advanced", "高级").fields(
+                    std::iter::once(
+                        FormField::new("search_path", "搜索路径", FormFieldType::Text)
+                            .optional()
+                            .placeholder("app, public"),
+                    )
+                    .chain(pool_fields())
+                    .collect(),
+                ),
+                TabGroup::new("ssl", "SSL").fields(ssl_fields()),
+                TabGroup::new("ssh", "SSH").fields(ssh_fields()),
+                TabGroup::new("http", "HTTP"),
+                TabGroup::new("notes", "备注"),
+            ],
+        }
+    }
+
+    /// SQL Server form configuration
+    pub fn mssql() -> Self {
+        Self {
+            db_type: DatabaseType::MSSQL,
+            title: "新建连接 (SQL Server)".to_string(),
+            tab_groups: vec![
+                TabGroup::new("general", "常规").fields(vec![
+                    FormField::new("name", "连接名称", FormFieldType::Text)
+                        .placeholder("My SQL Server Database")
+                        .default("Local SQL Server"),
+                    FormField::new("host", "主机", FormFieldType::Text)
+                        .placeholder("localhost")
+                        .default("localhost"),
+                    FormField::new("port", "端口", FormFieldType::Number)
+                        .placeholder("1433")
+                        .default("1433"),
+                    FormField::new("username", "用户名", FormFieldType::Text)
+                        .placeholder("sa")
+                        .default("sa"),
+                    FormField::new("password", "密码", FormFieldType::Password)
+                        .placeholder("Enter password"),
+                    FormField::new("database", "数据库", FormFieldType::Text)
+                        .optional()
+                        .placeholder("database name (optional)"),
+                ]),
+                TabGroup::new("advanced", "高级").fields(pool_fields()),
+                TabGroup::new("ssl", "SSL").fields(mssql_ssl_fields()),
+                TabGroup::new("ssh", "SSH"),
+                TabGroup::new("notes", "备注"),
+            ],
+        }
+    }
+
+    /// Oracle form configuration
+    pub fn oracle() -> Self {
+        Self {
+            db_type: DatabaseType::Oracle,
+            title: "新建连接 (Oracle)".to_string(),
+            tab_groups: vec![
+                TabGroup::new("general", "常规").fields(vec![
+                    FormField::new("name", "连接名称", FormFieldType::Text)
+                        .placeholder("My Oracle Database")
+                        .default("Local Oracle"),
+                    FormField::new("host", "主机", FormFieldType::Text)
+                        .placeholder("localhost")
+                        .default("localhost"),
+                    FormField::new("port", "端口", FormFieldType::Number)
+                        .placeholder("1521")
+                        .default("1521"),
+                    FormField::new("connect_by", "连接方式", FormFieldType::Select(vec![
+                        FieldOption::new("service_name", "服务名"),
+                        FieldOption::new("sid", "SID"),
+                    ]))
+                        .default("service_name"),
+                    FormField::new("service_name", "服务名/SID", FormFieldType::Text)
+                        .placeholder("ORCLPDB1")
+                        .default("ORCLPDB1"),
+                    FormField::new("username", "用户名", FormFieldType::Text)
+                        .placeholder("system")
+                        .default("system"),
+                    FormField::new("password", "密码", FormFieldType::Password)
+                        .placeholder("Enter password"),
+                ]),
+                TabGroup::new("advanced", "高级").fields(pool_fields()),
                 TabGroup::new("ssl", "SSL"),
                 TabGroup::new("ssh", "SSH"),
-                TabGroup::new("http", "HTTP"),
+                TabGroup::new("notes", "备注"),
+            ],
+        }
+    }
+
+    /// SQLite form configuration
+    pub fn sqlite() -> Self {
+        Self {
+            db_type: DatabaseType::SQLite,
+            title: "新建连接 (SQLite)".to_string(),
+            tab_groups: vec![
+                TabGroup::new("general", "常规").fields(vec![
+                    FormField::new("name", "连接名称", FormFieldType::Text)
+                        .placeholder("My SQLite Database")
+                        .default("Local SQLite"),
+                    FormField::new("database", "数据库文件", FormFieldType::Text)
+                        .placeholder("/path/to/database.db"),
+                ]),
+                TabGroup::new("advanced", "高级").fields(pool_fields()),
                 TabGroup::new("notes", "备注"),
             ],
         }
@@ -218,8 +440,10 @@ pub struct DbConnectionForm {
     // Field values stored as Entity<String> for reactivity
     field_values: Vec<(String, Entity<String>)>,
     field_inputs: Vec<Entity<InputState>>,
+    // Select state for FormFieldType::Select fields, indexed like field_inputs
+    field_selects: Vec<Option<Entity<SelectState<Vec<FieldOption>>>>>,
     is_testing: Entity<bool>,
-    test_result: Entity<Option<Result<bool, String>>>,
+    test_result: Entity<Option<Result<String, String>>>,
     workspace_select: Entity<SelectState<Vec<WorkspaceSelectItem>>>,
 }
 
@@ -231,6 +455,7 @@ impl DbConnectionForm {
         // Initialize field values and inputs
         let mut field_values = Vec::new();
         let mut field_inputs = Vec::new();
+        let mut field_selects = Vec::new();
 
         for tab_group in &config.tab_groups {
             for field in &tab_group.fields {
@@ -264,6 +489,29 @@ impl DbConnectionForm {
                 .detach();
 
                 field_inputs.push(input);
+
+                let select = if let FormFieldType::Select(options) = &field.field_type {
+                    let selected = options
+                        .iter()
+                        .position(|o| o.value == field.default_value)
+                        .map(IndexPath::new);
+                    let select = cx.new(|cx| SelectState::new(options.clone(), selected, window, cx));
+
+                    let value_clone = value.clone();
+                    cx.subscribe_in(&select, window, move |_form, _select, event: &SelectEvent<Vec<FieldOption>>, _window, cx| {
+                        if let SelectEvent::Confirm(Some(selected)) = event {
+                            value_clone.update(cx, |v, cx| {
+                                *v = selected.clone();
+                                cx.notify();
+                            });
+                        }
+                    })
+                    .detach();
+                    Some(select)
+                } else {
+                    None
+                };
+                field_selects.push(select);
             }
         }
 
@@ -282,6 +530,7 @@ impl DbConnectionForm {
             active_tab: 0,
             field_values,
             field_inputs,
+            field_selects,
             is_testing,
             test_result,
             workspace_select,
@@ -311,6 +560,9 @@ impl DbConnectionForm {
             if let Some(db) = &params.database {
                 self.set_field_value("database", db, window, cx);
             }
+            for (key, value) in &params.extra_params {
+                self.set_field_value(key, value, window, cx);
+            }
         }
         
         if let Some(ws_id) = connection.workspace_id {
@@ -333,6 +585,11 @@ impl DbConnectionForm {
             self.field_inputs[idx].update(cx, |input, cx| {
                 input.set_value(value.to_string(), window, cx);
             });
+            if let Some(select) = &self.field_selects[idx] {
+                select.update(cx, |select, cx| {
+                    select.set_selected_value(&value.to_string(), window, cx);
+                });
+            }
         }
     }
 
@@ -370,9 +627,20 @@ impl DbConnectionForm {
                 }
             },
             workspace_id,
+            extra_params: self.extra_params(cx),
         }
     }
 
+    /// Collect non-empty values of driver specific fields
+    fn extra_params(&self, cx: &App) -> HashMap<String, String> {
+        self.field_values
+            .iter()
+            .filter(|(name, _)| !BASE_FIELDS.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.read(cx).trim().to_string()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect()
+    }
+
     fn validate(&self, cx: &App) -> Result<(), String> {
         for tab_group in &self.config.tab_groups {
             for field in &tab_group.fields {
@@ -429,7 +697,8 @@ impl DbConnectionForm {
         *self.is_testing.read(cx)
     }
 
-    pub fn set_test_result(&mut self, result: Result<bool, String>, cx: &mut Context<Self>) {
+    /// Show the outcome of a test connection, `Ok` carries details such as the TLS status
+    pub fn set_test_result(&mut self, result: Result<String, String>, cx: &mut Context<Self>) {
         self.is_testing.update(cx, |testing, cx| {
             *testing = false;
             cx.notify();
@@ -453,8 +722,7 @@ impl Render for DbConnectionForm {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let is_testing = *self.is_testing.read(cx);
         let test_result_msg = self.test_result.read(cx).as_ref().map(|r| match r {
-            Ok(true) => "✓ 连接成功!".to_string(),
-            Ok(false) => "✗ 连接失败".to_string(),
+            Ok(details) => format!("✓ 连接成功! {}", details),
             Err(e) => format!("✗ {}", e),
         });
 
@@ -513,12 +781,15 @@ impl Render for DbConnectionForm {
                                         .enumerate()
                                         .map(|(i, field_info)| {
                                             let input_idx = field_input_offset + i;
-                                            field()
+                                            let form_field = field()
                                                 .label(field_info.label.clone())
                                                 .required(field_info.required)
                                                 .items_center()
-                                                .label_justify_end()
-                                                .child(Input::new(&self.field_inputs[input_idx]).w_full())
+                                                .label_justify_end();
+                                            match &self.field_selects[input_idx] {
+                                                Some(select) => form_field.child(Select::new(select).w_full()),
+                                                None => form_field.child(Input::new(&self.field_inputs[input_idx]).w_full()),
+                                            }
                                         }),
                                 )
                                 .when(is_general_tab, |form| {
@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use gpui::{App, AppContext, Context, Entity, IntoElement, InteractiveElement, ParentElement, Render, Styled, Window, div, StatefulInteractiveElement, EventEmitter, SharedString, Focusable, FocusHandle};
 use tracing::log::trace;
 use gpui_component::{ActiveTheme, IconName, h_flex, list::ListItem, menu::{ContextMenuExt, PopupMenuItem}, tree::TreeItem, v_flex, Icon, Sizable, Size};
-use db::{GlobalDbState, DbNode, DbNodeType, spawn_result};
+use db::{GlobalDbState, DbNode, DbNodeType, SessionPurpose, spawn_result};
 use gpui_component::context_menu_tree::{context_menu_tree, ContextMenuTreeState};
 // ============================================================================
 // DbTreeView Events
@@ -133,6 +133,19 @@ impl DbTreeView {
         self.connection_name = Some(name);
     }
 
+    /// 当前展开的节点，退出时随布局保存
+    pub fn expanded_node_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.expanded_nodes.iter().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// 恢复展开状态；展开的节点在渲染时才懒加载子节点，所以标签页显示后才会连接数据库
+    pub fn restore_expanded_nodes(&mut self, node_ids: Vec<String>, cx: &mut Context<Self>) {
+        self.expanded_nodes.extend(node_ids);
+        self.rebuild_tree(cx);
+    }
+
 
     /// 刷新指定节点及其子节点
     /// 
@@ -222,7 +235,9 @@ impl DbTreeView {
         cx.spawn(async move |this, cx| {
             // 使用 DatabasePlugin 的方法加载子节点
             let children_result = spawn_result(async move {
-                let (plugin, conn_arc) = global_state.get_plugin_and_connection(&connection_id).await?;
+                let (plugin, conn_arc) = global_state
+                    .get_plugin_and_database_connection(&connection_id, node.database().map(String::as_str), SessionPurpose::Metadata)
+                    .await?;
                 let conn = conn_arc.read().await;
 
                 // 加载子节点并返回结果
@@ -372,6 +387,8 @@ impl DbTreeView {
         match node.map(|n| &n.node_type) {
             Some(DbNodeType::Connection) => Icon::from(IconName::MySQLLineColor.color().with_size(Size::Large)),
             Some(DbNodeType::Database) => Icon::from(IconName::Database).text_color(cx.theme().primary),
+            Some(DbNodeType::Schema) => Icon::from(IconName::LayoutDashboard).text_color(cx.theme().primary),
+            Some(DbNodeType::SchemasFolder) |
             Some(DbNodeType::TablesFolder) | Some(DbNodeType::ViewsFolder) |
             Some(DbNodeType::FunctionsFolder) | Some(DbNodeType::ProceduresFolder) |
             Some(DbNodeType::TriggersFolder) | Some(DbNodeType::SequencesFolder) => {
@@ -699,6 +716,39 @@ impl Render for DbTreeView {
                                                                         )
                                                                         .separator();
                                                                 }
+                                                                DbNodeType::Schema => {
+                                                                    let node1 = node.clone();
+                                                                    let node2 = node.clone();
+                                                                    let node3 = node.clone();
+
+                                                                    menu = menu
+                                                                        .item(
+                                                                            PopupMenuItem::new("新建查询")
+                                                                                .on_click(window.listener_for(&view_clone, move |_this, _, _, cx| {
+                                                                                    cx.emit(DbTreeViewEvent::CreateNewQuery {
+                                                                                        node: node1.clone()
+                                                                                    });
+                                                                                }))
+                                                                        )
+                                                                        .separator()
+                                                                        .item(
+                                                                            PopupMenuItem::new("导入数据")
+                                                                                .on_click(window.listener_for(&view_clone, move |_this, _, _, cx| {
+                                                                                    cx.emit(DbTreeViewEvent::ImportData {
+                                                                                        node: node2.clone()
+                                                                                    });
+                                                                                }))
+                                                                        )
+                                                                        .item(
+                                                                            PopupMenuItem::new("导出 Schema")
+                                                                                .on_click(window.listener_for(&view_clone, move |_this, _, _, cx| {
+                                                                                    cx.emit(DbTreeViewEvent::ExportData {
+                                                                                        node: node3.clone()
+                                                                                    });
+                                                                                }))
+                                                                        )
+                                                                        .separator();
+                                                                }
                                                                 DbNodeType::Table => {
                                                                     let node1 = node.clone();
                                                                     let node2 = node.clone();
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use db::{PlanNode, QueryPlan};
+use gpui::prelude::FluentBuilder;
+use gpui::{div, px, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled, Window};
+use gpui_component::list::ListItem;
+use gpui_component::tag::Tag;
+use gpui_component::tree::{tree, TreeItem, TreeState};
+use gpui_component::{h_flex, v_flex, ActiveTheme, Icon, IconName, Sizable, StyledExt};
+
+/// 执行计划的树形视图，点击节点展开或折叠子节点
+pub struct ExplainPlanView {
+    sql: String,
+    plan: QueryPlan,
+    tree_state: Entity<TreeState>,
+    // 树节点 id -> 节点信息（不含子节点）
+    nodes: HashMap<SharedString, PlanNode>,
+}
+
+impl ExplainPlanView {
+    pub fn new(sql: impl Into<String>, plan: QueryPlan, cx: &mut Context<Self>) -> Self {
+        let mut nodes = HashMap::new();
+        let items: Vec<TreeItem> = plan
+            .roots
+            .iter()
+            .enumerate()
+            .map(|(ix, node)| Self::tree_item(ix.to_string(), node, &mut nodes))
+            .collect();
+        let tree_state = cx.new(|cx| TreeState::new(cx).items(items));
+
+        Self {
+            sql: sql.into(),
+            plan,
+            tree_state,
+            nodes,
+        }
+    }
+
+    /// 节点 id 是从根开始的下标路径，如 `0.1.2`；默认全部展开
+    fn tree_item(id: String, node: &PlanNode, nodes: &mut HashMap<SharedString, PlanNode>) -> TreeItem {
+        let children: Vec<TreeItem> = node
+            .children
+            .iter()
+            .enumerate()
+            .map(|(ix, child)| Self::tree_item(format!("{}.{}", id, ix), child, nodes))
+            .collect();
+        let id: SharedString = id.into();
+        nodes.insert(
+            id.clone(),
+            PlanNode {
+                children: Vec::new(),
+                ..node.clone()
+            },
+        );
+        TreeItem::new(id, node.operation.clone()).children(children).expanded(true)
+    }
+
+    fn full_scan_count(&self) -> usize {
+        fn count(nodes: &[PlanNode]) -> usize {
+            nodes.iter().map(|node| node.full_scan as usize + count(&node.children)).sum()
+        }
+        count(&self.plan.roots)
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+impl Render for ExplainPlanView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let nodes = self.nodes.clone();
+        let full_scans = self.full_scan_count();
+
+        let mut timing = Vec::new();
+        if let Some(ms) = self.plan.planning_ms {
+            timing.push(format!("规划 {:.2}ms", ms));
+        }
+        if let Some(ms) = self.plan.execution_ms {
+            timing.push(format!("执行 {:.2}ms", ms));
+        }
+
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .gap_3()
+                    .px_3()
+                    .py_2()
+                    .items_center()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(div().font_semibold().child("执行计划"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .truncate()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(self.sql.clone()),
+                    )
+                    .children(timing.into_iter().map(|text| {
+                        div().text_xs().text_color(cx.theme().muted_foreground).child(text)
+                    }))
+                    .when(full_scans > 0, |el| {
+                        el.child(Tag::danger().small().child(format!("{} 处全表扫描", full_scans)))
+                    }),
+            )
+            .child(
+                div().flex_1().p_2().child(tree(&self.tree_state, move |ix, entry, selected, _window, cx| {
+                    let item = entry.item();
+                    let node = nodes.get(&item.id);
+                    let full_scan = node.map(|node| node.full_scan).unwrap_or(false);
+
+                    let mut metrics = Vec::new();
+                    if let Some(node) = node {
+                        if let Some(cost) = node.cost {
+                            metrics.push(format!("cost {}", format_number(cost)));
+                        }
+                        if let Some(rows) = node.rows {
+                            metrics.push(format!("rows {}", format_number(rows)));
+                        }
+                        if let Some(rows) = node.actual_rows {
+                            metrics.push(format!("actual {}", format_number(rows)));
+                        }
+                        if let Some(ms) = node.time_ms {
+                            metrics.push(format!("{:.3}ms", ms));
+                        }
+                    }
+                    let icon = if !entry.is_folder() {
+                        IconName::Dash
+                    } else if entry.is_expanded() {
+                        IconName::ChevronDown
+                    } else {
+                        IconName::ChevronRight
+                    };
+
+                    ListItem::new(ix)
+                        .selected(selected)
+                        .pl(px(16.) * entry.depth() + px(8.))
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .items_center()
+                                .w_full()
+                                .child(Icon::new(icon).small().text_color(cx.theme().muted_foreground))
+                                .child(
+                                    div()
+                                        .text_sm()
+                                        .font_medium()
+                                        .when(full_scan, |el| el.text_color(cx.theme().danger))
+                                        .child(item.label.clone()),
+                                )
+                                .when_some(node.and_then(|node| node.relation.clone()), |el, relation| {
+                                    el.child(div().text_sm().child(relation))
+                                })
+                                .when(full_scan, |el| el.child(Tag::danger().small().child("全表扫描")))
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .text_xs()
+                                        .truncate()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .children(node.and_then(|node| node.detail.clone())),
+                                )
+                                .child(
+                                    div()
+                                        .flex_shrink_0()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(metrics.join("  ")),
+                                ),
+                        )
+                })),
+            )
+    }
+}
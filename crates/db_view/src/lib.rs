@@ -1,14 +1,20 @@
 pub mod connection_list_panel;
+pub mod connection_sessions_panel;
 pub mod data_export_view;
 pub mod data_import_view;
 pub mod database_objects_tab;
 pub mod database_tab;
 pub mod db_connection_form;
 pub mod db_tree_view;
+pub mod explain_plan_view;
 pub mod filter_editor;
 pub mod multi_text_editor;
+pub mod query_history_panel;
 pub mod results_delegate;
+pub mod snippet_panel;
+pub mod sql_drafts;
 pub mod sql_editor;
 pub mod sql_editor_view;
+pub mod sql_format_settings;
 pub mod sql_result_tab;
 pub mod table_data_tab;
\ No newline at end of file
@@ -0,0 +1,370 @@
+use std::any::Any;
+
+use chrono::{Local, TimeZone};
+use gpui::prelude::FluentBuilder;
+use gpui::{div, px, AnyElement, App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Subscription, Window};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::list::ListItem;
+use gpui_component::tag::Tag;
+use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, Sizable, Size, StyledExt};
+use one_core::gpui_tokio::Tokio;
+use one_core::storage::traits::Repository;
+use one_core::storage::{GlobalStorageState, HistoryRetention, KeyValueRepository, QueryHistory, QueryHistoryRepository};
+use one_core::tab_container::{TabContainer, TabContent, TabContentType, TabItem};
+use uuid::Uuid;
+
+use crate::sql_editor_view::SqlEditorTabContent;
+
+// 列表最多显示的条数
+const HISTORY_LIMIT: i64 = 500;
+
+/// 记录执行过的语句，并按保留设置清理旧记录
+pub fn record_query_history(entries: Vec<QueryHistory>, cx: &App) {
+    if entries.is_empty() {
+        return;
+    }
+    let storage = cx.global::<GlobalStorageState>().storage.clone();
+
+    Tokio::spawn(cx, async move {
+        let result: anyhow::Result<()> = async {
+            let repo = storage.get::<QueryHistoryRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("QueryHistoryRepository not found"))?;
+            let settings = storage.get::<KeyValueRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("KeyValueRepository not found"))?;
+            let pool = storage.get_pool().await?;
+
+            for mut entry in entries {
+                repo.insert(&pool, &mut entry).await?;
+            }
+            let retention = settings.history_retention(&pool).await?;
+            repo.prune(&pool, &retention).await?;
+            Ok(())
+        }.await;
+
+        if let Err(e) = result {
+            tracing::error!("Failed to record query history: {}", e);
+        }
+    }).detach();
+}
+
+/// 连接的查询历史，可搜索，点击记录在新的查询标签页中打开
+pub struct QueryHistoryPanel {
+    connection_ids: Vec<String>,
+    tab_container: Entity<TabContainer>,
+    search_input: Entity<InputState>,
+    max_entries_input: Entity<InputState>,
+    max_days_input: Entity<InputState>,
+    entries: Vec<QueryHistory>,
+    status: String,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl QueryHistoryPanel {
+    pub fn new(connection_ids: Vec<String>, tab_container: Entity<TabContainer>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("Search SQL..."));
+        let max_entries_input = cx.new(|cx| InputState::new(window, cx));
+        let max_days_input = cx.new(|cx| InputState::new(window, cx));
+
+        let subscriptions = vec![cx.subscribe_in(&search_input, window, |this, _input, event, _window, cx| {
+            if let InputEvent::Change = event {
+                this.load(cx);
+            }
+        })];
+
+        let mut panel = Self {
+            connection_ids,
+            tab_container,
+            search_input,
+            max_entries_input,
+            max_days_input,
+            entries: Vec::new(),
+            status: String::new(),
+            _subscriptions: subscriptions,
+        };
+        panel.load(cx);
+        panel.load_retention(window, cx);
+        panel
+    }
+
+    /// 按搜索框的关键字重新加载历史
+    pub fn load(&mut self, cx: &mut Context<Self>) {
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let connection_ids = self.connection_ids.clone();
+        let keyword = self.search_input.read(cx).text().to_string();
+
+        let task = Tokio::spawn(cx, async move {
+            let repo = storage.get::<QueryHistoryRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("QueryHistoryRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            let result: anyhow::Result<Vec<QueryHistory>> = repo.search(&pool, &connection_ids, &keyword, HISTORY_LIMIT).await;
+            result
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = match task.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!(e)),
+            };
+            _ = this.update(cx, |this, cx| {
+                match result {
+                    Ok(entries) => {
+                        this.entries = entries;
+                        this.status = String::new();
+                    }
+                    Err(e) => this.status = format!("Failed to load history: {}", e),
+                }
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn load_retention(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let task = Tokio::spawn(cx, async move {
+            let settings = storage.get::<KeyValueRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("KeyValueRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            settings.history_retention(&pool).await
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let retention = match task.await {
+                Ok(Ok(retention)) => retention,
+                _ => HistoryRetention::default(),
+            };
+            _ = this.update_in(cx, |this, window, cx| {
+                this.max_entries_input.update(cx, |input, cx| input.set_value(retention.max_entries.to_string(), window, cx));
+                this.max_days_input.update(cx, |input, cx| input.set_value(retention.max_days.to_string(), window, cx));
+            });
+        }).detach();
+    }
+
+    /// 保存保留设置并立即清理
+    fn save_retention(&mut self, cx: &mut Context<Self>) {
+        let parse = |input: &Entity<InputState>, cx: &App| input.read(cx).text().to_string().trim().parse::<i64>().ok().filter(|v| *v >= 0);
+        let (Some(max_entries), Some(max_days)) = (parse(&self.max_entries_input, cx), parse(&self.max_days_input, cx)) else {
+            self.status = "Retention limits must be whole numbers, 0 for no limit".to_string();
+            cx.notify();
+            return;
+        };
+        let retention = HistoryRetention { max_entries, max_days };
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+
+        let task = Tokio::spawn(cx, async move {
+            let repo = storage.get::<QueryHistoryRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("QueryHistoryRepository not found"))?;
+            let settings = storage.get::<KeyValueRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("KeyValueRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            settings.set_history_retention(&pool, &retention).await?;
+            repo.prune(&pool, &retention).await
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = match task.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!(e)),
+            };
+            _ = this.update(cx, |this, cx| {
+                match result {
+                    Ok(removed) => {
+                        this.status = format!("Retention saved, removed {} entries", removed);
+                        this.load(cx);
+                    }
+                    Err(e) => this.status = format!("Failed to save retention: {}", e),
+                }
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn clear_history(&mut self, cx: &mut Context<Self>) {
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let connection_ids = self.connection_ids.clone();
+
+        let task = Tokio::spawn(cx, async move {
+            let repo = storage.get::<QueryHistoryRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("QueryHistoryRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            repo.clear(&pool, &connection_ids).await
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = match task.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!(e)),
+            };
+            _ = this.update(cx, |this, cx| {
+                if let Err(e) = result {
+                    this.status = format!("Failed to clear history: {}", e);
+                }
+                this.load(cx);
+            });
+        }).detach();
+    }
+
+    /// 在新的查询标签页中打开历史记录
+    fn open_entry(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix).cloned() else {
+            return;
+        };
+        let title = match &entry.database {
+            Some(database) => format!("{} - History", database),
+            None => "History".to_string(),
+        };
+        let sql_editor = SqlEditorTabContent::new_with_config(title, entry.connection_id.clone(), entry.database.clone(), window, cx);
+        sql_editor.set_sql(entry.sql.clone(), window, cx);
+
+        self.tab_container.update(cx, |container, cx| {
+            let tab = TabItem::new(format!("query-history-{}", Uuid::new_v4()), sql_editor);
+            container.add_and_activate_tab(tab, cx);
+        });
+    }
+}
+
+fn format_time(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(|ts| Local.timestamp_opt(ts, 0).single())
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+impl Render for QueryHistoryPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .p_2()
+                    .items_center()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(div().w(px(280.)).child(Input::new(&self.search_input).with_size(Size::Small).cleanable(true)))
+                    .child(
+                        Button::new("refresh-history")
+                            .with_size(Size::Small)
+                            .ghost()
+                            .icon(IconName::Loader)
+                            .tooltip("刷新")
+                            .on_click(cx.listener(|this, _, _, cx| this.load(cx))),
+                    )
+                    .child(
+                        Button::new("clear-history")
+                            .with_size(Size::Small)
+                            .ghost()
+                            .label("清空")
+                            .on_click(cx.listener(|this, _, _, cx| this.clear_history(cx))),
+                    )
+                    .child(div().flex_1())
+                    .child(div().text_sm().text_color(cx.theme().muted_foreground).child("每个连接保留"))
+                    .child(div().w(px(80.)).child(Input::new(&self.max_entries_input).with_size(Size::Small)))
+                    .child(div().text_sm().text_color(cx.theme().muted_foreground).child("条，最多"))
+                    .child(div().w(px(60.)).child(Input::new(&self.max_days_input).with_size(Size::Small)))
+                    .child(div().text_sm().text_color(cx.theme().muted_foreground).child("天（0 表示不限）"))
+                    .child(
+                        Button::new("save-retention")
+                            .with_size(Size::Small)
+                            .ghost()
+                            .label("保存")
+                            .on_click(cx.listener(|this, _, _, cx| this.save_retention(cx))),
+                    ),
+            )
+            .when(!self.status.is_empty(), |el| {
+                el.child(div().px_2().py_1().text_sm().text_color(cx.theme().muted_foreground).child(self.status.clone()))
+            })
+            .child(
+                v_flex()
+                    .id("query-history-list")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .p_2()
+                    .gap_1()
+                    .when(self.entries.is_empty(), |el| {
+                        el.items_center()
+                            .justify_center()
+                            .child(div().text_color(cx.theme().muted_foreground).child("No query history"))
+                    })
+                    .children(self.entries.iter().enumerate().map(|(ix, entry)| {
+                        let summary = if entry.success {
+                            format!("{}ms · {} rows", entry.elapsed_ms, entry.rows)
+                        } else {
+                            entry.error.clone().unwrap_or_default()
+                        };
+                        let sql: String = entry.sql.split_whitespace().collect::<Vec<_>>().join(" ");
+
+                        ListItem::new(ix)
+                            .rounded(cx.theme().radius)
+                            .px_2()
+                            .py_1()
+                            .on_click(cx.listener(move |this, _, window, cx| this.open_entry(ix, window, cx)))
+                            .child(
+                                v_flex()
+                                    .gap_1()
+                                    .w_full()
+                                    .child(
+                                        h_flex()
+                                            .gap_2()
+                                            .items_center()
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(format_time(entry.created_at))
+                                            .when_some(entry.database.clone(), |el, database| el.child(database))
+                                            .child(if entry.success {
+                                                Tag::success().small().child("成功")
+                                            } else {
+                                                Tag::danger().small().child("失败")
+                                            })
+                                            .child(div().flex_1().truncate().child(summary)),
+                                    )
+                                    .child(div().text_sm().font_medium().truncate().child(sql)),
+                            )
+                    })),
+            )
+    }
+}
+
+/// 查询历史标签页
+#[derive(Clone)]
+pub struct QueryHistoryTabContent {
+    panel: Entity<QueryHistoryPanel>,
+}
+
+impl QueryHistoryTabContent {
+    pub fn new(connection_ids: Vec<String>, tab_container: Entity<TabContainer>, window: &mut Window, cx: &mut App) -> Self {
+        let panel = cx.new(|cx| QueryHistoryPanel::new(connection_ids, tab_container, window, cx));
+        Self { panel }
+    }
+}
+
+impl TabContent for QueryHistoryTabContent {
+    fn title(&self) -> SharedString {
+        SharedString::from("历史记录")
+    }
+
+    fn icon(&self) -> Option<IconName> {
+        Some(IconName::Calendar)
+    }
+
+    fn render_content(&self, _window: &mut Window, _cx: &mut App) -> AnyElement {
+        self.panel.clone().into_any_element()
+    }
+
+    fn on_activate(&self, _window: &mut Window, cx: &mut App) {
+        self.panel.update(cx, |panel, cx| panel.load(cx));
+    }
+
+    fn content_type(&self) -> TabContentType {
+        TabContentType::Custom("query-history".to_string())
+    }
+
+    // 连接由所在的数据库标签页决定，恢复时不需要额外状态
+    fn save_state(&self, _cx: &App) -> Option<serde_json::Value> {
+        Some(serde_json::Value::Null)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,11 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
-use db::{FieldType, TableColumnMeta};
-use gpui::{div, App, Context, IntoElement, ParentElement, Styled, Window};
+use db::{FieldType, QueryResult, QueryStream, SqlValue, TableColumnMeta};
+use gpui::{div, prelude::FluentBuilder as _, App, Context, IntoElement, ParentElement, Styled, Window};
 use gpui_component::{
     h_flex,
-    table::{Column, TableDelegate, TableState}
-    ,
+    notification::Notification,
+    table::{Column, ColumnSort, TableDelegate, TableState},
+    WindowExt,
 };
 
 /// Represents a single cell change with old and new values
@@ -325,11 +326,18 @@ impl TableDelegate for EditorTableDelegate {
         _window: &mut Window,
         _cx: &mut App,
     ) -> impl IntoElement {
-        self.rows
+        let value = self
+            .rows
             .get(row)
             .and_then(|r| r.get(col))
             .cloned()
-            .unwrap_or_default()
+            .unwrap_or_default();
+
+        // 数值列右对齐
+        div()
+            .w_full()
+            .when(self.get_field_type(col).is_numeric(), |this| this.text_right())
+            .child(value)
     }
 
     fn is_cell_editable(&self, row_ix: usize, _col_ix: usize, _cx: &App) -> bool {
@@ -520,7 +528,13 @@ impl EditorTableDelegate {
 
 pub struct ResultsDelegate {
     pub columns: Vec<Column>,
-    pub rows: Vec<Vec<String>>,
+    pub rows: Vec<Vec<SqlValue>>,
+    /// 查询返回的原始顺序，取消排序时恢复
+    original_rows: Vec<Vec<SqlValue>>,
+    field_types: Vec<FieldType>,
+    /// 服务端尚未读取的行，滚动到底部时按批加载
+    more_rows: Option<QueryStream>,
+    loading_more: bool,
 }
 
 impl Clone for ResultsDelegate {
@@ -528,22 +542,70 @@ impl Clone for ResultsDelegate {
         Self {
             columns: self.columns.clone(),
             rows: self.rows.clone(),
+            original_rows: self.original_rows.clone(),
+            field_types: self.field_types.clone(),
+            more_rows: self.more_rows.clone(),
+            loading_more: self.loading_more,
         }
     }
 }
 
 impl ResultsDelegate {
+    /// 纯文本表格（状态、错误、对象列表）
     pub(crate) fn new(columns: Vec<Column>, rows: Vec<Vec<String>>) -> Self {
+        let mut delegate = Self {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            original_rows: Vec::new(),
+            field_types: Vec::new(),
+            more_rows: None,
+            loading_more: false,
+        };
+        delegate.update_data(columns, rows);
+        delegate
+    }
+
+    /// 查询结果表格，保留每个单元格的类型用于对齐和排序
+    pub(crate) fn from_query_result(result: &QueryResult) -> Self {
+        let columns = result
+            .columns
+            .iter()
+            .map(|c| Column::new(c.name.clone(), c.name.clone()).sortable())
+            .collect();
         Self {
             columns,
-            rows,
+            rows: result.rows.clone(),
+            original_rows: result.rows.clone(),
+            field_types: result.columns.iter().map(|c| c.field_type).collect(),
+            more_rows: result.more_rows.clone(),
+            loading_more: false,
         }
     }
 
+    /// 仍有未读取完的行
+    pub(crate) fn has_more(&self) -> bool {
+        self.more_rows.as_ref().is_some_and(|s| !s.is_finished())
+    }
+
+    /// 未读取完的结果流，重新执行前需要关闭以释放连接
+    pub(crate) fn open_stream(&self) -> Option<QueryStream> {
+        self.more_rows.clone().filter(|s| !s.is_finished())
+    }
+
     pub(crate) fn update_data(&mut self, columns: Vec<Column>, rows: Vec<Vec<String>>) {
+        let rows: Vec<Vec<SqlValue>> = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(SqlValue::String).collect())
+            .collect();
+        self.field_types = vec![FieldType::Unknown; columns.len()];
         self.columns = columns;
+        self.original_rows = rows.clone();
         self.rows = rows;
     }
+
+    fn is_numeric_column(&self, col: usize) -> bool {
+        self.field_types.get(col).is_some_and(|t| t.is_numeric())
+    }
 }
 
 impl TableDelegate for ResultsDelegate {
@@ -559,6 +621,20 @@ impl TableDelegate for ResultsDelegate {
     fn column(&self, col_ix: usize, _cx: &App) -> &Column {
         &self.columns[col_ix]
     }
+    fn perform_sort(
+        &mut self,
+        col_ix: usize,
+        sort: ColumnSort,
+        _window: &mut Window,
+        _cx: &mut Context<TableState<Self>>,
+    ) {
+        let cell = |row: &Vec<SqlValue>| row.get(col_ix).cloned().unwrap_or(SqlValue::Null);
+        match sort {
+            ColumnSort::Ascending => self.rows.sort_by(|a, b| cell(a).cmp_for_sort(&cell(b))),
+            ColumnSort::Descending => self.rows.sort_by(|a, b| cell(b).cmp_for_sort(&cell(a))),
+            ColumnSort::Default => self.rows = self.original_rows.clone(),
+        }
+    }
     fn render_td(
         &self,
         row: usize,
@@ -566,10 +642,47 @@ impl TableDelegate for ResultsDelegate {
         _window: &mut Window,
         _cx: &mut App,
     ) -> impl IntoElement {
-        self.rows
-            .get(row)
-            .and_then(|r| r.get(col))
-            .cloned()
-            .unwrap_or_default()
+        let value = self.rows.get(row).and_then(|r| r.get(col));
+        let numeric = value.is_some_and(|v| v.is_numeric()) || self.is_numeric_column(col);
+
+        div()
+            .w_full()
+            .when(numeric, |this| this.text_right())
+            .child(value.map(|v| v.to_string()).unwrap_or_default())
+    }
+    fn is_eof(&self, _cx: &App) -> bool {
+        // 返回 true 时表格才会在滚动到底部时调用 load_more
+        !self.loading_more && self.has_more()
+    }
+    fn load_more(&mut self, window: &mut Window, cx: &mut Context<TableState<Self>>) {
+        let Some(stream) = self.open_stream() else {
+            return;
+        };
+        if self.loading_more {
+            return;
+        }
+        self.loading_more = true;
+
+        cx.spawn_in(window, async move |table, cx| {
+            let batch = stream.next_batch().await;
+            let _ = table.update_in(cx, |table, window, cx| {
+                let delegate = table.delegate_mut();
+                delegate.loading_more = false;
+                match batch {
+                    Some(Ok(rows)) => {
+                        delegate.original_rows.extend(rows.iter().cloned());
+                        delegate.rows.extend(rows);
+                    }
+                    // 流在出错后即结束，不会再次触发加载
+                    Some(Err(e)) => {
+                        tracing::warn!("Failed to load more rows: {}", e);
+                        window.push_notification(Notification::error(format!("加载更多行失败: {}", e)), cx);
+                    }
+                    None => {}
+                }
+                cx.notify();
+            });
+        })
+        .detach();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,395 @@
+use db::GlobalDbState;
+use gpui::prelude::FluentBuilder;
+use gpui::{div, px, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render, StatefulInteractiveElement, Styled, Subscription, Window};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::list::ListItem;
+use gpui_component::select::{SearchableVec, Select, SelectState};
+use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, IndexPath, Sizable, Size, StyledExt, WindowExt};
+use one_core::gpui_tokio::Tokio;
+use one_core::storage::traits::Repository;
+use one_core::storage::{GlobalStorageState, SnippetRepository, SqlSnippet};
+
+use crate::sql_editor::SqlEditor;
+
+// 保存对话框中的作用域选项，顺序与 SnippetScope 一致
+const SCOPE_LABELS: [&str; 3] = ["全局", "当前工作区", "当前连接"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum SnippetScope {
+    Global,
+    Workspace,
+    Connection,
+}
+
+impl SnippetScope {
+    fn of(snippet: &SqlSnippet) -> Self {
+        if snippet.connection_id.is_some() {
+            SnippetScope::Connection
+        } else if snippet.workspace_id.is_some() {
+            SnippetScope::Workspace
+        } else {
+            SnippetScope::Global
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            SnippetScope::Global => 0,
+            SnippetScope::Workspace => 1,
+            SnippetScope::Connection => 2,
+        }
+    }
+
+    fn from_label(label: &str) -> Self {
+        match SCOPE_LABELS.iter().position(|l| *l == label) {
+            Some(1) => SnippetScope::Workspace,
+            Some(2) => SnippetScope::Connection,
+            _ => SnippetScope::Global,
+        }
+    }
+}
+
+/// 代码片段面板，列出连接可用的片段，点击插入到编辑器
+pub struct SnippetPanel {
+    connection_id: String,
+    workspace_id: Option<i64>,
+    editor: Entity<SqlEditor>,
+    search_input: Entity<InputState>,
+    snippets: Vec<SqlSnippet>,
+    status: String,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SnippetPanel {
+    pub fn new(connection_id: impl Into<String>, editor: Entity<SqlEditor>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("Search snippets..."));
+        let subscriptions = vec![cx.subscribe_in(&search_input, window, |_this, _input, event, _window, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        })];
+
+        let mut panel = Self {
+            connection_id: connection_id.into(),
+            workspace_id: None,
+            editor,
+            search_input,
+            snippets: Vec::new(),
+            status: String::new(),
+            _subscriptions: subscriptions,
+        };
+        panel.load(cx);
+        panel
+    }
+
+    /// 重新加载片段，并更新编辑器的补全
+    pub fn load(&mut self, cx: &mut Context<Self>) {
+        let global_state = cx.global::<GlobalDbState>().clone();
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let connection_id = self.connection_id.clone();
+
+        cx.spawn(async move |this, cx| {
+            let workspace_id = global_state.get_config(&connection_id).await.and_then(|config| config.workspace_id);
+            let Ok(task) = cx.update(|cx| {
+                let connection_id = connection_id.clone();
+                Tokio::spawn(cx, async move {
+                    let repo = storage.get::<SnippetRepository>().await
+                        .ok_or_else(|| anyhow::anyhow!("SnippetRepository not found"))?;
+                    let pool = storage.get_pool().await?;
+                    repo.list_for_connection(&pool, workspace_id, &connection_id).await
+                })
+            }) else {
+                return;
+            };
+            let result = match task.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!(e)),
+            };
+
+            _ = this.update(cx, |this, cx| {
+                this.workspace_id = workspace_id;
+                match result {
+                    Ok(snippets) => {
+                        let completions = snippets.iter().map(|s| (s.name.clone(), s.sql.clone())).collect();
+                        this.editor.update(cx, |editor, cx| editor.set_snippets(completions, cx));
+                        this.snippets = snippets;
+                        this.status = String::new();
+                    }
+                    Err(e) => this.status = format!("Failed to load snippets: {}", e),
+                }
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn insert(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(snippet) = self.snippets.get(ix) else {
+            return;
+        };
+        let sql = snippet.sql.clone();
+        self.editor.update(cx, |editor, cx| editor.insert_snippet(&sql, window, cx));
+    }
+
+    fn delete(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(id) = self.snippets.get(ix).and_then(|s| s.id) else {
+            return;
+        };
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let task = Tokio::spawn(cx, async move {
+            let repo = storage.get::<SnippetRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("SnippetRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            repo.delete(&pool, id).await
+        });
+        self.reload_after(task, cx);
+    }
+
+    /// 保存片段：`snippet` 为空时新建，内容取编辑器的选中文本或全部文本
+    pub fn show_save_dialog(&mut self, snippet: Option<SqlSnippet>, window: &mut Window, cx: &mut Context<Self>) {
+        let snippet = snippet.unwrap_or_else(|| {
+            let editor = self.editor.read(cx);
+            let selected = editor.selected_text(cx);
+            let sql = if selected.trim().is_empty() { editor.get_text_from_app(cx) } else { selected };
+            SqlSnippet::new("", sql.trim())
+        });
+
+        let name_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx).placeholder("Name, typed in the editor to expand it");
+            state.set_value(snippet.name.clone(), window, cx);
+            state
+        });
+        let description_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx).placeholder("Description");
+            state.set_value(snippet.description.clone().unwrap_or_default(), window, cx);
+            state
+        });
+        let sql_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx).multi_line(true).rows(8);
+            state.set_value(snippet.sql.clone(), window, cx);
+            state
+        });
+        let scope_select = cx.new(|cx| {
+            SelectState::new(
+                SearchableVec::new(SCOPE_LABELS.iter().map(|l| l.to_string()).collect::<Vec<_>>()),
+                Some(IndexPath::new(SnippetScope::of(&snippet).index())),
+                window,
+                cx,
+            )
+        });
+        let error = cx.new(|_| String::new());
+        let panel = cx.entity();
+        let is_new = snippet.id.is_none();
+
+        window.open_dialog(cx, move |dialog, _window, cx| {
+            let error_msg = error.read(cx).clone();
+            let (name_input, description_input, sql_input, scope_select, error, panel, snippet) = (
+                name_input.clone(),
+                description_input.clone(),
+                sql_input.clone(),
+                scope_select.clone(),
+                error.clone(),
+                panel.clone(),
+                snippet.clone(),
+            );
+
+            dialog
+                .title(if is_new { "Save Snippet" } else { "Edit Snippet" })
+                .width(px(560.))
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_3()
+                        .p_4()
+                        .child(Input::new(&name_input))
+                        .child(Input::new(&description_input))
+                        .child(Input::new(&sql_input).h(px(180.)))
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .items_center()
+                                .child(div().text_sm().child("作用域"))
+                                .child(div().w(px(160.)).child(Select::new(&scope_select).with_size(Size::Small))),
+                        )
+                        .when(!error_msg.is_empty(), |el| {
+                            el.child(div().text_sm().text_color(cx.theme().danger).child(error_msg))
+                        }),
+                )
+                .on_ok(move |_, _window, cx| {
+                    let name = name_input.read(cx).text().to_string().trim().to_string();
+                    let sql = sql_input.read(cx).text().to_string();
+                    if name.is_empty() || sql.trim().is_empty() {
+                        error.update(cx, |msg, cx| {
+                            *msg = "Name and SQL are required".to_string();
+                            cx.notify();
+                        });
+                        return false;
+                    }
+                    let description = description_input.read(cx).text().to_string().trim().to_string();
+                    let scope = scope_select
+                        .read(cx)
+                        .selected_value()
+                        .map(|label| SnippetScope::from_label(label))
+                        .unwrap_or(SnippetScope::Global);
+
+                    let mut snippet = snippet.clone();
+                    snippet.name = name;
+                    snippet.sql = sql;
+                    snippet.description = (!description.is_empty()).then_some(description);
+                    panel.update(cx, |panel, cx| panel.save(snippet, scope, cx));
+                    true
+                })
+        });
+    }
+
+    fn save(&mut self, mut snippet: SqlSnippet, scope: SnippetScope, cx: &mut Context<Self>) {
+        snippet.workspace_id = None;
+        snippet.connection_id = None;
+        match scope {
+            SnippetScope::Global => {}
+            SnippetScope::Workspace => snippet.workspace_id = self.workspace_id,
+            SnippetScope::Connection => snippet.connection_id = Some(self.connection_id.clone()),
+        }
+
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let task = Tokio::spawn(cx, async move {
+            let repo = storage.get::<SnippetRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("SnippetRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            if snippet.id.is_some() {
+                repo.update(&pool, &snippet).await
+            } else {
+                repo.insert(&pool, &mut snippet).await.map(|_| ())
+            }
+        });
+        self.reload_after(task, cx);
+    }
+
+    fn reload_after(&mut self, task: gpui::Task<Result<anyhow::Result<()>, tokio::task::JoinError>>, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let result = match task.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!(e)),
+            };
+            _ = this.update(cx, |this, cx| {
+                if let Err(e) = result {
+                    this.status = format!("Failed to save snippets: {}", e);
+                }
+                this.load(cx);
+            });
+        }).detach();
+    }
+
+    fn scope_label(&self, snippet: &SqlSnippet) -> &'static str {
+        SCOPE_LABELS[SnippetScope::of(snippet).index()]
+    }
+}
+
+impl Render for SnippetPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let keyword = self.search_input.read(cx).text().to_string().to_lowercase();
+        let visible: Vec<usize> = self
+            .snippets
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                keyword.is_empty()
+                    || s.name.to_lowercase().contains(&keyword)
+                    || s.sql.to_lowercase().contains(&keyword)
+                    || s.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&keyword))
+            })
+            .map(|(ix, _)| ix)
+            .collect();
+
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .p_2()
+                    .items_center()
+                    .child(div().flex_1().font_semibold().text_sm().child("代码片段"))
+                    .child(
+                        Button::new("save-snippet")
+                            .with_size(Size::Small)
+                            .ghost()
+                            .icon(IconName::Plus)
+                            .tooltip("Save the selection or the whole text as a snippet")
+                            .on_click(cx.listener(|this, _, window, cx| this.show_save_dialog(None, window, cx))),
+                    )
+                    .child(
+                        Button::new("refresh-snippets")
+                            .with_size(Size::Small)
+                            .ghost()
+                            .icon(IconName::Loader)
+                            .on_click(cx.listener(|this, _, _, cx| this.load(cx))),
+                    ),
+            )
+            .child(div().px_2().pb_2().child(Input::new(&self.search_input).with_size(Size::Small).cleanable(true)))
+            .when(!self.status.is_empty(), |el| {
+                el.child(div().px_2().text_xs().text_color(cx.theme().danger).child(self.status.clone()))
+            })
+            .child(
+                v_flex()
+                    .id("snippet-list")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .px_1()
+                    .when(visible.is_empty(), |el| {
+                        el.child(div().p_2().text_sm().text_color(cx.theme().muted_foreground).child("No snippets"))
+                    })
+                    .children(visible.into_iter().map(|ix| {
+                        let snippet = &self.snippets[ix];
+                        let preview = snippet
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| snippet.sql.split_whitespace().collect::<Vec<_>>().join(" "));
+                        let editing = snippet.clone();
+
+                        ListItem::new(ix)
+                            .rounded(cx.theme().radius)
+                            .px_2()
+                            .py_1()
+                            .on_click(cx.listener(move |this, _, window, cx| this.insert(ix, window, cx)))
+                            .child(
+                                h_flex()
+                                    .gap_1()
+                                    .w_full()
+                                    .items_center()
+                                    .child(
+                                        v_flex()
+                                            .flex_1()
+                                            .overflow_hidden()
+                                            .child(
+                                                h_flex()
+                                                    .gap_2()
+                                                    .child(div().text_sm().font_medium().truncate().child(snippet.name.clone()))
+                                                    .child(div().text_xs().text_color(cx.theme().muted_foreground).child(self.scope_label(snippet))),
+                                            )
+                                            .child(div().text_xs().text_color(cx.theme().muted_foreground).truncate().child(preview)),
+                                    )
+                                    .child(
+                                        Button::new(("edit-snippet", ix))
+                                            .with_size(Size::XSmall)
+                                            .ghost()
+                                            .icon(IconName::Settings)
+                                            .on_click(cx.listener(move |this, _, window, cx| {
+                                                cx.stop_propagation();
+                                                this.show_save_dialog(Some(editing.clone()), window, cx)
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new(("delete-snippet", ix))
+                                            .with_size(Size::XSmall)
+                                            .ghost()
+                                            .icon(IconName::Delete)
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                cx.stop_propagation();
+                                                this.delete(ix, cx)
+                                            })),
+                                    ),
+                            )
+                    })),
+            )
+    }
+}
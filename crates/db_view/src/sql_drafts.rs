@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use one_core::storage::get_config_dir;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+// 已被打开的标签页占用的草稿，再次打开同一连接时不会重复恢复
+static CLAIMED_DRAFTS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 未保存的编辑器内容，每个 SQL 编辑器标签页对应配置目录下 `drafts/<id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqlDraft {
+    pub id: String,
+    pub title: String,
+    pub connection_id: String,
+    pub database: Option<String>,
+    pub file_path: Option<PathBuf>,
+    pub text: String,
+    pub updated_at: i64,
+}
+
+fn drafts_dir() -> Result<PathBuf> {
+    let dir = get_config_dir()?.join("drafts");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 先写临时文件再改名，避免退出时写了一半的草稿
+pub fn save_draft(draft: &SqlDraft) -> Result<()> {
+    let dir = drafts_dir()?;
+    let tmp = dir.join(format!("{}.json.tmp", draft.id));
+    fs::write(&tmp, serde_json::to_vec_pretty(draft)?)?;
+    fs::rename(&tmp, dir.join(format!("{}.json", draft.id)))?;
+    Ok(())
+}
+
+pub fn remove_draft(id: &str) -> Result<()> {
+    let path = drafts_dir()?.join(format!("{}.json", id));
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 读取某个连接的全部草稿，按最后修改时间排序；损坏的文件跳过
+pub fn load_drafts(connection_id: &str) -> Vec<SqlDraft> {
+    let Ok(entries) = drafts_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) else {
+        return Vec::new();
+    };
+
+    let mut drafts: Vec<SqlDraft> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = fs::read(&path).ok()?;
+            match serde_json::from_slice::<SqlDraft>(&content) {
+                Ok(draft) => Some(draft),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable draft {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .filter(|draft| draft.connection_id == connection_id)
+        .collect();
+    drafts.sort_by_key(|draft| draft.updated_at);
+    drafts
+}
+
+pub fn claim_draft(id: &str) {
+    if let Ok(mut claimed) = CLAIMED_DRAFTS.lock() {
+        claimed.insert(id.to_string());
+    }
+}
+
+pub fn release_draft(id: &str) {
+    if let Ok(mut claimed) = CLAIMED_DRAFTS.lock() {
+        claimed.remove(id);
+    }
+}
+
+/// 连接还没有标签页在使用的草稿，用于启动后恢复
+pub fn unclaimed_drafts(connection_id: &str) -> Vec<SqlDraft> {
+    let claimed = CLAIMED_DRAFTS.lock().map(|claimed| claimed.clone()).unwrap_or_default();
+    load_drafts(connection_id)
+        .into_iter()
+        .filter(|draft| !claimed.contains(&draft.id))
+        .collect()
+}
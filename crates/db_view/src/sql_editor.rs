@@ -1,26 +1,49 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 
 use anyhow::Result;
+use db::{
+    completion_context, join_conditions, CompletionKind, ForeignKeyInfo, ScriptStatement, SplitOptions,
+    SqlScriptSplitter, TableRef,
+};
+use futures::future::BoxFuture;
 use gpui::{App, AppContext, Context, Entity, IntoElement, Render, SharedString, Styled as _, Subscription, Task, Window};
-use gpui_component::highlighter::Language;
+use gpui_component::highlighter::{Diagnostic, DiagnosticSeverity, Language};
 use gpui_component::input::{
     CodeActionProvider, CompletionProvider, HoverProvider, Input, InputEvent, InputState, TabSize,
 };
 use gpui_component::{Rope, RopeExt};
+use one_core::storage::{DatabaseType, SqlFormatOptions};
 use lsp_types::{
     CompletionContext, CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit,
     Hover, HoverContents, InsertReplaceEdit, MarkedString, Range as LspRange, TextEdit, Uri,
     WorkspaceEdit,
 };
 
+/// Columns and foreign keys of a table, loaded the first time completion needs them.
+#[derive(Clone, Default)]
+pub struct TableMetadata {
+    pub columns: Vec<(String, String)>, // (name, doc)
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+}
+
+/// Loads the metadata of a table, given by its name in `SqlSchema::tables`.
+pub type TableMetadataLoader = Arc<dyn Fn(String) -> BoxFuture<'static, Result<TableMetadata>> + Send + Sync>;
+
 /// Simple schema hints to improve autocomplete suggestions.
 #[derive(Clone, Default)]
 pub struct SqlSchema {
     pub tables: Vec<(String, String)>,   // (name, doc)
     pub columns: Vec<(String, String)>,  // global (name, doc)
-    pub columns_by_table: std::collections::HashMap<String, Vec<(String, String)>>,
+    pub columns_by_table: HashMap<String, Vec<(String, String)>>,
+    /// Loads the tables missing from `columns_by_table` on demand
+    pub loader: Option<TableMetadataLoader>,
+    /// Metadata loaded so far, shared by the clones of this schema
+    loaded: Arc<RwLock<HashMap<String, TableMetadata>>>,
 }
 
 impl SqlSchema {
@@ -52,14 +75,79 @@ impl SqlSchema {
         );
         self
     }
+    pub fn with_loader(mut self, loader: TableMetadataLoader) -> Self {
+        self.loader = Some(loader);
+        self
+    }
+
+    /// Name a table reference is listed under in `tables`, an exact match preferred.
+    fn resolve_table(&self, table: &TableRef) -> Option<String> {
+        let wanted = table.qualified_name();
+        let names = || self.tables.iter().map(|(name, _)| name);
+        names()
+            .find(|name| **name == wanted)
+            .or_else(|| names().find(|name| name.eq_ignore_ascii_case(&wanted)))
+            .cloned()
+    }
+
+    /// Columns and foreign keys of a listed table, loaded once and then kept.
+    async fn table_metadata(&self, table: &str) -> Option<TableMetadata> {
+        if let Some(columns) = self.columns_by_table.get(table) {
+            return Some(TableMetadata { columns: columns.clone(), ..Default::default() });
+        }
+        let cached = self.loaded.read().ok().and_then(|loaded| loaded.get(table).cloned());
+        if cached.is_some() {
+            return cached;
+        }
+
+        let loader = self.loader.as_ref()?;
+        match loader(table.to_string()).await {
+            Ok(metadata) => {
+                if let Ok(mut loaded) = self.loaded.write() {
+                    loaded.insert(table.to_string(), metadata.clone());
+                }
+                Some(metadata)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load columns of {}: {}", table, e);
+                None
+            }
+        }
+    }
 }
 
 // Built-in SQL keywords and docs (trimmed for brevity vs example).
 const SQL_KEYWORDS: &[&str] = &[
     "SELECT", "INSERT", "UPDATE", "DELETE", "CREATE", "ALTER", "DROP", "FROM", "WHERE",
-    "JOIN", "LEFT", "RIGHT", "INNER", "GROUP", "ORDER", "BY", "HAVING", "LIMIT",
-    "OFFSET", "VALUES", "INTO", "AND", "OR", "NOT", "IN", "EXISTS", "BETWEEN", "LIKE",
-    "IS", "NULL", "AS", "DISTINCT", "UNION", "ALL", "ON",
+    "JOIN", "LEFT", "RIGHT", "INNER", "OUTER", "FULL", "CROSS", "GROUP", "ORDER", "BY",
+    "HAVING", "VALUES", "INTO", "SET", "AND", "OR", "NOT", "IN", "EXISTS", "BETWEEN", "LIKE",
+    "IS", "NULL", "AS", "DISTINCT", "UNION", "ALL", "ON", "CASE", "WHEN", "THEN", "ELSE",
+    "END", "ASC", "DESC", "WITH", "TABLE", "VIEW", "INDEX",
+];
+
+const MYSQL_KEYWORDS: &[&str] = &[
+    "LIMIT", "OFFSET", "SHOW", "DESCRIBE", "REPLACE", "IGNORE", "DUPLICATE", "KEY",
+    "AUTO_INCREMENT", "ENGINE", "STRAIGHT_JOIN", "REGEXP",
+];
+
+const POSTGRES_KEYWORDS: &[&str] = &[
+    "LIMIT", "OFFSET", "RETURNING", "ILIKE", "SIMILAR", "LATERAL", "CONFLICT", "DO", "NOTHING",
+    "FILTER", "OVER", "PARTITION", "ONLY", "USING", "RECURSIVE",
+];
+
+const MSSQL_KEYWORDS: &[&str] = &[
+    "TOP", "OFFSET", "FETCH", "NEXT", "ROWS", "ONLY", "OUTPUT", "APPLY", "PIVOT", "UNPIVOT",
+    "MERGE", "DECLARE", "EXEC", "IDENTITY",
+];
+
+const ORACLE_KEYWORDS: &[&str] = &[
+    "ROWNUM", "CONNECT", "PRIOR", "START", "MINUS", "MERGE", "OFFSET", "FETCH", "FIRST", "ROWS",
+    "ONLY", "DUAL",
+];
+
+const SQLITE_KEYWORDS: &[&str] = &[
+    "LIMIT", "OFFSET", "PRAGMA", "VACUUM", "ATTACH", "DETACH", "GLOB", "REPLACE",
+    "AUTOINCREMENT", "WITHOUT", "ROWID", "CONFLICT",
 ];
 
 const SQL_FUNCTIONS: &[(&str, &str)] = &[
@@ -68,7 +156,55 @@ const SQL_FUNCTIONS: &[(&str, &str)] = &[
     ("AVG(x)", "Average value"),
     ("MIN(x)", "Minimum value"),
     ("MAX(x)", "Maximum value"),
+    ("COALESCE(x, y)", "First non-NULL argument"),
+    ("CAST(x AS type)", "Convert a value to another type"),
+    ("UPPER(s)", "Upper-case text"),
+    ("LOWER(s)", "Lower-case text"),
+];
+
+const MYSQL_FUNCTIONS: &[(&str, &str)] = &[
+    ("NOW()", "Current timestamp"),
+    ("CONCAT(a, b)", "Concatenate strings"),
+    ("IFNULL(x, y)", "y when x is NULL"),
+    ("DATE_FORMAT(d, fmt)", "Format a date"),
+    ("GROUP_CONCAT(x)", "Concatenate the values of a group"),
+    ("JSON_EXTRACT(doc, path)", "Value at a JSON path"),
+];
+
+const POSTGRES_FUNCTIONS: &[(&str, &str)] = &[
     ("NOW()", "Current timestamp"),
+    ("STRING_AGG(x, sep)", "Concatenate the values of a group"),
+    ("ARRAY_AGG(x)", "Collect the values of a group into an array"),
+    ("TO_CHAR(x, fmt)", "Format a value as text"),
+    ("DATE_TRUNC(field, ts)", "Truncate a timestamp to a precision"),
+    ("GENERATE_SERIES(start, stop)", "Set of values from start to stop"),
+];
+
+const MSSQL_FUNCTIONS: &[(&str, &str)] = &[
+    ("GETDATE()", "Current timestamp"),
+    ("ISNULL(x, y)", "y when x is NULL"),
+    ("LEN(s)", "Length of a string"),
+    ("DATEADD(part, n, d)", "Add an interval to a date"),
+    ("DATEDIFF(part, a, b)", "Difference between two dates"),
+    ("STRING_AGG(x, sep)", "Concatenate the values of a group"),
+];
+
+const ORACLE_FUNCTIONS: &[(&str, &str)] = &[
+    ("SYSDATE", "Current date and time"),
+    ("NVL(x, y)", "y when x is NULL"),
+    ("TO_CHAR(x, fmt)", "Format a value as text"),
+    ("TO_DATE(s, fmt)", "Parse a date"),
+    ("DECODE(x, search, result)", "Compare x to each search value"),
+    ("LISTAGG(x, sep)", "Concatenate the values of a group"),
+];
+
+const SQLITE_FUNCTIONS: &[(&str, &str)] = &[
+    ("DATETIME('now')", "Current timestamp"),
+    ("IFNULL(x, y)", "y when x is NULL"),
+    ("GROUP_CONCAT(x)", "Concatenate the values of a group"),
+    ("STRFTIME(fmt, t)", "Format a date"),
+    ("SUBSTR(s, pos, len)", "Part of a string"),
+    ("JSON_EXTRACT(doc, path)", "Value at a JSON path"),
 ];
 
 const SQL_KEYWORD_DOCS: &[(&str, &str)] = &[
@@ -83,14 +219,60 @@ const SQL_KEYWORD_DOCS: &[(&str, &str)] = &[
     ("LIMIT", "Limit number of rows"),
 ];
 
+const DIALECTS: [DatabaseType; 5] = [
+    DatabaseType::MySQL,
+    DatabaseType::PostgreSQL,
+    DatabaseType::MSSQL,
+    DatabaseType::Oracle,
+    DatabaseType::SQLite,
+];
+
+/// Keywords of a dialect, the common ones included.
+fn dialect_keywords(dialect: DatabaseType) -> impl Iterator<Item = &'static str> {
+    let extra = match dialect {
+        DatabaseType::MySQL => MYSQL_KEYWORDS,
+        DatabaseType::PostgreSQL => POSTGRES_KEYWORDS,
+        DatabaseType::MSSQL => MSSQL_KEYWORDS,
+        DatabaseType::Oracle => ORACLE_KEYWORDS,
+        DatabaseType::SQLite => SQLITE_KEYWORDS,
+    };
+    SQL_KEYWORDS.iter().chain(extra).copied()
+}
+
+/// Functions of a dialect as (signature, doc), the common ones included.
+fn dialect_functions(dialect: DatabaseType) -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+    let extra = match dialect {
+        DatabaseType::MySQL => MYSQL_FUNCTIONS,
+        DatabaseType::PostgreSQL => POSTGRES_FUNCTIONS,
+        DatabaseType::MSSQL => MSSQL_FUNCTIONS,
+        DatabaseType::Oracle => ORACLE_FUNCTIONS,
+        DatabaseType::SQLite => SQLITE_FUNCTIONS,
+    };
+    SQL_FUNCTIONS.iter().chain(extra)
+}
+
 #[derive(Clone)]
 pub struct DefaultSqlCompletionProvider {
     schema: SqlSchema,
+    snippets: Vec<(String, String)>, // (name, sql)
+    dialect: DatabaseType,
 }
 
 impl DefaultSqlCompletionProvider {
     pub fn new(schema: SqlSchema) -> Self {
-        Self { schema }
+        Self { schema, snippets: Vec::new(), dialect: DatabaseType::MySQL }
+    }
+
+    /// Snippets offered by name, completing one inserts its SQL.
+    pub fn with_snippets(mut self, snippets: Vec<(String, String)>) -> Self {
+        self.snippets = snippets;
+        self
+    }
+
+    /// Dialect whose quoting rules, keywords and functions are used.
+    pub fn with_dialect(mut self, dialect: DatabaseType) -> Self {
+        self.dialect = dialect;
+        self
     }
 }
 
@@ -105,61 +287,24 @@ impl CompletionProvider for DefaultSqlCompletionProvider {
     ) -> Task<Result<CompletionResponse>> {
         let rope = rope.clone();
         let schema = self.schema.clone();
+        let snippets = self.snippets.clone();
+        let dialect = self.dialect;
 
         cx.background_spawn(async move {
-            // Current word
-            let mut chars_before = vec![];
-            for ch in rope.chars_at(0) {
-                if chars_before.len() >= offset {
-                    break;
-                }
-                chars_before.push(ch);
-            }
-            let word_start = chars_before
-                .iter()
-                .rev()
-                .take_while(|c| c.is_alphanumeric() || **c == '_')
-                .count();
-            let start_offset = offset.saturating_sub(word_start);
-            let current_word = rope
-                .slice(start_offset..offset)
-                .to_string()
-                .to_uppercase();
-
-            let start_pos = rope.offset_to_position(start_offset);
-            let end_pos = rope.offset_to_position(offset);
-            let replace_range = LspRange::new(start_pos, end_pos);
+            // Only the statement under the cursor matters, including the part after it
+            let text = rope.to_string();
+            let offset = offset.min(text.len());
+            let (start, end) = match SqlScriptSplitter::statement_at(&text, offset, SplitOptions::new(dialect)) {
+                Some(statement) => (statement.range.start.min(offset), statement.range.end.max(offset)),
+                None => (0, text.len()),
+            };
+            let context = completion_context(&text[start..end], offset - start, dialect);
 
-            let mut items = Vec::new();
+            let current_word = context.prefix.to_uppercase();
+            let start_offset = offset - context.prefix.len();
+            let replace_range = LspRange::new(rope.offset_to_position(start_offset), rope.offset_to_position(offset));
 
-            let before_text = rope.slice(0..offset).to_string().to_uppercase();
-            let after_kw = before_text.contains(" FROM ")
-                || before_text.contains(" JOIN ")
-                || before_text.contains(" INTO ")
-                || before_text.contains(" TABLE ");
-            let suggest_tables = after_kw;
-            let suggest_columns = before_text.contains(" SELECT ")
-                || (before_text.contains(" SELECT ") && before_text.ends_with(", "));
-
-            // Dot context: table.column
-            let mut dot_table: Option<String> = None;
-            {
-                let slice = rope.slice(offset.saturating_sub(128)..offset).to_string();
-                if let Some(dot_ix) = slice.rfind('.') {
-                    let left = &slice[..dot_ix];
-                    let mut t = String::new();
-                    for ch in left.chars().rev() {
-                        if ch.is_alphanumeric() || ch == '_' {
-                            t.push(ch);
-                        } else {
-                            break;
-                        }
-                    }
-                    if !t.is_empty() {
-                        dot_table = Some(t.chars().rev().collect::<String>());
-                    }
-                }
-            }
+            let matches = |label: &str| current_word.is_empty() || label.to_uppercase().starts_with(&current_word);
 
             // helper to compute matched prefix safely
             let matched_prefix = |label: &str| -> String {
@@ -172,122 +317,128 @@ impl CompletionProvider for DefaultSqlCompletionProvider {
                 }
             };
 
-            // Keywords
-            for keyword in SQL_KEYWORDS {
-                if keyword.starts_with(&current_word) || current_word.is_empty() {
-                    items.push(CompletionItem {
-                        label: keyword.to_string(),
-                        kind: Some(CompletionItemKind::KEYWORD),
-                        text_edit: Some(CompletionTextEdit::InsertAndReplace(
-                            InsertReplaceEdit {
-                                new_text: keyword.to_string(),
-                                insert: replace_range.clone(),
-                                replace: replace_range.clone(),
-                            },
-                        )),
-                        filter_text: Some(matched_prefix(keyword)),
-                        documentation: SQL_KEYWORD_DOCS
-                            .iter()
-                            .find(|(k, _)| k == keyword)
-                            .map(|(_, doc)| lsp_types::Documentation::String(doc.to_string())),
-                        sort_text: Some(format!("1_{}", keyword)),
-                        ..Default::default()
-                    });
-                }
-            }
+            let item = |label: &str, new_text: &str, kind: CompletionItemKind, detail: Option<String>, doc: Option<String>, sort_group: u8| CompletionItem {
+                label: label.to_string(),
+                kind: Some(kind),
+                detail,
+                text_edit: Some(CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+                    new_text: new_text.to_string(),
+                    insert: replace_range,
+                    replace: replace_range,
+                })),
+                filter_text: Some(matched_prefix(label)),
+                documentation: doc.map(lsp_types::Documentation::String),
+                sort_text: Some(format!("{}_{}", sort_group, label)),
+                ..Default::default()
+            };
 
-            // Functions
-            for (func, doc) in SQL_FUNCTIONS {
-                let func_name = func.split('(').next().unwrap_or("");
-                if func_name.starts_with(&current_word) || current_word.is_empty() {
-                    items.push(CompletionItem {
-                        label: func.to_string(),
-                        kind: Some(CompletionItemKind::FUNCTION),
-                        text_edit: Some(CompletionTextEdit::InsertAndReplace(
-                            InsertReplaceEdit {
-                                new_text: func.to_string(),
-                                insert: replace_range.clone(),
-                                replace: replace_range.clone(),
-                            },
-                        )),
-                        filter_text: Some(matched_prefix(func)),
-                        documentation: Some(lsp_types::Documentation::String(doc.to_string())),
-                        sort_text: Some(format!("2_{}", func)),
-                        ..Default::default()
-                    });
-                }
-            }
+            let mut items = Vec::new();
 
-            // Tables
-            if suggest_tables || current_word.is_empty() {
+            let tables_in = |items: &mut Vec<CompletionItem>, qualifier: Option<&str>| {
                 for (table, doc) in &schema.tables {
-                    let table_upper = table.to_uppercase();
-                    if table_upper.starts_with(&current_word) || current_word.is_empty() {
-                        items.push(CompletionItem {
-                            label: table.clone(),
-                            kind: Some(CompletionItemKind::STRUCT),
-                            detail: Some("Table".to_string()),
-                            text_edit: Some(CompletionTextEdit::InsertAndReplace(
-                                InsertReplaceEdit {
-                                    new_text: table.clone(),
-                                    insert: replace_range.clone(),
-                                    replace: replace_range.clone(),
-                                },
-                            )),
-                            filter_text: Some(matched_prefix(&table)),
-                            documentation: Some(lsp_types::Documentation::String(doc.clone())),
-                            sort_text: Some(format!("0_{}", table)),
-                            ..Default::default()
-                        });
+                    // Under `schema.` only that schema's tables, without the prefix
+                    let label = match qualifier {
+                        Some(qualifier) => match table.split_once('.') {
+                            Some((scope, name)) if scope.eq_ignore_ascii_case(qualifier) => name,
+                            _ => continue,
+                        },
+                        None => table.as_str(),
+                    };
+                    if matches(label) {
+                        items.push(item(label, label, CompletionItemKind::STRUCT, Some("Table".to_string()), Some(doc.clone()), 0));
+                    }
+                }
+            };
+
+            // Columns of the statement's tables, or the global ones when none is known
+            let mut scope_columns = Vec::new();
+            if matches!(context.kind, CompletionKind::Column | CompletionKind::JoinCondition { .. }) {
+                let mut seen = Vec::new();
+                for table in &context.tables {
+                    let Some(name) = schema.resolve_table(table) else { continue };
+                    if seen.contains(&(name.clone(), table.reference_name().to_string())) {
+                        continue;
+                    }
+                    seen.push((name.clone(), table.reference_name().to_string()));
+                    if let Some(metadata) = schema.table_metadata(&name).await {
+                        for (column, doc) in metadata.columns {
+                            scope_columns.push((column, format!("{}.column", table.reference_name()), doc));
+                        }
                     }
                 }
+                if seen.is_empty() {
+                    scope_columns.extend(schema.columns.iter().map(|(column, doc)| (column.clone(), "Column".to_string(), doc.clone())));
+                }
             }
 
-            // Columns (dot context first)
-            if let Some(tname) = dot_table.clone() {
-                if let Some(cols) = schema.columns_by_table.get(&tname) {
-                    for (column, doc) in cols {
-                        let column_upper = column.to_uppercase();
-                        if column_upper.starts_with(&current_word) || current_word.is_empty() {
-                            items.push(CompletionItem {
-                                label: column.clone(),
-                                kind: Some(CompletionItemKind::FIELD),
-                                detail: Some(format!("{}.column", tname)),
-                                text_edit: Some(CompletionTextEdit::InsertAndReplace(
-                                    InsertReplaceEdit {
-                                        new_text: column.clone(),
-                                        insert: replace_range.clone(),
-                                        replace: replace_range.clone(),
-                                    },
-                                )),
-                                filter_text: Some(matched_prefix(&column)),
-                                documentation: Some(lsp_types::Documentation::String(doc.clone())),
-                                sort_text: Some(format!("0_{}", column)),
-                                ..Default::default()
-                            });
+            match &context.kind {
+                CompletionKind::None => {}
+                CompletionKind::Table { schema: qualifier } => tables_in(&mut items, qualifier.as_deref()),
+                CompletionKind::QualifiedColumn { qualifier } => {
+                    // An alias or table of the statement, else a table named directly, else a schema
+                    let table = context.tables.iter().find(|t| t.matches(qualifier)).cloned().unwrap_or_else(|| TableRef {
+                        schema: None,
+                        name: qualifier.clone(),
+                        alias: None,
+                        offset: 0,
+                    });
+                    match schema.resolve_table(&table) {
+                        Some(name) => {
+                            for (column, doc) in schema.table_metadata(&name).await.unwrap_or_default().columns {
+                                if matches(&column) {
+                                    items.push(item(&column, &column, CompletionItemKind::FIELD, Some(format!("{}.column", qualifier)), Some(doc), 0));
+                                }
+                            }
                         }
+                        None => tables_in(&mut items, Some(qualifier)),
                     }
                 }
-            } else if suggest_columns || current_word.is_empty() {
-                for (column, doc) in &schema.columns {
-                    let column_upper = column.to_uppercase();
-                    if column_upper.starts_with(&current_word) || current_word.is_empty() {
-                        items.push(CompletionItem {
-                            label: column.clone(),
-                            kind: Some(CompletionItemKind::FIELD),
-                            detail: Some("Column".to_string()),
-                            text_edit: Some(CompletionTextEdit::InsertAndReplace(
-                                InsertReplaceEdit {
-                                    new_text: column.clone(),
-                                    insert: replace_range.clone(),
-                                    replace: replace_range.clone(),
-                                },
-                            )),
-                            filter_text: Some(matched_prefix(&column)),
-                            documentation: Some(lsp_types::Documentation::String(doc.clone())),
-                            sort_text: Some(format!("0_{}", column)),
-                            ..Default::default()
+                CompletionKind::Keyword | CompletionKind::Column | CompletionKind::JoinCondition { .. } => {
+                    if let CompletionKind::JoinCondition { table } = &context.kind {
+                        let mut foreign_keys: HashMap<usize, Vec<ForeignKeyInfo>> = HashMap::new();
+                        for other in context.tables.iter().filter(|other| other.offset <= table.offset) {
+                            if let Some(name) = schema.resolve_table(other) {
+                                let metadata = schema.table_metadata(&name).await.unwrap_or_default();
+                                foreign_keys.insert(other.offset, metadata.foreign_keys);
+                            }
+                        }
+                        let conditions = join_conditions(table, &context.tables, |t| {
+                            foreign_keys.get(&t.offset).map_or(&[][..], |keys| &keys[..])
                         });
+                        for condition in conditions {
+                            if matches(&condition) {
+                                items.push(item(&condition, &condition, CompletionItemKind::REFERENCE, Some("Foreign key".to_string()), None, 0));
+                            }
+                        }
+                    }
+
+                    for (column, detail, doc) in &scope_columns {
+                        if matches(column) {
+                            items.push(item(column, column, CompletionItemKind::FIELD, Some(detail.clone()), Some(doc.clone()), 0));
+                        }
+                    }
+
+                    for keyword in dialect_keywords(dialect) {
+                        if matches(keyword) {
+                            let doc = SQL_KEYWORD_DOCS.iter().find(|(k, _)| *k == keyword).map(|(_, doc)| doc.to_string());
+                            items.push(item(keyword, keyword, CompletionItemKind::KEYWORD, None, doc, 1));
+                        }
+                    }
+
+                    for (func, doc) in dialect_functions(dialect) {
+                        let func_name = func.split('(').next().unwrap_or("");
+                        if matches(func_name) {
+                            items.push(item(func, func, CompletionItemKind::FUNCTION, None, Some(doc.to_string()), 2));
+                        }
+                    }
+                }
+            }
+
+            // Snippets, only once part of the name is typed
+            if !current_word.is_empty() && context.kind != CompletionKind::None {
+                for (name, sql) in &snippets {
+                    if name.to_uppercase().starts_with(&current_word) {
+                        items.push(item(name, sql, CompletionItemKind::SNIPPET, Some("Snippet".to_string()), Some(sql.clone()), 0));
                     }
                 }
             }
@@ -341,7 +492,7 @@ impl HoverProvider for DefaultSqlHoverProvider {
                 return Task::ready(Ok(Some(hover)));
             }
         }
-        for (func, doc) in SQL_FUNCTIONS {
+        for (func, doc) in DIALECTS.into_iter().flat_map(dialect_functions) {
             let func_name = func.split('(').next().unwrap_or("");
             if func_name == word.as_str() {
                 let hover = Hover {
@@ -358,15 +509,30 @@ impl HoverProvider for DefaultSqlHoverProvider {
     }
 }
 
+/// Dialect and options the Format actions use.
+#[derive(Clone)]
+struct SqlFormatter {
+    dialect: DatabaseType,
+    options: SqlFormatOptions,
+}
+
+impl SqlFormatter {
+    fn format(&self, sql: &str) -> String {
+        db::format_sql(sql, self.dialect, &self.options)
+    }
+}
+
 #[derive(Clone)]
 struct SqlActionsProvider {
     /// Callback for executing SQL.
     on_execute: Option<Rc<dyn Fn(String, &mut Window, &mut gpui::App) + 'static>>,
+    /// Shared with the editor, which keeps it up to date
+    formatter: Rc<RefCell<SqlFormatter>>,
 }
 
 impl SqlActionsProvider {
-    fn new() -> Self {
-        Self { on_execute: None }
+    fn new(formatter: Rc<RefCell<SqlFormatter>>) -> Self {
+        Self { on_execute: None, formatter }
     }
     #[allow(dead_code)]
     fn with_execute(
@@ -377,38 +543,6 @@ impl SqlActionsProvider {
         self
     }
 
-    fn format_sql(sql: &str) -> String {
-        let mut formatted = String::new();
-        let mut indent_level = 0;
-        let lines: Vec<&str> = sql.lines().collect();
-        for line in lines {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            if trimmed.starts_with("FROM")
-                || trimmed.starts_with("WHERE")
-                || trimmed.starts_with("JOIN")
-                || trimmed.starts_with("INNER")
-                || trimmed.starts_with("LEFT")
-                || trimmed.starts_with("RIGHT")
-                || trimmed.starts_with("ORDER BY")
-                || trimmed.starts_with("GROUP BY")
-                || trimmed.starts_with("HAVING")
-                || trimmed.starts_with("LIMIT")
-            {
-                indent_level = 0;
-            }
-            formatted.push_str(&"  ".repeat(indent_level));
-            formatted.push_str(trimmed);
-            formatted.push('\n');
-            if trimmed.starts_with("SELECT") {
-                indent_level = 1;
-            }
-        }
-        formatted.trim_end().to_string()
-    }
-
     fn minify_sql(sql: &str) -> String {
         sql.split_whitespace().collect::<Vec<_>>().join(" ")
     }
@@ -455,7 +589,7 @@ impl SqlActionsProvider {
 
     fn uppercase_if_keyword(word: &str) -> String {
         let upper = word.to_uppercase();
-        if SQL_KEYWORDS.contains(&upper.as_str()) {
+        if DIALECTS.into_iter().flat_map(dialect_keywords).any(|keyword| keyword == upper) {
             upper
         } else {
             word.to_string()
@@ -485,6 +619,25 @@ impl CodeActionProvider for SqlActionsProvider {
             let end = state_read.text().offset_to_position(range.end);
             let lsp_range = lsp_types::Range { start, end };
 
+            // Format selection
+            let new_text = self.formatter.borrow().format(&old_text);
+            actions.push(lsp_types::CodeAction {
+                title: "Format Selection".into(),
+                kind: Some(lsp_types::CodeActionKind::REFACTOR),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(
+                        std::iter::once((
+                            document_uri.clone(),
+                            vec![TextEdit { range: lsp_range, new_text }],
+                        ))
+                        .collect(),
+                    ),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                ..Default::default()
+            });
+
             // Uppercase
             let new_text = Self::uppercase_keywords(&old_text);
             actions.push(lsp_types::CodeAction {
@@ -526,7 +679,7 @@ impl CodeActionProvider for SqlActionsProvider {
 
         // Format whole document
         let old_text = state_read.text().to_string();
-        let new_text = Self::format_sql(&old_text);
+        let new_text = self.formatter.borrow().format(&old_text);
         let start = state_read.text().offset_to_position(0);
         let end = state_read.text().offset_to_position(state_read.text().len());
         let lsp_range = lsp_types::Range { start, end };
@@ -566,11 +719,35 @@ impl CodeActionProvider for SqlActionsProvider {
 /// A reusable SQL editor component built on top of `Input`.
 pub struct SqlEditor {
     editor: Entity<InputState>,
+    /// Dialect used to find statement boundaries
+    dialect: DatabaseType,
+    /// Cursor and selection the statement highlight was computed for
+    highlighted_for: Option<(usize, std::ops::Range<usize>)>,
+    /// Schema and snippets the completion provider is built from
+    schema: SqlSchema,
+    snippets: Vec<(String, String)>,
+    /// Shared with the code action provider
+    formatter: Rc<RefCell<SqlFormatter>>,
     _subscriptions: Vec<Subscription>,
 }
 
 impl SqlEditor {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let default_schema = SqlSchema::default()
+            .with_tables([
+                ("users", "User accounts"),
+                ("orders", "Orders"),
+                ("products", "Products"),
+                ("customers", "Customers"),
+            ])
+            .with_columns([
+                ("id", "Identifier"),
+                ("name", "Name"),
+                ("email", "Email"),
+                ("created_at", "Created time"),
+                ("status", "Status"),
+            ]);
+
         let editor = cx.new(|cx| {
             let mut editor = InputState::new(window, cx)
                 .code_editor(Language::from_str("sql"))
@@ -582,40 +759,43 @@ impl SqlEditor {
                 .placeholder("Enter your SQL query here...");
 
             // Defaults: completion + hover + actions
-            let default_schema = SqlSchema::default()
-                .with_tables([
-                    ("users", "User accounts"),
-                    ("orders", "Orders"),
-                    ("products", "Products"),
-                    ("customers", "Customers"),
-                ])
-                .with_columns([
-                    ("id", "Identifier"),
-                    ("name", "Name"),
-                    ("email", "Email"),
-                    ("created_at", "Created time"),
-                    ("status", "Status"),
-                ]);
-
             editor.lsp.completion_provider =
-                Some(Rc::new(DefaultSqlCompletionProvider::new(default_schema)));
+                Some(Rc::new(DefaultSqlCompletionProvider::new(default_schema.clone())));
             editor.lsp.hover_provider = Some(Rc::new(DefaultSqlHoverProvider));
 
             editor
         });
 
-        let _subscriptions = vec![cx.subscribe_in(
-            &editor,
-            window,
-            move |_, _, _: &InputEvent, _window, cx| cx.notify(),
-        )];
+        let _subscriptions = vec![
+            cx.subscribe_in(&editor, window, move |this, _, event: &InputEvent, _window, cx| {
+                if let InputEvent::Change = event {
+                    this.highlighted_for = None;
+                    this.update_statement_highlight(cx);
+                }
+                cx.notify()
+            }),
+            // 光标或选区变化时重新标出将要执行的语句
+            cx.observe(&editor, |this, _, cx| this.update_statement_highlight(cx)),
+        ];
 
         // Provide default text utilities as code actions (format/minify/uppercase)
+        let formatter = Rc::new(RefCell::new(SqlFormatter {
+            dialect: DatabaseType::MySQL,
+            options: SqlFormatOptions::default(),
+        }));
         editor.update(cx, |state, _| {
-            state.lsp.code_action_providers.push(Rc::new(SqlActionsProvider::new()));
+            state.lsp.code_action_providers.push(Rc::new(SqlActionsProvider::new(formatter.clone())));
         });
 
-        Self { editor, _subscriptions }
+        Self {
+            editor,
+            dialect: DatabaseType::MySQL,
+            highlighted_for: None,
+            schema: default_schema,
+            snippets: Vec::new(),
+            formatter,
+            _subscriptions,
+        }
     }
 
     /// Access underlying editor state.
@@ -635,16 +815,36 @@ impl SqlEditor {
     }
 
     /// Set schema for default completion provider.
-    pub fn set_schema(
-        &mut self,
-        schema: SqlSchema,
-        _window: &mut Window,
-        cx: &mut Context<Self>,
-    ) {
+    pub fn set_schema(&mut self, schema: SqlSchema, cx: &mut Context<Self>) {
+        self.schema = schema;
+        self.update_completion_provider(cx);
+    }
+
+    /// Set the snippets the default completion provider expands by name, as (name, sql).
+    pub fn set_snippets(&mut self, snippets: Vec<(String, String)>, cx: &mut Context<Self>) {
+        self.snippets = snippets;
+        self.update_completion_provider(cx);
+    }
+
+    fn update_completion_provider(&mut self, cx: &mut Context<Self>) {
+        let provider = DefaultSqlCompletionProvider::new(self.schema.clone())
+            .with_snippets(self.snippets.clone())
+            .with_dialect(self.dialect);
         self.editor.update(cx, |state, _| {
-            state.lsp.completion_provider = Some(Rc::new(DefaultSqlCompletionProvider::new(
-                schema,
-            )));
+            state.lsp.completion_provider = Some(Rc::new(provider));
+        });
+    }
+
+    /// Insert a snippet over the selection and select its first placeholder, so typing replaces it.
+    pub fn insert_snippet(&mut self, sql: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let start = self.editor.read(cx).selected_range().start;
+        let first_placeholder = db::find_placeholders(sql, self.dialect).into_iter().next();
+        self.editor.update(cx, |state, cx| {
+            state.replace(sql.to_string(), window, cx);
+            if let Some(placeholder) = first_placeholder {
+                state.set_selected_range(start + placeholder.range.start..start + placeholder.range.end, cx);
+            }
+            state.focus(window, cx);
         });
     }
 
@@ -686,6 +886,114 @@ impl SqlEditor {
         self.editor.update(cx, |s, cx| s.set_value(text, window, cx));
     }
 
+    /// Set the dialect used to find statement boundaries and to complete keywords and functions.
+    pub fn set_dialect(&mut self, dialect: DatabaseType, cx: &mut Context<Self>) {
+        self.dialect = dialect;
+        self.highlighted_for = None;
+        self.update_statement_highlight(cx);
+        self.update_completion_provider(cx);
+        self.formatter.borrow_mut().dialect = dialect;
+    }
+
+    pub fn dialect(&self) -> DatabaseType {
+        self.dialect
+    }
+
+    /// Set the options the Format actions use.
+    pub fn set_format_options(&mut self, options: SqlFormatOptions) {
+        self.formatter.borrow_mut().options = options;
+    }
+
+    /// Format the whole text.
+    pub fn format_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let len = self.editor.read(cx).text().len();
+        self.format_range(0..len, window, cx);
+    }
+
+    /// Format the selection, or the statement under the cursor when nothing is selected.
+    pub fn format_selection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let selected = self.editor.read(cx).selected_range();
+        let range = if selected.is_empty() {
+            match self.current_statement(cx) {
+                Some(statement) => statement.range,
+                None => return,
+            }
+        } else {
+            selected
+        };
+        self.format_range(range, window, cx);
+    }
+
+    fn format_range(&mut self, range: std::ops::Range<usize>, window: &mut Window, cx: &mut Context<Self>) {
+        let old_text = self.editor.read(cx).text().slice(range.clone()).to_string();
+        let new_text = self.formatter.borrow().format(&old_text);
+        if new_text == old_text {
+            return;
+        }
+        self.editor.update(cx, |state, cx| {
+            state.set_selected_range(range, cx);
+            state.replace(new_text, window, cx);
+        });
+    }
+
+    /// Get the selected text, empty when nothing is selected.
+    pub fn selected_text(&self, cx: &App) -> String {
+        let state = self.editor.read(cx);
+        state.text().slice(state.selected_range()).to_string()
+    }
+
+    /// Get the statement under the cursor.
+    pub fn current_statement(&self, cx: &App) -> Option<ScriptStatement> {
+        let state = self.editor.read(cx);
+        SqlScriptSplitter::statement_at(
+            &state.text().to_string(),
+            state.cursor(),
+            SplitOptions::new(self.dialect),
+        )
+    }
+
+    /// Show statement errors inline, each given as a byte range of the text and its message.
+    /// They are dropped on the next edit.
+    pub fn set_error_diagnostics(&mut self, errors: Vec<(std::ops::Range<usize>, String)>, cx: &mut Context<Self>) {
+        self.editor.update(cx, |state, cx| {
+            let text = state.text().clone();
+            if let Some(set) = state.diagnostics_mut() {
+                set.clear();
+                set.extend(errors.into_iter().map(|(range, message)| {
+                    let range = text.offset_to_position(range.start)..text.offset_to_position(range.end);
+                    Diagnostic::new(range, message).with_severity(DiagnosticSeverity::Error)
+                }));
+            }
+            cx.notify();
+        });
+    }
+
+    /// Move the cursor to byte `offset` and focus the editor.
+    pub fn move_cursor_to(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor.update(cx, |state, cx| {
+            let position = state.text().offset_to_position(offset);
+            state.set_cursor_position(position, window, cx);
+        });
+    }
+
+    /// Highlight the statement "Run current statement" would execute.
+    /// Nothing is highlighted while a selection exists, since the selection is what runs then.
+    fn update_statement_highlight(&mut self, cx: &mut Context<Self>) {
+        let state = self.editor.read(cx);
+        let key = (state.cursor(), state.selected_range());
+        if self.highlighted_for.as_ref() == Some(&key) {
+            return;
+        }
+        let range = if key.1.is_empty() {
+            self.current_statement(cx).map(|statement| statement.range)
+        } else {
+            None
+        };
+        self.highlighted_for = Some(key);
+        self.editor
+            .update(cx, |state, cx| state.set_highlighted_range(range, cx));
+    }
+
     /// Get the current text content of the editor.
     /// This is a convenience method that accesses the underlying InputState.
     pub fn get_text<T>(&self, cx: &Context<T>) -> String {
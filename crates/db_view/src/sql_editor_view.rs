@@ -1,14 +1,60 @@
+use crate::query_history_panel::record_query_history;
+use crate::snippet_panel::SnippetPanel;
+use crate::sql_drafts::{claim_draft, release_draft, remove_draft, save_draft, SqlDraft};
 use crate::sql_editor::SqlEditor;
-use crate::sql_result_tab::SqlResultTabContainer;
+use crate::sql_format_settings::load_format_options;
+use crate::sql_result_tab::{SqlResultTabContainer, SqlResultTabEvent};
+use one_core::storage::{now, QueryHistory};
 use one_core::tab_container::{TabContent, TabContentType};
-use db::{ExecOptions, GlobalDbState};
-use gpui::{div, px, AnyElement, App, AppContext, ClickEvent, Entity, FocusHandle, Focusable, IntoElement, ParentElement, SharedString, Styled, Window};
+use db::{spawn_result, DbSession, ExecOptions, QueryStream, GlobalDbState, ParamType, Placeholder, RunningQuery, SessionPurpose, SqlResult, SqlValue};
+use futures::FutureExt;
+use gpui::prelude::FluentBuilder;
+use gpui::{actions, div, px, AnyElement, App, AppContext, Entity, FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, ParentElement, PathPromptOptions, SharedString, Styled, Window};
 use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputEvent, InputState};
 use gpui_component::resizable::{resizable_panel, v_resizable};
 use gpui_component::select::{SearchableVec, Select, SelectState};
-use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, Sizable, Size};
+use gpui_component::switch::Switch;
+use gpui_component::tag::Tag;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, IconName, IndexPath, Selectable, Sizable, Size, WindowExt};
 use std::any::Any;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+actions!(sql_editor, [RunQuery, RunCurrentStatement, RunSelection, Explain, ExplainAnalyze, OpenFile, SaveFile, SaveFileAs, FormatQuery, FormatSelection]);
+
+// 停止输入这么久之后才写草稿
+const DRAFT_SAVE_DELAY: Duration = Duration::from_millis(800);
+
+const CONTEXT: &str = "SqlEditor";
+// 绑定在编辑器输入框上，才能覆盖输入框自己的 secondary-enter
+const EDITOR_CONTEXT: &str = "SqlEditor > Input";
+
+pub fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("secondary-enter", RunQuery, Some(EDITOR_CONTEXT)),
+        KeyBinding::new("secondary-shift-enter", RunCurrentStatement, Some(EDITOR_CONTEXT)),
+        KeyBinding::new("secondary-alt-enter", RunSelection, Some(EDITOR_CONTEXT)),
+        KeyBinding::new("secondary-shift-e", Explain, Some(EDITOR_CONTEXT)),
+        KeyBinding::new("secondary-alt-e", ExplainAnalyze, Some(EDITOR_CONTEXT)),
+        KeyBinding::new("secondary-o", OpenFile, Some(CONTEXT)),
+        KeyBinding::new("secondary-s", SaveFile, Some(CONTEXT)),
+        KeyBinding::new("secondary-shift-s", SaveFileAs, Some(CONTEXT)),
+        KeyBinding::new("secondary-shift-f", FormatQuery, Some(EDITOR_CONTEXT)),
+        KeyBinding::new("alt-shift-f", FormatSelection, Some(EDITOR_CONTEXT)),
+    ]);
+}
+
+/// 标签页关联的 .sql 文件，及最后一次打开或保存时的内容
+#[derive(Default)]
+struct SqlFile {
+    path: Option<PathBuf>,
+    saved_text: String,
+}
 
 pub struct SqlEditorTabContent {
     title: SharedString,
@@ -19,6 +65,24 @@ pub struct SqlEditorTabContent {
     status_msg: Entity<String>,
     current_database: Arc<RwLock<Option<String>>>,
     database_select: Entity<SelectState<SearchableVec<String>>>,
+    // 标签页独占的会话，手动提交时事务保持在这个会话上
+    session: Arc<RwLock<Option<DbSession>>>,
+    auto_commit: Arc<RwLock<bool>>,
+    // 正在执行的查询，停止按钮通过它取消
+    running_query: Arc<RwLock<Option<RunningQuery>>>,
+    // 上次输入的参数值和类型，按占位符记住
+    param_values: Arc<RwLock<HashMap<String, (ParamType, String)>>>,
+    // 代码片段面板及其是否显示
+    snippet_panel: Entity<SnippetPanel>,
+    show_snippets: Entity<bool>,
+    file: Arc<RwLock<SqlFile>>,
+    // 内容与文件不一致，标签标题据此显示未保存标记
+    dirty: Entity<bool>,
+    // 未保存内容写在配置目录的草稿里，下次启动时恢复
+    draft_id: String,
+    draft_generation: Arc<AtomicU64>,
+    // 恢复的标签页延迟到首次显示时再加载连接信息
+    metadata_pending: Arc<AtomicBool>,
     // Add focus handle
     focus_handle: FocusHandle,
 }
@@ -40,7 +104,40 @@ impl SqlEditorTabContent {
         window: &mut Window,
         cx: &mut App,
     ) -> Self {
+        let instance = Self::new_with_draft_id(title, connection_id, initial_database, Uuid::new_v4().to_string(), window, cx);
+        instance.load_connection_metadata(cx);
+        instance
+    }
+
+    /// 从草稿或保存的布局恢复标签页，关联的文件仍在时以它的内容判断是否有改动。
+    /// 标签页首次显示时才连接数据库
+    pub fn from_draft(draft: SqlDraft, window: &mut Window, cx: &mut App) -> Self {
+        let instance = Self::new_with_draft_id(draft.title, draft.connection_id, draft.database, draft.id, window, cx);
+        instance.metadata_pending.store(true, Ordering::SeqCst);
+        if let Ok(mut file) = instance.file.write() {
+            file.saved_text = draft
+                .file_path
+                .as_ref()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .unwrap_or_default();
+            file.path = draft.file_path;
+        }
+        instance.set_sql(draft.text, window, cx);
+        instance
+    }
+
+    fn new_with_draft_id(
+        title: impl Into<SharedString>,
+        connection_id: impl Into<String>,
+        initial_database: Option<String>,
+        draft_id: String,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let connection_id = connection_id.into();
+        claim_draft(&draft_id);
         let editor = cx.new(|cx| SqlEditor::new(window, cx));
+        let snippet_panel = cx.new(|cx| SnippetPanel::new(connection_id.clone(), editor.clone(), window, cx));
         let focus_handle = cx.focus_handle();
 
         let result_tabs = Arc::new(RwLock::new(Vec::new()));
@@ -58,14 +155,32 @@ impl SqlEditorTabContent {
         let instance = Self {
             title: title.into(),
             editor: editor.clone(),
-            connection_id: connection_id.into(),
+            connection_id,
             sql_result_tab_container: cx.new(|cx| SqlResultTabContainer::new(result_tabs, active_result_tab,cx)),
             status_msg,
             current_database: current_database.clone(),
             database_select: database_select.clone(),
+            session: Arc::new(RwLock::new(None)),
+            auto_commit: Arc::new(RwLock::new(true)),
+            running_query: Arc::new(RwLock::new(None)),
+            param_values: Arc::new(RwLock::new(HashMap::new())),
+            snippet_panel,
+            show_snippets: cx.new(|_| false),
+            file: Arc::new(RwLock::new(SqlFile::default())),
+            dirty: cx.new(|_| false),
+            draft_id,
+            draft_generation: Arc::new(AtomicU64::new(0)),
+            metadata_pending: Arc::new(AtomicBool::new(false)),
             focus_handle,
         };
 
+        let instance_for_changes = instance.clone();
+        cx.subscribe(&editor.read(cx).input(), move |_input, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                instance_for_changes.handle_text_changed(cx);
+            }
+        }).detach();
+
         // Subscribe to select events for database switching
         let current_db_clone = current_database.clone();
         let instance_clone = instance.clone();
@@ -90,27 +205,225 @@ impl SqlEditorTabContent {
             }
         }).detach();
 
+        // Clicking an error result shows the failing statement in the editor
+        let editor_for_errors = editor.clone();
+        cx.subscribe(&instance.sql_result_tab_container, move |_container, event, cx| {
+            let SqlResultTabEvent::JumpToError { range } = event;
+            let offset = range.start;
+            if let Some(window_id) = cx.active_window() {
+                let _ = cx.update_window(window_id, |_entity, window, cx| {
+                    editor_for_errors.update(cx, |editor, cx| editor.move_cursor_to(offset, window, cx));
+                });
+            }
+        }).detach();
+
+        instance
+    }
+
+    /// 加载数据库列表、方言和当前数据库的表结构，需要连上数据库
+    fn load_connection_metadata(&self, cx: &mut App) {
         // If initial database is provided, load schema
-        if let Some(db) = initial_database {
-            let instance_for_schema = instance.clone();
-            let db_clone = db.clone();
+        if let Some(db) = self.current_database.read().ok().and_then(|guard| guard.clone()) {
+            let instance_for_schema = self.clone();
 
             cx.spawn(async move |cx| {
                 // Update editor schema
                 cx.update(|cx| {
-                    instance_for_schema.update_schema_for_db(&db_clone, cx);
+                    instance_for_schema.update_schema_for_db(&db, cx);
                 }).ok();
             }).detach();
         }
 
         // Load databases in background
-        instance.load_databases_async(cx);
-
-        instance
+        self.load_databases_async(cx);
+        self.load_dialect_async(cx);
     }
 
     pub fn set_sql(&self, sql: String, window: &mut Window, cx: &mut App) {
         self.editor.update(cx, |e, cx| e.set_value(sql, window, cx));
+        self.handle_text_changed(cx);
+    }
+
+    fn handle_text_changed(&self, cx: &mut App) {
+        let text = self.get_sql_text(cx);
+        let dirty = self.file.read().map(|file| file.saved_text != text).unwrap_or(true);
+        if *self.dirty.read(cx) != dirty {
+            self.dirty.update(cx, |d, cx| {
+                *d = dirty;
+                cx.notify();
+            });
+        }
+        self.schedule_draft_save(dirty.then_some(text), cx);
+    }
+
+    /// 合并连续的改动，只写最后一次；没有未保存内容时删除草稿
+    fn schedule_draft_save(&self, text: Option<String>, cx: &mut App) {
+        let generation = self.draft_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let draft_generation = self.draft_generation.clone();
+        let draft_id = self.draft_id.clone();
+        let draft = text.map(|text| self.draft(text));
+
+        cx.spawn(async move |cx| {
+            cx.background_executor().timer(DRAFT_SAVE_DELAY).await;
+            if draft_generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let result = cx
+                .background_spawn(async move {
+                    match draft {
+                        Some(draft) => save_draft(&draft),
+                        None => remove_draft(&draft_id),
+                    }
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!("Failed to persist editor draft: {}", e);
+            }
+        }).detach();
+    }
+
+    fn draft(&self, text: String) -> SqlDraft {
+        SqlDraft {
+            id: self.draft_id.clone(),
+            title: self.title.to_string(),
+            connection_id: self.connection_id.clone(),
+            database: self.current_database.read().ok().and_then(|guard| guard.clone()),
+            file_path: self.file.read().ok().and_then(|file| file.path.clone()),
+            text,
+            updated_at: now(),
+        }
+    }
+
+    fn set_status(&self, message: String, cx: &mut App) {
+        self.status_msg.update(cx, |msg, cx| {
+            *msg = message;
+            cx.notify();
+        });
+    }
+
+    /// 打开 .sql 文件替换当前内容，有未保存的改动时先确认
+    fn handle_open_file(&self, window: &mut Window, cx: &mut App) {
+        if !*self.dirty.read(cx) {
+            self.prompt_open_file(cx);
+            return;
+        }
+
+        let this = self.clone();
+        window.open_dialog(cx, move |dialog, _window, _cx| {
+            let this = this.clone();
+            dialog
+                .title("Open File")
+                .confirm()
+                .child(format!("\"{}\" has unsaved changes that will be replaced. Continue?", this.title()))
+                .on_ok(move |_, _, cx| {
+                    this.prompt_open_file(cx);
+                    true
+                })
+        });
+    }
+
+    fn prompt_open_file(&self, cx: &mut App) {
+        let this = self.clone();
+        let future = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            multiple: false,
+            directories: false,
+            prompt: Some("打开 SQL 文件".into()),
+        });
+
+        cx.spawn(async move |cx| {
+            let Ok(Ok(Some(paths))) = future.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let read_path = path.clone();
+            let result = cx
+                .background_spawn(async move { std::fs::read_to_string(read_path) })
+                .await;
+            let Some(window_id) = cx.update(|cx| cx.active_window()).ok().flatten() else {
+                return;
+            };
+            let _ = cx.update_window(window_id, |_entity, window, cx| match result {
+                Ok(text) => {
+                    if let Ok(mut file) = this.file.write() {
+                        file.path = Some(path.clone());
+                        file.saved_text = text.clone();
+                    }
+                    this.set_sql(text, window, cx);
+                    this.set_status(format!("Opened {}", path.display()), cx);
+                }
+                Err(e) => this.set_status(format!("Failed to open {}: {}", path.display(), e), cx),
+            });
+        }).detach();
+    }
+
+    fn handle_save_file(&self, cx: &mut App) {
+        match self.file.read().ok().and_then(|file| file.path.clone()) {
+            Some(path) => self.write_file(path, cx),
+            None => self.handle_save_file_as(cx),
+        }
+    }
+
+    fn handle_save_file_as(&self, cx: &mut App) {
+        let this = self.clone();
+        let current_path = self.file.read().ok().and_then(|file| file.path.clone());
+        let directory = current_path
+            .as_ref()
+            .and_then(|path| path.parent().map(|dir| dir.to_path_buf()))
+            .or_else(dirs::home_dir)
+            .unwrap_or_default();
+        let suggested_name = current_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}.sql", self.title));
+        let future = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+
+        cx.spawn(async move |cx| {
+            if let Ok(Ok(Some(path))) = future.await {
+                let _ = cx.update(|cx| this.write_file(path, cx));
+            }
+        }).detach();
+    }
+
+    fn write_file(&self, path: PathBuf, cx: &mut App) {
+        let this = self.clone();
+        let text = self.get_sql_text(cx);
+        let write_path = path.clone();
+        let write_text = text.clone();
+
+        cx.spawn(async move |cx| {
+            let result = cx
+                .background_spawn(async move { std::fs::write(write_path, write_text) })
+                .await;
+            let _ = cx.update(|cx| match result {
+                Ok(()) => {
+                    if let Ok(mut file) = this.file.write() {
+                        file.path = Some(path.clone());
+                        file.saved_text = text;
+                    }
+                    this.handle_text_changed(cx);
+                    this.set_status(format!("Saved to {}", path.display()), cx);
+                }
+                Err(e) => this.set_status(format!("Failed to save {}: {}", path.display(), e), cx),
+            });
+        }).detach();
+    }
+
+    /// 按连接的数据库类型切分语句，用于标出和执行光标所在语句
+    fn load_dialect_async(&self, cx: &mut App) {
+        let global_state = cx.global::<GlobalDbState>().clone();
+        let connection_id = self.connection_id.clone();
+        let editor = self.editor.clone();
+
+        cx.spawn(async move |cx| {
+            let Some(config) = global_state.get_config(&connection_id).await else {
+                return;
+            };
+            editor.update(cx, |editor, cx| editor.set_dialect(config.database_type, cx)).ok();
+        }).detach();
     }
 
     /// Load databases into the select dropdown
@@ -122,7 +435,7 @@ impl SqlEditorTabContent {
 
         // Spawn async task to load databases
         cx.spawn(async move |cx| {
-            let (plugin, conn_arc) = match global_state.get_plugin_and_connection(&connection_id).await {
+            let (plugin, conn_arc) = match global_state.get_plugin_and_connection(&connection_id, SessionPurpose::Metadata).await {
                 Ok(result) => result,
                 Err(e) => {
                     eprintln!("Failed to get connection: {}", e);
@@ -180,9 +493,9 @@ impl SqlEditorTabContent {
         }).detach();
     }
 
-    /// Update SQL editor schema with tables and columns from current database
+    /// 更新编辑器补全用的表结构：这里只加载表名，列和外键等补全用到某张表时再加载
     pub fn update_schema_for_db(&self, database: &str, cx: &mut App) {
-        use crate::sql_editor::SqlSchema;
+        use crate::sql_editor::{SqlSchema, TableMetadata, TableMetadataLoader};
 
         let global_state = cx.global::<GlobalDbState>().clone();
         let connection_id = self.connection_id.clone();
@@ -190,60 +503,96 @@ impl SqlEditorTabContent {
         let db = database.to_string();
 
         cx.spawn(async move |cx| {
-            let (plugin, conn_arc) = match global_state.get_plugin_and_connection(&connection_id).await {
-                Ok(result) => result,
-                Err(e) => {
-                    eprintln!("Failed to get connection: {}", e);
-                    return;
+            let loader_state = global_state.clone();
+            let loader_connection_id = connection_id.clone();
+            let loader_db = db.clone();
+
+            let listed = spawn_result(async move {
+                let (plugin, conn_arc) = global_state.get_plugin_and_database_connection(&connection_id, Some(&db), SessionPurpose::Metadata).await?;
+                let conn = conn_arc.read().await;
+
+                // (作用域, 补全时的表名前缀)：schema 下的表同时以 schema.table 形式补全
+                let scopes: Vec<(String, Option<String>)> = if plugin.supports_schema() {
+                    plugin.list_schemas(&**conn).await?.into_iter().map(|s| (s.clone(), Some(s))).collect()
+                } else {
+                    vec![(db.clone(), None)]
+                };
+
+                let mut table_items: Vec<(String, String)> = Vec::new();
+                // 补全里的表名 -> (作用域, 表名)，同名的表取第一个作用域
+                let mut table_scopes: HashMap<String, (String, String)> = HashMap::new();
+
+                for (scope, prefix) in &scopes {
+                    let tables = plugin.list_tables(&**conn, scope).await?;
+
+                    for table in &tables {
+                        let qualified = match prefix {
+                            Some(prefix) => format!("{}.{}", prefix, table.name),
+                            None => table.name.clone(),
+                        };
+                        let description = if let Some(comment) = &table.comment {
+                            format!("Table: {} - {}", qualified, comment)
+                        } else {
+                            format!("Table: {}", qualified)
+                        };
+
+                        if prefix.is_some() {
+                            table_items.push((qualified.clone(), description.clone()));
+                            table_scopes.insert(qualified, (scope.clone(), table.name.clone()));
+                        }
+                        table_items.push((table.name.clone(), description));
+                        table_scopes
+                            .entry(table.name.clone())
+                            .or_insert_with(|| (scope.clone(), table.name.clone()));
+                    }
                 }
-            };
 
-            // Load tables
-            let conn = conn_arc.read().await;
-            let tables = match plugin.list_tables(&**conn, &db).await {
-                Ok(t) => t,
+                Ok((table_items, table_scopes))
+            }).await;
+
+            let (table_items, table_scopes) = match listed {
+                Ok(result) => result,
                 Err(e) => {
-                    eprintln!("Failed to list tables: {}", e);
+                    eprintln!("Failed to load tables: {}", e);
                     return;
                 }
             };
 
-            let mut schema = SqlSchema::default();
+            let table_scopes = Arc::new(table_scopes);
+            let loader: TableMetadataLoader = Arc::new(move |table: String| {
+                let global_state = loader_state.clone();
+                let connection_id = loader_connection_id.clone();
+                let db = loader_db.clone();
+                let table_scopes = table_scopes.clone();
 
-            // Add tables to schema
-            let table_items: Vec<(String, String)> = tables.iter()
-                .map(|t| {
-                    let description = if let Some(comment) = &t.comment {
-                        format!("Table: {} - {}", t.name, comment)
-                    } else {
-                        format!("Table: {}", t.name)
-                    };
-                    (t.name.clone(), description)
+                spawn_result(async move {
+                    let (scope, name) = table_scopes
+                        .get(&table)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Unknown table: {}", table))?;
+                    let (plugin, conn_arc) = global_state.get_plugin_and_database_connection(&connection_id, Some(&db), SessionPurpose::Metadata).await?;
+                    let conn = conn_arc.read().await;
+
+                    let columns = plugin.list_columns(&**conn, &scope, &name).await?;
+                    // 外键只用于补全 JOIN 条件，取不到时不影响列的补全
+                    let foreign_keys = plugin.list_foreign_keys(&**conn, &scope, &name).await.unwrap_or_default();
+
+                    Ok(TableMetadata {
+                        columns: columns
+                            .iter()
+                            .map(|c| (c.name.clone(), format!("{} - {}", c.data_type, c.comment.as_deref().unwrap_or(""))))
+                            .collect(),
+                        foreign_keys,
+                    })
                 })
-                .collect();
-            schema = schema.with_tables(table_items);
-
-            // Load columns for each table
-            for table in &tables {
-                if let Ok(columns) = plugin.list_columns(&**conn, &db, &table.name).await {
-                    let column_items: Vec<(String, String)> = columns.iter()
-                        .map(|c| (c.name.clone(), format!("{} - {}",
-c.data_type,
-                            c.comment.as_ref().unwrap_or(&String::new()))))
-                        .collect();
-                    schema = schema.with_table_columns(&table.name, column_items);
-                }
-            }
+                .boxed()
+            });
+
+            let schema = SqlSchema::default().with_tables(table_items).with_loader(loader);
 
             // Update editor schema
             cx.update(|cx| {
-                editor.update(cx, |e, _cx| {
-                    e.input().update(_cx, |state, _| {
-                        use std::rc::Rc;
-                        use crate::sql_editor::DefaultSqlCompletionProvider;
-                        state.lsp.completion_provider = Some(Rc::new(DefaultSqlCompletionProvider::new(schema)));
-                    });
-                });
+                editor.update(cx, |e, cx| e.set_schema(schema, cx));
             }).ok();
         }).detach();
     }
@@ -252,14 +601,146 @@ c.data_type,
         self.editor.read(cx).get_text_from_app(cx)
     }
 
-    fn handle_run_query(&self, _: &ClickEvent, _window: &mut Window, cx: &mut App) {
+    fn handle_run_query(&self, window: &mut Window, cx: &mut App) {
         let sql = self.get_sql_text(cx);
+        self.run_sql(sql, 0, window, cx);
+    }
+
+    /// 执行光标所在的语句，按原文发送，出错位置才能对应回编辑器
+    fn handle_run_current_statement(&self, window: &mut Window, cx: &mut App) {
+        let Some(statement) = self.editor.read(cx).current_statement(cx) else {
+            self.run_sql(String::new(), 0, window, cx);
+            return;
+        };
+        let sql = self.get_sql_text(cx)[statement.range.clone()].to_string();
+        self.run_sql(sql, statement.range.start, window, cx);
+    }
+
+    /// 只执行选中的文本
+    fn handle_run_selection(&self, window: &mut Window, cx: &mut App) {
+        let editor = self.editor.read(cx);
+        let sql = editor.selected_text(cx);
+        let offset = editor.input().read(cx).selected_range().start;
+        self.run_sql(sql, offset, window, cx);
+    }
+
+    /// 执行 `sql`，`offset` 是它在编辑器中的起始位置
+    fn run_sql(&self, sql: String, offset: usize, window: &mut Window, cx: &mut App) {
+        if self.is_running() {
+            return;
+        }
+        let dialect = self.editor.read(cx).dialect();
+        let parameters = db::script_parameters(&sql, dialect);
+        if parameters.is_empty() {
+            self.run_query(sql, offset, None, cx);
+        } else {
+            self.show_params_dialog(sql, offset, parameters, window, cx);
+        }
+    }
+
+    /// 弹出参数输入框，确定后以绑定参数执行；每个占位符记住上次的值和类型
+    fn show_params_dialog(&self, sql: String, offset: usize, parameters: Vec<Placeholder>, window: &mut Window, cx: &mut App) {
+        let remembered = self.param_values.read().map(|guard| guard.clone()).unwrap_or_default();
+        let type_labels: Vec<String> = ParamType::all().iter().map(|t| t.label().to_string()).collect();
+
+        let fields: Vec<(String, Entity<InputState>, Entity<SelectState<SearchableVec<String>>>)> = parameters
+            .into_iter()
+            .map(|placeholder| {
+                let (param_type, text) = remembered.get(&placeholder.key).cloned().unwrap_or_default();
+                let input = cx.new(|cx| {
+                    let mut state = InputState::new(window, cx);
+                    state.set_value(text, window, cx);
+                    state
+                });
+                let type_index = ParamType::all().iter().position(|t| *t == param_type).unwrap_or(0);
+                let type_select = cx.new(|cx| {
+                    SelectState::new(
+                        SearchableVec::new(type_labels.clone()),
+                        Some(IndexPath::new(type_index)),
+                        window,
+                        cx,
+                    )
+                });
+                (placeholder.key, input, type_select)
+            })
+            .collect();
+        let error = cx.new(|_| String::new());
+        let this = self.clone();
+
+        window.open_dialog(cx, move |dialog, _window, cx| {
+            let fields = fields.clone();
+            let error_msg = error.read(cx).clone();
+            let error = error.clone();
+            let this = this.clone();
+            let sql = sql.clone();
+
+            dialog
+                .title("Query Parameters")
+                .width(px(560.))
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_3()
+                        .p_4()
+                        .children(fields.iter().map(|(key, input, type_select)| {
+                            h_flex()
+                                .gap_2()
+                                .items_center()
+                                .child(div().w(px(120.)).overflow_hidden().text_ellipsis().child(key.clone()))
+                                .child(div().flex_1().child(Input::new(input)))
+                                .child(div().w(px(130.)).child(Select::new(type_select).with_size(Size::Small)))
+                        }))
+                        .when(!error_msg.is_empty(), |el| {
+                            el.child(div().text_sm().text_color(cx.theme().danger).child(error_msg))
+                        }),
+                )
+                .on_ok(move |_, _window, cx| {
+                    let mut values = HashMap::new();
+                    let mut entered = HashMap::new();
+                    for (key, input, type_select) in &fields {
+                        let text = input.read(cx).text().to_string();
+                        let param_type = type_select
+                            .read(cx)
+                            .selected_value()
+                            .and_then(|label| ParamType::all().iter().find(|t| t.label() == label.as_str()))
+                            .copied()
+                            .unwrap_or_default();
+                        match param_type.parse(&text) {
+                            Ok(value) => {
+                                values.insert(key.clone(), value);
+                            }
+                            Err(e) => {
+                                error.update(cx, |msg, cx| {
+                                    *msg = format!("{}: {}", key, e);
+                                    cx.notify();
+                                });
+                                return false;
+                            }
+                        }
+                        entered.insert(key.clone(), (param_type, text));
+                    }
+
+                    if let Ok(mut guard) = this.param_values.write() {
+                        guard.extend(entered);
+                    }
+                    this.run_query(sql.clone(), offset, Some(values), cx);
+                    true
+                })
+        });
+    }
+
+    /// 在标签页的会话上执行脚本，`params` 为占位符绑定的值
+    fn run_query(&self, sql: String, offset: usize, params: Option<HashMap<String, SqlValue>>, cx: &mut App) {
         let status_msg = self.status_msg.clone();
+        let editor = self.editor.clone();
         let global_state = cx.global::<GlobalDbState>().clone();
         let connection_id = self.connection_id.clone();
         let current_database = self.current_database.clone();
         let sql_result_tab_container = self.sql_result_tab_container.clone();
-        
+        let session = self.session.clone();
+        let auto_commit = self.auto_commit.read().map(|guard| *guard).unwrap_or(true);
+        let running_query = self.running_query.clone();
+
         cx.spawn(async move |cx| {
             // Check if SQL is empty
             if sql.trim().is_empty() {
@@ -272,40 +753,62 @@ c.data_type,
                 return;
             }
             
-            // Get connection with current database
-            let config = match global_state.get_config(&connection_id).await {
-                Some(mut cfg) => {
-                    cfg.database = current_database.read().ok().and_then(|guard| guard.clone());
-                    cfg
-                }
-                None => {
+            let database = current_database.read().ok().and_then(|guard| guard.clone());
+            let session = match Self::ensure_session(&global_state, &connection_id, database, &session).await {
+                Ok(s) => s,
+                Err(e) => {
                     cx.update(|cx| {
                         status_msg.update(cx, |msg, cx| {
-                            *msg = "Connection not found".to_string();
+                            *msg = e;
                             cx.notify();
                         });
                     }).ok();
                     return;
                 }
             };
-            
-            let conn_arc = match global_state.connection_pool.get_connection(config, &global_state.db_manager).await {
-                Ok(c) => c,
-                Err(e) => {
+
+            let open_streams = cx
+                .update(|cx| sql_result_tab_container.read(cx).open_streams(cx))
+                .unwrap_or_default();
+            Self::close_streams(&session, open_streams).await;
+
+            // 手动提交模式下，第一条语句执行前开启事务
+            if !auto_commit && !session.transaction().open {
+                if let Err(e) = session.begin().await {
                     cx.update(|cx| {
                         status_msg.update(cx, |msg, cx| {
-                            *msg = format!("Failed to get connection: {}", e);
+                            *msg = format!("Failed to begin transaction: {}", e);
                             cx.notify();
                         });
                     }).ok();
                     return;
                 }
-            };
+            }
 
-            // Execute script directly on connection
+            // 记录执行中的查询，停止按钮据此取消
+            let running = RunningQuery::new(session.connection());
+            if let Ok(mut guard) = running_query.write() {
+                *guard = Some(running.clone());
+            }
+            cx.update(|cx| {
+                status_msg.update(cx, |msg, cx| {
+                    *msg = "Executing...".to_string();
+                    cx.notify();
+                });
+            }).ok();
+
+            // Execute script on the tab's session
             let options = ExecOptions::default();
-            let conn = conn_arc.read().await;
-            let results = match conn.execute(&sql, options).await {
+            let outcome = match &params {
+                Some(values) => session.execute_with_params(&sql, values, options).await,
+                None => session.execute(&sql, options).await,
+            };
+            if let Ok(mut guard) = running_query.write() {
+                *guard = None;
+            }
+            let cancelled = running.is_cancelled();
+
+            let results = match outcome {
                 Ok(r) => r,
                 Err(e) => {
                     cx.update(|cx| {
@@ -322,7 +825,7 @@ c.data_type,
             if results.is_empty() {
                 cx.update(|cx| {
                     status_msg.update(cx, |msg, cx| {
-                        *msg = "No results".to_string();
+                        *msg = if cancelled { "Query cancelled" } else { "No results" }.to_string();
                         cx.notify();
                     });
                 }).ok();
@@ -332,12 +835,34 @@ c.data_type,
             let results_len = results.len();
             let sql_clone = sql.clone();
 
+            let database = session.database().map(str::to_string);
+            let history: Vec<QueryHistory> = results
+                .iter()
+                .map(|result| Self::history_entry(&connection_id, database.clone(), &sql, result))
+                .collect();
+            cx.update(|cx| record_query_history(history, cx)).ok();
+
             // Update result tabs
             let _ = cx.update(|cx| {
+                // 执行期间编辑器内容变了，出错位置就对不上了
+                let text = editor.read(cx).get_text_from_app(cx);
+                let offset = (text.get(offset..offset + sql_clone.len()) == Some(sql_clone.as_str())).then_some(offset);
+                let errors: Vec<_> = results
+                    .iter()
+                    .filter_map(|result| match (result, offset) {
+                        (SqlResult::Error(error), Some(offset)) => {
+                            let range = error.location(&sql_clone)?;
+                            Some((range.start + offset..range.end + offset, error.message.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                editor.update(cx, |editor, cx| editor.set_error_diagnostics(errors, cx));
+
                 if let Some(window_id) = cx.active_window() {
                     let _ = cx.update_window(window_id, |_entity, window, cx| {
                         sql_result_tab_container.update(cx, |state, cx| {
-                            state.set_result(&sql_clone, results, window, cx);
+                            state.set_result(&sql_clone, offset, results, window, cx);
                         });
                     });
                 }
@@ -346,7 +871,11 @@ c.data_type,
             // Update status
             cx.update(|cx| {
                 status_msg.update(cx, |msg, cx| {
-                    *msg = format!("Executed {} statement(s)", results_len);
+                    *msg = if cancelled {
+                        format!("Query cancelled, showing {} result(s) gathered before it stopped", results_len)
+                    } else {
+                        format!("Executed {} statement(s)", results_len)
+                    };
                     cx.notify();
                 });
             }).ok();
@@ -354,23 +883,288 @@ c.data_type,
             .detach();
     }
 
-    fn handle_format_query(&self, _: &ClickEvent, window: &mut Window, cx: &mut App) {
-        let text = self.get_sql_text(cx);
-        let formatted = text
-            .split('\n')
-            .map(|l| l.trim().to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
-        self.editor
-            .update(cx, |s, cx| s.set_value(formatted, window, cx));
+    /// 上一次结果还有未读完的行时，流会一直占用会话连接，先中断并关闭。
+    /// 手动事务未提交时只关闭流：PostgreSQL 的取消会中止整个事务，未提交的修改会丢失
+    async fn close_streams(session: &DbSession, open_streams: Vec<QueryStream>) {
+        if open_streams.is_empty() {
+            return;
+        }
+        if !session.transaction().open {
+            let _ = session.connection().read().await.cancel_query().await;
+        }
+        for stream in open_streams {
+            stream.close().await;
+        }
     }
-}
 
+    /// 查看选中文本或光标所在语句的执行计划；`analyze` 时实际执行查询以得到真实行数和耗时
+    fn handle_explain(&self, analyze: bool, window: &mut Window, cx: &mut App) {
+        if self.is_running() {
+            return;
+        }
+        let editor = self.editor.read(cx);
+        let selected = editor.selected_text(cx);
+        let sql = if selected.trim().is_empty() {
+            match editor.current_statement(cx) {
+                Some(statement) => self.get_sql_text(cx)[statement.range].to_string(),
+                None => String::new(),
+            }
+        } else {
+            selected
+        };
 
+        let status_msg = self.status_msg.clone();
+        let global_state = cx.global::<GlobalDbState>().clone();
+        let connection_id = self.connection_id.clone();
+        let current_database = self.current_database.clone();
+        let sql_result_tab_container = self.sql_result_tab_container.clone();
+        let session = self.session.clone();
+        let running_query = self.running_query.clone();
+        let window_handle = window.window_handle();
+
+        cx.spawn(async move |cx| {
+            let set_status = |message: String, cx: &mut gpui::AsyncApp| {
+                cx.update(|cx| {
+                    status_msg.update(cx, |msg, cx| {
+                        *msg = message;
+                        cx.notify();
+                    });
+                }).ok();
+            };
+
+            let Some(config) = global_state.get_config(&connection_id).await else {
+                return;
+            };
+            let Some(explain_sql) = db::explain_statement(config.database_type, &sql, analyze) else {
+                let message = if sql.trim().is_empty() {
+                    "No SQL statement to explain".to_string()
+                } else {
+                    format!("Query plans are not supported for {}", config.database_type.as_str())
+                };
+                set_status(message, cx);
+                return;
+            };
+
+            let database = current_database.read().ok().and_then(|guard| guard.clone());
+            let session = match Self::ensure_session(&global_state, &connection_id, database, &session).await {
+                Ok(s) => s,
+                Err(e) => {
+                    set_status(e, cx);
+                    return;
+                }
+            };
+            let open_streams = cx
+                .update(|cx| sql_result_tab_container.read(cx).open_streams(cx))
+                .unwrap_or_default();
+            Self::close_streams(&session, open_streams).await;
+
+            let running = RunningQuery::new(session.connection());
+            if let Ok(mut guard) = running_query.write() {
+                *guard = Some(running.clone());
+            }
+            set_status("Explaining...".to_string(), cx);
+
+            // 计划需要完整读出，不分页
+            let options = ExecOptions { max_rows: None, ..ExecOptions::default() };
+            let outcome = session.execute(&explain_sql, options).await;
+            if let Ok(mut guard) = running_query.write() {
+                *guard = None;
+            }
+
+            let results = match outcome {
+                Ok(results) => results,
+                Err(e) => {
+                    set_status(format!("Failed to explain: {}", e), cx);
+                    return;
+                }
+            };
+            let plan = match results.first() {
+                Some(SqlResult::Query(result)) => db::parse_plan(config.database_type, result),
+                Some(SqlResult::Error(error)) => Err(db::DbError::QueryError(error.message.clone())),
+                _ => Err(db::DbError::QueryError("The database returned no plan".to_string())),
+            };
+
+            match plan {
+                Ok(plan) => {
+                    cx.update(|cx| {
+                        sql_result_tab_container.update(cx, |container, cx| container.set_plan(sql.trim(), plan, cx));
+                    }).ok();
+                    set_status("Query plan ready".to_string(), cx);
+                }
+                Err(e) => {
+                    // 语句本身有错时照常显示错误结果
+                    if matches!(results.first(), Some(SqlResult::Error(_))) {
+                        let _ = cx.update_window(window_handle, |_, window, cx| {
+                            sql_result_tab_container.update(cx, |container, cx| {
+                                container.set_result(&explain_sql, None, results, window, cx);
+                            });
+                        });
+                    }
+                    set_status(format!("Failed to explain: {}", e), cx);
+                }
+            }
+        }).detach();
+    }
+
+    /// 一条执行结果对应的历史记录，结果不带语句时记录整段脚本
+    fn history_entry(connection_id: &str, database: Option<String>, script: &str, result: &SqlResult) -> QueryHistory {
+        let sql = if result.sql().trim().is_empty() { script } else { result.sql() };
+        let mut entry = QueryHistory::new(connection_id, database, sql.trim());
+        match result {
+            SqlResult::Query(query) => {
+                entry.elapsed_ms = query.elapsed_ms as i64;
+                entry.rows = query.rows.len() as i64;
+            }
+            SqlResult::Exec(exec) => {
+                entry.elapsed_ms = exec.elapsed_ms as i64;
+                entry.rows = exec.rows_affected as i64;
+            }
+            SqlResult::Error(error) => {
+                entry.success = false;
+                entry.error = Some(error.message.clone());
+            }
+        }
+        entry
+    }
+
+    fn is_running(&self) -> bool {
+        self.running_query.read().map(|guard| guard.is_some()).unwrap_or(false)
+    }
+
+    /// 取消正在执行的查询，已经返回的结果仍会显示
+    fn handle_cancel_query(&self, cx: &mut App) {
+        let Some(running) = self.running_query.read().ok().and_then(|guard| guard.clone()) else {
+            return;
+        };
+        let status_msg = self.status_msg.clone();
+        status_msg.update(cx, |msg, cx| {
+            *msg = "Cancelling...".to_string();
+            cx.notify();
+        });
+
+        cx.spawn(async move |cx| {
+            if let Err(e) = running.cancel().await {
+                cx.update(|cx| {
+                    status_msg.update(cx, |msg, cx| {
+                        *msg = format!("Failed to cancel query: {}", e);
+                        cx.notify();
+                    });
+                }).ok();
+            }
+        }).detach();
+    }
+
+    /// 获取标签页的会话；切换数据库后重新打开，未提交的事务不允许切换
+    async fn ensure_session(
+        global_state: &GlobalDbState,
+        connection_id: &str,
+        database: Option<String>,
+        session: &Arc<RwLock<Option<DbSession>>>,
+    ) -> Result<DbSession, String> {
+        let current = session.read().ok().and_then(|guard| guard.clone());
+        if let Some(current) = current {
+            if !current.is_closed() {
+                if current.database() == database.as_deref() {
+                    return Ok(current);
+                }
+                if current.transaction().open {
+                    return Err("Commit or roll back the open transaction before switching database".to_string());
+                }
+            }
+            global_state.close_session(&current).await;
+        }
+
+        let opened = global_state
+            .open_session(connection_id, database)
+            .await
+            .map_err(|e| format!("Failed to open session: {}", e))?;
+        if let Ok(mut guard) = session.write() {
+            *guard = Some(opened.clone());
+        }
+        Ok(opened)
+    }
+
+    /// 提交或回滚当前会话上的事务
+    fn handle_end_transaction(&self, commit: bool, cx: &mut App) {
+        let Some(session) = self.session.read().ok().and_then(|guard| guard.clone()) else {
+            return;
+        };
+        let status_msg = self.status_msg.clone();
+
+        cx.spawn(async move |cx| {
+            let pending = session.transaction().pending_statements;
+            let result = if commit {
+                session.commit().await
+            } else {
+                session.rollback().await
+            };
+
+            let message = match result {
+                Ok(()) if commit => format!("Committed {} statement(s)", pending),
+                Ok(()) => format!("Rolled back {} statement(s)", pending),
+                Err(e) => format!("Failed to end transaction: {}", e),
+            };
+            cx.update(|cx| {
+                status_msg.update(cx, |msg, cx| {
+                    *msg = message;
+                    cx.notify();
+                });
+            }).ok();
+        }).detach();
+    }
+
+    /// 切换自动提交；存在未提交的事务时不能切回自动提交
+    fn handle_toggle_auto_commit(&self, auto_commit: bool, cx: &mut App) {
+        let transaction_open = self.transaction_state().map(|state| state.open).unwrap_or(false);
+        let message = if auto_commit && transaction_open {
+            "Commit or roll back the open transaction first".to_string()
+        } else {
+            if let Ok(mut guard) = self.auto_commit.write() {
+                *guard = auto_commit;
+            }
+            if auto_commit { "Auto-commit enabled" } else { "Manual commit: statements run inside a transaction" }.to_string()
+        };
+
+        self.status_msg.update(cx, |msg, cx| {
+            *msg = message;
+            cx.notify();
+        });
+    }
+
+    fn transaction_state(&self) -> Option<db::TransactionState> {
+        self.session
+            .read()
+            .ok()
+            .and_then(|guard| guard.as_ref().filter(|s| !s.is_closed()).map(|s| s.transaction()))
+    }
+
+    /// 按设置中的格式化选项格式化整个编辑器
+    fn handle_format_query(&self, window: &mut Window, cx: &mut App) {
+        let options = load_format_options(cx);
+        self.editor.update(cx, |editor, cx| {
+            editor.set_format_options(options);
+            editor.format_all(window, cx);
+        });
+    }
+
+    /// 格式化选中的文本，没有选中时格式化光标所在语句
+    fn handle_format_selection(&self, window: &mut Window, cx: &mut App) {
+        let options = load_format_options(cx);
+        self.editor.update(cx, |editor, cx| {
+            editor.set_format_options(options);
+            editor.format_selection(window, cx);
+        });
+    }
+}
 
 impl TabContent for SqlEditorTabContent {
     fn title(&self) -> SharedString {
-        self.title.clone()
+        self.file
+            .read()
+            .ok()
+            .and_then(|file| file.path.as_ref()?.file_name().map(|name| name.to_string_lossy().to_string()))
+            .map(SharedString::from)
+            .unwrap_or_else(|| self.title.clone())
     }
 
     fn icon(&self) -> Option<IconName> {
@@ -385,14 +1179,63 @@ impl TabContent for SqlEditorTabContent {
         TabContentType::SqlEditor
     }
 
+    fn is_dirty(&self, cx: &App) -> bool {
+        *self.dirty.read(cx)
+    }
+
+    fn save_state(&self, cx: &App) -> Option<serde_json::Value> {
+        serde_json::to_value(self.draft(self.get_sql_text(cx))).ok()
+    }
+
+    fn close_warning(&self, cx: &App) -> Option<SharedString> {
+        let mut warnings = Vec::new();
+        if let Some(state) = self.transaction_state().filter(|state| state.open) {
+            warnings.push(format!(
+                "\"{}\" has an open transaction with {} pending statement(s). Closing the tab rolls it back.",
+                self.title(), state.pending_statements
+            ));
+        }
+        if self.is_dirty(cx) {
+            warnings.push(format!("\"{}\" has unsaved changes that will be lost.", self.title()));
+        }
+        (!warnings.is_empty()).then(|| warnings.join("\n").into())
+    }
+
+    fn on_close(&self, cx: &mut App) {
+        // 关闭即放弃未保存的内容，同时取消还没写出的草稿
+        self.draft_generation.fetch_add(1, Ordering::SeqCst);
+        let draft_id = self.draft_id.clone();
+        cx.background_spawn(async move {
+            if let Err(e) = remove_draft(&draft_id) {
+                tracing::error!("Failed to remove editor draft: {}", e);
+            }
+            release_draft(&draft_id);
+        }).detach();
+
+        let Some(session) = self.session.write().ok().and_then(|mut guard| guard.take()) else {
+            return;
+        };
+        let global_state = cx.global::<GlobalDbState>().clone();
+        cx.spawn(async move |_cx| {
+            global_state.close_session(&session).await;
+        }).detach();
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 
     fn render_content(&self, _window: &mut Window, cx: &mut App) -> AnyElement {
+        if self.metadata_pending.swap(false, Ordering::SeqCst) {
+            self.load_connection_metadata(cx);
+        }
         let status_msg_render = self.status_msg.clone();
         let editor = self.editor.clone();
         let database_select = self.database_select.clone();
+        let auto_commit = self.auto_commit.read().map(|guard| *guard).unwrap_or(true);
+        let transaction = self.transaction_state().filter(|state| state.open);
+        let running = self.is_running();
+        let show_snippets = *self.show_snippets.read(cx);
 
         // Build the main layout with resizable panels
         // Wrap in v_flex().size_full() to ensure proper containment within tab
@@ -424,26 +1267,174 @@ impl TabContent for SqlEditorTabContent {
                                             .placeholder("Select Database")
                                             .w(px(200.))
                                     )
+                                    .child(
+                                        Button::new("open-file")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .icon(IconName::FolderOpen)
+                                            .tooltip_with_action("Open .sql file", &OpenFile, Some(CONTEXT))
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_open_file(w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("save-file")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Save")
+                                            .tooltip_with_action("Save to .sql file", &SaveFile, Some(CONTEXT))
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, _w, cx| this.handle_save_file(cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("save-file-as")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Save As")
+                                            .tooltip_with_action("Save to a new .sql file", &SaveFileAs, Some(CONTEXT))
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, _w, cx| this.handle_save_file_as(cx)
+                                            }),
+                                    )
                                     .child(
                                         Button::new("run-query")
                                             .with_size(Size::Small)
                                             .primary()
                                             .label("Run (⌘+Enter)")
                                             .icon(IconName::ArrowRight)
+                                            .loading(running)
+                                            .tooltip_with_action("Run script", &RunQuery, Some(EDITOR_CONTEXT))
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_run_query(w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("run-current-statement")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Run Current")
+                                            .tooltip_with_action("Run the statement under the cursor", &RunCurrentStatement, Some(EDITOR_CONTEXT))
+                                            .disabled(running)
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_run_current_statement(w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("run-selection")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Run Selection")
+                                            .tooltip_with_action("Run the selected text", &RunSelection, Some(EDITOR_CONTEXT))
+                                            .disabled(running)
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_run_selection(w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("explain-query")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Explain")
+                                            .tooltip_with_action("Show the plan of the selection or the current statement", &Explain, Some(EDITOR_CONTEXT))
+                                            .disabled(running)
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_explain(false, w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("explain-analyze-query")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Explain Analyze")
+                                            .tooltip_with_action("Run the query to show actual rows and timing (PostgreSQL)", &ExplainAnalyze, Some(EDITOR_CONTEXT))
+                                            .disabled(running)
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_explain(true, w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("stop-query")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Stop")
+                                            .icon(IconName::CircleX)
+                                            .disabled(!running)
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, _w, cx| this.handle_cancel_query(cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Switch::new("auto-commit")
+                                            .checked(auto_commit)
+                                            .label("Auto-commit")
+                                            .tooltip("Off: statements stay in a transaction until Commit or Rollback")
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |checked, _w, cx| this.handle_toggle_auto_commit(*checked, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("commit-transaction")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Commit")
+                                            .icon(IconName::Check)
+                                            .disabled(transaction.is_none())
                                             .on_click({
                                                 let this = self.clone();
-                                                move |e, w, cx| this.handle_run_query(e, w, cx)
+                                                move |_e, _w, cx| this.handle_end_transaction(true, cx)
                                             }),
                                     )
+                                    .child(
+                                        Button::new("rollback-transaction")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Rollback")
+                                            .icon(IconName::Close)
+                                            .disabled(transaction.is_none())
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, _w, cx| this.handle_end_transaction(false, cx)
+                                            }),
+                                    )
+                                    .when_some(transaction, |el, state| {
+                                        el.child(
+                                            Tag::warning()
+                                                .small()
+                                                .child(format!("Transaction open, {} pending", state.pending_statements)),
+                                        )
+                                    })
                                     .child(
                                         Button::new("format-query")
                                             .with_size(Size::Small)
                                             .ghost()
                                             .label("Format")
                                             .icon(IconName::Star)
+                                            .tooltip_with_action("Format the whole script", &FormatQuery, Some(EDITOR_CONTEXT))
                                             .on_click({
                                                 let this = self.clone();
-                                                move |e, w, cx| this.handle_format_query(e, w, cx)
+                                                move |_e, w, cx| this.handle_format_query(w, cx)
+                                            }),
+                                    )
+                                    .child(
+                                        Button::new("format-selection")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Format Selection")
+                                            .tooltip_with_action("Format the selection or the current statement", &FormatSelection, Some(EDITOR_CONTEXT))
+                                            .on_click({
+                                                let this = self.clone();
+                                                move |_e, w, cx| this.handle_format_selection(w, cx)
                                             }),
                                     )
                                     .child(
@@ -464,6 +1455,22 @@ impl TabContent for SqlEditorTabContent {
                                                 }
                                             }),
                                     )
+                                    .child(
+                                        Button::new("toggle-snippets")
+                                            .with_size(Size::Small)
+                                            .ghost()
+                                            .label("Snippets")
+                                            .selected(show_snippets)
+                                            .on_click({
+                                                let show_snippets = self.show_snippets.clone();
+                                                move |_e, _w, cx| {
+                                                    show_snippets.update(cx, |show, cx| {
+                                                        *show = !*show;
+                                                        cx.notify();
+                                                    });
+                                                }
+                                            }),
+                                    )
                                     .child(
                                         Button::new("export-query")
                                             .with_size(Size::Small)
@@ -491,7 +1498,63 @@ impl TabContent for SqlEditorTabContent {
                                 // Editor
                                 v_flex()
                                     .flex_1()
-                                    .child(editor)
+                                    .key_context(CONTEXT)
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &RunQuery, w, cx| this.handle_run_query(w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &RunCurrentStatement, w, cx| this.handle_run_current_statement(w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &RunSelection, w, cx| this.handle_run_selection(w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &Explain, w, cx| this.handle_explain(false, w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &ExplainAnalyze, w, cx| this.handle_explain(true, w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &OpenFile, w, cx| this.handle_open_file(w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &SaveFile, _w, cx| this.handle_save_file(cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &SaveFileAs, _w, cx| this.handle_save_file_as(cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &FormatQuery, w, cx| this.handle_format_query(w, cx)
+                                    })
+                                    .on_action({
+                                        let this = self.clone();
+                                        move |_: &FormatSelection, w, cx| this.handle_format_selection(w, cx)
+                                    })
+                                    .child(
+                                        h_flex()
+                                            .flex_1()
+                                            .size_full()
+                                            .child(div().flex_1().h_full().child(editor))
+                                            .when(show_snippets, |el| {
+                                                el.child(
+                                                    div()
+                                                        .w(px(280.))
+                                                        .h_full()
+                                                        .border_l_1()
+                                                        .border_color(cx.theme().border)
+                                                        .child(self.snippet_panel.clone()),
+                                                )
+                                            }),
+                                    )
                             )
                     )
             )
@@ -517,6 +1580,17 @@ impl Clone for SqlEditorTabContent {
             status_msg: self.status_msg.clone(),
             current_database: self.current_database.clone(),
             database_select: self.database_select.clone(),
+            session: self.session.clone(),
+            auto_commit: self.auto_commit.clone(),
+            running_query: self.running_query.clone(),
+            param_values: self.param_values.clone(),
+            snippet_panel: self.snippet_panel.clone(),
+            show_snippets: self.show_snippets.clone(),
+            file: self.file.clone(),
+            dirty: self.dirty.clone(),
+            draft_id: self.draft_id.clone(),
+            draft_generation: self.draft_generation.clone(),
+            metadata_pending: self.metadata_pending.clone(),
             focus_handle: self.focus_handle.clone(),
         }
     }
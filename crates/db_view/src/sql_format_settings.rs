@@ -0,0 +1,152 @@
+use gpui::{div, px, App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled, Window};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::{h_flex, v_flex, ActiveTheme, Selectable, Sizable, Size};
+use one_core::gpui_tokio::Tokio;
+use one_core::storage::{CommaStyle, GlobalStorageState, KeyValueRepository, KeywordCase, SqlFormatOptions};
+
+/// 读取保存的格式化选项，没有保存过或读取失败时用默认值
+pub fn load_format_options(cx: &App) -> SqlFormatOptions {
+    let storage = cx.global::<GlobalStorageState>().storage.clone();
+    Tokio::block_on(cx, async move {
+        let settings = storage.get::<KeyValueRepository>().await?;
+        let pool = storage.get_pool().await.ok()?;
+        settings.sql_format_options(&pool).await.ok()
+    })
+    .unwrap_or_default()
+}
+
+/// 设置页中的 SQL 格式化选项
+pub struct SqlFormatSettingsPanel {
+    options: SqlFormatOptions,
+    indent_input: Entity<InputState>,
+    width_input: Entity<InputState>,
+    status: String,
+}
+
+impl SqlFormatSettingsPanel {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let options = load_format_options(cx);
+        let indent_input = cx.new(|cx| InputState::new(window, cx).default_value(options.indent_width.to_string()));
+        let width_input = cx.new(|cx| InputState::new(window, cx).default_value(options.max_line_width.to_string()));
+
+        Self {
+            options,
+            indent_input,
+            width_input,
+            status: String::new(),
+        }
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let parse = |input: &Entity<InputState>, cx: &App| input.read(cx).text().to_string().trim().parse::<usize>().ok();
+        let indent_width = parse(&self.indent_input, cx).filter(|width| (1..=8).contains(width));
+        let max_line_width = parse(&self.width_input, cx).filter(|width| *width >= 20);
+        let (Some(indent_width), Some(max_line_width)) = (indent_width, max_line_width) else {
+            self.status = "缩进为 1 到 8 个空格，最大行宽不小于 20".to_string();
+            cx.notify();
+            return;
+        };
+        self.options.indent_width = indent_width;
+        self.options.max_line_width = max_line_width;
+
+        let options = self.options.clone();
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+        let task = Tokio::spawn(cx, async move {
+            let settings = storage.get::<KeyValueRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("KeyValueRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            settings.set_sql_format_options(&pool, &options).await
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = match task.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!(e)),
+            };
+            _ = this.update(cx, |this, cx| {
+                this.status = match result {
+                    Ok(()) => "格式化选项已保存".to_string(),
+                    Err(e) => format!("保存失败: {}", e),
+                };
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn option_button(
+        id: &'static str,
+        label: &'static str,
+        selected: bool,
+        cx: &mut Context<Self>,
+        on_click: impl Fn(&mut SqlFormatOptions) + 'static,
+    ) -> Button {
+        Button::new(id)
+            .with_size(Size::Small)
+            .ghost()
+            .label(label)
+            .selected(selected)
+            .on_click(cx.listener(move |this, _, _, cx| {
+                on_click(&mut this.options);
+                cx.notify();
+            }))
+    }
+
+    fn row(label: &'static str, cx: &App) -> gpui::Div {
+        h_flex()
+            .gap_2()
+            .items_center()
+            .child(div().w(px(120.)).text_sm().text_color(cx.theme().muted_foreground).child(label))
+    }
+}
+
+impl Render for SqlFormatSettingsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let keyword_case = self.options.keyword_case;
+        let comma_style = self.options.comma_style;
+
+        v_flex()
+            .gap_3()
+            .child(div().text_lg().child("SQL 格式化"))
+            .child(
+                Self::row("关键字大小写", cx)
+                    .child(Self::option_button("keyword-upper", "大写", keyword_case == KeywordCase::Upper, cx, |o| {
+                        o.keyword_case = KeywordCase::Upper
+                    }))
+                    .child(Self::option_button("keyword-lower", "小写", keyword_case == KeywordCase::Lower, cx, |o| {
+                        o.keyword_case = KeywordCase::Lower
+                    }))
+                    .child(Self::option_button("keyword-preserve", "保持原样", keyword_case == KeywordCase::Preserve, cx, |o| {
+                        o.keyword_case = KeywordCase::Preserve
+                    })),
+            )
+            .child(
+                Self::row("逗号位置", cx)
+                    .child(Self::option_button("comma-trailing", "行尾", comma_style == CommaStyle::Trailing, cx, |o| {
+                        o.comma_style = CommaStyle::Trailing
+                    }))
+                    .child(Self::option_button("comma-leading", "行首", comma_style == CommaStyle::Leading, cx, |o| {
+                        o.comma_style = CommaStyle::Leading
+                    })),
+            )
+            .child(Self::row("缩进空格数", cx).child(div().w(px(60.)).child(Input::new(&self.indent_input).with_size(Size::Small))))
+            .child(Self::row("最大行宽", cx).child(div().w(px(60.)).child(Input::new(&self.width_input).with_size(Size::Small))))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        Button::new("save-format-options")
+                            .with_size(Size::Small)
+                            .label("保存")
+                            .on_click(cx.listener(|this, _, _, cx| this.save(cx))),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(SharedString::from(self.status.clone())),
+                    ),
+            )
+    }
+}
@@ -1,10 +1,12 @@
+use std::ops::Range;
 use std::sync::{Arc, RwLock};
-use gpui::{div, px, AnyElement, App, AppContext, Context, Entity, IntoElement, ParentElement, Render, Styled, Window};
-use db::SqlResult;
+use gpui::{div, px, AnyElement, App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled, Window};
+use db::{QueryPlan, QueryStream, SqlResult};
 use gpui_component::table::{Column, Table, TableState};
 use gpui_component::{h_flex, v_flex, ActiveTheme, IconName, Sizable, Size, StyledExt};
 use gpui_component::list::ListItem;
 use gpui_component::tab::{Tab, TabBar};
+use crate::explain_plan_view::ExplainPlanView;
 use crate::results_delegate::ResultsDelegate;
 
 // Structure to hold a single SQL result with its metadata
@@ -15,6 +17,13 @@ pub struct SqlResultTab {
     pub execution_time: String,
     pub rows_count: String,
     pub table: Entity<TableState<ResultsDelegate>>,
+    /// Where a failed statement's error sits in the editor, as a byte range
+    pub location: Option<Range<usize>>,
+}
+
+pub enum SqlResultTabEvent {
+    /// An error result was selected, show its statement in the editor
+    JumpToError { range: Range<usize> },
 }
 
 
@@ -22,6 +31,8 @@ pub struct SqlResultTab {
 pub struct SqlResultTabContainer {
     pub result_tabs: Arc<RwLock<Vec<SqlResultTab>>>,
     pub active_result_tab: Arc<RwLock<usize>>,
+    // 执行计划，显示时代替结果标签页
+    pub plan: Option<Entity<ExplainPlanView>>,
 }
 
 impl SqlResultTabContainer {
@@ -29,54 +40,45 @@ impl SqlResultTabContainer {
         SqlResultTabContainer {
             result_tabs,
             active_result_tab,
+            plan: None,
         }
     }
 }
 
 impl SqlResultTabContainer {
 
-    pub fn set_result(&mut self, sql: &str, results: Vec<SqlResult>, window: &mut Window, cx: &mut Context<Self>) {
-        // Split SQL into individual statements for labeling
-        let sql_statements: Vec<String> = sql
-            .split(';')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-
+    /// Show the results of running `sql`, which starts at byte `offset` of the editor.
+    /// Without an offset, e.g. when the editor changed meanwhile, errors are not located.
+    pub fn set_result(&mut self, sql: &str, offset: Option<usize>, results: Vec<SqlResult>, window: &mut Window, cx: &mut Context<Self>) {
         // Create tabs for each result
         let mut new_tabs = Vec::new();
 
         for (idx, result) in results.iter().enumerate() {
-            let sql_text = sql_statements.get(idx)
-                .map(|s| {
-                    if s.len() > 50 {
-                        format!("{}...", &s[..50])
-                    } else {
-                        s.clone()
-                    }
-                })
-                .unwrap_or_else(|| format!("Statement {}", idx + 1));
+            // Label with the statement the result belongs to
+            let statement = result.sql();
+            let sql_text = if statement.is_empty() {
+                format!("Statement {}", idx + 1)
+            } else if statement.chars().count() > 50 {
+                format!("{}...", statement.chars().take(50).collect::<String>())
+            } else {
+                statement.to_string()
+            };
 
             match result {
                 SqlResult::Query(query_result) => {
-                    let columns = query_result.columns.iter()
-                        .map(|h| Column::new(h.clone(), h.clone()))
-                        .collect();
-                    let rows = query_result.rows.iter()
-                        .map(|row| {
-                            row.iter()
-                                .map(|cell| cell.clone().unwrap_or_else(|| "NULL".to_string()))
-                                .collect()
-                        })
-                        .collect();
-                    let delegate = ResultsDelegate::new(columns, rows);
+                    let delegate = ResultsDelegate::from_query_result(query_result);
                     let table = cx.new(|cx| TableState::new(delegate, window, cx));
                     new_tabs.push(SqlResultTab {
                         sql: sql_text,
                         result: result.clone(),
                         execution_time: format!("{}ms", query_result.elapsed_ms),
-                        rows_count: format!("{} rows", query_result.rows.len()),
+                        rows_count: if query_result.has_more {
+                            format!("{}+ rows", query_result.rows.len())
+                        } else {
+                            format!("{} rows", query_result.rows.len())
+                        },
                         table,
+                        location: None,
                     });
                 }
                 SqlResult::Exec(exec_result) => {
@@ -96,6 +98,7 @@ impl SqlResultTabContainer {
                         execution_time: format!("{}ms", exec_result.elapsed_ms),
                         rows_count: format!("{} rows affected", exec_result.rows_affected),
                         table,
+                        location: None,
                     });
                 }
                 SqlResult::Error(error) => {
@@ -109,11 +112,17 @@ impl SqlResultTabContainer {
                         execution_time: "Error".to_string(),
                         rows_count: "Error".to_string(),
                         table,
+                        location: offset.and_then(|offset| {
+                            let range = error.location(sql)?;
+                            Some(range.start + offset..range.end + offset)
+                        }),
                     });
                 }
             }
         }
 
+        self.plan = None;
+
         // Update result tabs
         if let Ok(mut tabs) = self.result_tabs.write() {
             *tabs = new_tabs;
@@ -126,14 +135,43 @@ impl SqlResultTabContainer {
         cx.notify();
     }
 
+    /// 显示 `sql` 的执行计划
+    pub fn set_plan(&mut self, sql: &str, plan: QueryPlan, cx: &mut Context<Self>) {
+        let sql = sql.to_string();
+        self.plan = Some(cx.new(|cx| ExplainPlanView::new(sql, plan, cx)));
+        cx.notify();
+    }
+
+    /// 结果中仍未读取完的流，它们占用着会话连接
+    pub fn open_streams(&self, cx: &App) -> Vec<QueryStream> {
+        self.result_tabs
+            .read()
+            .map(|tabs| {
+                tabs.iter()
+                    .filter_map(|tab| tab.table.read(cx).delegate().open_stream())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
+impl EventEmitter<SqlResultTabEvent> for SqlResultTabContainer {}
+
 impl Render for SqlResultTabContainer {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let tabs = self.result_tabs.read().unwrap();
         let active_idx = *self.active_result_tab.read().unwrap();
 
-        if tabs.is_empty() {
+        if let Some(plan) = self.plan.clone() {
+            v_flex()
+                .size_full()
+                .bg(cx.theme().background)
+                .border_1()
+                .border_color(cx.theme().border)
+                .rounded_md()
+                .overflow_hidden()
+                .child(plan)
+        } else if tabs.is_empty() {
             // Show empty state
             v_flex()
                 .size_full()
@@ -160,12 +198,16 @@ impl Render for SqlResultTabContainer {
                         .pill()
                         .with_size(Size::Small)
                         .selected_index(active_idx)
-                        .on_click({
-                            let active_tab = self.active_result_tab.clone();
-                            move |_ix: &usize, _w, _cx| {
-                                *active_tab.write().unwrap() = *_ix;
+                        .on_click(cx.listener(|this, ix: &usize, _w, cx| {
+                            *this.active_result_tab.write().unwrap() = *ix;
+                            // 选中出错的结果时，在编辑器中定位出错的语句
+                            let location = ix
+                                .checked_sub(1)
+                                .and_then(|idx| this.result_tabs.read().ok()?.get(idx)?.location.clone());
+                            if let Some(range) = location {
+                                cx.emit(SqlResultTabEvent::JumpToError { range });
                             }
-                        })
+                        }))
                         .child(
                             // Summary tab
                             Tab::new().label("摘要")
@@ -10,23 +10,30 @@ use gpui_component::{
     h_flex,
     resizable::{resizable_panel, v_resizable},
     table::{Column, Table, TableState},
-    v_flex, ActiveTheme as _, IconName, Sizable as _, Size,
+    v_flex, ActiveTheme as _, Disableable as _, IconName, Sizable as _, Size,
 };
 
 use crate::filter_editor::{ColumnSchema, TableFilterEditor, TableSchema};
 use crate::multi_text_editor::{create_multi_text_editor_with_content, MultiTextEditor};
+use crate::query_history_panel::record_query_history;
 use crate::results_delegate::{EditorTableDelegate};
-use db::{GlobalDbState, TableDataRequest};
+use db::{DatabasePlugin, GlobalDbState, RunningQuery, SessionPurpose, TableDataRequest};
 use gpui_component::table::TableEvent;
+use one_core::storage::QueryHistory;
 use one_core::tab_container::{TabContent, TabContentType};
+use serde::{Deserialize, Serialize};
 // ============================================================================
 // Table Data Tab Content - Display table rows
 // ============================================================================
 
 pub struct TableDataTabContent {
     database_name: String,
+    /// 表所在的 schema（仅支持 schema 的数据库）
+    schema_name: Option<String>,
     table_name: String,
     connection_id: String,
+    /// 标签页自己的池连接，加载数据时不会被其他标签页的查询阻塞
+    session_purpose: SessionPurpose,
     table: Entity<TableState<EditorTableDelegate>>,
     status_msg: Entity<String>,
     focus_handle: FocusHandle,
@@ -45,15 +52,59 @@ pub struct TableDataTabContent {
     filter_editor: Entity<TableFilterEditor>,
     /// Editor visibility state
     editor_visible: Entity<bool>,
+    /// Query currently loading the page, cancelled by the Stop button
+    running_query: Entity<Option<RunningQuery>>,
     /// Subscription to table events (stored but not used directly)
     _table_subscription: Option<Subscription>,
+    // 从布局恢复的标签页首次显示时才加载这一页
+    pending_page: Entity<Option<usize>>,
     /// Marker to make the struct Send + Sync
     _phantom: PhantomData<*const ()>,
 }
 
+/// 退出时保存的表数据标签页状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDataState {
+    pub connection_id: String,
+    pub database: String,
+    pub schema: Option<String>,
+    pub table: String,
+    pub where_clause: String,
+    pub order_by_clause: String,
+    pub page: usize,
+}
+
 impl TableDataTabContent {
     pub fn new(
         database_name: impl Into<String>,
+        schema_name: Option<String>,
+        table_name: impl Into<String>,
+        connection_id: impl Into<String>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let result = Self::create(database_name, schema_name, table_name, connection_id, window, cx);
+
+        // Load data initially
+        result.load_data_with_clauses(1, cx);
+
+        result
+    }
+
+    /// 按保存的过滤条件和页码恢复，数据在标签页显示时才加载
+    pub fn restore(state: TableDataState, window: &mut Window, cx: &mut App) -> Self {
+        let result = Self::create(state.database, state.schema, state.table, state.connection_id, window, cx);
+        result.filter_editor.update(cx, |editor, cx| {
+            editor.set_where_clause(state.where_clause, window, cx);
+            editor.set_order_by_clause(state.order_by_clause, window, cx);
+        });
+        result.pending_page.update(cx, |page, _| *page = Some(state.page.max(1)));
+        result
+    }
+
+    fn create(
+        database_name: impl Into<String>,
+        schema_name: Option<String>,
         table_name: impl Into<String>,
         connection_id: impl Into<String>,
         window: &mut Window,
@@ -76,6 +127,7 @@ impl TableDataTabContent {
 
         // Editor visibility state (default hidden)
         let editor_visible = cx.new(|_| false);
+        let running_query = cx.new(|_| None);
 
         // Create multi text editor for cell editing
         let text_editor = create_multi_text_editor_with_content(None, window, cx);
@@ -107,8 +159,10 @@ impl TableDataTabContent {
 
         let result = Self {
             database_name: database_name.clone(),
+            schema_name,
             table_name: table_name.clone(),
             connection_id,
+            session_purpose: SessionPurpose::Editor(uuid::Uuid::new_v4().to_string()),
             table: table.clone(),
             status_msg: status_msg.clone(),
             focus_handle,
@@ -120,16 +174,23 @@ impl TableDataTabContent {
             total_count,
             filter_editor,
             editor_visible,
+            running_query,
             _table_subscription: Some(table_subscription),
+            pending_page: cx.new(|_| None),
             _phantom: PhantomData,
         };
 
-        // Load data initially
-        result.load_data_with_clauses(1, cx);
-
         result
     }
 
+    /// 显示用的完整表名：database[.schema].table
+    fn display_name(&self) -> String {
+        match &self.schema_name {
+            Some(schema) => format!("{}.{}.{}", self.database_name, schema, self.table_name),
+            None => format!("{}.{}", self.database_name, self.table_name),
+        }
+    }
+
     fn update_status(status_msg: &Entity<String>, message: String, cx: &mut App) {
         status_msg.update(cx, |s, cx| {
             *s = message;
@@ -140,8 +201,10 @@ impl TableDataTabContent {
     fn load_data_with_clauses(&self, page: usize, cx: &mut App) {
         let global_state = cx.global::<GlobalDbState>().clone();
         let connection_id = self.connection_id.clone();
+        let session_purpose = self.session_purpose.clone();
         let table_name = self.table_name.clone();
         let database_name = self.database_name.clone();
+        let schema_name = self.schema_name.clone();
         let status_msg = self.status_msg.clone();
         let table_state = self.table.clone();
         let current_page = self.current_page.clone();
@@ -150,9 +213,10 @@ impl TableDataTabContent {
         let where_clause = self.filter_editor.read(cx).get_where_clause(cx);
         let order_by_clause = self.filter_editor.read(cx).get_order_by_clause(cx);
         let filter_editor = self.filter_editor.clone();
+        let running_query = self.running_query.clone();
 
         cx.spawn(async move |cx| {
-            let (plugin, conn_arc) = match global_state.get_plugin_and_connection(&connection_id).await {
+            let (plugin, conn_arc) = match global_state.get_plugin_and_database_connection(&connection_id, Some(&database_name), session_purpose).await {
                 Ok(result) => result,
                 Err(e) => {
                     cx.update(|cx| {
@@ -162,15 +226,39 @@ impl TableDataTabContent {
                 }
             };
 
+            let running = RunningQuery::new(conn_arc.clone());
+            cx.update(|cx| {
+                running_query.update(cx, |query, cx| {
+                    *query = Some(running.clone());
+                    cx.notify();
+                });
+                Self::update_status(&status_msg, "Loading...".to_string(), cx);
+            }).ok();
+
             let conn = conn_arc.read().await;
 
             // Build request with raw where/order by clauses
             let request = TableDataRequest::new(&database_name, &table_name)
+                .with_schema(schema_name)
                 .with_page(page, page_size)
                 .with_where_clause(where_clause)
                 .with_order_by_clause(order_by_clause);
 
-            match plugin.query_table_data(&**conn, &request).await {
+            let outcome = plugin.query_table_data(&**conn, &request).await;
+            cx.update(|cx| {
+                running_query.update(cx, |query, cx| {
+                    *query = None;
+                    cx.notify();
+                });
+            }).ok();
+
+            match outcome {
+                _ if running.is_cancelled() => {
+                    // 取消后保留表格中已有的数据
+                    cx.update(|cx| {
+                        Self::update_status(&status_msg, "Query cancelled, showing the previous rows".to_string(), cx);
+                    }).ok();
+                }
                 Ok(response) => {
                     let columns: Vec<Column> = response
                         .columns
@@ -183,7 +271,7 @@ impl TableDataTabContent {
                         .iter()
                         .map(|row| {
                             row.iter()
-                                .map(|cell| cell.as_ref().map(|s| s.to_string()).unwrap_or_else(|| "NULL".to_string()))
+                                .map(|cell| cell.to_string())
                                 .collect()
                         })
                         .collect();
@@ -203,6 +291,7 @@ impl TableDataTabContent {
                             is_nullable: col.nullable,
                         })
                         .collect();
+                    let column_meta = response.columns;
 
                     cx.update(|cx| {
                         // Update filter editor schema
@@ -216,6 +305,7 @@ impl TableDataTabContent {
                         table_state.update(cx, |state, cx| {
                             state.delegate_mut().update_data(columns, rows);
                             state.delegate_mut().set_primary_keys(pk_columns);
+                            state.delegate_mut().set_column_meta(column_meta);
                             state.refresh(cx);
                         });
 
@@ -247,6 +337,23 @@ impl TableDataTabContent {
         .detach();
     }
 
+    /// 取消正在加载的查询
+    fn handle_cancel_query(&self, cx: &mut App) {
+        let Some(running) = self.running_query.read(cx).clone() else {
+            return;
+        };
+        let status_msg = self.status_msg.clone();
+        Self::update_status(&status_msg, "Cancelling...".to_string(), cx);
+
+        cx.spawn(async move |cx| {
+            if let Err(e) = running.cancel().await {
+                cx.update(|cx| {
+                    Self::update_status(&status_msg, format!("Failed to cancel query: {}", e), cx);
+                }).ok();
+            }
+        }).detach();
+    }
+
     fn handle_refresh(&self, _: &ClickEvent, _: &mut Window, cx: &mut App) {
         let page = *self.current_page.read(cx);
         self.load_data_with_clauses(page, cx);
@@ -296,13 +403,15 @@ impl TableDataTabContent {
 
         let global_state = cx.global::<GlobalDbState>().clone();
         let connection_id = self.connection_id.clone();
+        let session_purpose = self.session_purpose.clone();
         let table_name = self.table_name.clone();
         let database_name = self.database_name.clone();
+        let scope = self.schema_name.clone().unwrap_or_else(|| self.database_name.clone());
         let status_msg = self.status_msg.clone();
         let table_state = self.table.clone();
 
         cx.spawn(async move |cx| {
-            let (plugin, conn_arc) = match global_state.get_plugin_and_connection(&connection_id).await {
+            let (plugin, conn_arc) = match global_state.get_plugin_and_database_connection(&connection_id, Some(&database_name), session_purpose).await {
                 Ok(result) => result,
                 Err(e) => {
                     cx.update(|cx| {
@@ -315,29 +424,39 @@ impl TableDataTabContent {
             let conn = conn_arc.read().await;
             let mut success_count = 0;
             let mut error_messages = Vec::new();
+            let mut history = Vec::new();
+            let table_ref = plugin.qualified_table_name(&scope, &table_name);
 
             for change in changes {
-                let sql = Self::generate_sql(&change, &database_name, &table_name, &column_names, &pk_columns);
+                let sql = Self::generate_sql(&*plugin, &change, &table_ref, &column_names, &pk_columns);
                 if sql.is_empty() {
                     continue;
                 }
 
+                let mut entry = QueryHistory::new(connection_id.clone(), Some(database_name.clone()), sql.clone());
                 match plugin.execute_query(&**conn, &database_name, &sql, None).await {
                     Ok(db::SqlResult::Exec(result)) => {
                         success_count += 1;
-                        let _ = result.rows_affected;
+                        entry.elapsed_ms = result.elapsed_ms as i64;
+                        entry.rows = result.rows_affected as i64;
                     }
                     Ok(db::SqlResult::Error(err)) => {
+                        entry.success = false;
+                        entry.error = Some(err.message.clone());
                         error_messages.push(err.message);
                     }
                     Err(e) => {
+                        entry.success = false;
+                        entry.error = Some(e.to_string());
                         error_messages.push(e.to_string());
                     }
                     _ => {}
                 }
+                history.push(entry);
             }
 
             cx.update(|cx| {
+                record_query_history(history, cx);
                 if error_messages.is_empty() {
                     table_state.update(cx, |state, cx| {
                         state.delegate_mut().clear_changes();
@@ -366,9 +485,9 @@ impl TableDataTabContent {
     }
 
     fn generate_sql(
+        plugin: &dyn DatabasePlugin,
         change: &crate::results_delegate::RowChange,
-        database_name: &str,
-        table_name: &str,
+        table_ref: &str,
         column_names: &[String],
         pk_columns: &[usize],
     ) -> String {
@@ -376,7 +495,10 @@ impl TableDataTabContent {
 
         match change {
             RowChange::Added { data } => {
-                let columns = column_names.join("`, `");
+                let columns: Vec<String> = column_names
+                    .iter()
+                    .map(|c| plugin.quote_identifier(c))
+                    .collect();
                 let values: Vec<String> = data
                     .iter()
                     .map(|v| {
@@ -388,10 +510,9 @@ impl TableDataTabContent {
                     })
                     .collect();
                 format!(
-                    "INSERT INTO `{}`.`{}` (`{}`) VALUES ({})",
-                    database_name,
-                    table_name,
-                    columns,
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table_ref,
+                    columns.join(", "),
                     values.join(", ")
                 )
             }
@@ -408,31 +529,32 @@ impl TableDataTabContent {
                         } else {
                             format!("'{}'", c.new_value.replace('\'', "''"))
                         };
-                        format!("`{}` = {}", c.col_name, value)
+                        format!("{} = {}", plugin.quote_identifier(&c.col_name), value)
                     })
                     .collect();
 
-                let where_clause = Self::build_where_clause(original_data, column_names, pk_columns);
+                let where_clause = Self::build_where_clause(plugin, original_data, column_names, pk_columns);
 
                 format!(
-                    "UPDATE `{}`.`{}` SET {} WHERE {}",
-                    database_name,
-                    table_name,
+                    "UPDATE {} SET {} WHERE {}",
+                    table_ref,
                     set_clause.join(", "),
                     where_clause
                 )
             }
             RowChange::Deleted { original_data } => {
-                let where_clause = Self::build_where_clause(original_data, column_names, pk_columns);
-                format!(
-                    "DELETE FROM `{}`.`{}` WHERE {}",
-                    database_name, table_name, where_clause
-                )
+                let where_clause = Self::build_where_clause(plugin, original_data, column_names, pk_columns);
+                format!("DELETE FROM {} WHERE {}", table_ref, where_clause)
             }
         }
     }
 
-    fn build_where_clause(original_data: &[String], column_names: &[String], pk_columns: &[usize]) -> String {
+    fn build_where_clause(
+        plugin: &dyn DatabasePlugin,
+        original_data: &[String],
+        column_names: &[String],
+        pk_columns: &[usize],
+    ) -> String {
         // If we have primary keys, only use those columns
         let indices: Vec<usize> = if pk_columns.is_empty() {
             (0..column_names.len()).collect()
@@ -448,10 +570,11 @@ impl TableDataTabContent {
                 Some((col_name, value))
             })
             .map(|(col_name, value)| {
+                let col = plugin.quote_identifier(col_name);
                 if value == "NULL" {
-                    format!("`{}` IS NULL", col_name)
+                    format!("{} IS NULL", col)
                 } else {
-                    format!("`{}` = '{}'", col_name, value.replace('\'', "''"))
+                    format!("{} = '{}'", col, value.replace('\'', "''"))
                 }
             })
             .collect::<Vec<_>>()
@@ -527,7 +650,7 @@ impl TableDataTabContent {
 
 impl TabContent for TableDataTabContent {
     fn title(&self) -> SharedString {
-        format!("{}.{} - Data", self.database_name, self.table_name).into()
+        format!("{} - Data", self.display_name()).into()
     }
 
     fn icon(&self) -> Option<IconName> {
@@ -539,7 +662,11 @@ impl TabContent for TableDataTabContent {
     }
 
     fn render_content(&self, _window: &mut Window, cx: &mut App) -> AnyElement {
+        if let Some(page) = self.pending_page.update(cx, |page, _| page.take()) {
+            self.load_data_with_clauses(page, cx);
+        }
         let status_msg_render = self.status_msg.clone();
+        let running = self.running_query.read(cx).is_some();
 
         v_flex()
             .size_full()
@@ -670,10 +797,22 @@ impl TabContent for TableDataTabContent {
                             .primary()
                             .label("Apply")
                             .icon(IconName::Check)
+                            .loading(running)
                             .on_click({
                                 let this = self.clone();
                                 move |_, _, cx| this.handle_apply_query(cx)
                             }),
+                    )
+                    .child(
+                        Button::new("stop-query")
+                            .with_size(Size::Small)
+                            .label("Stop")
+                            .icon(IconName::CircleX)
+                            .disabled(!running)
+                            .on_click({
+                                let this = self.clone();
+                                move |_, _, cx| this.handle_cancel_query(cx)
+                            }),
                     ),
             )
             .child({
@@ -742,7 +881,31 @@ impl TabContent for TableDataTabContent {
     }
 
     fn content_type(&self) -> TabContentType {
-        TabContentType::TableData(format!("{}.{}", self.database_name, self.table_name))
+        TabContentType::TableData(self.display_name())
+    }
+
+    fn save_state(&self, cx: &App) -> Option<serde_json::Value> {
+        let filter_editor = self.filter_editor.read(cx);
+        let page = self.pending_page.read(cx).unwrap_or(*self.current_page.read(cx));
+        let state = TableDataState {
+            connection_id: self.connection_id.clone(),
+            database: self.database_name.clone(),
+            schema: self.schema_name.clone(),
+            table: self.table_name.clone(),
+            where_clause: filter_editor.get_where_clause(cx),
+            order_by_clause: filter_editor.get_order_by_clause(cx),
+            page,
+        };
+        serde_json::to_value(state).ok()
+    }
+
+    fn on_close(&self, cx: &mut App) {
+        let global_state = cx.global::<GlobalDbState>().clone();
+        let connection_id = self.connection_id.clone();
+        let session_purpose = self.session_purpose.clone();
+        cx.spawn(async move |_cx| {
+            global_state.release_connections(&connection_id, &session_purpose).await;
+        }).detach();
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -756,8 +919,10 @@ impl Clone for TableDataTabContent {
     fn clone(&self) -> Self {
         Self {
             database_name: self.database_name.clone(),
+            schema_name: self.schema_name.clone(),
             table_name: self.table_name.clone(),
             connection_id: self.connection_id.clone(),
+            session_purpose: self.session_purpose.clone(),
             table: self.table.clone(),
             status_msg: self.status_msg.clone(),
             focus_handle: self.focus_handle.clone(),
@@ -769,7 +934,9 @@ impl Clone for TableDataTabContent {
             total_count: self.total_count.clone(),
             filter_editor: self.filter_editor.clone(),
             editor_visible: self.editor_visible.clone(),
+            running_query: self.running_query.clone(),
             _table_subscription: None,
+            pending_page: self.pending_page.clone(),
             _phantom: PhantomData,
         }
     }
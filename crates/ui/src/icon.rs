@@ -109,6 +109,7 @@ pub enum IconName {
     PostgreSQLColor,
     MSSQLColor,
     OracleColor,
+    SQLiteColor,
     Workspace,
     
 }
@@ -208,9 +209,10 @@ impl IconName {
             Self::MongoDB => "icons/mongodb.svg",
             Self::MySQLColor => "icons/mysql_color.svg",
             Self::PostgreSQLColor => "icons/postgresql_color.svg",
-            Self::MSSQLColor => "icons/mssql_color.svg",
+            Self::MSSQLColor => "icons/sqlserver_color.svg",
             Self::MySQLLineColor => "icons/mysql_line_color.svg",
             Self::OracleColor => "icons/oracle_color.svg",
+            Self::SQLiteColor => "icons/sqlite_color.svg",
             Self::Workspace => "icons/workspace.svg",
         }
         .into()
@@ -422,6 +422,23 @@ impl TextElement {
         Self::layout_match_range(symbol_range, last_layout, bounds)
     }
 
+    fn layout_highlighted_range(
+        &self,
+        last_layout: &LastLayout,
+        bounds: &Bounds<Pixels>,
+        cx: &mut App,
+    ) -> Option<Path<Pixels>> {
+        let range = self.state.read(cx).highlighted_range.clone()?;
+        // Only the visible part is painted, so the range may span beyond the viewport.
+        let visible = &last_layout.visible_range_offset;
+        let start = range.start.max(visible.start);
+        let end = range.end.min(visible.end);
+        if start >= end {
+            return None;
+        }
+        Self::layout_match_range(start..end, last_layout, bounds)
+    }
+
     fn layout_document_colors(
         &self,
         document_colors: &[(Range<usize>, Hsla)],
@@ -720,6 +737,7 @@ pub(super) struct PrepaintState {
     current_row: Option<usize>,
     selection_path: Option<Path<Pixels>>,
     hover_highlight_path: Option<Path<Pixels>>,
+    highlighted_range_path: Option<Path<Pixels>>,
     search_match_paths: Vec<(Path<Pixels>, bool)>,
     document_color_paths: Vec<(Path<Pixels>, Hsla)>,
     hover_definition_hitbox: Option<Hitbox>,
@@ -1054,6 +1072,7 @@ impl Element for TextElement {
         let search_match_paths = self.layout_search_matches(&last_layout, &mut bounds, cx);
         let selection_path = self.layout_selections(&last_layout, &mut bounds, cx);
         let hover_highlight_path = self.layout_hover_highlight(&last_layout, &mut bounds, cx);
+        let highlighted_range_path = self.layout_highlighted_range(&last_layout, &bounds, cx);
         let document_color_paths =
             self.layout_document_colors(&document_colors, &last_layout, &bounds);
 
@@ -1119,6 +1138,7 @@ impl Element for TextElement {
             selection_path,
             search_match_paths,
             hover_highlight_path,
+            highlighted_range_path,
             hover_definition_hitbox,
             document_color_paths,
             indent_guides_path,
@@ -1219,6 +1239,11 @@ impl Element for TextElement {
             window.paint_path(path, cx.theme().border.opacity(0.85));
         }
 
+        // Paint highlighted range
+        if let Some(path) = prepaint.highlighted_range_path.take() {
+            window.paint_path(path, cx.theme().accent.opacity(0.5));
+        }
+
         // Paint selections
         if window.is_window_active() {
             let secondary_selection = cx.theme().selection.saturation(0.1);
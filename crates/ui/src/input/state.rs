@@ -285,6 +285,8 @@ pub struct InputState {
     /// The text bounds
     pub(super) last_bounds: Option<Bounds<Pixels>>,
     pub(super) last_selected_range: Option<Selection>,
+    /// A range painted with a background to mark a span of text (e.g. the statement to run).
+    pub(super) highlighted_range: Option<Range<usize>>,
     pub(super) selecting: bool,
     pub(super) size: Size,
     pub(super) disabled: bool,
@@ -390,6 +392,7 @@ impl InputState {
             last_layout: None,
             last_bounds: None,
             last_selected_range: None,
+            highlighted_range: None,
             last_cursor: None,
             scroll_handle: ScrollHandle::new(),
             scroll_size: gpui::size(px(0.), px(0.)),
@@ -1488,6 +1491,37 @@ impl InputState {
         }
     }
 
+    /// Get the selected byte range, it is empty when nothing is selected.
+    pub fn selected_range(&self) -> Range<usize> {
+        self.selected_range.into()
+    }
+
+    /// Select a byte range, the cursor is placed at its end.
+    pub fn set_selected_range(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        let end = range.end.min(self.text.len());
+        let start = range.start.min(end);
+        self.selection_reversed = false;
+        self.selected_word_range = None;
+        self.selected_range = (start..end).into();
+        cx.notify();
+    }
+
+    /// Get the highlighted byte range, see [`InputState::set_highlighted_range`].
+    pub fn highlighted_range(&self) -> Option<Range<usize>> {
+        self.highlighted_range.clone()
+    }
+
+    /// Set a byte range to paint with a subtle background, or `None` to clear it.
+    ///
+    /// The range is not adjusted on text changes, the caller is expected to update it.
+    pub fn set_highlighted_range(&mut self, range: Option<Range<usize>>, cx: &mut Context<Self>) {
+        if self.highlighted_range == range {
+            return;
+        }
+        self.highlighted_range = range;
+        cx.notify();
+    }
+
     pub(crate) fn index_for_mouse_position(&self, position: Point<Pixels>) -> usize {
         // If the text is empty, always return 0
         if self.text.len() == 0 {
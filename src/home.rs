@@ -290,9 +290,9 @@ impl HomePage {
         let config = match db_type {
             DatabaseType::MySQL => DbFormConfig::mysql(),
             DatabaseType::PostgreSQL => DbFormConfig::postgres(),
-            _ => {
-                panic!("Unsupported database type");
-            }
+            DatabaseType::SQLite => DbFormConfig::sqlite(),
+            DatabaseType::MSSQL => DbFormConfig::mssql(),
+            DatabaseType::Oracle => DbFormConfig::oracle(),
         };
 
         let form = cx.new(|cx| {
@@ -411,18 +411,24 @@ impl HomePage {
                 let db_plugin = manager.get_plugin(&db_type)?;
                 let conn = db_plugin.create_connection(config).await?;
                 conn.ping().await?;
-                Ok::<bool, Error>(true)
+                // 报告实际协商的加密方式，不支持查询的数据库不显示
+                let tls = match db_plugin.tls_status(&*conn).await {
+                    Ok(Some(tls)) => format!("已加密 ({})", tls),
+                    Ok(None) => "未加密".to_string(),
+                    Err(_) => String::new(),
+                };
+                Ok::<String, Error>(tls)
             }.await;
 
             match test_result {
-                Ok(_) => {
+                Ok(details) => {
                     form.update(cx, |form, cx1| {
-                        form.set_test_result(Ok(true), cx1)
+                        form.set_test_result(Ok(details), cx1)
                     })
                 }
-                Err(_) => {
+                Err(e) => {
                     form.update(cx, |form, cx1| {
-                        form.set_test_result(Err("测试连接失败".to_string()), cx1)
+                        form.set_test_result(Err(format!("测试连接失败: {}", e)), cx1)
                     })
                 }
             }
@@ -452,6 +458,7 @@ impl HomePage {
                 username: config.username.clone(),
                 password: config.password.clone(),
                 database: config.database.clone(),
+                extra_params: config.extra_params.clone(),
             }).unwrap();
             conn
         } else {
@@ -506,8 +513,8 @@ impl HomePage {
 
     pub fn add_settings_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.tab_container.update(cx, |tc, cx| {
-            tc.activate_or_add_tab_lazy("settings", |_, _| {
-                TabItem::new("settings", SettingsTabContent::new())
+            tc.activate_or_add_tab_lazy("settings", |window, cx| {
+                TabItem::new("settings", SettingsTabContent::new(window, cx))
             }, window, cx);
         });
     }
@@ -568,6 +575,27 @@ impl HomePage {
                                             this.editing_connection_id = None;
                                             this.show_connection_form(DatabaseType::PostgreSQL, window, cx);
                                         }))
+                                ).item(
+                                    PopupMenuItem::new("SQL Server")
+                                        .icon(IconName::Database)
+                                        .on_click(window.listener_for(&view, move |this, _, window, cx| {
+                                            this.editing_connection_id = None;
+                                            this.show_connection_form(DatabaseType::MSSQL, window, cx);
+                                        }))
+                                ).item(
+                                    PopupMenuItem::new("Oracle")
+                                        .icon(IconName::Database)
+                                        .on_click(window.listener_for(&view, move |this, _, window, cx| {
+                                            this.editing_connection_id = None;
+                                            this.show_connection_form(DatabaseType::Oracle, window, cx);
+                                        }))
+                                ).item(
+                                    PopupMenuItem::new("SQLite")
+                                        .icon(IconName::Database)
+                                        .on_click(window.listener_for(&view, move |this, _, window, cx| {
+                                            this.editing_connection_id = None;
+                                            this.show_connection_form(DatabaseType::SQLite, window, cx);
+                                        }))
                                 )
                             })
                     )
@@ -7,6 +7,7 @@ use gpui_component::Root;
 use assets::Assets;
 use db::GlobalDbState;
 use crate::onehup_app::OneHupApp;
+use std::sync::Arc;
 
 fn main() {
     let app = Application::new().with_assets(Assets);
@@ -14,7 +15,9 @@ fn main() {
     app.run(move |cx| {
         onehup_app::init(cx);
         // Initialize global database state
-        cx.set_global(GlobalDbState::new());
+        let db_state = GlobalDbState::new();
+        Arc::new(db_state.connection_pool.clone()).start_cleanup_task();
+        cx.set_global(db_state);
         let mut window_size = size(px(1600.0), px(1200.0));
         if let Some(display) = cx.primary_display() {
             let display_size = display.bounds().size;
@@ -1,6 +1,11 @@
 use crate::home::HomeTabContent;
-use one_core::tab_container::{TabContainer, TabItem};
-use gpui::{div, px, App, AppContext, Context, Entity, IntoElement, KeyBinding, ParentElement, Render, Styled, Window};
+use crate::setting_tab::SettingsTabContent;
+use db_view::database_tab::{DatabaseTabContent, DatabaseTabState};
+use one_core::gpui_tokio::Tokio;
+use one_core::storage::traits::Repository;
+use one_core::storage::{ConnectionRepository, GlobalStorageState, KeyValueRepository, StoredConnection};
+use one_core::tab_container::{TabContainer, TabContentType, TabItem, TabState};
+use gpui::{div, App, AppContext, Context, Entity, IntoElement, KeyBinding, ParentElement, Render, Styled, Subscription, Window};
 use gpui_component::dock::{ClosePanel, ToggleZoom};
 use gpui_component::{ActiveTheme, Root};
 use tracing_subscriber::layer::SubscriberExt;
@@ -18,6 +23,7 @@ pub fn init(cx: &mut App) {
 
     gpui_component::init(cx);
     one_core::init(cx);
+    db_view::sql_editor_view::init(cx);
     cx.bind_keys(vec![
         KeyBinding::new("shift-escape", ToggleZoom, None),
         KeyBinding::new("ctrl-w", ClosePanel, None),
@@ -28,6 +34,7 @@ pub fn init(cx: &mut App) {
 
 pub struct OneHupApp {
     tab_container: Entity<TabContainer>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl OneHupApp {
@@ -55,8 +62,8 @@ impl OneHupApp {
             #[cfg(target_os = "macos")]
             {
                 container = container
-                    .with_left_padding(px(80.0))
-                    .with_top_padding(px(4.0))
+                    .with_left_padding(gpui::px(80.0))
+                    .with_top_padding(gpui::px(4.0))
             }
             
             container
@@ -68,9 +75,89 @@ impl OneHupApp {
             tc.add_and_activate_tab(home_tab, cx);
         });
 
-        Self {
+        // 退出时保存打开的标签页，下次启动时恢复
+        let _subscriptions = vec![cx.on_app_quit(|this, cx| this.save_layout(cx))];
+
+        let app = Self {
             tab_container,
+            _subscriptions,
+        };
+        app.restore_layout(window, cx);
+        app
+    }
+
+    fn save_layout(&mut self, cx: &mut Context<Self>) -> impl Future<Output = ()> + use<> {
+        let layout = self.tab_container.read(cx).save_layout(cx);
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+
+        let task = Tokio::spawn(cx, async move {
+            let repo = storage.get::<KeyValueRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("KeyValueRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            repo.set_tab_layout(&pool, &layout).await
+        });
+
+        async move {
+            match task.await {
+                Ok(Err(e)) => tracing::error!("Failed to save tab layout: {}", e),
+                Err(e) => tracing::error!("Task join error: {}", e),
+                Ok(Ok(())) => {}
+            }
+        }
+    }
+
+    /// 恢复上次退出时打开的标签页，数据库在标签页显示时才重新连接
+    fn restore_layout(&self, window: &mut Window, cx: &mut Context<Self>) {
+        let storage = cx.global::<GlobalStorageState>().storage.clone();
+
+        let task = Tokio::spawn(cx, async move {
+            let kv_repo = storage.get::<KeyValueRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("KeyValueRepository not found"))?;
+            let conn_repo = storage.get::<ConnectionRepository>().await
+                .ok_or_else(|| anyhow::anyhow!("ConnectionRepository not found"))?;
+            let pool = storage.get_pool().await?;
+            let layout = kv_repo.tab_layout(&pool).await?;
+            let connections = conn_repo.list(&pool).await?;
+            anyhow::Ok((layout, connections))
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let (layout, connections) = match task.await {
+                Ok(Ok((Some(layout), connections))) => (layout, connections),
+                Ok(Ok((None, _))) => return,
+                Ok(Err(e)) => {
+                    tracing::error!("Failed to load tab layout: {}", e);
+                    return;
+                }
+                Err(e) => {
+                    tracing::error!("Task join error: {}", e);
+                    return;
+                }
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.tab_container.update(cx, |container, cx| {
+                    container.restore_layout(layout, |tab, window, cx| {
+                        Self::restore_tab(tab, &connections, window, cx)
+                    }, window, cx);
+                });
+            });
+        }).detach();
+    }
+
+    fn restore_tab(tab: &TabState, connections: &[StoredConnection], window: &mut Window, cx: &mut App) -> Option<TabItem> {
+        let TabContentType::Custom(kind) = &tab.content_type else {
+            return None;
+        };
+        if kind == "settings" {
+            return Some(TabItem::new(tab.id.clone(), SettingsTabContent::new(window, cx)));
+        }
+        if kind.starts_with("database-") {
+            let state: DatabaseTabState = serde_json::from_value(tab.data.clone()).ok()?;
+            let content = DatabaseTabContent::restore(state, connections, window, cx)?;
+            return Some(TabItem::new(tab.id.clone(), content));
         }
+        None
     }
 }
 
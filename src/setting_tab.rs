@@ -1,13 +1,18 @@
 use std::any::Any;
-use gpui::{div, AnyElement, App, FontWeight, IntoElement, ParentElement, SharedString, Styled, Window};
+use db_view::sql_format_settings::SqlFormatSettingsPanel;
+use gpui::{div, AnyElement, App, AppContext, Entity, FontWeight, IntoElement, ParentElement, SharedString, Styled, Window};
 use gpui_component::{v_flex, ActiveTheme, IconName};
 use one_core::tab_container::{TabContent, TabContentType};
 
-pub struct SettingsTabContent;
+pub struct SettingsTabContent {
+    sql_format: Entity<SqlFormatSettingsPanel>,
+}
 
 impl SettingsTabContent {
-    pub fn new() -> Self {
-        Self
+    pub fn new(window: &mut Window, cx: &mut App) -> Self {
+        Self {
+            sql_format: cx.new(|cx| SqlFormatSettingsPanel::new(window, cx)),
+        }
     }
 }
 
@@ -42,6 +47,7 @@ impl TabContent for SettingsTabContent {
                             .text_color(cx.theme().muted_foreground)
                             .child("应用程序设置和配置")
                     )
+                    .child(self.sql_format.clone())
             )
             .into_any_element()
     }
@@ -50,6 +56,10 @@ impl TabContent for SettingsTabContent {
         TabContentType::Custom("settings".to_string())
     }
 
+    fn save_state(&self, _cx: &App) -> Option<serde_json::Value> {
+        Some(serde_json::Value::Null)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }